@@ -13,10 +13,12 @@ use ark_bls12_381::Bls12_381;
 use ark_ec::{PairingEngine, TEModelParameters};
 use ark_ed_on_bls12_381::EdwardsParameters;
 use ark_ff::{FftField, PrimeField};
+use ark_serialize::CanonicalSerialize;
 use blake2;
 use core::marker::PhantomData;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use plonk::commitment::{HomomorphicCommitment, IPA, KZG10};
+use plonk::lookup::{LookupMode, LookupTable};
 use plonk::prelude::*;
 use rand_core::OsRng;
 
@@ -27,6 +29,12 @@ pub struct BenchCircuit<F, P> {
     /// Circuit Size
     size: usize,
 
+    /// Lookup Argument
+    lookup_mode: LookupMode,
+
+    /// Rows added to the lookup table on top of the dummy ones
+    table_padding: usize,
+
     /// Field and parameters
     _phantom: PhantomData<(F, P)>,
 }
@@ -35,8 +43,30 @@ impl<F, P> BenchCircuit<F, P> {
     /// Builds a new circuit with a constraint count of `2^degree`.
     #[inline]
     pub fn new(degree: usize) -> Self {
+        Self::with_lookup_mode(degree, LookupMode::default())
+    }
+
+    /// Builds a new circuit with a constraint count of `2^degree`, whose
+    /// lookups are proven with the given argument.
+    #[inline]
+    pub fn with_lookup_mode(degree: usize, lookup_mode: LookupMode) -> Self {
         Self {
             size: 1 << degree,
+            lookup_mode,
+            table_padding: 0,
+            _phantom: PhantomData::<(F, P)>,
+        }
+    }
+
+    /// Builds a new circuit with a few lookup gates querying a table of more
+    /// than `2^(degree - 1)` rows, so that the domain of `2^degree` rows is
+    /// sized by the table rather than by the gates.
+    #[inline]
+    pub fn with_large_table(degree: usize, lookup_mode: LookupMode) -> Self {
+        Self {
+            size: 1 << degree,
+            lookup_mode,
+            table_padding: 1 << (degree - 1),
             _phantom: PhantomData::<(F, P)>,
         }
     }
@@ -54,17 +84,27 @@ where
         &mut self,
         composer: &mut StandardComposer<F, P>,
     ) -> Result<(), Error> {
-        composer.add_dummy_lookup_table();
+        composer.set_lookup_mode(self.lookup_mode);
+        let table = composer.add_dummy_lookup_table()?;
+        if self.table_padding > 0 {
+            let mut padding = LookupTable::new();
+            for i in 0..self.table_padding {
+                padding.insert_row(
+                    F::from(i as u64),
+                    F::zero(),
+                    F::zero(),
+                    F::zero(),
+                );
+            }
+            composer.add_lookup_table("padding", padding)?;
+            composer.add_dummy_constraints(table);
+            return Ok(());
+        }
         while composer.circuit_bound() < self.size - 1 {
-            composer.add_dummy_constraints();
+            composer.add_dummy_constraints(table);
         }
         Ok(())
     }
-
-    #[inline]
-    fn padded_circuit_size(&self) -> usize {
-        self.size
-    }
 }
 
 fn kzg10_benchmarks(c: &mut Criterion) {
@@ -127,26 +167,89 @@ where
             &degree,
             |b, _| {
                 b.iter(|| {
-                    circuit.gen_proof::<HC>(&pp, pk_p.clone(), &label).unwrap()
+                    circuit
+                        .gen_proof::<HC, Transcript>(&pp, pk_p.clone(), &label)
+                        .unwrap()
                 })
             },
         );
     }
     proving_benchmarks.finish();
 
+    // Every constraint of the benchmark circuit is a lookup, so proving it
+    // with either argument compares their prover times, and the size of the
+    // proofs is reported alongside. The circuits with a large table hold a
+    // few lookups only, the domain being sized by the rows of the table.
+    let mut lookup_proving_benchmarks =
+        c.benchmark_group(format!("{0}/prove_lookup", name));
+    for degree in MINIMUM_DEGREE..MAXIMUM_DEGREE {
+        for (mode_name, mut circuit) in [
+            (
+                "plookup",
+                BenchCircuit::<F, P>::with_lookup_mode(
+                    degree,
+                    LookupMode::Plookup,
+                ),
+            ),
+            (
+                "logup",
+                BenchCircuit::with_lookup_mode(degree, LookupMode::LogUp),
+            ),
+            (
+                "plookup_large_table",
+                BenchCircuit::with_large_table(degree, LookupMode::Plookup),
+            ),
+            (
+                "logup_large_table",
+                BenchCircuit::with_large_table(degree, LookupMode::LogUp),
+            ),
+        ] {
+            let (pk_p, _) = circuit
+                .compile::<HC>(&pp)
+                .expect("Unable to compile circuit.");
+            let (proof, _) = circuit
+                .gen_proof::<HC, Transcript>(&pp, pk_p.clone(), &label)
+                .unwrap();
+            println!(
+                "{}/prove_lookup/{}/{}: proof size {} bytes",
+                name,
+                mode_name,
+                degree,
+                proof.serialized_size()
+            );
+            lookup_proving_benchmarks.bench_with_input(
+                BenchmarkId::new(mode_name, degree),
+                &degree,
+                |b, _| {
+                    b.iter(|| {
+                        circuit
+                            .gen_proof::<HC, Transcript>(
+                                &pp,
+                                pk_p.clone(),
+                                &label,
+                            )
+                            .unwrap()
+                    })
+                },
+            );
+        }
+    }
+    lookup_proving_benchmarks.finish();
+
     let mut verifying_benchmarks = c.benchmark_group("verify");
     for degree in MINIMUM_DEGREE..MAXIMUM_DEGREE {
         let mut circuit = BenchCircuit::<F, P>::new(degree);
         let (pk_p, vk) =
             circuit.compile(&pp).expect("Unable to compile circuit.");
-        let (proof, pi) =
-            circuit.gen_proof::<HC>(&pp, pk_p.clone(), &label).unwrap();
+        let (proof, pi) = circuit
+            .gen_proof::<HC, Transcript>(&pp, pk_p.clone(), &label)
+            .unwrap();
         verifying_benchmarks.bench_with_input(
             BenchmarkId::from_parameter(degree),
             &degree,
             |b, _| {
                 b.iter(|| {
-                    plonk::circuit::verify_proof::<F, P, HC>(
+                    plonk::circuit::verify_proof::<F, P, HC, Transcript>(
                         &pp,
                         vk.clone(),
                         &proof,
@@ -159,6 +262,77 @@ where
         );
     }
     verifying_benchmarks.finish();
+
+    // Verifying several proofs one after the other performs one opening check
+    // per proof, whereas batching them performs a single one.
+    const BATCH_DEGREE: usize = 10;
+    const MAXIMUM_BATCH_SIZE: usize = 16;
+
+    let mut circuit = BenchCircuit::<F, P>::new(BATCH_DEGREE);
+    let (pk_p, vk) = circuit.compile(&pp).expect("Unable to compile circuit.");
+    let (_, pc_vk) = HC::trim(&pp, vk.padded_circuit_size(), 0, None)
+        .expect("Unable to trim public parameters.");
+    let proofs = (0..MAXIMUM_BATCH_SIZE)
+        .map(|_| {
+            circuit
+                .gen_proof::<HC, Transcript>(&pp, pk_p.clone(), label)
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let mut separate_verifying_benchmarks =
+        c.benchmark_group(format!("{0}/verify_separately", name));
+    let mut batch_size = 1;
+    while batch_size <= MAXIMUM_BATCH_SIZE {
+        separate_verifying_benchmarks.bench_with_input(
+            BenchmarkId::from_parameter(batch_size),
+            &batch_size,
+            |b, &batch_size| {
+                b.iter(|| {
+                    for (proof, pi) in &proofs[..batch_size] {
+                        plonk::circuit::verify_proof::<F, P, HC, Transcript>(
+                            &pp,
+                            vk.clone(),
+                            proof,
+                            pi,
+                            label,
+                        )
+                        .expect("Unable to verify benchmark circuit.");
+                    }
+                })
+            },
+        );
+        batch_size *= 2;
+    }
+    separate_verifying_benchmarks.finish();
+
+    let mut batch_verifying_benchmarks =
+        c.benchmark_group(format!("{0}/verify_batch", name));
+    let mut batch_size = 1;
+    while batch_size <= MAXIMUM_BATCH_SIZE {
+        let instances = proofs[..batch_size]
+            .iter()
+            .map(|(proof, pi)| (&vk, proof, pi))
+            .collect::<Vec<_>>();
+        batch_verifying_benchmarks.bench_with_input(
+            BenchmarkId::from_parameter(batch_size),
+            &batch_size,
+            |b, _| {
+                b.iter(|| {
+                    plonk::proof_system::verify_batch::<F, P, HC, Transcript, _>(
+                        &pc_vk,
+                        &instances,
+                        &plonk::proof_system::custom::CustomGates::new(),
+                        label,
+                        &mut OsRng,
+                    )
+                    .expect("Unable to verify benchmark circuits.");
+                })
+            },
+        );
+        batch_size *= 2;
+    }
+    batch_verifying_benchmarks.finish();
 }
 
 criterion_group! {
@@ -0,0 +1,156 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Poseidon Round Gate Benchmarks
+//!
+//! Compares the proving time of full Poseidon rounds constrained with the
+//! Poseidon round gate against the same rounds built out of arithmetic gates.
+
+use ark_bls12_381::Bls12_381;
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ed_on_bls12_381::EdwardsParameters;
+use ark_ff::PrimeField;
+use core::marker::PhantomData;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use plonk::commitment::{HomomorphicCommitment, KZG10};
+use plonk::hashing::poseidon::{PlonkSpec, PoseidonConstants, PoseidonSpec};
+use plonk::prelude::*;
+use plonk::proof_system::poseidon::{mds_matrix, WIDTH};
+use rand_core::OsRng;
+
+/// Circuit applying full Poseidon rounds to a width-4 state.
+#[derive(derivative::Derivative)]
+#[derivative(Debug)]
+pub struct PoseidonCircuit<F, P>
+where
+    F: PrimeField,
+{
+    /// Round constants, one array per round
+    round_constants: Vec<[F; WIDTH]>,
+
+    /// Whether the rounds use the Poseidon round gate
+    use_gate: bool,
+
+    /// Field and parameters
+    _phantom: PhantomData<P>,
+}
+
+impl<F, P> PoseidonCircuit<F, P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    /// Builds a circuit of `rounds` full rounds.
+    pub fn new(rounds: usize, use_gate: bool) -> Self {
+        let round_constants = (0..rounds)
+            .map(|_| [(); WIDTH].map(|_| F::rand(&mut OsRng)))
+            .collect();
+        Self {
+            round_constants,
+            use_gate,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F, P> Circuit<F, P> for PoseidonCircuit<F, P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    const CIRCUIT_ID: [u8; 32] = [0xff; 32];
+
+    fn gadget(
+        &mut self,
+        composer: &mut StandardComposer<F, P>,
+    ) -> Result<(), Error> {
+        let mut state = [composer.zero_var(); WIDTH];
+        for (i, var) in state.iter_mut().enumerate() {
+            *var = composer.add_input(F::from(i as u64));
+        }
+        if self.use_gate {
+            composer.poseidon_round_gate(state, &self.round_constants);
+        } else {
+            let constants = PoseidonConstants::new(
+                WIDTH,
+                self.round_constants.len(),
+                0,
+                5,
+                mds_matrix::<F>().iter().map(|row| row.to_vec()).collect(),
+                self.round_constants.concat(),
+            )
+            .expect("valid parameters");
+            PlonkSpec::permute(composer, &constants, &mut state);
+        }
+        Ok(())
+    }
+}
+
+/// Benchmarks proving `rounds` full rounds with and without the Poseidon
+/// round gate.
+fn poseidon_benchmark<F, P, HC>(name: &str, c: &mut Criterion)
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    HC: HomomorphicCommitment<F>,
+{
+    let label = b"ark".as_slice();
+
+    const ROUNDS: [usize; 3] = [8, 64, 512];
+
+    let pp = HC::setup(1 << 14, None, &mut OsRng)
+        .expect("Unable to sample public parameters.");
+
+    let mut proving_benchmarks =
+        c.benchmark_group(format!("{0}/poseidon/prove", name));
+    for rounds in ROUNDS {
+        for (variant, use_gate) in [("poseidon", true), ("arithmetic", false)] {
+            let mut circuit = PoseidonCircuit::<F, P>::new(rounds, use_gate);
+            println!(
+                "{} rounds with {} gates: circuit size {}",
+                rounds,
+                variant,
+                circuit.required_srs_degree().unwrap()
+            );
+            let (pk_p, _) = circuit
+                .compile::<HC>(&pp)
+                .expect("Unable to compile circuit.");
+            proving_benchmarks.bench_with_input(
+                BenchmarkId::new(variant, rounds),
+                &rounds,
+                |b, _| {
+                    b.iter(|| {
+                        circuit
+                            .gen_proof::<HC, Transcript>(
+                                &pp,
+                                pk_p.clone(),
+                                label,
+                            )
+                            .unwrap()
+                    })
+                },
+            );
+        }
+    }
+    proving_benchmarks.finish();
+}
+
+fn kzg10_benchmarks(c: &mut Criterion) {
+    poseidon_benchmark::<
+        <Bls12_381 as PairingEngine>::Fr,
+        EdwardsParameters,
+        KZG10<Bls12_381>,
+    >("KZG10", c);
+}
+
+criterion_group! {
+    name = poseidon;
+    config = Criterion::default().sample_size(10);
+    targets = kzg10_benchmarks
+}
+criterion_main!(poseidon);
@@ -85,10 +85,6 @@ fn main() -> Result<(), Error> {
             composer.assert_equal_public_point(scalar_mul_result, self.f);
             Ok(())
         }
-
-        fn padded_circuit_size(&self) -> usize {
-            1 << 9
-        }
     }
 
     // Generate CRS
@@ -116,12 +112,12 @@ fn main() -> Result<(), Error> {
                 e: JubJubScalar::from(2u64),
                 f: point_f_pi,
             };
-        circuit.gen_proof::<PC>(&pp, pk_p, b"Test")
+        circuit.gen_proof::<PC, Transcript>(&pp, pk_p, b"Test")
     }?;
 
     // Verifier POV
     let verifier_data = VerifierData::new(vk, pi);
-    verify_proof::<BlsScalar, JubJubParameters, PC>(
+    verify_proof::<BlsScalar, JubJubParameters, PC, Transcript>(
         &pp,
         verifier_data.key,
         &proof,
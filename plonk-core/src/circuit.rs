@@ -11,11 +11,14 @@ use crate::{
     error::{to_pc_error, Error},
     prelude::StandardComposer,
     proof_system::{
-        pi::PublicInputs, Proof, Prover, ProverKey, Verifier, VerifierKey,
+        custom::CustomGates, pi::PublicInputs, Proof, Prover, ProverKey,
+        Verifier, VerifierKey,
     },
+    transcript::TranscriptProtocol,
 };
 use ark_ec::models::TEModelParameters;
 use ark_ff::PrimeField;
+use ark_poly_commit::PCUniversalParams;
 use ark_serialize::*;
 
 /// Collection of structs/objects that the Verifier will use in order to
@@ -149,19 +152,16 @@ where
 ///            composer.assert_equal_public_point(scalar_mul_result, self.f);
 ///            Ok(())
 ///        }
-///
-///        fn padded_circuit_size(&self) -> usize {
-///            1 << 11
-///        }
 ///    }
 ///
+/// let mut circuit = TestCircuit::<BlsScalar, JubJubParameters>::default();
+///
 /// // Generate CRS
 /// type PC = SonicKZG10::<Bls12_381,DensePolynomial<BlsScalar>>;
 /// let pp = PC::setup(
-///     1 << 10, None, &mut OsRng
+///     circuit.required_srs_degree()?, None, &mut OsRng
 ///  )?;
 ///
-/// let mut circuit = TestCircuit::<BlsScalar, JubJubParameters>::default();
 /// // Compile the circuit
 /// let (pk_p, vk) = circuit.compile::<PC>(&pp)?;
 ///
@@ -182,7 +182,7 @@ where
 ///         e: JubJubScalar::from(2u64),
 ///         f: point_f_pi,
 ///     };
-///     circuit.gen_proof::<PC>(&pp, pk_p, b"Test")
+///     circuit.gen_proof::<PC, Transcript>(&pp, pk_p, b"Test")
 /// }?;
 ///
 /// let verifier_data = VerifierData::new(vk, pi);
@@ -196,7 +196,7 @@ where
 /// assert!(deserialized_verifier_data == verifier_data);
 ///
 /// // Verifier POV
-/// verify_proof::<BlsScalar, JubJubParameters, PC>(
+/// verify_proof::<BlsScalar, JubJubParameters, PC, Transcript>(
 ///     &pp,
 ///     verifier_data.key,
 ///     &proof,
@@ -219,8 +219,22 @@ where
         composer: &mut StandardComposer<F, P>,
     ) -> Result<(), Error>;
 
+    /// Returns the degree the universal parameters must support to compile
+    /// the circuit and prove it, as computed by
+    /// [`StandardComposer::required_srs_degree`] from the gates and lookup
+    /// tables added by the [`gadget`](Circuit::gadget).
+    fn required_srs_degree(&mut self) -> Result<usize, Error> {
+        let mut composer = StandardComposer::<F, P>::new();
+        self.gadget(&mut composer)?;
+        Ok(composer.required_srs_degree())
+    }
+
     /// Compiles the circuit by using a function that returns a `Result`
     /// with the [`ProverKey`], [`VerifierKey`] and the circuit size.
+    ///
+    /// The domain of the circuit is sized to hold its gates and lookup
+    /// tables, and an [`Error::CircuitSizeMismatch`] is returned if
+    /// `u_params` do not support it.
     #[allow(clippy::type_complexity)] // NOTE: Clippy is too harsh here.
     fn compile<PC>(
         &mut self,
@@ -230,18 +244,16 @@ where
         F: PrimeField,
         PC: HomomorphicCommitment<F>,
     {
-        // Setup PublicParams
-        let circuit_size = self.padded_circuit_size();
-        let (ck, _) = PC::trim(u_params, circuit_size, 0, None)
-            .map_err(to_pc_error::<F, PC>)?;
-
         //Generate & save `ProverKey` with some random values.
         let mut prover = Prover::<F, P, PC>::new(b"CircuitCompilation");
         self.gadget(prover.mut_cs())?;
+
+        // Setup PublicParams
+        let (ck, _) = trim::<F, PC>(u_params, prover.required_srs_degree())?;
         prover.preprocess(&ck)?;
 
         // Generate & save `VerifierKey` with some random values.
-        let mut verifier = Verifier::new(b"CircuitCompilation");
+        let mut verifier = Verifier::<F, P, PC>::new(b"CircuitCompilation");
         self.gadget(verifier.mut_cs())?;
         verifier.preprocess(&ck)?;
         Ok((
@@ -255,9 +267,10 @@ where
     }
 
     /// Generates a proof using the provided [`ProverKey`] and
-    /// [`ark_poly_commit::PCUniversalParams`]. Returns a
+    /// [`ark_poly_commit::PCUniversalParams`], deriving the challenges from
+    /// a transcript `T` initialized with `transcript_init`. Returns a
     /// [`crate::proof_system::Proof`] and the [`PublicInputs`].
-    fn gen_proof<PC>(
+    fn gen_proof<PC, T>(
         &mut self,
         u_params: &PC::UniversalParams,
         prover_key: ProverKey<F>,
@@ -267,30 +280,72 @@ where
         F: PrimeField,
         P: TEModelParameters<BaseField = F>,
         PC: HomomorphicCommitment<F>,
+        T: TranscriptProtocol<F>,
     {
-        let circuit_size = self.padded_circuit_size();
-        let (ck, _) = PC::trim(u_params, circuit_size, 0, None)
-            .map_err(to_pc_error::<F, PC>)?;
         // New Prover instance
-        let mut prover = Prover::new(transcript_init);
+        let mut prover = Prover::<F, P, PC, T>::new(transcript_init);
         // Fill witnesses for Prover
         self.gadget(prover.mut_cs())?;
+        let (ck, _) = trim::<F, PC>(u_params, prover.required_srs_degree())?;
         // Add ProverKey to Prover
         prover.prover_key = Some(prover_key);
         let pi = prover.cs.get_pi().clone();
 
         Ok((prover.prove(&ck)?, pi))
     }
+}
 
-    /// Returns the Circuit size padded to the next power of two.
-    fn padded_circuit_size(&self) -> usize;
+/// Trims `u_params` to commit to polynomials of degree up to
+/// `supported_degree`, failing with an [`Error::CircuitSizeMismatch`] if they
+/// were set up for a smaller degree.
+fn trim<F, PC>(
+    u_params: &PC::UniversalParams,
+    supported_degree: usize,
+) -> Result<(PC::CommitterKey, PC::VerifierKey), Error>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    if u_params.max_degree() < supported_degree {
+        return Err(Error::CircuitSizeMismatch {
+            circuit_size: supported_degree,
+            supported_degree: u_params.max_degree(),
+        });
+    }
+    PC::trim(u_params, supported_degree, 0, None).map_err(to_pc_error::<F, PC>)
 }
 
 /// Verifies a proof using the provided `CircuitInputs` & `VerifierKey`
-/// instances.
-pub fn verify_proof<F, P, PC>(
+/// instances, with the transcript `T` the proof was generated with.
+pub fn verify_proof<F, P, PC, T>(
+    u_params: &PC::UniversalParams,
+    plonk_verifier_key: VerifierKey<F, PC>,
+    proof: &Proof<F, PC>,
+    public_inputs: &PublicInputs<F>,
+    transcript_init: &'static [u8],
+) -> Result<(), Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol<F>,
+{
+    verify_proof_with_custom_gates::<F, P, PC, T>(
+        u_params,
+        plonk_verifier_key,
+        &CustomGates::new(),
+        proof,
+        public_inputs,
+        transcript_init,
+    )
+}
+
+/// Verifies a proof as [`verify_proof`] does, for a circuit using the
+/// `custom_gates`.
+pub fn verify_proof_with_custom_gates<F, P, PC, T>(
     u_params: &PC::UniversalParams,
     plonk_verifier_key: VerifierKey<F, PC>,
+    custom_gates: &CustomGates<F>,
     proof: &Proof<F, PC>,
     public_inputs: &PublicInputs<F>,
     transcript_init: &'static [u8],
@@ -299,12 +354,13 @@ where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol<F>,
 {
-    let mut verifier: Verifier<F, P, PC> = Verifier::new(transcript_init);
+    let mut verifier: Verifier<F, P, PC, T> = Verifier::new(transcript_init);
+    verifier.cs.custom_gates = custom_gates.clone();
     let padded_circuit_size = plonk_verifier_key.padded_circuit_size();
     verifier.verifier_key = Some(plonk_verifier_key);
-    let (_, vk) = PC::trim(u_params, padded_circuit_size, 0, None)
-        .map_err(to_pc_error::<F, PC>)?;
+    let (_, vk) = trim::<F, PC>(u_params, padded_circuit_size)?;
 
     verifier.verify(proof, &vk, public_inputs)
 }
@@ -312,7 +368,12 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{constraint_system::StandardComposer, util};
+    use crate::{
+        constraint_system::StandardComposer,
+        lookup::{LookupMode, LookupTable},
+        transcript::Transcript,
+        util,
+    };
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
     use ark_ec::{
@@ -379,10 +440,6 @@ mod test {
             composer.assert_equal_public_point(scalar_mul_result, self.f);
             Ok(())
         }
-
-        fn padded_circuit_size(&self) -> usize {
-            1 << 9
-        }
     }
 
     fn test_full<F, P, PC>() -> Result<(), Error>
@@ -429,7 +486,7 @@ mod test {
                 }
             }
 
-            circuit.gen_proof::<PC>(&pp, pk, b"Test")?
+            circuit.gen_proof::<PC, Transcript>(&pp, pk, b"Test")?
         };
 
         let verifier_data = VerifierData::new(vk, pi);
@@ -446,7 +503,7 @@ mod test {
         // Verifier POV
 
         // TODO: non-ideal hack for a first functional version.
-        assert!(verify_proof::<F, P, PC>(
+        assert!(verify_proof::<F, P, PC, Transcript>(
             &pp,
             verifier_data.key,
             &proof,
@@ -458,6 +515,128 @@ mod test {
         Ok(())
     }
 
+    // Implements a circuit looking up two rows of a table with more rows than
+    // the circuit has gates.
+    #[derive(derivative::Derivative)]
+    #[derivative(Debug(bound = ""), Default(bound = ""))]
+    pub struct TableCircuit<F: FftField, P: TEModelParameters<BaseField = F>> {
+        lookup_mode: LookupMode,
+        _phantom: core::marker::PhantomData<(F, P)>,
+    }
+
+    impl<F, P> Circuit<F, P> for TableCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        const CIRCUIT_ID: [u8; 32] = [0xff; 32];
+
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<F, P>,
+        ) -> Result<(), Error> {
+            composer.set_lookup_mode(self.lookup_mode);
+            let xor = composer
+                .add_lookup_table("xor", LookupTable::xor_table(0, 4))?;
+            let negative_one = composer.add_input(-F::one());
+            let a = composer.add_input(F::from(3u64));
+            let b = composer.add_input(F::from(5u64));
+            let c = composer.add_input(F::from(6u64));
+            composer.lookup_gate(xor, a, b, c, Some(negative_one), None);
+            composer.lookup_gate(xor, c, a, b, Some(negative_one), None);
+            Ok(())
+        }
+    }
+
+    fn test_large_table<F, P, PC>() -> Result<(), Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut circuit = TableCircuit::<F, P>::default();
+
+        // The domain is sized by the 256 rows of the table.
+        let mut composer = StandardComposer::<F, P>::new();
+        circuit.gadget(&mut composer)?;
+        assert!(composer.circuit_size() < 1 << 8);
+        assert_eq!(circuit.required_srs_degree()?, 1 << 8);
+
+        // Parameters too small for the table are rejected.
+        let small_pp = PC::setup(1 << 7, None, &mut OsRng)
+            .map_err(to_pc_error::<F, PC>)?;
+        let res = circuit.compile::<PC>(&small_pp);
+        assert!(
+            matches!(
+                res,
+                Err(Error::CircuitSizeMismatch {
+                    circuit_size: 256,
+                    ..
+                })
+            ),
+            "{:?}",
+            res.err()
+        );
+
+        let pp = PC::setup(circuit.required_srs_degree()?, None, &mut OsRng)
+            .map_err(to_pc_error::<F, PC>)?;
+        let (pk, vk) = circuit.compile::<PC>(&pp)?;
+
+        // So are commit keys too small for the table.
+        let (small_ck, _) =
+            PC::trim(&pp, 1 << 7, 0, None).map_err(to_pc_error::<F, PC>)?;
+        let mut prover = Prover::<F, P, PC>::new(b"Test");
+        circuit.gadget(prover.mut_cs())?;
+        let res = prover.preprocess(&small_ck);
+        assert!(
+            matches!(res, Err(Error::CircuitSizeMismatch { .. })),
+            "{:?}",
+            res
+        );
+        let res =
+            circuit.gen_proof::<PC, Transcript>(&small_pp, pk.clone(), b"Test");
+        assert!(
+            matches!(res, Err(Error::CircuitSizeMismatch { .. })),
+            "{:?}",
+            res.err()
+        );
+
+        let (proof, pi) =
+            circuit.gen_proof::<PC, Transcript>(&pp, pk, b"Test")?;
+        verify_proof::<F, P, PC, Transcript>(&pp, vk, &proof, &pi, b"Test")?;
+
+        // The LogUp argument interpolates the table over the same domain.
+        circuit.lookup_mode = LookupMode::LogUp;
+        assert_eq!(circuit.required_srs_degree()?, 1 << 8);
+        let (pk, vk) = circuit.compile::<PC>(&pp)?;
+        let (proof, pi) =
+            circuit.gen_proof::<PC, Transcript>(&pp, pk, b"Test")?;
+        verify_proof::<F, P, PC, Transcript>(&pp, vk, &proof, &pi, b"Test")
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_large_table_on_Bls12_381() -> Result<(), Error> {
+        test_large_table::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::KZG10<Bls12_381>,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_large_table_on_Bls12_377_ipa() -> Result<(), Error> {
+        test_large_table::<
+            <Bls12_377 as PairingEngine>::Fr,
+            ark_ed_on_bls12_377::EdwardsParameters,
+            crate::commitment::IPA<
+                <Bls12_377 as PairingEngine>::G1Affine,
+                blake2::Blake2b,
+            >,
+        >()
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn test_full_on_Bls12_381() -> Result<(), Error> {
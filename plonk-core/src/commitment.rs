@@ -2,7 +2,9 @@
 use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine};
 use ark_ff::{Field, PrimeField};
 use ark_poly::univariate::DensePolynomial;
-use ark_poly_commit::{sonic_pc::SonicKZG10, PolynomialCommitment};
+use ark_poly_commit::{
+    sonic_pc::SonicKZG10, PCRandomness, PCVerifierKey, PolynomialCommitment,
+};
 
 /// A homomorphic polynomial commitment
 pub trait HomomorphicCommitment<F>:
@@ -16,6 +18,22 @@ where
         commitments: &[Self::Commitment],
         scalars: &[F],
     ) -> Self::Commitment;
+
+    /// Combine a linear combination of the randomness of hiding commitments,
+    /// giving the randomness of their [`multi_scalar_mul`].
+    ///
+    /// [`multi_scalar_mul`]: HomomorphicCommitment::multi_scalar_mul
+    fn combine_randomness(
+        randomness: &[Self::Randomness],
+        scalars: &[F],
+    ) -> Self::Randomness;
+
+    /// Returns `true` if `proof` has the shape expected by `verifier_key`,
+    /// which must hold before checking it.
+    fn is_well_formed(
+        verifier_key: &Self::VerifierKey,
+        proof: &Self::Proof,
+    ) -> bool;
 }
 
 /// The Default KZG-style commitment scheme
@@ -46,6 +64,26 @@ where
                 .into(),
         )
     }
+
+    fn combine_randomness(
+        randomness: &[Self::Randomness],
+        scalars: &[E::Fr],
+    ) -> Self::Randomness {
+        randomness.iter().zip(scalars).fold(
+            Self::Randomness::empty(),
+            |mut combined, (rand, scalar)| {
+                combined += (*scalar, rand);
+                combined
+            },
+        )
+    }
+
+    fn is_well_formed(
+        _verifier_key: &Self::VerifierKey,
+        _proof: &Self::Proof,
+    ) -> bool {
+        true
+    }
 }
 
 /// Shortened type for Inner Product Argument polynomial commitment schemes
@@ -88,6 +126,33 @@ where
             shifted_comm: None, // TODO: support degree bounds?
         }
     }
+
+    fn combine_randomness(
+        randomness: &[Self::Randomness],
+        scalars: &[<G as ark_ec::AffineCurve>::ScalarField],
+    ) -> Self::Randomness {
+        ark_poly_commit::ipa_pc::Randomness {
+            rand: randomness
+                .iter()
+                .zip(scalars)
+                .map(|(rand, scalar)| rand.rand * scalar)
+                .sum(),
+            shifted_rand: None,
+        }
+    }
+
+    fn is_well_formed(
+        verifier_key: &Self::VerifierKey,
+        proof: &Self::Proof,
+    ) -> bool {
+        // Checking panics when only one of the hiding commitment and of its
+        // randomness is given, and the batch check doesn't verify the number
+        // of rounds.
+        let rounds = ark_std::log2(verifier_key.supported_degree() + 1);
+        proof.l_vec.len() == rounds as usize
+            && proof.r_vec.len() == rounds as usize
+            && proof.hiding_comm.is_some() == proof.rand.is_some()
+    }
 }
 
 /// Computes a linear combination of the polynomial evaluations and polynomial
@@ -133,10 +133,11 @@ where
         self.q_logic.push(F::zero());
         self.q_fixed_group_add.push(F::zero());
         self.q_variable_group_add.push(F::zero());
+        self.q_poseidon.push(F::zero());
         self.q_lookup.push(F::zero());
 
         if let Some(pi) = gate.pi {
-            self.public_inputs.insert(self.n, pi);
+            self.insert_pi(pi);
         };
 
         let c = gate_witness.2.unwrap_or_else(|| {
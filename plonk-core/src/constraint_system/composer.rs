@@ -16,8 +16,9 @@
 
 use crate::{constraint_system::Variable, permutation::Permutation};
 
-use crate::lookup::LookupTable;
-use crate::proof_system::pi::PublicInputs;
+use crate::error::Error;
+use crate::lookup::{LookupMode, LookupTable, LookupTableId, LookupTables};
+use crate::proof_system::{custom::CustomGates, pi::PublicInputs};
 use ark_ec::{models::TEModelParameters, ModelParameters};
 use ark_ff::PrimeField;
 use core::cmp::max;
@@ -85,11 +86,26 @@ where
     pub(crate) q_variable_group_add: Vec<F>,
     /// Lookup gate selector
     pub(crate) q_lookup: Vec<F>,
+    /// Lookup table selector, holding the id of the table queried by the
+    /// lookup gates. It is only filled up to the last lookup gate.
+    pub(crate) q_table: Vec<F>,
+    /// Poseidon round selector
+    pub(crate) q_poseidon: Vec<F>,
+    /// Custom gate selectors, in order of registration. They are only
+    /// filled up to the last gate using them.
+    pub(crate) custom_selectors: Vec<Vec<F>>,
+
+    /// Custom gates used by the circuit.
+    pub(crate) custom_gates: CustomGates<F>,
 
     /// Sparse representation of the Public Inputs linking the positions of the
     /// non-zero ones to it's actual values.
     pub(crate) public_inputs: PublicInputs<F>,
 
+    /// Positions of all the public inputs, including the zero ones which are
+    /// missing from `public_inputs`.
+    pub(crate) pi_positions: Vec<usize>,
+
     // Witness vectors
     /// Left wire witness vector.
     pub(crate) w_l: Vec<Variable>,
@@ -100,8 +116,11 @@ where
     /// Fourth wire witness vector.
     pub(crate) w_4: Vec<Variable>,
 
-    /// Public lookup table
-    pub(crate) lookup_table: LookupTable<F>,
+    /// Public lookup tables
+    pub(crate) lookup_tables: LookupTables<F>,
+
+    /// Argument proving the lookups of the circuit.
+    pub(crate) lookup_mode: LookupMode,
 
     /// A zero Variable that is a part of the circuit description.
     /// We reserve a variable to be zero in the system
@@ -124,9 +143,17 @@ where
     F: PrimeField,
     P: ModelParameters<BaseField = F>,
 {
-    /// Returns the length of the circuit that can accomodate the lookup table.
+    /// Returns the number of rows needed by the circuit, which holds its
+    /// gates, including the rows blinding the wires and the permutation
+    /// polynomial, and the rows of its lookup tables, which may outnumber
+    /// the gates.
     fn total_size(&self) -> usize {
-        max(self.n, self.lookup_table.size())
+        max(self.n, self.lookup_tables.size())
+    }
+
+    /// Returns the number of gates in the circuit.
+    pub fn circuit_size(&self) -> usize {
+        self.n
     }
 
     /// Returns the smallest power of two needed for the curcuit.
@@ -134,11 +161,43 @@ where
         self.total_size().next_power_of_two()
     }
 
+    /// Returns the degree the commitment key must support to preprocess and
+    /// prove the circuit, which is the size of the domain holding its gates
+    /// and lookup tables.
+    ///
+    /// Universal parameters set up for a smaller degree are rejected with an
+    /// [`Error::CircuitSizeMismatch`].
+    pub fn required_srs_degree(&self) -> usize {
+        self.circuit_bound()
+    }
+
+    /// Checks that a commitment key supporting `supported_degree` can commit
+    /// to the polynomials of the circuit.
+    pub(crate) fn check_srs_degree(
+        &self,
+        supported_degree: usize,
+    ) -> Result<(), Error> {
+        let circuit_size = self.required_srs_degree();
+        if supported_degree < circuit_size {
+            return Err(Error::CircuitSizeMismatch {
+                circuit_size,
+                supported_degree,
+            });
+        }
+        Ok(())
+    }
+
     /// Returns a reference to the [`PublicInputs`] stored in the
     /// [`StandardComposer`].
     pub fn get_pi(&self) -> &PublicInputs<F> {
         &self.public_inputs
     }
+
+    /// Sets `value` as the public input of the current gate.
+    pub(crate) fn insert_pi(&mut self, value: F) {
+        self.pi_positions.push(self.n);
+        self.public_inputs.insert(self.n, value);
+    }
 }
 
 impl<F, P> Default for StandardComposer<F, P>
@@ -196,12 +255,18 @@ where
             q_fixed_group_add: Vec::with_capacity(expected_size),
             q_variable_group_add: Vec::with_capacity(expected_size),
             q_lookup: Vec::with_capacity(expected_size),
+            q_table: Vec::new(),
+            q_poseidon: Vec::with_capacity(expected_size),
+            custom_selectors: Vec::new(),
+            custom_gates: CustomGates::new(),
             public_inputs: PublicInputs::new(),
+            pi_positions: Vec::new(),
             w_l: Vec::with_capacity(expected_size),
             w_r: Vec::with_capacity(expected_size),
             w_o: Vec::with_capacity(expected_size),
             w_4: Vec::with_capacity(expected_size),
-            lookup_table: LookupTable::new(),
+            lookup_tables: LookupTables::new(),
+            lookup_mode: LookupMode::default(),
             zero_var: Variable(0),
             variables: HashMap::with_capacity(expected_size),
             perm: Permutation::new(),
@@ -277,10 +342,11 @@ where
         self.q_logic.push(F::zero());
         self.q_fixed_group_add.push(F::zero());
         self.q_variable_group_add.push(F::zero());
+        self.q_poseidon.push(F::zero());
         self.q_lookup.push(F::zero());
 
         if let Some(pi) = pi {
-            self.public_inputs.insert(self.n, pi);
+            self.insert_pi(pi);
         }
 
         self.perm
@@ -467,9 +533,11 @@ where
     }
 
     /// This function adds two dummy gates to the circuit
-    /// description which are guaranteed to always satisfy the gate equation.
+    /// description which are guaranteed to always satisfy the gate equation,
+    /// their wires being looked up in the `table` returned by
+    /// [`add_dummy_lookup_table`](Self::add_dummy_lookup_table).
     /// This function is only used in benchmarking
-    pub fn add_dummy_constraints(&mut self) {
+    pub fn add_dummy_constraints(&mut self, table: LookupTableId) {
         let var_six = self.add_input(F::from(6u64));
         let var_one = self.add_input(F::one());
         let var_seven = self.add_input(F::from(7u64));
@@ -486,7 +554,10 @@ where
         self.q_logic.push(F::zero());
         self.q_fixed_group_add.push(F::zero());
         self.q_variable_group_add.push(F::zero());
+        self.q_poseidon.push(F::zero());
         self.q_lookup.push(F::one());
+        self.q_table.resize(self.n, F::zero());
+        self.q_table.push(table.to_field());
         self.w_l.push(var_six);
         self.w_r.push(var_seven);
         self.w_o.push(var_min_twenty);
@@ -511,7 +582,10 @@ where
         self.q_logic.push(F::zero());
         self.q_fixed_group_add.push(F::zero());
         self.q_variable_group_add.push(F::zero());
+        self.q_poseidon.push(F::zero());
         self.q_lookup.push(F::one());
+        self.q_table.resize(self.n, F::zero());
+        self.q_table.push(table.to_field());
         self.w_l.push(var_min_twenty);
         self.w_r.push(var_six);
         self.w_o.push(var_seven);
@@ -526,30 +600,28 @@ where
         self.n += 1;
     }
 
-    /// Adds 3 dummy rows to the lookup table
+    /// Adds a lookup table of 3 dummy rows named `dummy`
     /// The first rows match the witness values used for `add_dummy_constraint`
     /// This function is only used for benchmarking
-    pub fn add_dummy_lookup_table(&mut self) {
-        self.lookup_table.insert_row(
+    pub fn add_dummy_lookup_table(&mut self) -> Result<LookupTableId, Error> {
+        let mut table = LookupTable::new();
+        table.insert_row(
             F::from(6u64),
             F::from(7u64),
             -F::from(20u64),
             F::one(),
         );
 
-        self.lookup_table.insert_row(
+        table.insert_row(
             -F::from(20u64),
             F::from(6u64),
             F::from(7u64),
             F::zero(),
         );
 
-        self.lookup_table.insert_row(
-            F::from(3u64),
-            F::one(),
-            F::from(4u64),
-            F::from(9u64),
-        );
+        table.insert_row(F::from(3u64), F::one(), F::from(4u64), F::from(9u64));
+
+        self.add_lookup_table("dummy", table)
     }
 
     /// This function is used to add a blinding factors to the witness
@@ -586,6 +658,7 @@ where
             self.q_logic.push(F::zero());
             self.q_fixed_group_add.push(F::zero());
             self.q_variable_group_add.push(F::zero());
+            self.q_poseidon.push(F::zero());
             self.q_lookup.push(F::zero());
 
             self.perm.add_variables_to_map(
@@ -614,6 +687,7 @@ where
         self.q_logic.push(F::zero());
         self.q_fixed_group_add.push(F::zero());
         self.q_variable_group_add.push(F::zero());
+        self.q_poseidon.push(F::zero());
         self.q_lookup.push(F::zero());
 
         self.perm.add_variables_to_map(
@@ -0,0 +1,688 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Custom Gates
+
+use crate::{
+    constraint_system::{StandardComposer, Variable},
+    error::Error,
+    proof_system::{
+        custom::{CustomGateId, CustomGates},
+        expression::{Expression, ExpressionVals},
+        GateConstraint, WitnessValues,
+    },
+};
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+
+impl<F, P> StandardComposer<F, P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    /// Registers the custom gate `G` under `name`, giving it a selector of
+    /// its own. See the [`custom`](crate::proof_system::custom) module for
+    /// the values its constraint can use.
+    ///
+    /// The [`Verifier`](crate::proof_system::Verifier) of the circuit must
+    /// register the same gate under the same `name`.
+    pub fn register_gate<G>(
+        &mut self,
+        name: &str,
+    ) -> Result<CustomGateId, Error>
+    where
+        G: GateConstraint<F> + 'static,
+    {
+        let gate = self.custom_gates.register::<G>(name)?;
+        self.custom_selectors.push(Vec::new());
+        Ok(gate)
+    }
+
+    /// Registers the custom gate with the identity `expression` under `name`,
+    /// giving it a selector of its own.
+    ///
+    /// The [`Verifier`](crate::proof_system::Verifier) of the circuit must
+    /// register the same expression under the same `name`.
+    pub fn register_expression_gate(
+        &mut self,
+        name: &str,
+        expression: Expression<F>,
+    ) -> Result<CustomGateId, Error> {
+        let gate = self.custom_gates.register_expression(name, expression)?;
+        self.custom_selectors.push(Vec::new());
+        Ok(gate)
+    }
+
+    /// Returns the custom gates registered to the [`StandardComposer`].
+    pub fn custom_gates(&self) -> &CustomGates<F> {
+        &self.custom_gates
+    }
+
+    /// Adds a gate constrained by the custom gate `gate` to the circuit, with
+    /// the left, right, output and fourth `wires` and the `constants`
+    /// `[q_l, q_r, q_o, q_4, q_c]`.
+    ///
+    /// The values of the wires of the next gate, which the constraint can use,
+    /// are the ones of the gate added after this one.
+    ///
+    /// # Panics
+    /// This function will panic if `gate` was not registered to this
+    /// [`StandardComposer`].
+    pub fn custom_gate(
+        &mut self,
+        gate: CustomGateId,
+        wires: [Variable; 4],
+        constants: [F; 5],
+    ) {
+        let [a, b, c, d] = wires;
+        let [q_l, q_r, q_o, q_4, q_c] = constants;
+
+        self.w_l.push(a);
+        self.w_r.push(b);
+        self.w_o.push(c);
+        self.w_4.push(d);
+
+        // Add selector vectors
+        self.q_m.push(F::zero());
+        self.q_l.push(q_l);
+        self.q_r.push(q_r);
+        self.q_o.push(q_o);
+        self.q_4.push(q_4);
+        self.q_c.push(q_c);
+        self.q_arith.push(F::zero());
+        self.q_range.push(F::zero());
+        self.q_logic.push(F::zero());
+        self.q_fixed_group_add.push(F::zero());
+        self.q_variable_group_add.push(F::zero());
+        self.q_poseidon.push(F::zero());
+        self.q_lookup.push(F::zero());
+
+        let selector = &mut self.custom_selectors[gate.0];
+        selector.resize(self.n, F::zero());
+        selector.push(F::one());
+
+        self.perm.add_variables_to_map(a, b, c, d, self.n);
+        self.n += 1;
+    }
+
+    /// Returns `true` if the gate at `index` satisfies `expression` with the
+    /// current witness, the gate after the last one of the circuit being the
+    /// first one if the circuit fills its domain and a gate of zeros
+    /// otherwise.
+    pub fn is_gate_satisfied(
+        &self,
+        expression: &Expression<F>,
+        index: usize,
+    ) -> bool {
+        let value = |wire: &[Variable], i: usize| {
+            wire.get(i).map_or_else(F::zero, |var| self.variables[var])
+        };
+        let next = (index + 1) % self.circuit_bound();
+        let wit_vals = WitnessValues {
+            a_val: value(&self.w_l, index),
+            b_val: value(&self.w_r, index),
+            c_val: value(&self.w_o, index),
+            d_val: value(&self.w_4, index),
+        };
+        let vals = ExpressionVals {
+            a_next_val: value(&self.w_l, next),
+            b_next_val: value(&self.w_r, next),
+            c_next_val: value(&self.w_o, next),
+            d_next_val: value(&self.w_4, next),
+            q_l_val: self.q_l[index],
+            q_r_val: self.q_r[index],
+            q_o_val: self.q_o[index],
+            q_4_val: self.q_4[index],
+            q_c_val: self.q_c[index],
+        };
+        expression.is_satisfied(wit_vals, &vals)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test, batch_test_field_params,
+        circuit::{verify_proof, verify_proof_with_custom_gates, Circuit},
+        commitment::HomomorphicCommitment,
+        constraint_system::helper::*,
+        error::to_pc_error,
+        proof_system::{
+            ecc::{CurveAddition, FixedBaseScalarMul},
+            expression::Value,
+            logic::Logic,
+            range::Range,
+            CustomEvaluations, CustomValues,
+        },
+        transcript::Transcript,
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::twisted_edwards_extended::GroupAffine as TEGroupAffine;
+    use core::marker::PhantomData;
+    use rand_core::OsRng;
+
+    /// Values of the [`Cube`] gate.
+    struct CubeVals<F>
+    where
+        F: PrimeField,
+    {
+        a_next_val: F,
+    }
+
+    impl<F> CustomValues<F> for CubeVals<F>
+    where
+        F: PrimeField,
+    {
+        fn from_evaluations(custom_evals: &CustomEvaluations<F>) -> Self {
+            let a_next_val = custom_evals.get("a_next_eval");
+            CubeVals { a_next_val }
+        }
+    }
+
+    /// Gate checking that the left wire of the next gate is the cube of the
+    /// left wire.
+    struct Cube<F>(PhantomData<F>);
+
+    impl<F> GateConstraint<F> for Cube<F>
+    where
+        F: PrimeField,
+    {
+        type CustomVals = CubeVals<F>;
+
+        fn constraints(
+            separation_challenge: F,
+            wit_vals: WitnessValues<F>,
+            custom_vals: Self::CustomVals,
+        ) -> F {
+            let a = wit_vals.a_val;
+            (a * a * a - custom_vals.a_next_val) * separation_challenge
+        }
+    }
+
+    /// Returns the identity of the [`Cube`] gate.
+    fn cube_expression<F>() -> Expression<F>
+    where
+        F: PrimeField,
+    {
+        let a = Expression::from(Value::A);
+        (a.pow(3) - Expression::from(Value::ANext)) * Expression::challenge()
+    }
+
+    /// Returns the identity of a gate checking that the left wire of the next
+    /// gate is `(a + q_c)^3`.
+    fn shifted_cube_expression<F>() -> Expression<F>
+    where
+        F: PrimeField,
+    {
+        let a = Expression::from(Value::A) + Expression::from(Value::Qc);
+        (a.pow(3) - Expression::from(Value::ANext)) * Expression::challenge()
+    }
+
+    /// Values of the [`Product`] gate.
+    struct ProductVals<F>
+    where
+        F: PrimeField,
+    {
+        q_c_val: F,
+    }
+
+    impl<F> CustomValues<F> for ProductVals<F>
+    where
+        F: PrimeField,
+    {
+        fn from_evaluations(custom_evals: &CustomEvaluations<F>) -> Self {
+            let q_c_val = custom_evals.get("q_c_eval");
+            ProductVals { q_c_val }
+        }
+    }
+
+    /// Gate checking that `a * b * c + q_c = d`.
+    struct Product<F>(PhantomData<F>);
+
+    impl<F> GateConstraint<F> for Product<F>
+    where
+        F: PrimeField,
+    {
+        type CustomVals = ProductVals<F>;
+
+        fn constraints(
+            separation_challenge: F,
+            wit_vals: WitnessValues<F>,
+            custom_vals: Self::CustomVals,
+        ) -> F {
+            (wit_vals.a_val * wit_vals.b_val * wit_vals.c_val
+                + custom_vals.q_c_val
+                - wit_vals.d_val)
+                * separation_challenge
+        }
+    }
+
+    /// Checks that `x^3 * b * c + 5 = d` with the [`Cube`] and [`Product`]
+    /// gates.
+    fn cube_product_gadget<F, P>(
+        composer: &mut StandardComposer<F, P>,
+        x: u64,
+        b: u64,
+        c: u64,
+        d: u64,
+    ) -> Result<(), Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let cube = composer.register_gate::<Cube<F>>("cube")?;
+        cube_product_gates(composer, cube, x, b, c, d)
+    }
+
+    /// Checks that `x^3 * b * c + 5 = d` with the `cube` gate and the
+    /// [`Product`] gate.
+    fn cube_product_gates<F, P>(
+        composer: &mut StandardComposer<F, P>,
+        cube: CustomGateId,
+        x: u64,
+        b: u64,
+        c: u64,
+        d: u64,
+    ) -> Result<(), Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let product = composer.register_gate::<Product<F>>("product")?;
+        let zero = composer.zero_var();
+        let x_value = F::from(x);
+        let x = composer.add_input(x_value);
+        let x_cube = composer.add_input(x_value * x_value * x_value);
+        let b = composer.add_input(F::from(b));
+        let c = composer.add_input(F::from(c));
+        let d = composer.add_input(F::from(d));
+        composer.custom_gate(cube, [x, zero, zero, zero], [F::zero(); 5]);
+        composer.custom_gate(
+            product,
+            [x_cube, b, c, d],
+            [F::zero(), F::zero(), F::zero(), F::zero(), F::from(5u64)],
+        );
+        Ok(())
+    }
+
+    fn test_custom_gates<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                cube_product_gadget(composer, 3, 2, 1, 59).unwrap();
+            },
+            32,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+        assert_eq!(res.unwrap().t_comms.len(), 4);
+
+        // Should fail as `3^3 * 2 * 1 + 5 != 60`
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                cube_product_gadget(composer, 3, 2, 1, 60).unwrap();
+            },
+            32,
+        );
+        assert!(res.is_err());
+
+        // Should fail as the constant `5` read by the product gate is opened
+        let res = tampered_gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                cube_product_gadget(composer, 3, 2, 1, 59).unwrap();
+            },
+            32,
+            |proof| tamper_custom_eval(proof, "q_c_eval"),
+        );
+        assert!(
+            matches!(res, Err(Error::OpeningCheckFailure)),
+            "{:?}",
+            res.err()
+        );
+    }
+
+    fn test_expression_gates<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let cube = composer
+                    .register_expression_gate("cube", cube_expression())
+                    .unwrap();
+                cube_product_gates(composer, cube, 3, 2, 1, 59).unwrap();
+                let index = composer.circuit_size() - 2;
+                assert!(composer.is_gate_satisfied(&cube_expression(), index));
+            },
+            32,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+
+        // Should fail as `4^3 != 27`
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let cube = composer
+                    .register_expression_gate("cube", cube_expression())
+                    .unwrap();
+                let zero = composer.zero_var();
+                let x = composer.add_input(F::from(4u64));
+                let x_cube = composer.add_input(F::from(27u64));
+                composer.custom_gate(
+                    cube,
+                    [x, zero, zero, zero],
+                    [F::zero(); 5],
+                );
+                composer.constrain_to_constant(x_cube, F::from(27u64), None);
+                let index = composer.circuit_size() - 2;
+                assert!(!composer.is_gate_satisfied(&cube_expression(), index));
+            },
+            32,
+        );
+        assert!(res.is_err());
+
+        // Should fail as the selector value `q_c` read by the expression is
+        // opened
+        let res = tampered_gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let shifted_cube = composer
+                    .register_expression_gate(
+                        "shifted_cube",
+                        shifted_cube_expression(),
+                    )
+                    .unwrap();
+                let zero = composer.zero_var();
+                let x = composer.add_input(F::from(2u64));
+                let y = composer.add_input(F::from(125u64));
+                composer.custom_gate(
+                    shifted_cube,
+                    [x, zero, zero, zero],
+                    [F::zero(), F::zero(), F::zero(), F::zero(), F::from(3u64)],
+                );
+                composer.constrain_to_constant(y, F::from(125u64), None);
+                let index = composer.circuit_size() - 2;
+                assert!(composer
+                    .is_gate_satisfied(&shifted_cube_expression(), index));
+            },
+            32,
+            |proof| tamper_custom_eval(proof, "q_c_eval"),
+        );
+        assert!(
+            matches!(res, Err(Error::OpeningCheckFailure)),
+            "{:?}",
+            res.err()
+        );
+    }
+
+    /// Returns the identity of a gate checking that the left wire of the next
+    /// gate is `(a + q_c)^5`, of degree 5.
+    fn fifth_power_expression<F>() -> Expression<F>
+    where
+        F: PrimeField,
+    {
+        let a = Expression::from(Value::A) + Expression::from(Value::Qc);
+        (a.pow(5) - Expression::from(Value::ANext)) * Expression::challenge()
+    }
+
+    /// Checks that `(x + 3)^5 = y` with the gate of [`fifth_power_expression`].
+    fn fifth_power_gadget<F, P>(
+        composer: &mut StandardComposer<F, P>,
+        x: u64,
+        y: u64,
+    ) where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let fifth_power = composer
+            .register_expression_gate("fifth_power", fifth_power_expression())
+            .unwrap();
+        let zero = composer.zero_var();
+        let x = composer.add_input(F::from(x));
+        let y_value = F::from(y);
+        let y = composer.add_input(y_value);
+        composer.custom_gate(
+            fifth_power,
+            [x, zero, zero, zero],
+            [F::zero(), F::zero(), F::zero(), F::zero(), F::from(3u64)],
+        );
+        composer.constrain_to_constant(y, y_value, None);
+    }
+
+    fn test_high_degree_gate<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        assert_eq!(fifth_power_expression::<F>().degree(), 5);
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                fifth_power_gadget(composer, 2, 3125);
+            },
+            32,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+        assert_eq!(res.unwrap().t_comms.len(), 5);
+
+        // Should fail as `(2 + 3)^5 != 3124`
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                fifth_power_gadget(composer, 2, 3124);
+            },
+            32,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_builtin_gate_expressions<F, P>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut composer = StandardComposer::<F, P>::new();
+        let value = composer.add_input(F::from(0x1234u64));
+        composer.range_gate(value, 16);
+        let a = composer.add_input(F::from(0xa5u64));
+        let b = composer.add_input(F::from(0x3cu64));
+        composer.xor_gate(a, b, 8);
+        composer.and_gate(a, b, 8);
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator = TEGroupAffine::new(x, y);
+        let point = composer.add_affine(generator);
+        composer.point_addition_gate(point, point);
+        let scalar = composer.add_input(F::from(0xabcdu64));
+        composer.fixed_base_scalar_mul(scalar, generator);
+
+        let gates = [
+            (&composer.q_range, Range::expression()),
+            (&composer.q_logic, Logic::expression()),
+            (
+                &composer.q_variable_group_add,
+                CurveAddition::<F, P>::expression(),
+            ),
+            (
+                &composer.q_fixed_group_add,
+                FixedBaseScalarMul::<F, P>::expression(),
+            ),
+        ];
+        for (selector, expression) in gates.iter() {
+            let indices = (0..composer.circuit_size())
+                .filter(|i| !selector[*i].is_zero())
+                .collect::<Vec<_>>();
+            assert!(!indices.is_empty());
+            for index in indices {
+                assert!(composer.is_gate_satisfied(expression, index));
+            }
+        }
+
+        // Breaks the first digit of the range gate
+        let index = composer
+            .q_range
+            .iter()
+            .position(|selector| !selector.is_zero())
+            .unwrap();
+        let digit = composer.w_o[index];
+        composer.variables.insert(digit, F::from(1000u64));
+        assert!(!composer.is_gate_satisfied(&Range::expression(), index));
+    }
+
+    fn test_register_gate_twice<F, P>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut composer = StandardComposer::<F, P>::new();
+        assert!(composer.register_gate::<Cube<F>>("cube").is_ok());
+        let res = composer.register_gate::<Product<F>>("cube");
+        assert!(
+            matches!(
+                res,
+                Err(Error::CustomGateAlreadyRegistered { ref name })
+                    if name == "cube"
+            ),
+            "{:?}",
+            res
+        );
+        assert_eq!(composer.custom_gates().len(), 1);
+    }
+
+    /// Circuit checking that `x^3 * b * c + 5 = d`.
+    #[derive(derivative::Derivative)]
+    #[derivative(Default(bound = ""))]
+    struct CubeProductCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        values: [u64; 4],
+        _phantom: PhantomData<(F, P)>,
+    }
+
+    impl<F, P> Circuit<F, P> for CubeProductCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        const CIRCUIT_ID: [u8; 32] = [0xff; 32];
+
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<F, P>,
+        ) -> Result<(), Error> {
+            let [x, b, c, d] = self.values;
+            cube_product_gadget(composer, x, b, c, d)
+        }
+    }
+
+    fn test_verify_custom_gates<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = (|| -> Result<(), Error> {
+            let pp = PC::setup(1 << 5, None, &mut OsRng)
+                .map_err(to_pc_error::<F, PC>)?;
+            let (pk, vk) =
+                CubeProductCircuit::<F, P>::default().compile::<PC>(&pp)?;
+            let (proof, pi) = CubeProductCircuit::<F, P> {
+                values: [2, 3, 4, 101],
+                _phantom: PhantomData,
+            }
+            .gen_proof::<PC, Transcript>(&pp, pk, b"custom")?;
+
+            let mut custom_gates = CustomGates::new();
+            custom_gates.register::<Product<F>>("product")?;
+            let res = verify_proof_with_custom_gates::<F, P, PC, Transcript>(
+                &pp,
+                vk.clone(),
+                &custom_gates,
+                &proof,
+                &pi,
+                b"custom",
+            );
+            assert!(
+                matches!(
+                    res,
+                    Err(Error::UnregisteredCustomGate { ref name })
+                        if name == "cube"
+                ),
+                "{:?}",
+                res
+            );
+            assert!(matches!(
+                verify_proof::<F, P, PC, Transcript>(
+                    &pp,
+                    vk.clone(),
+                    &proof,
+                    &pi,
+                    b"custom",
+                ),
+                Err(Error::UnregisteredCustomGate { .. })
+            ));
+
+            custom_gates.register::<Cube<F>>("cube")?;
+            verify_proof_with_custom_gates::<F, P, PC, Transcript>(
+                &pp,
+                vk,
+                &custom_gates,
+                &proof,
+                &pi,
+                b"custom",
+            )
+        })();
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [
+            test_custom_gates,
+            test_expression_gates,
+            test_high_degree_gate,
+            test_verify_custom_gates
+        ],
+        []
+        => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+    batch_test_field_params!(
+        [test_builtin_gate_expressions, test_register_gate_twice],
+        []
+        => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [
+            test_custom_gates,
+            test_expression_gates,
+            test_high_degree_gate,
+            test_verify_custom_gates
+        ],
+        []
+        => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+    batch_test_field_params!(
+        [test_builtin_gate_expressions, test_register_gate_twice],
+        []
+        => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
@@ -87,6 +87,7 @@ where
         self.q_o.push(F::zero());
         self.q_fixed_group_add.push(F::one());
         self.q_variable_group_add.push(F::zero());
+        self.q_poseidon.push(F::zero());
 
         self.q_m.push(F::zero());
         self.q_4.push(F::zero());
@@ -73,6 +73,7 @@ where
         self.q_logic.extend(&zeros);
         self.q_fixed_group_add.extend(&zeros);
         self.q_lookup.extend(&zeros);
+        self.q_poseidon.extend(&zeros);
 
         self.q_variable_group_add.push(F::one());
         self.q_variable_group_add.push(F::zero());
@@ -8,7 +8,7 @@ use super::StandardComposer;
 use crate::{
     commitment::HomomorphicCommitment,
     error::{to_pc_error, Error},
-    proof_system::{Prover, Verifier},
+    proof_system::{Proof, Prover, Verifier},
 };
 use ark_ec::TEModelParameters;
 use ark_ff::PrimeField;
@@ -32,13 +32,47 @@ pub(crate) fn dummy_gadget<F, P>(
     }
 }
 
+/// Adds one to the evaluation of `proof` labelled `label`, which a sound
+/// verifier must notice.
+#[allow(dead_code)]
+pub(crate) fn tamper_custom_eval<F, PC>(proof: &mut Proof<F, PC>, label: &str)
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    proof
+        .evaluations
+        .custom_evals
+        .vals
+        .iter_mut()
+        .find(|(eval_label, _)| eval_label == label)
+        .expect("evaluation not found")
+        .1 += F::one();
+}
+
 /// Takes a generic gadget function with no auxillary input and tests whether it
 /// passes an end-to-end test.
 #[allow(dead_code)]
 pub(crate) fn gadget_tester<F, P, PC>(
     gadget: fn(&mut StandardComposer<F, P>),
     n: usize,
-) -> Result<crate::proof_system::Proof<F, PC>, Error>
+) -> Result<Proof<F, PC>, Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    PC: HomomorphicCommitment<F>,
+{
+    tampered_gadget_tester(gadget, n, |_| {})
+}
+
+/// Tests a gadget end-to-end as [`gadget_tester`] does, changing the proof
+/// with `tamper` before verifying it.
+#[allow(dead_code)]
+pub(crate) fn tampered_gadget_tester<F, P, PC>(
+    gadget: fn(&mut StandardComposer<F, P>),
+    n: usize,
+    tamper: fn(&mut Proof<F, PC>),
+) -> Result<Proof<F, PC>, Error>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
@@ -61,7 +95,7 @@ where
 
         // Commit Key
         let (ck, _) =
-            PC::trim(&universal_params, prover.circuit_bound(), 0, None)
+            PC::trim(&universal_params, prover.required_srs_degree(), 0, None)
                 .map_err(to_pc_error::<F, PC>)?;
 
         // Preprocess circuit
@@ -72,12 +106,14 @@ where
         let public_inputs = prover.cs.get_pi().clone();
 
         // Compute Proof
-        (prover.prove(&ck)?, public_inputs)
+        let mut proof = prover.prove(&ck)?;
+        tamper(&mut proof);
+        (proof, public_inputs)
     };
     // Verifiers view
     //
     // Create a Verifier object
-    let mut verifier = Verifier::new(b"demo");
+    let mut verifier = Verifier::<F, P, PC>::new(b"demo");
 
     // Additionally key the transcript
     verifier.key_transcript(b"key", b"additional seed information");
@@ -87,7 +123,7 @@ where
 
     // Compute Commit and Verifier Key
     let (ck, vk) =
-        PC::trim(&universal_params, verifier.circuit_bound(), 0, None)
+        PC::trim(&universal_params, verifier.required_srs_degree(), 0, None)
             .map_err(to_pc_error::<F, PC>)?;
 
     // Preprocess circuit
@@ -248,6 +248,7 @@ where
             self.q_range.push(F::zero());
             self.q_fixed_group_add.push(F::zero());
             self.q_variable_group_add.push(F::zero());
+            self.q_poseidon.push(F::zero());
             self.q_lookup.push(F::zero());
             match is_xor_gate {
                 true => {
@@ -270,6 +271,7 @@ where
         self.q_range.push(F::zero());
         self.q_fixed_group_add.push(F::zero());
         self.q_variable_group_add.push(F::zero());
+        self.q_poseidon.push(F::zero());
         self.q_lookup.push(F::zero());
         self.q_c.push(F::zero());
         self.q_logic.push(F::zero());
@@ -4,7 +4,11 @@
 //
 // Copyright (c) ZK-Garage. All rights reserved.
 
-use crate::constraint_system::{StandardComposer, Variable};
+use crate::{
+    constraint_system::{StandardComposer, Variable},
+    error::Error,
+    lookup::{LookupMode, LookupTable, LookupTableId, LookupTables},
+};
 use ark_ec::TEModelParameters;
 use ark_ff::PrimeField;
 
@@ -13,10 +17,74 @@ where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
 {
+    /// Adds `table` to the lookup tables of the circuit under `name`, so that
+    /// gadgets can query it with [`StandardComposer::lookup_gate`], returning
+    /// an [`Error::LookupTableAlreadyAdded`] if the name is already taken.
+    ///
+    /// The [`Verifier`](crate::proof_system::Verifier) of the circuit must add
+    /// the same tables in the same order.
+    pub fn add_lookup_table(
+        &mut self,
+        name: &str,
+        table: LookupTable<F>,
+    ) -> Result<LookupTableId, Error> {
+        self.lookup_tables.insert(name, table)
+    }
+
+    /// Returns the handle to the lookup table added under `name`, if any.
+    pub fn lookup_table_id(&self, name: &str) -> Option<LookupTableId> {
+        self.lookup_tables.id(name)
+    }
+
+    /// Returns the handle to the lookup table added under `name`, adding the
+    /// table built by `table` under this name first if there is none, so that
+    /// a gadget used several times in a circuit adds its table once.
+    pub fn get_or_add_lookup_table<T>(
+        &mut self,
+        name: &str,
+        table: T,
+    ) -> LookupTableId
+    where
+        T: FnOnce() -> LookupTable<F>,
+    {
+        match self.lookup_tables.id(name) {
+            Some(id) => id,
+            None => self
+                .lookup_tables
+                .insert(name, table())
+                .expect("the name of the table is not taken"),
+        }
+    }
+
+    /// Returns the lookup tables added to the [`StandardComposer`].
+    pub fn lookup_tables(&self) -> &LookupTables<F> {
+        &self.lookup_tables
+    }
+
+    /// Sets the argument proving the lookups of the circuit, which is
+    /// [`LookupMode::Plookup`] by default.
+    ///
+    /// The mode is recorded in the keys of the circuit, so the
+    /// [`Verifier`](crate::proof_system::Verifier) of the circuit must set the
+    /// same one.
+    pub fn set_lookup_mode(&mut self, mode: LookupMode) {
+        self.lookup_mode = mode;
+    }
+
+    /// Returns the argument proving the lookups of the circuit.
+    pub fn lookup_mode(&self) -> LookupMode {
+        self.lookup_mode
+    }
+
     /// Adds a plookup gate to the circuit with its corresponding
-    /// constraints.
+    /// constraints, the row `(a, b, c, d)` being looked up in `table` only.
+    ///
+    /// # Note
+    /// The circuit cannot be proven if `table` was not added to this
+    /// [`StandardComposer`].
     pub fn lookup_gate(
         &mut self,
+        table: LookupTableId,
         a: Variable,
         b: Variable,
         c: Variable,
@@ -46,13 +114,16 @@ where
         self.q_logic.push(F::zero());
         self.q_fixed_group_add.push(F::zero());
         self.q_variable_group_add.push(F::zero());
+        self.q_poseidon.push(F::zero());
 
         // For a lookup gate, only one selector poly is
         // turned on as the output is inputted directly
         self.q_lookup.push(F::one());
+        self.q_table.resize(self.n, F::zero());
+        self.q_table.push(table.to_field());
 
         if let Some(pi) = pi {
-            self.public_inputs.insert(self.n, pi);
+            self.insert_pi(pi);
         }
 
         self.perm.add_variables_to_map(a, b, c, d, self.n);
@@ -68,7 +139,7 @@ mod test {
     use super::*;
     use crate::{
         batch_test, commitment::HomomorphicCommitment,
-        constraint_system::helper::*, lookup::LookupTable,
+        constraint_system::helper::*,
     };
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
@@ -84,7 +155,9 @@ mod test {
             |composer: &mut StandardComposer<F, P>| {
                 let rng = &mut OsRng;
 
-                composer.lookup_table = LookupTable::<F>::xor_table(0, 4);
+                let xor = composer
+                    .add_lookup_table("xor", LookupTable::xor_table(0, 4))
+                    .unwrap();
 
                 let negative_one = composer.add_input(-F::one());
 
@@ -105,6 +178,7 @@ mod test {
                 let xor23_var = composer.add_input(F::from(xor23));
 
                 composer.lookup_gate(
+                    xor,
                     rand1_var,
                     rand2_var,
                     xor12_var,
@@ -113,6 +187,7 @@ mod test {
                 );
 
                 composer.lookup_gate(
+                    xor,
                     rand1_var,
                     rand3_var,
                     xor13_var,
@@ -121,6 +196,7 @@ mod test {
                 );
 
                 composer.lookup_gate(
+                    xor,
                     rand2_var,
                     rand3_var,
                     xor23_var,
@@ -141,10 +217,145 @@ mod test {
         assert!(res.is_ok(), "{:?}", res.err().unwrap());
     }
 
+    /// Adds the tables `identity` and `successor`, holding the rows
+    /// `(x, 0, x, 0)` and `(x, 0, x + 1, 0)`, and looks up `(x, 0, y, 0)` in
+    /// the given one.
+    fn lookup_in_table<F, P>(
+        composer: &mut StandardComposer<F, P>,
+        table: &str,
+        x: u64,
+        y: u64,
+    ) where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut identity = LookupTable::new();
+        let mut successor = LookupTable::new();
+        for x in 0..8u64 {
+            identity.insert_row(F::from(x), F::zero(), F::from(x), F::zero());
+            successor.insert_row(
+                F::from(x),
+                F::zero(),
+                F::from(x + 1),
+                F::zero(),
+            );
+        }
+        composer.add_lookup_table("identity", identity).unwrap();
+        composer.add_lookup_table("successor", successor).unwrap();
+        assert!(matches!(
+            composer.add_lookup_table("identity", LookupTable::new()),
+            Err(Error::LookupTableAlreadyAdded { name }) if name == "identity"
+        ));
+
+        let table = composer.lookup_table_id(table).unwrap();
+        let x = composer.add_input(F::from(x));
+        let y = composer.add_input(F::from(y));
+        composer.lookup_gate(table, x, composer.zero_var(), y, None, None);
+    }
+
+    fn test_lookup_in_intended_table<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                lookup_in_table(composer, "successor", 3, 4)
+            },
+            32,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_lookup_in_other_table<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // The row `(3, 0, 4, 0)` is only in the `successor` table.
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                lookup_in_table(composer, "identity", 3, 4)
+            },
+            32,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_log_up_lookups<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // The xor table has more rows than there are queries, and the row
+        // `(3, 5, 6, -1)` is queried twice.
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                composer.set_lookup_mode(LookupMode::LogUp);
+                let xor = composer
+                    .add_lookup_table("xor", LookupTable::xor_table(0, 4))
+                    .unwrap();
+                let negative_one = composer.add_input(-F::one());
+                let a = composer.add_input(F::from(3u64));
+                let b = composer.add_input(F::from(5u64));
+                let c = composer.add_input(F::from(6u64));
+                let d = composer.add_input(F::from(9u64));
+                let e = composer.add_input(F::from(10u64));
+                let negative_one = Some(negative_one);
+                composer.lookup_gate(xor, a, b, c, negative_one, None);
+                composer.lookup_gate(xor, a, b, c, negative_one, None);
+                composer.lookup_gate(xor, c, a, b, negative_one, None);
+                composer.lookup_gate(xor, a, d, e, negative_one, None);
+            },
+            256,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_log_up_lookup_in_intended_table<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                composer.set_lookup_mode(LookupMode::LogUp);
+                lookup_in_table(composer, "successor", 3, 4)
+            },
+            32,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_log_up_lookup_in_other_table<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                composer.set_lookup_mode(LookupMode::LogUp);
+                lookup_in_table(composer, "identity", 3, 4)
+            },
+            32,
+        );
+        assert!(res.is_err());
+    }
+
     // Bls12-381 tests
     batch_test!(
         [
-            test_plookup_xor
+            test_plookup_xor,
+            test_lookup_in_intended_table,
+            test_lookup_in_other_table,
+            test_log_up_lookups,
+            test_log_up_lookup_in_intended_table,
+            test_log_up_lookup_in_other_table
         ],
         [] => (
             Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
@@ -154,7 +365,12 @@ mod test {
     // Bls12-377 tests
     batch_test!(
         [
-            test_plookup_xor
+            test_plookup_xor,
+            test_lookup_in_intended_table,
+            test_lookup_in_other_table,
+            test_log_up_lookups,
+            test_log_up_lookup_in_intended_table,
+            test_log_up_lookup_in_other_table
         ],
         [] => (
             Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
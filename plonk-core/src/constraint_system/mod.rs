@@ -10,8 +10,10 @@
 
 mod arithmetic;
 mod boolean;
+mod custom;
 mod logic;
 mod lookup;
+mod poseidon;
 mod range;
 
 pub(crate) mod composer;
@@ -0,0 +1,274 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Poseidon Round Gate
+
+use crate::{
+    constraint_system::{StandardComposer, Variable},
+    proof_system::poseidon::{self, WIDTH},
+};
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+
+impl<F, P> StandardComposer<F, P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    /// Applies to `state` one full round of a width-4 Poseidon permutation
+    /// per element of `round_constants`, using the `x^5` S-box and the MDS
+    /// matrix returned by [`poseidon::mds_matrix`], and returns the
+    /// resulting state.
+    ///
+    /// Every round takes a single gate whose output is held by the wires of
+    /// the next gate, so that `round_constants.len() + 1` gates are added to
+    /// the circuit description, the last one holding the output state with
+    /// all of its selectors turned off.
+    pub fn poseidon_round_gate(
+        &mut self,
+        state: [Variable; WIDTH],
+        round_constants: &[[F; WIDTH]],
+    ) -> [Variable; WIDTH] {
+        let mut state = state;
+        for rc in round_constants {
+            self.w_l.push(state[0]);
+            self.w_r.push(state[1]);
+            self.w_o.push(state[2]);
+            self.w_4.push(state[3]);
+
+            // The round constants are stored in the selectors of the wires
+            // they are added to.
+            self.q_l.push(rc[0]);
+            self.q_r.push(rc[1]);
+            self.q_o.push(rc[2]);
+            self.q_4.push(rc[3]);
+            self.q_m.push(F::zero());
+            self.q_c.push(F::zero());
+            self.q_arith.push(F::zero());
+            self.q_range.push(F::zero());
+            self.q_logic.push(F::zero());
+            self.q_fixed_group_add.push(F::zero());
+            self.q_variable_group_add.push(F::zero());
+            self.q_lookup.push(F::zero());
+            self.q_poseidon.push(F::one());
+
+            self.perm.add_variables_to_map(
+                state[0], state[1], state[2], state[3], self.n,
+            );
+            self.n += 1;
+
+            let mut values = [F::zero(); WIDTH];
+            for (value, var) in values.iter_mut().zip(state) {
+                *value = self.variables[&var];
+            }
+            let output = poseidon::round(values, *rc);
+            for (var, value) in state.iter_mut().zip(output) {
+                *var = self.add_input(value);
+            }
+        }
+
+        // The output of the last round must be in the gate right after it.
+        self.w_l.push(state[0]);
+        self.w_r.push(state[1]);
+        self.w_o.push(state[2]);
+        self.w_4.push(state[3]);
+        self.q_l.push(F::zero());
+        self.q_r.push(F::zero());
+        self.q_o.push(F::zero());
+        self.q_4.push(F::zero());
+        self.q_m.push(F::zero());
+        self.q_c.push(F::zero());
+        self.q_arith.push(F::zero());
+        self.q_range.push(F::zero());
+        self.q_logic.push(F::zero());
+        self.q_fixed_group_add.push(F::zero());
+        self.q_variable_group_add.push(F::zero());
+        self.q_lookup.push(F::zero());
+        self.q_poseidon.push(F::zero());
+        self.perm.add_variables_to_map(
+            state[0], state[1], state[2], state[3], self.n,
+        );
+        self.n += 1;
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test, commitment::HomomorphicCommitment,
+        constraint_system::helper::*, error::Error,
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+
+    /// Round constants used by the tests, which are not those of any
+    /// standard instance of Poseidon.
+    fn round_constants<F>(rounds: u64) -> Vec<[F; WIDTH]>
+    where
+        F: PrimeField,
+    {
+        (0..rounds)
+            .map(|r| {
+                let mut rc = [F::zero(); WIDTH];
+                for (i, c) in rc.iter_mut().enumerate() {
+                    *c = F::from(r * WIDTH as u64 + i as u64 + 1);
+                }
+                rc
+            })
+            .collect()
+    }
+
+    /// Computes the rounds with arithmetic gates only.
+    fn arithmetic_rounds<F, P>(
+        composer: &mut StandardComposer<F, P>,
+        state: [Variable; WIDTH],
+        round_constants: &[[F; WIDTH]],
+    ) -> [Variable; WIDTH]
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mds = poseidon::mds_matrix::<F>();
+        let mut state = state;
+        for rc in round_constants {
+            let mut sboxed = state;
+            for (s, c) in sboxed.iter_mut().zip(rc) {
+                let x = composer.arithmetic_gate(|gate| {
+                    gate.witness(*s, *s, None)
+                        .add(F::one(), F::zero())
+                        .constant(*c)
+                });
+                let x_2 = composer.arithmetic_gate(|gate| {
+                    gate.witness(x, x, None).mul(F::one())
+                });
+                let x_4 = composer.arithmetic_gate(|gate| {
+                    gate.witness(x_2, x_2, None).mul(F::one())
+                });
+                *s = composer.arithmetic_gate(|gate| {
+                    gate.witness(x_4, x, None).mul(F::one())
+                });
+            }
+            for (s, row) in state.iter_mut().zip(mds) {
+                let sum = composer.arithmetic_gate(|gate| {
+                    gate.witness(sboxed[0], sboxed[1], None)
+                        .add(row[0], row[1])
+                        .fan_in_3(row[2], sboxed[2])
+                });
+                *s = composer.arithmetic_gate(|gate| {
+                    gate.witness(sum, sboxed[3], None).add(F::one(), row[3])
+                });
+            }
+        }
+        state
+    }
+
+    fn test_poseidon_round_gate<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let round_constants = round_constants::<F>(8);
+                let mut input = [composer.zero_var(); WIDTH];
+                for (i, var) in input.iter_mut().enumerate() {
+                    *var = composer.add_input(F::from(i as u64));
+                }
+                let output =
+                    composer.poseidon_round_gate(input, &round_constants);
+                let expected =
+                    arithmetic_rounds(composer, input, &round_constants);
+                for (out, exp) in output.iter().zip(expected) {
+                    composer.assert_equal(*out, exp);
+                }
+            },
+            200,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+        // The round gate has a degree of 5, which adds a quotient piece.
+        assert_eq!(res.unwrap().t_comms.len(), 5);
+    }
+
+    fn test_poseidon_round_gate_wrong_round_constant<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // The round constants are the evaluations of the `q_l`, `q_r`, `q_o`
+        // and `q_4` selectors, which must be opened against their
+        // commitments.
+        let res = tampered_gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let round_constants = round_constants::<F>(2);
+                let mut input = [composer.zero_var(); WIDTH];
+                for (i, var) in input.iter_mut().enumerate() {
+                    *var = composer.add_input(F::from(i as u64));
+                }
+                composer.poseidon_round_gate(input, &round_constants);
+            },
+            200,
+            |proof| tamper_custom_eval(proof, "q_l_eval"),
+        );
+        assert!(
+            matches!(res, Err(Error::OpeningCheckFailure)),
+            "{:?}",
+            res.err()
+        );
+    }
+
+    fn test_poseidon_round_gate_wrong_output<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let round_constants = round_constants::<F>(2);
+                let mut input = [composer.zero_var(); WIDTH];
+                for (i, var) in input.iter_mut().enumerate() {
+                    *var = composer.add_input(F::from(i as u64));
+                }
+                composer.poseidon_round_gate(input, &round_constants);
+                // Change the value of the state after the first round, which
+                // is held by the wires of the second gate.
+                let var = composer.w_l[composer.n - 2];
+                composer.variables.insert(var, F::from(42u64));
+            },
+            200,
+        );
+        assert!(res.is_err());
+    }
+
+    // Test for Bls12_381
+    batch_test!(
+        [
+            test_poseidon_round_gate,
+            test_poseidon_round_gate_wrong_round_constant,
+            test_poseidon_round_gate_wrong_output
+        ],
+        [] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test for Bls12_377
+    batch_test!(
+        [
+            test_poseidon_round_gate,
+            test_poseidon_round_gate_wrong_round_constant,
+            test_poseidon_round_gate_wrong_output
+        ],
+        [] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
@@ -172,6 +172,7 @@ where
         self.q_4.extend(zeros.iter());
         self.q_fixed_group_add.extend(zeros.iter());
         self.q_variable_group_add.extend(zeros.iter());
+        self.q_poseidon.extend(zeros.iter());
         self.q_range.extend(ones.iter());
         self.q_logic.extend(zeros.iter());
         self.q_lookup.extend(zeros.iter());
@@ -22,6 +22,48 @@ pub enum Error {
     // Prover/Verifier errors
     /// This error occurs when a proof verification fails.
     ProofVerificationError,
+    /// This error occurs when the opening of the aggregated witnesses at the
+    /// evaluation challenge `z` is invalid.
+    OpeningCheckFailure,
+    /// This error occurs when the opening of the shifted aggregated witnesses
+    /// at `z * omega` is invalid.
+    ShiftedOpeningCheckFailure,
+    /// This error occurs when a proof can't be checked at all, because its
+    /// opening proofs don't have the shape expected by the commitment
+    /// scheme or because it leads to degenerate challenges.
+    MalformedProof,
+    /// This error occurs when the prover draws two equal permutation or
+    /// lookup challenges, which happens with negligible probability.
+    DegenerateChallenges,
+    /// This error occurs when the universal parameters, the commitment key
+    /// or the commitment verifier key don't support the size of the circuit.
+    CircuitSizeMismatch {
+        /// Padded size of the circuit
+        circuit_size: usize,
+        /// Degree supported by the commitment verifier key
+        supported_degree: usize,
+    },
+    /// This error occurs when more public inputs are given than the circuit
+    /// has.
+    PublicInputLengthMismatch {
+        /// Number of public inputs of the circuit
+        expected: usize,
+        /// Number of non-zero public inputs given
+        found: usize,
+    },
+    /// This error occurs when a public input is given at a position which
+    /// isn't a public input of the circuit.
+    PublicInputPositionMismatch {
+        /// Position of the unexpected public input
+        position: usize,
+    },
+    /// This error occurs when a proof of a batch fails verification.
+    BatchVerificationError {
+        /// Index of the first invalid proof of the batch
+        index: usize,
+        /// Reason why the proof is invalid
+        error: Box<Error>,
+    },
     /// This error occurs when the circuit is not provided with all of the
     /// required inputs.
     CircuitInputsNotFound,
@@ -35,6 +77,9 @@ pub enum Error {
     /// This error occurs when the Prover structure already contains a
     /// preprocessed circuit inside, but you call preprocess again.
     CircuitAlreadyPreprocessed,
+    /// This error occurs when a hiding proof is requested for a circuit with
+    /// lookup gates, whose lookup polynomials are not blinded.
+    HidingLookupsUnsupported,
 
     // Preprocessing errors
     /// This error occurs when an error triggers during the preprocessing
@@ -82,6 +127,26 @@ pub enum Error {
     ElementNotIndexed,
     /// Cannot commit to table column polynomial
     TablePreProcessingError,
+    /// This error occurs when a lookup table is added to a circuit under the
+    /// name of an already added table.
+    LookupTableAlreadyAdded {
+        /// Name of the table
+        name: String,
+    },
+
+    // Custom gate errors
+    /// This error occurs when a custom gate is registered under the name of
+    /// an already registered gate.
+    CustomGateAlreadyRegistered {
+        /// Name of the gate
+        name: String,
+    },
+    /// This error occurs when a circuit uses a custom gate which is not
+    /// registered to the prover or the verifier.
+    UnregisteredCustomGate {
+        /// Name of the gate
+        name: String,
+    },
 }
 
 impl From<ark_poly_commit::error::Error> for Error {
@@ -121,6 +186,36 @@ impl std::fmt::Display for Error {
             Self::ProofVerificationError => {
                 write!(f, "proof verification failed")
             }
+            Self::OpeningCheckFailure => {
+                write!(f, "opening at the evaluation challenge failed")
+            }
+            Self::ShiftedOpeningCheckFailure => {
+                write!(f, "opening at the shifted evaluation challenge failed")
+            }
+            Self::MalformedProof => write!(f, "proof malformed"),
+            Self::DegenerateChallenges => {
+                write!(f, "challenges not distinct")
+            }
+            Self::CircuitSizeMismatch {
+                circuit_size,
+                supported_degree,
+            } => write!(
+                f,
+                "circuit of size {} exceeds the supported degree {} of the \
+                commitment parameters",
+                circuit_size, supported_degree
+            ),
+            Self::PublicInputLengthMismatch { expected, found } => write!(
+                f,
+                "expected at most {} public inputs, found {}",
+                expected, found
+            ),
+            Self::PublicInputPositionMismatch { position } => {
+                write!(f, "no public input at position {}", position)
+            }
+            Self::BatchVerificationError { index, error } => {
+                write!(f, "verification of proof {} failed: {}", index, error)
+            }
             Self::CircuitInputsNotFound => {
                 write!(f, "circuit inputs not found")
             }
@@ -142,6 +237,9 @@ impl std::fmt::Display for Error {
             Self::CircuitAlreadyPreprocessed => {
                 write!(f, "circuit has already been preprocessed")
             }
+            Self::HidingLookupsUnsupported => {
+                write!(f, "hiding proofs of lookup gates are not supported")
+            }
             Self::DegreeIsZero => {
                 write!(f, "cannot create PublicParameters with max degree 0")
             }
@@ -169,6 +267,15 @@ impl std::fmt::Display for Error {
             Self::TablePreProcessingError => {
                 write!(f, "lookup table not preprocessed correctly")
             }
+            Self::LookupTableAlreadyAdded { name } => {
+                write!(f, "lookup table {} is already added", name)
+            }
+            Self::CustomGateAlreadyRegistered { name } => {
+                write!(f, "custom gate {} is already registered", name)
+            }
+            Self::UnregisteredCustomGate { name } => {
+                write!(f, "custom gate {} is not registered", name)
+            }
         }
     }
 }
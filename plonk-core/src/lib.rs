@@ -36,7 +36,6 @@
 extern crate alloc;
 
 mod permutation;
-mod transcript;
 mod util;
 
 pub mod circuit;
@@ -46,6 +45,7 @@ pub mod error;
 pub mod lookup;
 pub mod prelude;
 pub mod proof_system;
+pub mod transcript;
 
 #[cfg(test)]
 mod test;
@@ -204,6 +204,104 @@ where
     }
 }
 
+/// Handle to a lookup table added to a
+/// [`StandardComposer`](crate::constraint_system::StandardComposer).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LookupTableId(pub(crate) usize);
+
+impl LookupTableId {
+    /// Returns the value of the table-id column at the rows of the table.
+    pub(crate) fn to_field<F>(self) -> F
+    where
+        F: Field,
+    {
+        F::from(self.0 as u64)
+    }
+}
+
+/// Set of lookup tables identified by their names.
+///
+/// The tables are stacked in order of addition into the public table of the
+/// circuit, whose rows are extended with a fifth column holding the
+/// [`LookupTableId`] of the table they come from, so that a query made
+/// against a table can only match rows of that table.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LookupTables<F>
+where
+    F: Field,
+{
+    /// Added tables, in order of addition.
+    tables: Vec<(String, LookupTable<F>)>,
+}
+
+impl<F> LookupTables<F>
+where
+    F: Field,
+{
+    /// Creates an empty set of lookup tables.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds `table` under `name`, returning an
+    /// [`Error::LookupTableAlreadyAdded`] if the name is already taken.
+    pub fn insert(
+        &mut self,
+        name: &str,
+        table: LookupTable<F>,
+    ) -> Result<LookupTableId, Error> {
+        if self.id(name).is_some() {
+            return Err(Error::LookupTableAlreadyAdded {
+                name: name.to_owned(),
+            });
+        }
+        self.tables.push((name.to_owned(), table));
+        Ok(LookupTableId(self.tables.len() - 1))
+    }
+
+    /// Returns the handle to the table added under `name`, if any.
+    pub fn id(&self, name: &str) -> Option<LookupTableId> {
+        self.tables
+            .iter()
+            .position(|(table_name, _)| table_name == name)
+            .map(LookupTableId)
+    }
+
+    /// Returns the table added under `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&LookupTable<F>> {
+        self.tables
+            .iter()
+            .find(|(table_name, _)| table_name == name)
+            .map(|(_, table)| table)
+    }
+
+    /// Returns the names of the added tables, in order of addition.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tables.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the total number of rows of the tables.
+    pub fn size(&self) -> usize {
+        self.tables.iter().map(|(_, table)| table.size()).sum()
+    }
+
+    /// Stacks the tables into 5 distinct multisets for a, b, c, d and the
+    /// table id.
+    pub fn vec_to_multiset(&self) -> Vec<MultiSet<F>> {
+        let mut result = vec![MultiSet::with_capacity(self.size()); 5];
+        for (index, (_, table)) in self.tables.iter().enumerate() {
+            let id = LookupTableId(index).to_field();
+            for row in &table.0 {
+                for (multiset, value) in result.iter_mut().zip(row) {
+                    multiset.push(*value);
+                }
+                result[4].push(id);
+            }
+        }
+        result
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -325,6 +423,39 @@ mod test {
         assert_eq!(xor, F::from(5u64));
     }
 
+    fn test_stacked_tables<F>()
+    where
+        F: Field,
+    {
+        let mut tables = LookupTables::<F>::new();
+        let xor = tables.insert("xor", LookupTable::xor_table(0, 2)).unwrap();
+        let add = tables.insert("add", LookupTable::add_table(0, 1)).unwrap();
+        assert_eq!(tables.id("xor"), Some(xor));
+        assert_eq!(tables.id("add"), Some(add));
+        assert_eq!(tables.id("mul"), None);
+        assert_eq!(tables.size(), 20);
+
+        let columns = tables.vec_to_multiset();
+        assert_eq!(columns.len(), 5);
+        assert!(columns.iter().all(|column| column.len() == 20));
+        assert!(columns[4].0[..16].iter().all(|id| *id == xor.to_field()));
+        assert!(columns[4].0[16..].iter().all(|id| *id == add.to_field()));
+        assert_ne!(xor.to_field::<F>(), add.to_field::<F>());
+    }
+
+    fn test_duplicate_table_name<F>()
+    where
+        F: Field,
+    {
+        let mut tables = LookupTables::<F>::new();
+        tables.insert("xor", LookupTable::xor_table(0, 2)).unwrap();
+        assert!(matches!(
+            tables.insert("xor", LookupTable::xor_table(0, 3)),
+            Err(Error::LookupTableAlreadyAdded { name }) if name == "xor"
+        ));
+        assert_eq!(tables.size(), 16);
+    }
+
     // Bls12-381 tests
     batch_field_test!(
         [
@@ -333,7 +464,9 @@ mod test {
             test_mul_table,
             test_lookup_arity_3,
             test_missing_lookup_value,
-            test_concatenated_table
+            test_concatenated_table,
+            test_stacked_tables,
+            test_duplicate_table_name
         ],
         [] => Bls12_381_scalar_field
     );
@@ -346,7 +479,9 @@ mod test {
             test_mul_table,
             test_lookup_arity_3,
             test_missing_lookup_value,
-            test_concatenated_table
+            test_concatenated_table,
+            test_stacked_tables,
+            test_duplicate_table_name
         ],
         [] => Bls12_377_scalar_field
     );
@@ -7,11 +7,13 @@
 //! Module containing the lookups.
 
 pub(crate) mod lookup_table;
+pub(crate) mod mode;
 pub(crate) mod multiset;
 pub(crate) mod preprocess;
 pub(crate) mod witness_table;
 
-pub use lookup_table::LookupTable;
+pub use lookup_table::{LookupTable, LookupTableId, LookupTables};
+pub use mode::LookupMode;
 pub use multiset::MultiSet;
 pub use preprocess::PreprocessedLookupTable;
 pub use witness_table::WitnessTable;
@@ -0,0 +1,81 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Lookup Arguments
+//!
+//! The queries of the lookup gates of a circuit are compressed into the query
+//! polynomial `f` and the rows of its lookup tables into the table polynomial
+//! `t`, and the lookup argument proves that every value of `f` on the domain
+//! is a value of `t`. The argument used for a circuit is chosen with
+//! [`StandardComposer::set_lookup_mode`](crate::constraint_system::StandardComposer::set_lookup_mode)
+//! and recorded in its keys.
+
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
+};
+
+/// Argument proving the lookups of a circuit.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LookupMode {
+    /// The plookup argument, which commits to the sorted concatenation of the
+    /// queries and the table, split into the polynomials `h_1` and `h_2`, and
+    /// to the grand product `z_2` checking that it is a permutation of them.
+    Plookup,
+
+    /// The LogUp argument, which commits to the multiplicities `m` of the
+    /// rows of the table among the queries and to the running sum `phi` of
+    /// the logarithmic derivatives `1 / (X + f_i) - m_i / (X + t_i)`, whose
+    /// total is zero at a random point.
+    ///
+    /// Its proofs hold one commitment and four evaluations fewer than the
+    /// ones of plookup, and the queries are not sorted by the prover.
+    ///
+    /// # Note
+    /// The table is interpolated over the domain of the circuit, which grows
+    /// to hold its rows, so a few lookups into a large table cost as much to
+    /// prove as with plookup.
+    LogUp,
+}
+
+impl Default for LookupMode {
+    #[inline]
+    fn default() -> Self {
+        Self::Plookup
+    }
+}
+
+impl LookupMode {
+    /// Returns the byte identifying the argument when serialized.
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Plookup => 0,
+            Self::LogUp => 1,
+        }
+    }
+}
+
+impl CanonicalSerialize for LookupMode {
+    #[inline]
+    fn serialize<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        self.to_byte().serialize(writer)
+    }
+
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        self.to_byte().serialized_size()
+    }
+}
+
+impl CanonicalDeserialize for LookupMode {
+    #[inline]
+    fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize(reader)? {
+            0 => Ok(Self::Plookup),
+            1 => Ok(Self::LogUp),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
@@ -173,6 +173,32 @@ where
         Ok((Self(evens), Self(odds)))
     }
 
+    /// Counts the occurrences in the multiset `f` of the elements of the
+    /// multiset calling the method (t), returning the multiset of the counts
+    /// in the order of t. All elements of `f` must exist in t.
+    ///
+    /// The occurrences of an element found several times in t are counted at
+    /// its first position, the other ones having a count of zero.
+    ///
+    /// If we have t: {2,4,1,4} and f: {2,4,4,2,4}, the multiplicities will
+    /// be: {2,3,0,0}.
+    pub fn multiplicities(&self, f: &Self) -> Result<Self, Error> {
+        let mut positions: IndexMap<F, usize> = IndexMap::new();
+        for (index, element) in self.0.iter().enumerate() {
+            positions.entry(*element).or_insert(index);
+        }
+
+        let mut counts = vec![0u64; self.len()];
+        for element in &f.0 {
+            match positions.get(element) {
+                Some(index) => counts[*index] += 1,
+                _ => return Err(Error::ElementNotIndexed),
+            }
+        }
+
+        Ok(counts.into_iter().map(F::from).collect())
+    }
+
     /// Checks whether one mutltiset is a subset of another.
     /// This function will be used to check if the all elements
     /// in set f, from the paper, are contained inside t.
@@ -390,6 +416,38 @@ mod test {
         assert_eq!(odds, h2);
     }
 
+    fn test_multiplicities<F>()
+    where
+        F: Field,
+    {
+        let t = MultiSet(vec![
+            F::from(2u32),
+            F::from(4u32),
+            F::one(),
+            F::from(4u32),
+        ]);
+        let f = MultiSet(vec![
+            F::from(2u32),
+            F::from(4u32),
+            F::from(4u32),
+            F::from(2u32),
+            F::from(4u32),
+        ]);
+
+        let m = t.multiplicities(&f).unwrap();
+        assert_eq!(
+            m,
+            MultiSet(vec![F::from(2u32), F::from(3u32), F::zero(), F::zero()])
+        );
+
+        let mut g = f.clone();
+        g.push(F::from(3u32));
+        assert!(matches!(
+            t.multiplicities(&g),
+            Err(Error::ElementNotIndexed)
+        ));
+    }
+
     // TODO Delete if not used
     fn _multiset_compression_input<F>()
     where
@@ -431,7 +489,8 @@ mod test {
         [
             test_to_polynomial,
             test_is_subset,
-            test_combine_split
+            test_combine_split,
+            test_multiplicities
         ],
         [] => Bls12_381_scalar_field
     );
@@ -441,7 +500,8 @@ mod test {
         [
             test_to_polynomial,
             test_is_subset,
-            test_combine_split
+            test_combine_split,
+            test_multiplicities
         ],
         [] => Bls12_377_scalar_field
     );
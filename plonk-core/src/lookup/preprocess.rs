@@ -6,14 +6,14 @@
 
 use crate::commitment::HomomorphicCommitment;
 use crate::error::{to_pc_error, Error};
-use crate::lookup::{LookupTable, MultiSet};
+use crate::lookup::{LookupTables, MultiSet};
 use ark_ff::PrimeField;
 use ark_poly::domain::EvaluationDomain;
 use ark_poly::polynomial::univariate::DensePolynomial;
 
-/// This table will be the preprocessed version of the precomputed table,
-/// T, with arity 4. This structure is passed to the proof alongside the
-/// table of witness values.
+/// This table will be the preprocessed version of the precomputed tables,
+/// T, stacked into rows of arity 4 extended with the id of their table. This
+/// structure is passed to the proof alongside the table of witness values.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PreprocessedLookupTable<F, PC>
 where
@@ -33,20 +33,20 @@ where
     F: PrimeField,
     PC: HomomorphicCommitment<F>,
 {
-    /// This function takes in a precomputed look up table and
-    /// pads it to the length of the circuit entries, as a power
-    /// of 2. The function then interpolates a polynomial from the
-    /// padded table and makes a commitment to the poly. The
-    /// outputted struct will be used in the proof alongside our
-    /// circuit witness table.
+    /// This function takes in the precomputed look up tables, stacks them
+    /// and pads them to the length of the circuit entries, as a power
+    /// of 2. The function then interpolates a polynomial from each
+    /// padded column, the fifth one holding the table ids, and makes a
+    /// commitment to the poly. The outputted struct will be used in the
+    /// proof alongside our circuit witness table.
     pub fn preprocess(
-        table: &LookupTable<F>,
+        tables: &LookupTables<F>,
         commit_key: &PC::CommitterKey,
         n: u32,
     ) -> Result<Self, Error> {
         assert!(n.is_power_of_two());
         let domain = EvaluationDomain::new(n as usize).unwrap();
-        let result = table
+        let result = tables
             .vec_to_multiset()
             .into_iter()
             .enumerate()
@@ -74,7 +74,7 @@ mod test {
     use super::*;
     use crate::batch_test;
     use crate::commitment::HomomorphicCommitment;
-    use crate::lookup::{LookupTable, PreprocessedLookupTable};
+    use crate::lookup::{LookupTable, LookupTables, PreprocessedLookupTable};
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
     use ark_ec::TEModelParameters;
@@ -107,10 +107,15 @@ mod test {
             table.insert_xor_row(4u64, 2u64, 64u64);
         });
 
+        let mut tables = LookupTables::new();
+        tables.insert("xor", table).unwrap();
+        tables.insert("add", LookupTable::add_table(0, 1)).unwrap();
+
         let preprocessed_table =
-            PreprocessedLookupTable::<F, PC>::preprocess(&table, &ck, 32)
+            PreprocessedLookupTable::<F, PC>::preprocess(&tables, &ck, 32)
                 .unwrap();
 
+        assert_eq!(preprocessed_table.t.len(), 5);
         preprocessed_table.t.iter().for_each(|column| {
             assert!(preprocessed_table.n as usize == column.0.len());
         });
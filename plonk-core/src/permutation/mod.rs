@@ -9,7 +9,7 @@
 pub(crate) mod constants;
 
 use crate::constraint_system::{Variable, WireData};
-use ark_ff::FftField;
+use ark_ff::{batch_inversion, FftField};
 use ark_poly::{
     domain::{EvaluationDomain, GeneralEvaluationDomain},
     univariate::DensePolynomial,
@@ -820,6 +820,49 @@ impl Permutation {
                 .inverse()
                 .unwrap()
     }
+
+    /// Computes the running sum `phi` of the LogUp argument, whose value at
+    /// the `i`-th element of `domain` is the sum of the logarithmic
+    /// derivatives `1 / (epsilon + f_j) - m_j / (epsilon + t_j)` for `j < i`.
+    ///
+    /// The sum of all the derivatives is zero when the multiplicities `m`
+    /// count the occurrences of the rows of `t` in `f`, so that the running
+    /// sum wraps around to its first value, zero.
+    pub(crate) fn compute_lookup_running_sum_poly<F: FftField>(
+        &self,
+        domain: &GeneralEvaluationDomain<F>,
+        f: &[F],
+        t: &[F],
+        m: &[F],
+        epsilon: F,
+    ) -> DensePolynomial<F> {
+        let n = domain.size();
+
+        assert_eq!(f.len(), n);
+        assert_eq!(t.len(), n);
+        assert_eq!(m.len(), n);
+
+        // Inverses of `epsilon + f_i` followed by those of `epsilon + t_i`
+        let mut inverses = f
+            .iter()
+            .chain(t)
+            .map(|value| epsilon + value)
+            .collect::<Vec<_>>();
+        batch_inversion(&mut inverses);
+        let (f_inverses, t_inverses) = inverses.split_at(n);
+
+        let mut state = F::zero();
+        let mut p = Vec::with_capacity(n + 1);
+        p.push(state);
+        for (f_inverse, t_inverse, m) in izip!(f_inverses, t_inverses, m) {
+            state += *f_inverse - *m * t_inverse;
+            p.push(state);
+        }
+        p.pop();
+        assert_eq!(n, p.len());
+
+        DensePolynomial::from_coefficients_vec(domain.ifft(&p))
+    }
 }
 
 #[cfg(test)]
@@ -1379,11 +1422,47 @@ mod test {
         }
     }
 
+    fn test_lookup_running_sum_poly<F: FftField>() {
+        let perm = Permutation::new();
+        let domain = GeneralEvaluationDomain::<F>::new(4).unwrap();
+        let epsilon = F::rand(&mut OsRng);
+
+        let t = [F::one(), F::from(2u64), F::from(3u64), F::one()];
+        let f = [F::from(3u64), F::one(), F::from(3u64), F::from(3u64)];
+        let m = [F::one(), F::zero(), F::from(3u64), F::zero()];
+
+        let phi_poly =
+            perm.compute_lookup_running_sum_poly(&domain, &f, &t, &m, epsilon);
+        let phi = domain.fft(&phi_poly);
+        assert_eq!(phi[0], F::zero());
+
+        // phi(Xw) - phi(X) = 1 / (epsilon + f(X)) - m(X) / (epsilon + t(X))
+        // holds on the whole domain, wrapping around to the first element
+        for i in 0..domain.size() {
+            let next = phi[(i + 1) % domain.size()];
+            assert_eq!(
+                (next - phi[i]) * (epsilon + f[i]) * (epsilon + t[i]),
+                epsilon + t[i] - m[i] * (epsilon + f[i])
+            );
+        }
+
+        // Multiplicities which do not count the queries break the wrap around
+        let m = [F::one(), F::zero(), F::from(2u64), F::zero()];
+        let phi_poly =
+            perm.compute_lookup_running_sum_poly(&domain, &f, &t, &m, epsilon);
+        let phi = domain.fft(&phi_poly);
+        assert_ne!(
+            (phi[0] - phi[3]) * (epsilon + f[3]) * (epsilon + t[3]),
+            epsilon + t[3] - m[3] * (epsilon + f[3])
+        );
+    }
+
     // Test on Bls12-381
     batch_test_field!(
         [test_permutation_compute_sigmas_only_left_wires,
         test_permutation_compute_sigmas,
-        test_basic_slow_permutation_poly
+        test_basic_slow_permutation_poly,
+        test_lookup_running_sum_poly
         ],
         []
         => (
@@ -1395,7 +1474,8 @@ mod test {
     batch_test_field!(
         [test_permutation_compute_sigmas_only_left_wires,
         test_permutation_compute_sigmas,
-        test_basic_slow_permutation_poly
+        test_basic_slow_permutation_poly,
+        test_lookup_running_sum_poly
         ],
         []
         => (
@@ -14,5 +14,6 @@ pub use crate::{
     constraint_system::{ecc::Point, StandardComposer, Variable},
     error::Error,
     proof_system::{Proof, ProverKey, VerifierKey},
+    transcript::{Transcript, TranscriptProtocol},
     util::from_embedded_curve_scalar,
 };
@@ -7,18 +7,22 @@
 use crate::{
     error::Error,
     label_eval,
+    lookup::LookupMode,
     proof_system::{
-        ecc::{CAVals, CurveAddition, FBSMVals, FixedBaseScalarMul},
-        logic::{Logic, LogicVals},
+        custom::CustomGates,
+        ecc::{CurveAddition, FixedBaseScalarMul},
+        expression::ExpressionVals,
+        logic::Logic,
+        poseidon::{PoseidonRound, PoseidonVals},
         proof,
-        range::{Range, RangeVals},
-        widget::GateConstraint,
+        range::Range,
+        widget::{lookup::LookupPolynomials, GateConstraint},
         CustomValues, ProverKey, WitnessValues,
     },
     util::EvaluationDomainExt,
 };
 use ark_ec::TEModelParameters;
-use ark_ff::{Field, PrimeField};
+use ark_ff::{Field, PrimeField, Zero};
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain,
     Polynomial,
@@ -72,26 +76,12 @@ where
 
 // Probably all of these should go into CustomEvals
 #[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
-#[derivative(Clone, Debug, Default, Eq, PartialEq)]
+#[derivative(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct LookupEvaluations<F>
 where
     F: Field,
 {
     pub q_lookup_eval: F,
-    // (Shifted) Evaluation of the lookup permutation polynomial at `z * root
-    // of unity`
-    pub z2_next_eval: F,
-
-    /// Evaluations of the first half of sorted plonkup poly at `z`
-    pub h1_eval: F,
-
-    /// (Shifted) Evaluations of the even indexed half of sorted plonkup poly
-    /// at `z root of unity
-    pub h1_next_eval: F,
-
-    /// Evaluations of the odd indexed half of sorted plonkup poly at `z
-    /// root of unity
-    pub h2_eval: F,
 
     /// Evaluations of the query polynomial at `z`
     pub f_eval: F,
@@ -99,18 +89,159 @@ where
     /// Evaluations of the table polynomial at `z`
     pub table_eval: F,
 
-    /// Evaluations of the table polynomial at `z * root of unity`
-    pub table_next_eval: F,
+    /// Evaluations of the polynomials of the lookup argument
+    pub argument_evals: LookupArgumentEvaluations<F>,
+}
+
+/// Subset of the [`LookupEvaluations`]. Evaluations of the polynomials of the
+/// lookup argument, which depend on its [`LookupMode`].
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LookupArgumentEvaluations<F>
+where
+    F: Field,
+{
+    /// Evaluations of the polynomials of the plookup argument.
+    Plookup {
+        /// (Shifted) Evaluation of the lookup permutation polynomial at `z *
+        /// root of unity`
+        z2_next_eval: F,
+
+        /// Evaluations of the first half of sorted plonkup poly at `z`
+        h1_eval: F,
+
+        /// (Shifted) Evaluations of the even indexed half of sorted plonkup
+        /// poly at `z root of unity
+        h1_next_eval: F,
+
+        /// Evaluations of the odd indexed half of sorted plonkup poly at `z
+        /// root of unity
+        h2_eval: F,
+
+        /// Evaluations of the table polynomial at `z * root of unity`
+        table_next_eval: F,
+    },
+
+    /// Evaluations of the polynomials of the LogUp argument.
+    LogUp {
+        /// (Shifted) Evaluation of the running sum polynomial at `z * root of
+        /// unity`
+        phi_next_eval: F,
+    },
+}
+
+impl<F> LookupArgumentEvaluations<F>
+where
+    F: Field,
+{
+    /// Returns the argument of the evaluations.
+    pub fn mode(&self) -> LookupMode {
+        match self {
+            Self::Plookup { .. } => LookupMode::Plookup,
+            Self::LogUp { .. } => LookupMode::LogUp,
+        }
+    }
+}
+
+impl<F> Default for LookupArgumentEvaluations<F>
+where
+    F: Field,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::Plookup {
+            z2_next_eval: F::zero(),
+            h1_eval: F::zero(),
+            h1_next_eval: F::zero(),
+            h2_eval: F::zero(),
+            table_next_eval: F::zero(),
+        }
+    }
 }
 
-/// Subset of the [`ProofEvaluations`]. Evaluations at `z`  or `z *w` where `w`
-/// is the nth root of unity of selectors polynomials needed for custom gates
+impl<F> CanonicalSerialize for LookupArgumentEvaluations<F>
+where
+    F: Field,
+{
+    fn serialize<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        self.mode().serialize(&mut writer)?;
+        match self {
+            Self::Plookup {
+                z2_next_eval,
+                h1_eval,
+                h1_next_eval,
+                h2_eval,
+                table_next_eval,
+            } => [
+                z2_next_eval,
+                h1_eval,
+                h1_next_eval,
+                h2_eval,
+                table_next_eval,
+            ]
+            .iter()
+            .try_for_each(|eval| eval.serialize(&mut writer)),
+            Self::LogUp { phi_next_eval } => phi_next_eval.serialize(writer),
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        let evals = match self {
+            Self::Plookup { .. } => 5,
+            Self::LogUp { .. } => 1,
+        };
+        self.mode().serialized_size() + evals * F::zero().serialized_size()
+    }
+}
+
+impl<F> CanonicalDeserialize for LookupArgumentEvaluations<F>
+where
+    F: Field,
+{
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        match LookupMode::deserialize(&mut reader)? {
+            LookupMode::Plookup => Ok(Self::Plookup {
+                z2_next_eval: F::deserialize(&mut reader)?,
+                h1_eval: F::deserialize(&mut reader)?,
+                h1_next_eval: F::deserialize(&mut reader)?,
+                h2_eval: F::deserialize(&mut reader)?,
+                table_next_eval: F::deserialize(&mut reader)?,
+            }),
+            LookupMode::LogUp => Ok(Self::LogUp {
+                phi_next_eval: F::deserialize(&mut reader)?,
+            }),
+        }
+    }
+}
+
+/// Labels of the [`CustomEvaluations`] of a [`Proof`](super::Proof), in the
+/// order in which the prover sets them.
+pub(crate) const CUSTOM_EVALUATION_LABELS: [&str; 10] = [
+    "q_arith_eval",
+    "q_c_eval",
+    "q_l_eval",
+    "q_r_eval",
+    "q_o_eval",
+    "q_4_eval",
+    "a_next_eval",
+    "b_next_eval",
+    "c_next_eval",
+    "d_next_eval",
+];
+
+/// Subset of the evaluations of a [`Proof`](super::Proof). Evaluations at `z`
+/// or `z *w` where `w` is the nth root of unity of selectors polynomials
+/// needed for custom gates
 #[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
 #[derivative(Clone, Debug, Default, Eq, PartialEq)]
 pub struct CustomEvaluations<F>
 where
     F: Field,
 {
+    /// Evaluations, labelled by their names.
     pub vals: Vec<(String, F)>,
 }
 
@@ -119,7 +250,8 @@ where
     F: Field,
 {
     /// Get the evaluation of the specified label.
-    /// This funtions panics if the requested label is not found
+    /// This funtions panics if the requested label is not found, which the
+    /// verifier rules out by checking the labels of a proof beforehand.
     pub fn get(&self, label: &str) -> F {
         if let Some(result) = &self.vals.iter().find(|entry| entry.0 == label) {
             result.1
@@ -128,6 +260,15 @@ where
         }
     }
 
+    /// Returns `true` if the labels of the evaluations are
+    /// [`CUSTOM_EVALUATION_LABELS`], in the same order.
+    pub(crate) fn has_expected_labels(&self) -> bool {
+        self.vals
+            .iter()
+            .map(|(label, _)| label.as_str())
+            .eq(CUSTOM_EVALUATION_LABELS)
+    }
+
     /// Add evaluation of poly at point if the label is not already
     /// in the set of evaluations
     pub fn add(&mut self, label: &str, poly: DensePolynomial<F>, point: F) {
@@ -174,22 +315,20 @@ pub fn compute<F, P>(
     logic_separation_challenge: &F,
     fixed_base_separation_challenge: &F,
     var_base_separation_challenge: &F,
+    poseidon_separation_challenge: &F,
     lookup_separation_challenge: &F,
+    custom_gates: &CustomGates<F>,
+    custom_separation_challenges: &[F],
     z_challenge: &F,
     w_l_poly: &DensePolynomial<F>,
     w_r_poly: &DensePolynomial<F>,
     w_o_poly: &DensePolynomial<F>,
     w_4_poly: &DensePolynomial<F>,
-    t_1_poly: &DensePolynomial<F>,
-    t_2_poly: &DensePolynomial<F>,
-    t_3_poly: &DensePolynomial<F>,
-    t_4_poly: &DensePolynomial<F>,
+    t_polys: &[DensePolynomial<F>],
     z_poly: &DensePolynomial<F>,
-    z2_poly: &DensePolynomial<F>,
     f_poly: &DensePolynomial<F>,
-    h1_poly: &DensePolynomial<F>,
-    h2_poly: &DensePolynomial<F>,
     table_poly: &DensePolynomial<F>,
+    lookup_polys: &LookupPolynomials<F>,
 ) -> Result<(DensePolynomial<F>, ProofEvaluations<F>), Error>
 where
     F: PrimeField,
@@ -236,8 +375,11 @@ where
     let q_c_eval = prover_key.arithmetic.q_c.0.evaluate(z_challenge);
     let q_l_eval = prover_key.arithmetic.q_l.0.evaluate(z_challenge);
     let q_r_eval = prover_key.arithmetic.q_r.0.evaluate(z_challenge);
+    let q_o_eval = prover_key.arithmetic.q_o.0.evaluate(z_challenge);
+    let q_4_eval = prover_key.arithmetic.q_4.0.evaluate(z_challenge);
     let a_next_eval = w_l_poly.evaluate(&shifted_z_challenge);
     let b_next_eval = w_r_poly.evaluate(&shifted_z_challenge);
+    let c_next_eval = w_o_poly.evaluate(&shifted_z_challenge);
     let d_next_eval = w_4_poly.evaluate(&shifted_z_challenge);
 
     let custom_evals = CustomEvaluations {
@@ -246,24 +388,38 @@ where
             label_eval!(q_c_eval),
             label_eval!(q_l_eval),
             label_eval!(q_r_eval),
+            label_eval!(q_o_eval),
+            label_eval!(q_4_eval),
             label_eval!(a_next_eval),
             label_eval!(b_next_eval),
+            label_eval!(c_next_eval),
             label_eval!(d_next_eval),
         ],
     };
 
-    let z2_next_eval = z2_poly.evaluate(&shifted_z_challenge);
-    let h1_eval = h1_poly.evaluate(z_challenge);
-    let h1_next_eval = h1_poly.evaluate(&shifted_z_challenge);
-    let h2_eval = h2_poly.evaluate(z_challenge);
     let f_eval = f_poly.evaluate(z_challenge);
     let table_eval = table_poly.evaluate(z_challenge);
-    let table_next_eval = table_poly.evaluate(&shifted_z_challenge);
+    let argument_evals = match lookup_polys {
+        LookupPolynomials::Plookup { h_1, h_2, z_2 } => {
+            LookupArgumentEvaluations::Plookup {
+                z2_next_eval: z_2.evaluate(&shifted_z_challenge),
+                h1_eval: h_1.evaluate(z_challenge),
+                h1_next_eval: h_1.evaluate(&shifted_z_challenge),
+                h2_eval: h_2.evaluate(z_challenge),
+                table_next_eval: table_poly.evaluate(&shifted_z_challenge),
+            }
+        }
+        LookupPolynomials::LogUp { phi, .. } => {
+            LookupArgumentEvaluations::LogUp {
+                phi_next_eval: phi.evaluate(&shifted_z_challenge),
+            }
+        }
+    };
 
     // Compute the last term in the linearisation polynomial
     // (negative_quotient_term):
     // - Z_h(z_challenge) * [t_1(X) + z_challenge^n * t_2(X) + z_challenge^2n *
-    //   t_3(X) + z_challenge^3n * t_4(X)]
+    //   t_3(X) + ... + z_challenge^(k-1)n * t_k(X)] for k pieces
     let vanishing_poly_eval =
         domain.evaluate_vanishing_polynomial(*z_challenge);
     let z_challenge_to_n = vanishing_poly_eval + F::one();
@@ -275,13 +431,9 @@ where
 
     let lookup_evals = LookupEvaluations {
         q_lookup_eval,
-        z2_next_eval,
-        h1_eval,
-        h1_next_eval,
-        h2_eval,
         f_eval,
         table_eval,
-        table_next_eval,
+        argument_evals,
     };
 
     let gate_constraints = compute_gate_constraint_satisfiability::<F, P>(
@@ -289,11 +441,14 @@ where
         logic_separation_challenge,
         fixed_base_separation_challenge,
         var_base_separation_challenge,
+        poseidon_separation_challenge,
+        custom_gates,
+        custom_separation_challenges,
         &wire_evals,
         q_arith_eval,
         &custom_evals,
         prover_key,
-    );
+    )?;
 
     let lookup = prover_key.lookup.compute_linearisation(
         l1_eval,
@@ -301,17 +456,9 @@ where
         b_eval,
         c_eval,
         d_eval,
-        f_eval,
-        table_eval,
-        table_next_eval,
-        h1_next_eval,
-        h2_eval,
-        z2_next_eval,
-        *delta,
-        *epsilon,
-        *zeta,
-        z2_poly,
-        h1_poly,
+        &lookup_evals,
+        (*delta, *epsilon, *zeta),
+        lookup_polys,
         *lookup_separation_challenge,
     );
 
@@ -325,12 +472,12 @@ where
         z_poly,
     )?;
 
-    let quotient_term = &(&(&(&(&(&(t_4_poly * z_challenge_to_n)
-        + t_3_poly)
-        * z_challenge_to_n)
-        + t_2_poly)
-        * z_challenge_to_n)
-        + t_1_poly)
+    let quotient_term = &t_polys
+        .iter()
+        .rev()
+        .fold(DensePolynomial::zero(), |acc, t_i_poly| {
+            &(&acc * z_challenge_to_n) + t_i_poly
+        })
         * vanishing_poly_eval;
     let negative_quotient_term = &quotient_term * (-F::one());
 
@@ -355,11 +502,14 @@ fn compute_gate_constraint_satisfiability<F, P>(
     logic_separation_challenge: &F,
     fixed_base_separation_challenge: &F,
     var_base_separation_challenge: &F,
+    poseidon_separation_challenge: &F,
+    custom_gates: &CustomGates<F>,
+    custom_separation_challenges: &[F],
     wire_evals: &WireEvaluations<F>,
     q_arith_eval: F,
     custom_evals: &CustomEvaluations<F>,
     prover_key: &ProverKey<F>,
-) -> DensePolynomial<F>
+) -> Result<DensePolynomial<F>, Error>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
@@ -379,33 +529,64 @@ where
         q_arith_eval,
     );
 
-    let range = Range::linearisation_term(
+    let vals = ExpressionVals::from_evaluations(custom_evals);
+
+    let range = Range::expression().linearisation_term(
         &prover_key.range_selector.0,
         *range_separation_challenge,
         wit_vals,
-        RangeVals::from_evaluations(custom_evals),
+        &vals,
     );
 
-    let logic = Logic::linearisation_term(
+    let logic = Logic::expression().linearisation_term(
         &prover_key.logic_selector.0,
         *logic_separation_challenge,
         wit_vals,
-        LogicVals::from_evaluations(custom_evals),
+        &vals,
     );
 
-    let fixed_base_scalar_mul = FixedBaseScalarMul::<F, P>::linearisation_term(
-        &prover_key.fixed_group_add_selector.0,
-        *fixed_base_separation_challenge,
+    let fixed_base_scalar_mul = FixedBaseScalarMul::<F, P>::expression()
+        .linearisation_term(
+            &prover_key.fixed_group_add_selector.0,
+            *fixed_base_separation_challenge,
+            wit_vals,
+            &vals,
+        );
+
+    let curve_addition = CurveAddition::<F, P>::expression()
+        .linearisation_term(
+            &prover_key.variable_group_add_selector.0,
+            *var_base_separation_challenge,
+            wit_vals,
+            &vals,
+        );
+
+    let poseidon = PoseidonRound::linearisation_term(
+        &prover_key.poseidon_selector.0,
+        *poseidon_separation_challenge,
         wit_vals,
-        FBSMVals::from_evaluations(custom_evals),
+        PoseidonVals::from_evaluations(custom_evals),
     );
 
-    let curve_addition = CurveAddition::<F, P>::linearisation_term(
-        &prover_key.variable_group_add_selector.0,
-        *var_base_separation_challenge,
-        wit_vals,
-        CAVals::from_evaluations(custom_evals),
-    );
+    let mut custom = DensePolynomial::zero();
+    for (key, challenge) in prover_key
+        .custom_gates
+        .iter()
+        .zip(custom_separation_challenges)
+    {
+        custom += &key.compute_linearisation(
+            custom_gates.get(&key.name)?,
+            *challenge,
+            wit_vals,
+            custom_evals,
+        );
+    }
 
-    arithmetic + range + logic + fixed_base_scalar_mul + curve_addition
+    Ok(arithmetic
+        + range
+        + logic
+        + fixed_base_scalar_mul
+        + curve_addition
+        + poseidon
+        + custom)
 }
@@ -17,7 +17,8 @@ pub mod proof;
 pub mod prover;
 pub mod verifier;
 
+pub use linearisation_poly::CustomEvaluations;
 pub use proof::*;
-pub use prover::Prover;
-pub use verifier::Verifier;
+pub use prover::{Prover, HIDING_BOUND};
+pub use verifier::{verify_batch, Verifier};
 pub use widget::*;
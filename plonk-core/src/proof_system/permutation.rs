@@ -334,32 +334,12 @@ where
         l1_eval: F,
         z_comm: PCC,
     ) {
-        let alpha_sq = alpha.square();
-
-        // (a_eval + beta * z + gamma)(b_eval + beta * z * k1 +
-        // gamma)(c_eval + beta * k2 * z + gamma)(d_eval + beta
-        // * k3 * z + gamma) * alpha
-        let x = {
-            let beta_z = beta * z_challenge;
-            let q_0 = evaluations.wire_evals.a_eval + beta_z + gamma;
-
-            let beta_k1_z = beta * K1::<F>() * z_challenge;
-            let q_1 = evaluations.wire_evals.b_eval + beta_k1_z + gamma;
-
-            let beta_k2_z = beta * K2::<F>() * z_challenge;
-            let q_2 = evaluations.wire_evals.c_eval + beta_k2_z + gamma;
-
-            let beta_k3_z = beta * K3::<F>() * z_challenge;
-            let q_3 =
-                (evaluations.wire_evals.d_eval + beta_k3_z + gamma) * alpha;
-
-            q_0 * q_1 * q_2 * q_3
-        };
-
-        // l1(z) * alpha^2
-        let r = l1_eval * alpha_sq;
-
-        scalars.push(x + r);
+        scalars.push(compute_z_linearisation_scalar(
+            evaluations,
+            z_challenge,
+            (alpha, beta, gamma),
+            l1_eval,
+        ));
         points.push(z_comm);
 
         // -(a_eval + beta * sigma_1_eval + gamma)(b_eval + beta *
@@ -384,3 +364,41 @@ where
         points.push(self.fourth_sigma.clone());
     }
 }
+
+/// Computes the scalar multiplying the permutation polynomial `z` in the
+/// linearisation polynomial.
+pub(crate) fn compute_z_linearisation_scalar<F>(
+    evaluations: &ProofEvaluations<F>,
+    z_challenge: F,
+    (alpha, beta, gamma): (F, F, F),
+    l1_eval: F,
+) -> F
+where
+    F: FftField,
+{
+    let alpha_sq = alpha.square();
+
+    // (a_eval + beta * z + gamma)(b_eval + beta * z * k1 +
+    // gamma)(c_eval + beta * k2 * z + gamma)(d_eval + beta
+    // * k3 * z + gamma) * alpha
+    let x = {
+        let beta_z = beta * z_challenge;
+        let q_0 = evaluations.wire_evals.a_eval + beta_z + gamma;
+
+        let beta_k1_z = beta * K1::<F>() * z_challenge;
+        let q_1 = evaluations.wire_evals.b_eval + beta_k1_z + gamma;
+
+        let beta_k2_z = beta * K2::<F>() * z_challenge;
+        let q_2 = evaluations.wire_evals.c_eval + beta_k2_z + gamma;
+
+        let beta_k3_z = beta * K3::<F>() * z_challenge;
+        let q_3 = (evaluations.wire_evals.d_eval + beta_k3_z + gamma) * alpha;
+
+        q_0 * q_1 * q_2 * q_3
+    };
+
+    // l1(z) * alpha^2
+    let r = l1_eval * alpha_sq;
+
+    x + r
+}
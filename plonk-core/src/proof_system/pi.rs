@@ -98,6 +98,28 @@ where
         Ok(count)
     }
 
+    /// Returns the number of non-zero public inputs.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if all the public inputs are zero.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the positions of the non-zero public inputs in increasing
+    /// order.
+    pub fn positions(&self) -> impl Iterator<Item = &usize> {
+        self.values.keys()
+    }
+
+    /// Returns the positions and the values of the non-zero public inputs in
+    /// increasing order of position.
+    pub fn iter(&self) -> impl Iterator<Item = (&usize, &F)> {
+        self.values.iter()
+    }
+
     /// Returns the public inputs as a vector of `n` evaluations.
     /// The provided `n` must be a power of 2.
     pub fn as_evals(&self, n: usize) -> Vec<F> {
@@ -12,7 +12,18 @@ use crate::{
     error::{to_pc_error, Error},
     label_polynomial,
     lookup::PreprocessedLookupTable,
-    proof_system::{widget, ProverKey},
+    proof_system::{
+        widget::{
+            self, custom,
+            ecc::{CurveAddition, FixedBaseScalarMul},
+            logic::Logic,
+            poseidon::PoseidonRound,
+            range::Range,
+            GateConstraint,
+        },
+        ProverKey,
+    },
+    transcript::TranscriptProtocol,
 };
 use ark_ec::TEModelParameters;
 use ark_ff::{FftField, PrimeField};
@@ -20,8 +31,8 @@ use ark_poly::{
     polynomial::univariate::DensePolynomial, EvaluationDomain, Evaluations,
     GeneralEvaluationDomain, UVPolynomial,
 };
+use ark_poly_commit::{LabeledPolynomial, PCCommitterKey};
 use core::marker::PhantomData;
-use merlin::Transcript;
 
 /// Struct that contains all of the selector and permutation [`Polynomial`]s in
 /// PLONK.
@@ -41,12 +52,15 @@ where
     q_range: DensePolynomial<F>,
     q_logic: DensePolynomial<F>,
     q_lookup: DensePolynomial<F>,
+    q_table: DensePolynomial<F>,
     q_fixed_group_add: DensePolynomial<F>,
     q_variable_group_add: DensePolynomial<F>,
+    q_poseidon: DensePolynomial<F>,
     left_sigma: DensePolynomial<F>,
     right_sigma: DensePolynomial<F>,
     out_sigma: DensePolynomial<F>,
     fourth_sigma: DensePolynomial<F>,
+    custom_selectors: Vec<DensePolynomial<F>>,
 }
 
 impl<F, P> StandardComposer<F, P>
@@ -78,6 +92,7 @@ where
         self.q_lookup.extend(zeroes_scalar.iter());
         self.q_fixed_group_add.extend(zeroes_scalar.iter());
         self.q_variable_group_add.extend(zeroes_scalar.iter());
+        self.q_poseidon.extend(zeroes_scalar.iter());
 
         self.w_l.extend(zeroes_var.iter());
         self.w_r.extend(zeroes_var.iter());
@@ -85,6 +100,47 @@ where
         self.w_4.extend(zeroes_var.iter());
 
         self.n += diff;
+
+        self.q_table.resize(self.n, zero_scalar);
+        for selector in &mut self.custom_selectors {
+            selector.resize(self.n, zero_scalar);
+        }
+    }
+
+    /// Returns the number of pieces of degree `n` the quotient polynomial is
+    /// split in.
+    ///
+    /// The quotient of the permutation check has a degree lower than `4n`,
+    /// and a gate of degree `d` adds a term of degree lower than `(d - 1)n`
+    /// when its selector is used in the circuit.
+    fn quotient_pieces(&self) -> Result<usize, Error> {
+        fn is_used<F: PrimeField>(selector: &[F]) -> bool {
+            selector.iter().any(|q| !q.is_zero())
+        }
+        let builtin_gates = [
+            (&self.q_range, Range::<F>::degree()),
+            (&self.q_logic, Logic::<F>::degree()),
+            (
+                &self.q_fixed_group_add,
+                FixedBaseScalarMul::<F, P>::degree(),
+            ),
+            (&self.q_variable_group_add, CurveAddition::<F, P>::degree()),
+            (&self.q_poseidon, PoseidonRound::<F>::degree()),
+        ];
+        let mut degree = 5;
+        for (selector, gate_degree) in builtin_gates {
+            if is_used(selector) {
+                degree = degree.max(gate_degree + 1);
+            }
+        }
+        for (name, selector) in
+            self.custom_gates.names().zip(&self.custom_selectors)
+        {
+            if is_used(selector) {
+                degree = degree.max(self.custom_gates.get(name)?.degree() + 1);
+            }
+        }
+        Ok(degree - 1)
     }
 
     /// Checks that all of the wires of the composer have the same
@@ -103,10 +159,16 @@ where
             && self.q_lookup.len() == k
             && self.q_fixed_group_add.len() == k
             && self.q_variable_group_add.len() == k
+            && self.q_poseidon.len() == k
             && self.w_l.len() == k
             && self.w_r.len() == k
             && self.w_o.len() == k
             && self.w_4.len() == k
+            && self.q_table.len() <= k
+            && self
+                .custom_selectors
+                .iter()
+                .all(|selector| selector.len() <= k)
         {
             Ok(())
         } else {
@@ -123,136 +185,175 @@ where
     /// Although the prover does not need the verification key, he must compute
     /// the commitments in order to seed the transcript, allowing both the
     /// prover and verifier to have the same view
-    pub fn preprocess_prover<PC>(
+    pub fn preprocess_prover<PC, T>(
         &mut self,
         commit_key: &PC::CommitterKey,
-        transcript: &mut Transcript,
+        transcript: &mut T,
         _pc: PhantomData<PC>,
     ) -> Result<ProverKey<F>, Error>
     where
         PC: HomomorphicCommitment<F>,
+        T: TranscriptProtocol<F>,
     {
-        let (_, selectors, domain, preprocessed_table) =
+        let (verifier_key, selectors, domain, preprocessed_table) =
             self.preprocess_shared(commit_key, transcript, _pc)?;
 
-        let domain_4n =
-            GeneralEvaluationDomain::new(4 * domain.size()).ok_or(Error::InvalidEvalDomainSize {
-                log_size_of_group: (4 * domain.size()).trailing_zeros(),
+        // The quotient polynomial has a degree lower than `pieces * n`, so it
+        // is computed over the smallest coset holding as many elements.
+        let pieces = verifier_key.quotient_pieces;
+        let ext_size = pieces.next_power_of_two() * domain.size();
+        let domain_ext =
+            GeneralEvaluationDomain::new(ext_size).ok_or(Error::InvalidEvalDomainSize {
+                log_size_of_group: ext_size.trailing_zeros(),
                 adicity:
                     <<F as FftField>::FftParams as ark_ff::FftParameters>::TWO_ADICITY,
             })?;
-        let q_m_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_m),
-            domain_4n,
+        let q_m_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_m),
+            domain_ext,
         );
-        let q_l_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_l),
-            domain_4n,
+        let q_l_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_l),
+            domain_ext,
         );
-        let q_r_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_r),
-            domain_4n,
+        let q_r_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_r),
+            domain_ext,
         );
-        let q_o_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_o),
-            domain_4n,
+        let q_o_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_o),
+            domain_ext,
         );
-        let q_c_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_c),
-            domain_4n,
+        let q_c_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_c),
+            domain_ext,
         );
-        let q_4_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_4),
-            domain_4n,
+        let q_4_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_4),
+            domain_ext,
         );
-        let q_arith_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_arith),
-            domain_4n,
+        let q_arith_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_arith),
+            domain_ext,
         );
-        let q_range_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_range),
-            domain_4n,
+        let q_range_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_range),
+            domain_ext,
         );
-        let q_logic_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_logic),
-            domain_4n,
+        let q_logic_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_logic),
+            domain_ext,
         );
-        let q_lookup_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_lookup),
-            domain_4n,
+        let q_lookup_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_lookup),
+            domain_ext,
         );
-        let q_fixed_group_add_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_fixed_group_add),
-            domain_4n,
+        let q_table_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_table),
+            domain_ext,
         );
-        let q_variable_group_add_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_variable_group_add),
-            domain_4n,
+        let q_fixed_group_add_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_fixed_group_add),
+            domain_ext,
         );
-        let left_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.left_sigma),
-            domain_4n,
+        let q_variable_group_add_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_variable_group_add),
+            domain_ext,
         );
-        let right_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.right_sigma),
-            domain_4n,
+        let q_poseidon_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.q_poseidon),
+            domain_ext,
         );
-        let out_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.out_sigma),
-            domain_4n,
+        let left_sigma_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.left_sigma),
+            domain_ext,
         );
-        let fourth_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.fourth_sigma),
-            domain_4n,
+        let right_sigma_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.right_sigma),
+            domain_ext,
         );
+        let out_sigma_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.out_sigma),
+            domain_ext,
+        );
+        let fourth_sigma_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&selectors.fourth_sigma),
+            domain_ext,
+        );
+        let custom_gates = self
+            .custom_gates
+            .names()
+            .zip(selectors.custom_selectors)
+            .map(|(name, q_custom)| {
+                let q_custom_eval_ext = Evaluations::from_vec_and_domain(
+                    domain_ext.coset_fft(&q_custom),
+                    domain_ext,
+                );
+                custom::ProverKey {
+                    name: name.to_owned(),
+                    q_custom: (q_custom, q_custom_eval_ext),
+                }
+            })
+            .collect();
         // XXX: Remove this and compute it on the fly
-        let linear_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&[F::zero(), F::one()]),
-            domain_4n,
+        let linear_eval_ext = Evaluations::from_vec_and_domain(
+            domain_ext.coset_fft(&[F::zero(), F::one()]),
+            domain_ext,
         );
 
-        // Compute 4n evaluations for X^n -1
-        let v_h_coset_4n =
-            compute_vanishing_poly_over_coset(domain_4n, domain.size() as u64);
+        // Compute the evaluations of X^n - 1 over the extended coset
+        let v_h_coset_ext =
+            compute_vanishing_poly_over_coset(domain_ext, domain.size() as u64);
 
         Ok(ProverKey::from_polynomials_and_evals(
             domain.size(),
-            (selectors.q_m, q_m_eval_4n),
-            (selectors.q_l, q_l_eval_4n),
-            (selectors.q_r, q_r_eval_4n),
-            (selectors.q_o, q_o_eval_4n),
-            (selectors.q_4, q_4_eval_4n),
-            (selectors.q_c, q_c_eval_4n),
-            (selectors.q_arith, q_arith_eval_4n),
-            (selectors.q_range, q_range_eval_4n),
-            (selectors.q_logic, q_logic_eval_4n),
-            (selectors.q_lookup, q_lookup_eval_4n),
-            (selectors.q_fixed_group_add, q_fixed_group_add_eval_4n),
-            (selectors.q_variable_group_add, q_variable_group_add_eval_4n),
-            (selectors.left_sigma, left_sigma_eval_4n),
-            (selectors.right_sigma, right_sigma_eval_4n),
-            (selectors.out_sigma, out_sigma_eval_4n),
-            (selectors.fourth_sigma, fourth_sigma_eval_4n),
-            linear_eval_4n,
-            v_h_coset_4n,
+            pieces,
+            (selectors.q_m, q_m_eval_ext),
+            (selectors.q_l, q_l_eval_ext),
+            (selectors.q_r, q_r_eval_ext),
+            (selectors.q_o, q_o_eval_ext),
+            (selectors.q_4, q_4_eval_ext),
+            (selectors.q_c, q_c_eval_ext),
+            (selectors.q_arith, q_arith_eval_ext),
+            (selectors.q_range, q_range_eval_ext),
+            (selectors.q_logic, q_logic_eval_ext),
+            (selectors.q_lookup, q_lookup_eval_ext),
+            (selectors.q_table, q_table_eval_ext),
+            (selectors.q_fixed_group_add, q_fixed_group_add_eval_ext),
+            (
+                selectors.q_variable_group_add,
+                q_variable_group_add_eval_ext,
+            ),
+            (selectors.q_poseidon, q_poseidon_eval_ext),
+            (selectors.left_sigma, left_sigma_eval_ext),
+            (selectors.right_sigma, right_sigma_eval_ext),
+            (selectors.out_sigma, out_sigma_eval_ext),
+            (selectors.fourth_sigma, fourth_sigma_eval_ext),
+            linear_eval_ext,
+            v_h_coset_ext,
             preprocessed_table.t[0].0.clone(),
             preprocessed_table.t[1].0.clone(),
             preprocessed_table.t[2].0.clone(),
             preprocessed_table.t[3].0.clone(),
+            preprocessed_table.t[4].0.clone(),
+            self.lookup_mode,
+            custom_gates,
         ))
     }
 
     /// The verifier only requires the commitments in order to verify a
     /// [`Proof`](super::Proof) We can therefore speed up preprocessing for the
-    /// verifier by skipping the FFTs needed to compute the 4n evaluations.
-    pub fn preprocess_verifier<PC>(
+    /// verifier by skipping the FFTs needed to compute the evaluations over the
+    /// extended coset.
+    pub fn preprocess_verifier<PC, T>(
         &mut self,
         commit_key: &PC::CommitterKey,
-        transcript: &mut Transcript,
+        transcript: &mut T,
         _pc: PhantomData<PC>,
     ) -> Result<widget::VerifierKey<F, PC>, Error>
     where
         PC: HomomorphicCommitment<F>,
+        T: TranscriptProtocol<F>,
     {
         let (verifier_key, _, _, _) =
             self.preprocess_shared(commit_key, transcript, _pc)?;
@@ -264,10 +365,10 @@ where
     /// polynomials in order to commit to them and have the same transcript
     /// view.
     #[allow(clippy::type_complexity)] // FIXME: Add struct for prover side (last two tuple items).
-    fn preprocess_shared<PC>(
+    fn preprocess_shared<PC, T>(
         &mut self,
         commit_key: &PC::CommitterKey,
-        transcript: &mut Transcript,
+        transcript: &mut T,
         _pc: PhantomData<PC>,
     ) -> Result<
         (
@@ -280,7 +381,10 @@ where
     >
     where
         PC: HomomorphicCommitment<F>,
+        T: TranscriptProtocol<F>,
     {
+        self.check_srs_degree(commit_key.supported_degree())?;
+
         let domain = GeneralEvaluationDomain::new(self.circuit_bound()).ok_or(Error::InvalidEvalDomainSize {
             log_size_of_group: (self.circuit_bound()).trailing_zeros(),
             adicity:
@@ -288,15 +392,16 @@ where
         })?;
 
         let preprocessed_table = PreprocessedLookupTable::<F, PC>::preprocess(
-            &self.lookup_table,
+            &self.lookup_tables,
             commit_key,
             domain.size() as u32,
-        )
-        .unwrap();
+        )?;
 
         // Check that the length of the wires is consistent.
         self.check_poly_same_len()?;
 
+        let quotient_pieces = self.quotient_pieces()?;
+
         // 1. Pad circuit to a power of two
         self.pad(domain.size() as usize - self.n);
 
@@ -330,6 +435,9 @@ where
         let q_lookup_poly: DensePolynomial<F> =
             DensePolynomial::from_coefficients_vec(domain.ifft(&self.q_lookup));
 
+        let q_table_poly: DensePolynomial<F> =
+            DensePolynomial::from_coefficients_vec(domain.ifft(&self.q_table));
+
         let q_fixed_group_add_poly: DensePolynomial<F> =
             DensePolynomial::from_coefficients_vec(
                 domain.ifft(&self.q_fixed_group_add),
@@ -340,6 +448,19 @@ where
                 domain.ifft(&self.q_variable_group_add),
             );
 
+        let q_poseidon_poly: DensePolynomial<F> =
+            DensePolynomial::from_coefficients_vec(
+                domain.ifft(&self.q_poseidon),
+            );
+
+        let custom_selector_polys = self
+            .custom_selectors
+            .iter()
+            .map(|selector| {
+                DensePolynomial::from_coefficients_vec(domain.ifft(selector))
+            })
+            .collect::<Vec<_>>();
+
         // 2. Compute the sigma polynomials
         let (
             left_sigma_poly,
@@ -363,6 +484,8 @@ where
                 label_polynomial!(q_lookup_poly),
                 label_polynomial!(q_fixed_group_add_poly),
                 label_polynomial!(q_variable_group_add_poly),
+                label_polynomial!(q_poseidon_poly),
+                label_polynomial!(q_table_poly),
                 label_polynomial!(left_sigma_poly),
                 label_polynomial!(right_sigma_poly),
                 label_polynomial!(out_sigma_poly),
@@ -373,8 +496,36 @@ where
         )
         .map_err(to_pc_error::<F, PC>)?;
 
+        let (custom_commitments, _) = PC::commit(
+            commit_key,
+            self.custom_gates
+                .names()
+                .zip(&custom_selector_polys)
+                .map(|(name, q_custom)| {
+                    LabeledPolynomial::new(
+                        name.to_owned(),
+                        q_custom.clone(),
+                        None,
+                        None,
+                    )
+                })
+                .collect::<Vec<_>>()
+                .iter(),
+            None,
+        )
+        .map_err(to_pc_error::<F, PC>)?;
+        let custom_gates = custom_commitments
+            .into_iter()
+            .map(|commitment| custom::VerifierKey {
+                name: commitment.label().clone(),
+                q_custom: commitment.commitment().clone(),
+            })
+            .collect();
+
         let verifier_key = widget::VerifierKey::from_polynomial_commitments(
             self.n,
+            self.pi_positions.clone(),
+            quotient_pieces,
             commitments[0].commitment().clone(), // q_m
             commitments[1].commitment().clone(), // q_l
             commitments[2].commitment().clone(), // q_r
@@ -385,16 +536,21 @@ where
             commitments[7].commitment().clone(), // q_range
             commitments[8].commitment().clone(), // q_logic
             commitments[9].commitment().clone(), // q_lookup
+            commitments[13].commitment().clone(), // q_table
             commitments[10].commitment().clone(), // q_fixed_group_add
             commitments[11].commitment().clone(), // q_variable_group_add
-            commitments[12].commitment().clone(), // left_sigma
-            commitments[13].commitment().clone(), // right_sigma
-            commitments[14].commitment().clone(), // out_sigma
-            commitments[15].commitment().clone(), // fourth_sigma
+            commitments[12].commitment().clone(), // q_poseidon
+            commitments[14].commitment().clone(), // left_sigma
+            commitments[15].commitment().clone(), // right_sigma
+            commitments[16].commitment().clone(), // out_sigma
+            commitments[17].commitment().clone(), // fourth_sigma
             preprocessed_table.t[0].1.clone(),
             preprocessed_table.t[1].1.clone(),
             preprocessed_table.t[2].1.clone(),
             preprocessed_table.t[3].1.clone(),
+            preprocessed_table.t[4].1.clone(),
+            self.lookup_mode,
+            custom_gates,
         );
 
         let selectors = SelectorPolynomials {
@@ -408,12 +564,15 @@ where
             q_range: q_range_poly,
             q_logic: q_logic_poly,
             q_lookup: q_lookup_poly,
+            q_table: q_table_poly,
             q_fixed_group_add: q_fixed_group_add_poly,
             q_variable_group_add: q_variable_group_add_poly,
+            q_poseidon: q_poseidon_poly,
             left_sigma: left_sigma_poly,
             right_sigma: right_sigma_poly,
             out_sigma: out_sigma_poly,
             fourth_sigma: fourth_sigma_poly,
+            custom_selectors: custom_selector_polys,
         };
 
         // Add the circuit description to the transcript
@@ -483,8 +642,10 @@ mod test {
         assert_eq!(composer.q_range.len(), size);
         assert_eq!(composer.q_logic.len(), size);
         assert_eq!(composer.q_lookup.len(), size);
+        assert_eq!(composer.q_table.len(), size);
         assert_eq!(composer.q_fixed_group_add.len(), size);
         assert_eq!(composer.q_variable_group_add.len(), size);
+        assert_eq!(composer.q_poseidon.len(), size);
         assert_eq!(composer.w_l.len(), size);
         assert_eq!(composer.w_r.len(), size);
         assert_eq!(composer.w_o.len(), size);
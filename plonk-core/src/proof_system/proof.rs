@@ -11,14 +11,18 @@
 //! structure and it's methods.
 
 use crate::{
-    commitment::HomomorphicCommitment,
-    error::Error,
-    label_commitment,
+    commitment::{linear_combination, HomomorphicCommitment},
+    error::{to_pc_error, Error},
     proof_system::{
+        custom::CustomGates,
         ecc::{CurveAddition, FixedBaseScalarMul},
-        linearisation_poly::ProofEvaluations,
+        linearisation_poly::{LookupArgumentEvaluations, ProofEvaluations},
         logic::Logic,
+        poseidon::PoseidonRound,
         range::Range,
+        widget::lookup::{
+            compute_argument_linearisation_constant, LookupCommitments,
+        },
         GateConstraint, VerifierKey as PlonkVerifierKey,
     },
     transcript::TranscriptProtocol,
@@ -26,12 +30,17 @@ use crate::{
 };
 use ark_ec::TEModelParameters;
 
-use ark_ff::{fields::batch_inversion, FftField, PrimeField};
+use ark_ff::{fields::batch_inversion, BigInteger, FftField, PrimeField};
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_poly_commit::{
+    Evaluations, LabeledCommitment, PCVerifierKey, QuerySet,
+};
 use ark_serialize::{
     CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
 };
-use merlin::Transcript;
+
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use rand_core::RngCore;
 
 use super::pi::PublicInputs;
 
@@ -71,26 +80,12 @@ where
     /// Commitment to the lookup query polynomial.
     pub(crate) f_comm: PC::Commitment,
 
-    /// Commitment to first half of sorted polynomial
-    pub(crate) h_1_comm: PC::Commitment,
-
-    /// Commitment to second half of sorted polynomial
-    pub(crate) h_2_comm: PC::Commitment,
-
-    /// Commitment to the lookup permutation polynomial.
-    pub(crate) z_2_comm: PC::Commitment,
-
-    /// Commitment to the quotient polynomial.
-    pub(crate) t_1_comm: PC::Commitment,
-
-    /// Commitment to the quotient polynomial.
-    pub(crate) t_2_comm: PC::Commitment,
+    /// Commitments to the polynomials of the lookup argument.
+    pub(crate) lookup_comms: LookupCommitments<PC::Commitment>,
 
-    /// Commitment to the quotient polynomial.
-    pub(crate) t_3_comm: PC::Commitment,
-
-    /// Commitment to the quotient polynomial.
-    pub(crate) t_4_comm: PC::Commitment,
+    /// Commitments to the pieces of the quotient polynomial, as many as the
+    /// [`VerifierKey`](widget::VerifierKey) of the circuit sets.
+    pub(crate) t_comms: Vec<PC::Commitment>,
 
     /// Batch opening proof of the aggregated witnesses
     pub aw_opening: PC::Proof,
@@ -102,22 +97,106 @@ where
     pub(crate) evaluations: ProofEvaluations<F>,
 }
 
+/// Claim that a commitment opens to `value` at `point`, as proven by the
+/// opening `proof`.
+pub(crate) struct OpeningClaim<F, PC>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    /// Commitment to the opened polynomial.
+    pub(crate) commitment: PC::Commitment,
+
+    /// Point at which the polynomial is opened.
+    pub(crate) point: F,
+
+    /// Claimed evaluation of the polynomial at `point`.
+    pub(crate) value: F,
+
+    /// Opening proof of the claim.
+    pub(crate) proof: PC::Proof,
+}
+
 impl<F, PC> Proof<F, PC>
 where
     F: PrimeField,
     PC: HomomorphicCommitment<F>,
 {
     /// Performs the verification of a [`Proof`] returning a boolean result.
-    pub(crate) fn verify<P>(
+    pub(crate) fn verify<P, T>(
         &self,
         plonk_verifier_key: &PlonkVerifierKey<F, PC>,
-        transcript: &mut Transcript,
+        custom_gates: &CustomGates<F>,
+        transcript: &mut T,
         verifier_key: &PC::VerifierKey,
         pub_inputs: &PublicInputs<F>,
     ) -> Result<(), Error>
     where
         P: TEModelParameters<BaseField = F>,
+        T: TranscriptProtocol<F>,
     {
+        let claims = self.opening_claims::<P, T>(
+            plonk_verifier_key,
+            custom_gates,
+            transcript,
+            verifier_key,
+            pub_inputs,
+        )?;
+
+        // Both openings are checked with a single multi-point check, folded
+        // by a randomizer which the prover can't anticipate: it is seeded by
+        // the transcript once the opening proofs are added to it.
+        transcript.append(b"aw_opening", &self.aw_opening);
+        transcript.append(b"saw_opening", &self.saw_opening);
+        let mut rng = transcript_rng(transcript, b"opening batching");
+
+        if batch_check_openings(verifier_key, &claims, &mut rng)? {
+            Ok(())
+        } else {
+            // Find out which of the openings is invalid.
+            check_opening_claims(verifier_key, &claims)?;
+            Err(Error::ProofVerificationError)
+        }
+    }
+
+    /// Replays the transcript of the [`Proof`] and reduces its verification
+    /// to the two [`OpeningClaim`]s of the aggregated witnesses at the
+    /// evaluation challenge `z` and at its shift `z * omega`.
+    ///
+    /// The circuit size, the shape of the opening proofs and the public
+    /// inputs are checked beforehand, so that the claims can be checked
+    /// against `verifier_key` without a panic.
+    pub(crate) fn opening_claims<P, T>(
+        &self,
+        plonk_verifier_key: &PlonkVerifierKey<F, PC>,
+        custom_gates: &CustomGates<F>,
+        transcript: &mut T,
+        verifier_key: &PC::VerifierKey,
+        pub_inputs: &PublicInputs<F>,
+    ) -> Result<[OpeningClaim<F, PC>; 2], Error>
+    where
+        P: TEModelParameters<BaseField = F>,
+        T: TranscriptProtocol<F>,
+    {
+        let circuit_size = plonk_verifier_key.padded_circuit_size();
+        if verifier_key.supported_degree() < circuit_size {
+            return Err(Error::CircuitSizeMismatch {
+                circuit_size,
+                supported_degree: verifier_key.supported_degree(),
+            });
+        }
+        if !PC::is_well_formed(verifier_key, &self.aw_opening)
+            || !PC::is_well_formed(verifier_key, &self.saw_opening)
+            || self.t_comms.len() != plonk_verifier_key.quotient_pieces
+            || self.lookup_comms.mode() != plonk_verifier_key.lookup.mode
+            || self.evaluations.lookup_evals.argument_evals.mode()
+                != plonk_verifier_key.lookup.mode
+            || !self.evaluations.custom_evals.has_expected_labels()
+        {
+            return Err(Error::MalformedProof);
+        }
+        plonk_verifier_key.check_public_inputs(pub_inputs)?;
+
         let domain =
             GeneralEvaluationDomain::<F>::new(plonk_verifier_key.n).ok_or(Error::InvalidEvalDomainSize {
                 log_size_of_group: plonk_verifier_key.n.trailing_zeros(),
@@ -144,86 +223,134 @@ where
 
         // Compute table compression challenge `zeta`.
         let zeta = transcript.challenge_scalar(b"zeta");
-        transcript.append(b"zeta", &zeta);
+        transcript.append_scalar(b"zeta", &zeta);
 
         // Add f_poly commitment to transcript
         transcript.append(b"f", &self.f_comm);
 
-        // Add h polynomials to transcript
-        transcript.append(b"h1", &self.h_1_comm);
-        transcript.append(b"h2", &self.h_2_comm);
+        // Add the polynomials of the sets of the lookup argument to transcript
+        match &self.lookup_comms {
+            LookupCommitments::Plookup {
+                h_1_comm, h_2_comm, ..
+            } => {
+                transcript.append(b"h1", h_1_comm);
+                transcript.append(b"h2", h_2_comm);
+            }
+            LookupCommitments::LogUp { m_comm, .. } => {
+                transcript.append(b"m", m_comm);
+            }
+        }
 
         // Compute permutation challenges and add them to transcript
 
         // Compute permutation challenge `beta`.
         let beta = transcript.challenge_scalar(b"beta");
-        transcript.append(b"beta", &beta);
+        transcript.append_scalar(b"beta", &beta);
 
         // Compute permutation challenge `gamma`.
         let gamma = transcript.challenge_scalar(b"gamma");
-        transcript.append(b"gamma", &gamma);
+        transcript.append_scalar(b"gamma", &gamma);
 
         // Compute permutation challenge `delta`.
         let delta = transcript.challenge_scalar(b"delta");
-        transcript.append(b"delta", &delta);
+        transcript.append_scalar(b"delta", &delta);
 
         // Compute permutation challenge `epsilon`.
         let epsilon = transcript.challenge_scalar(b"epsilon");
-        transcript.append(b"epsilon", &epsilon);
+        transcript.append_scalar(b"epsilon", &epsilon);
 
         // Challenges must be different
-        assert!(beta != gamma, "challenges must be different");
-        assert!(beta != delta, "challenges must be different");
-        assert!(beta != epsilon, "challenges must be different");
-        assert!(gamma != delta, "challenges must be different");
-        assert!(gamma != epsilon, "challenges must be different");
-        assert!(delta != epsilon, "challenges must be different");
+        if beta == gamma
+            || beta == delta
+            || beta == epsilon
+            || gamma == delta
+            || gamma == epsilon
+            || delta == epsilon
+        {
+            return Err(Error::MalformedProof);
+        }
 
         // Add commitment to permutation polynomial to transcript
         transcript.append(b"z", &self.z_comm);
 
+        // Add commitment to lookup accumulator polynomial to transcript
+        match &self.lookup_comms {
+            LookupCommitments::Plookup { z_2_comm, .. } => {
+                transcript.append(b"z_2", z_2_comm);
+            }
+            LookupCommitments::LogUp { phi_comm, .. } => {
+                transcript.append(b"phi", phi_comm);
+            }
+        }
+
         // Compute quotient challenge
         let alpha = transcript.challenge_scalar(b"alpha");
-        transcript.append(b"alpha", &alpha);
+        transcript.append_scalar(b"alpha", &alpha);
         let range_sep_challenge =
             transcript.challenge_scalar(b"range separation challenge");
-        transcript.append(b"range seperation challenge", &range_sep_challenge);
+        transcript
+            .append_scalar(b"range seperation challenge", &range_sep_challenge);
 
         let logic_sep_challenge =
             transcript.challenge_scalar(b"logic separation challenge");
-        transcript.append(b"logic seperation challenge", &logic_sep_challenge);
+        transcript
+            .append_scalar(b"logic seperation challenge", &logic_sep_challenge);
 
         let fixed_base_sep_challenge =
             transcript.challenge_scalar(b"fixed base separation challenge");
-        transcript.append(
+        transcript.append_scalar(
             b"fixed base separation challenge",
             &fixed_base_sep_challenge,
         );
 
         let var_base_sep_challenge =
             transcript.challenge_scalar(b"variable base separation challenge");
-        transcript.append(
+        transcript.append_scalar(
             b"variable base separation challenge",
             &var_base_sep_challenge,
         );
 
+        let poseidon_sep_challenge =
+            transcript.challenge_scalar(b"poseidon separation challenge");
+        transcript.append_scalar(
+            b"poseidon separation challenge",
+            &poseidon_sep_challenge,
+        );
+
         let lookup_sep_challenge =
             transcript.challenge_scalar(b"lookup separation challenge");
-        transcript
-            .append(b"lookup separation challenge", &lookup_sep_challenge);
+        transcript.append_scalar(
+            b"lookup separation challenge",
+            &lookup_sep_challenge,
+        );
+
+        let custom_sep_challenges = plonk_verifier_key
+            .custom_gates
+            .iter()
+            .map(|_| {
+                let challenge =
+                    transcript.challenge_scalar(b"custom separation challenge");
+                transcript
+                    .append_scalar(b"custom separation challenge", &challenge);
+                challenge
+            })
+            .collect::<Vec<_>>();
 
         // Add commitment to quotient polynomial to transcript
-        transcript.append(b"t_1", &self.t_1_comm);
-        transcript.append(b"t_2", &self.t_2_comm);
-        transcript.append(b"t_3", &self.t_3_comm);
-        transcript.append(b"t_4", &self.t_4_comm);
+        for t_comm in &self.t_comms {
+            transcript.append(b"t", t_comm);
+        }
 
         // Compute evaluation point challenge
         let z_challenge = transcript.challenge_scalar(b"z");
-        transcript.append(b"z", &z_challenge);
+        transcript.append_scalar(b"z", &z_challenge);
 
-        // Compute zero polynomial evaluated at `z_challenge`
+        // Compute zero polynomial evaluated at `z_challenge`, which must be
+        // outside of the domain
         let z_h_eval = domain.evaluate_vanishing_polynomial(z_challenge);
+        if z_h_eval.is_zero() {
+            return Err(Error::MalformedProof);
+        }
 
         // Compute first lagrange polynomial evaluated at `z_challenge`
         let l1_eval =
@@ -240,50 +367,64 @@ where
             z_challenge,
             l1_eval,
             self.evaluations.perm_evals.permutation_eval,
-            self.evaluations.lookup_evals.z2_next_eval,
-            self.evaluations.lookup_evals.h1_next_eval,
-            self.evaluations.lookup_evals.h2_eval,
             lookup_sep_challenge,
         );
 
         // Add evaluations to transcript
-        transcript.append(b"a_eval", &self.evaluations.wire_evals.a_eval);
-        transcript.append(b"b_eval", &self.evaluations.wire_evals.b_eval);
-        transcript.append(b"c_eval", &self.evaluations.wire_evals.c_eval);
-        transcript.append(b"d_eval", &self.evaluations.wire_evals.d_eval);
+        transcript
+            .append_scalar(b"a_eval", &self.evaluations.wire_evals.a_eval);
+        transcript
+            .append_scalar(b"b_eval", &self.evaluations.wire_evals.b_eval);
+        transcript
+            .append_scalar(b"c_eval", &self.evaluations.wire_evals.c_eval);
+        transcript
+            .append_scalar(b"d_eval", &self.evaluations.wire_evals.d_eval);
 
-        transcript.append(
+        transcript.append_scalar(
             b"left_sig_eval",
             &self.evaluations.perm_evals.left_sigma_eval,
         );
-        transcript.append(
+        transcript.append_scalar(
             b"right_sig_eval",
             &self.evaluations.perm_evals.right_sigma_eval,
         );
-        transcript.append(
+        transcript.append_scalar(
             b"out_sig_eval",
             &self.evaluations.perm_evals.out_sigma_eval,
         );
-        transcript.append(
+        transcript.append_scalar(
             b"perm_eval",
             &self.evaluations.perm_evals.permutation_eval,
         );
 
-        transcript.append(b"f_eval", &self.evaluations.lookup_evals.f_eval);
-        transcript.append(
+        transcript
+            .append_scalar(b"f_eval", &self.evaluations.lookup_evals.f_eval);
+        transcript.append_scalar(
             b"q_lookup_eval",
             &self.evaluations.lookup_evals.q_lookup_eval,
         );
-        transcript.append(
-            b"lookup_perm_eval",
-            &self.evaluations.lookup_evals.z2_next_eval,
-        );
-        transcript.append(b"h_1_eval", &self.evaluations.lookup_evals.h1_eval);
-        transcript.append(
-            b"h_1_next_eval",
-            &self.evaluations.lookup_evals.h1_next_eval,
+        transcript.append_scalar(
+            b"table_eval",
+            &self.evaluations.lookup_evals.table_eval,
         );
-        transcript.append(b"h_2_eval", &self.evaluations.lookup_evals.h2_eval);
+        match self.evaluations.lookup_evals.argument_evals {
+            LookupArgumentEvaluations::Plookup {
+                z2_next_eval,
+                h1_eval,
+                h1_next_eval,
+                h2_eval,
+                table_next_eval,
+            } => {
+                transcript.append_scalar(b"lookup_perm_eval", &z2_next_eval);
+                transcript.append_scalar(b"h_1_eval", &h1_eval);
+                transcript.append_scalar(b"h_1_next_eval", &h1_next_eval);
+                transcript.append_scalar(b"h_2_eval", &h2_eval);
+                transcript.append_scalar(b"table_next_eval", &table_next_eval);
+            }
+            LookupArgumentEvaluations::LogUp { phi_next_eval } => {
+                transcript.append_scalar(b"phi_next_eval", &phi_next_eval);
+            }
+        }
 
         self.evaluations
             .custom_evals
@@ -291,7 +432,7 @@ where
             .iter()
             .for_each(|(label, eval)| {
                 let static_label = Box::leak(label.to_owned().into_boxed_str());
-                transcript.append(static_label.as_bytes(), eval);
+                transcript.append_scalar(static_label.as_bytes(), eval);
             });
 
         // Compute linearisation commitment
@@ -307,11 +448,14 @@ where
             logic_sep_challenge,
             fixed_base_sep_challenge,
             var_base_sep_challenge,
+            poseidon_sep_challenge,
             lookup_sep_challenge,
+            custom_gates,
+            &custom_sep_challenges,
             z_challenge,
             l1_eval,
             plonk_verifier_key,
-        );
+        )?;
 
         let zeta_sq = zeta.square();
         let table_comm = PC::multi_scalar_mul(
@@ -320,8 +464,9 @@ where
                 plonk_verifier_key.lookup.table_2.clone(),
                 plonk_verifier_key.lookup.table_3.clone(),
                 plonk_verifier_key.lookup.table_4.clone(),
+                plonk_verifier_key.lookup.table_id.clone(),
             ],
-            &[F::one(), zeta, zeta_sq, zeta_sq * zeta],
+            &[F::one(), zeta, zeta_sq, zeta_sq * zeta, zeta_sq.square()],
         );
 
         // Commitment Scheme
@@ -344,85 +489,143 @@ where
         // challenge `z`
         let aw_challenge: F = transcript.challenge_scalar(b"aggregate_witness");
 
-        let aw_commits = [
-            label_commitment!(lin_comm),
-            label_commitment!(plonk_verifier_key.permutation.left_sigma),
-            label_commitment!(plonk_verifier_key.permutation.right_sigma),
-            label_commitment!(plonk_verifier_key.permutation.out_sigma),
-            label_commitment!(self.f_comm),
-            label_commitment!(self.h_2_comm),
-            label_commitment!(table_comm),
-            label_commitment!(self.a_comm),
-            label_commitment!(self.b_comm),
-            label_commitment!(self.c_comm),
-            label_commitment!(self.d_comm),
-        ];
-
-        let aw_evals = [
-            -r0,
-            self.evaluations.perm_evals.left_sigma_eval,
-            self.evaluations.perm_evals.right_sigma_eval,
-            self.evaluations.perm_evals.out_sigma_eval,
-            self.evaluations.lookup_evals.f_eval,
-            self.evaluations.lookup_evals.h2_eval,
-            self.evaluations.lookup_evals.table_eval,
-            self.evaluations.wire_evals.a_eval,
-            self.evaluations.wire_evals.b_eval,
-            self.evaluations.wire_evals.c_eval,
-            self.evaluations.wire_evals.d_eval,
-        ];
+        // Besides the accumulator, plookup opens `h_2` at `z` and `h_1` and
+        // the table at the shifted `z`, whereas LogUp opens no other
+        // polynomial of its argument.
+        let (lookup_aw, lookup_saw) = match (
+            &self.lookup_comms,
+            self.evaluations.lookup_evals.argument_evals,
+        ) {
+            (
+                LookupCommitments::Plookup {
+                    h_1_comm,
+                    h_2_comm,
+                    z_2_comm,
+                },
+                LookupArgumentEvaluations::Plookup {
+                    z2_next_eval,
+                    h1_next_eval,
+                    h2_eval,
+                    table_next_eval,
+                    ..
+                },
+            ) => (
+                vec![(h_2_comm.clone(), h2_eval)],
+                vec![
+                    (h_1_comm.clone(), h1_next_eval),
+                    (z_2_comm.clone(), z2_next_eval),
+                    (table_comm.clone(), table_next_eval),
+                ],
+            ),
+            (
+                LookupCommitments::LogUp { phi_comm, .. },
+                LookupArgumentEvaluations::LogUp { phi_next_eval },
+            ) => (vec![], vec![(phi_comm.clone(), phi_next_eval)]),
+            _ => return Err(Error::MalformedProof),
+        };
+
+        let (aw_commits, aw_evals): (Vec<_>, Vec<_>) = [
+            (lin_comm, -r0),
+            (
+                plonk_verifier_key.permutation.left_sigma.clone(),
+                self.evaluations.perm_evals.left_sigma_eval,
+            ),
+            (
+                plonk_verifier_key.permutation.right_sigma.clone(),
+                self.evaluations.perm_evals.right_sigma_eval,
+            ),
+            (
+                plonk_verifier_key.permutation.out_sigma.clone(),
+                self.evaluations.perm_evals.out_sigma_eval,
+            ),
+            (
+                plonk_verifier_key.arithmetic.q_l.clone(),
+                self.evaluations.custom_evals.get("q_l_eval"),
+            ),
+            (
+                plonk_verifier_key.arithmetic.q_r.clone(),
+                self.evaluations.custom_evals.get("q_r_eval"),
+            ),
+            (
+                plonk_verifier_key.arithmetic.q_o.clone(),
+                self.evaluations.custom_evals.get("q_o_eval"),
+            ),
+            (
+                plonk_verifier_key.arithmetic.q_4.clone(),
+                self.evaluations.custom_evals.get("q_4_eval"),
+            ),
+            (
+                plonk_verifier_key.arithmetic.q_c.clone(),
+                self.evaluations.custom_evals.get("q_c_eval"),
+            ),
+            (
+                plonk_verifier_key.arithmetic.q_arith.clone(),
+                self.evaluations.custom_evals.get("q_arith_eval"),
+            ),
+            (self.f_comm.clone(), self.evaluations.lookup_evals.f_eval),
+        ]
+        .into_iter()
+        .chain(lookup_aw)
+        .chain([
+            (table_comm, self.evaluations.lookup_evals.table_eval),
+            (self.a_comm.clone(), self.evaluations.wire_evals.a_eval),
+            (self.b_comm.clone(), self.evaluations.wire_evals.b_eval),
+            (self.c_comm.clone(), self.evaluations.wire_evals.c_eval),
+            (self.d_comm.clone(), self.evaluations.wire_evals.d_eval),
+        ])
+        .unzip();
 
         let saw_challenge: F =
             transcript.challenge_scalar(b"aggregate_witness");
 
-        let saw_commits = [
-            label_commitment!(self.z_comm),
-            label_commitment!(self.a_comm),
-            label_commitment!(self.b_comm),
-            label_commitment!(self.d_comm),
-            label_commitment!(self.h_1_comm),
-            label_commitment!(self.z_2_comm),
-            label_commitment!(table_comm),
-        ];
-
-        let saw_evals = [
-            self.evaluations.perm_evals.permutation_eval,
-            self.evaluations.custom_evals.get("a_next_eval"),
-            self.evaluations.custom_evals.get("b_next_eval"),
-            self.evaluations.custom_evals.get("d_next_eval"),
-            self.evaluations.lookup_evals.h1_next_eval,
-            self.evaluations.lookup_evals.z2_next_eval,
-            self.evaluations.lookup_evals.table_next_eval,
-        ];
-
-        match PC::check(
-            verifier_key,
-            &aw_commits,
-            &z_challenge,
-            aw_evals,
-            &self.aw_opening,
-            aw_challenge,
-            None,
-        ) {
-            Ok(true) => Ok(()),
-            Ok(false) => Err(Error::ProofVerificationError),
-            Err(e) => panic!("{:?}", e),
-        }
-        .and_then(|_| {
-            match PC::check(
-                verifier_key,
-                &saw_commits,
-                &(z_challenge * domain.element(1)),
-                saw_evals,
-                &self.saw_opening,
-                saw_challenge,
-                None,
-            ) {
-                Ok(true) => Ok(()),
-                Ok(false) => Err(Error::ProofVerificationError),
-                Err(e) => panic!("{:?}", e),
-            }
-        })
+        let (saw_commits, saw_evals): (Vec<_>, Vec<_>) = [
+            (
+                self.z_comm.clone(),
+                self.evaluations.perm_evals.permutation_eval,
+            ),
+            (
+                self.a_comm.clone(),
+                self.evaluations.custom_evals.get("a_next_eval"),
+            ),
+            (
+                self.b_comm.clone(),
+                self.evaluations.custom_evals.get("b_next_eval"),
+            ),
+            (
+                self.c_comm.clone(),
+                self.evaluations.custom_evals.get("c_next_eval"),
+            ),
+            (
+                self.d_comm.clone(),
+                self.evaluations.custom_evals.get("d_next_eval"),
+            ),
+        ]
+        .into_iter()
+        .chain(lookup_saw)
+        .unzip();
+
+        let (aw_comm, aw_eval) =
+            linear_combination::<F, PC>(&aw_evals, &aw_commits, aw_challenge);
+        let (saw_comm, saw_eval) = linear_combination::<F, PC>(
+            &saw_evals,
+            &saw_commits,
+            saw_challenge,
+        );
+
+        Ok([
+            OpeningClaim {
+                commitment: aw_comm,
+                point: z_challenge,
+                value: aw_eval,
+                proof: self.aw_opening.clone(),
+            },
+            OpeningClaim {
+                commitment: saw_comm,
+                point: z_challenge * domain.element(1),
+                value: saw_eval,
+                proof: self.saw_opening.clone(),
+            },
+        ])
     }
 
     fn compute_r0(
@@ -437,9 +640,6 @@ where
         z_challenge: F,
         l1_eval: F,
         z_hat_eval: F,
-        z2_next_eval: F,
-        h1_next_eval: F,
-        h2_eval: F,
         lookup_sep_challenge: F,
     ) -> F {
         // Compute the public input polynomial evaluated at `z_challenge`
@@ -447,10 +647,6 @@ where
 
         let alpha_sq = alpha.square();
 
-        let lookup_sep_challenge_sq = lookup_sep_challenge.square();
-        let lookup_sep_challenge_cu =
-            lookup_sep_challenge_sq * lookup_sep_challenge;
-
         // a + beta * sigma_1 + gamma
         let beta_sig1 = beta * self.evaluations.perm_evals.left_sigma_eval;
         let b_0 = self.evaluations.wire_evals.a_eval + beta_sig1 + gamma;
@@ -472,17 +668,15 @@ where
         // l_1(z) * alpha^2
         let c = l1_eval * alpha_sq;
 
-        let epsilon_one_plus_delta = epsilon * (F::one() + delta);
-
-        let d_0 = lookup_sep_challenge_sq * z2_next_eval;
-        let d_1 = epsilon_one_plus_delta + delta * h2_eval;
-        let d_2 = epsilon_one_plus_delta + h2_eval + delta * h1_next_eval;
-        let d = d_0 * d_1 * d_2;
-
-        let e = lookup_sep_challenge_cu * l1_eval;
+        let d = compute_argument_linearisation_constant(
+            &self.evaluations.lookup_evals,
+            (delta, epsilon),
+            lookup_sep_challenge,
+            l1_eval,
+        );
 
         // Return r_0
-        pi_eval - b - c - d - e
+        pi_eval - b - c + d
     }
 
     /// Computes the commitment to `[r]_1`.
@@ -499,11 +693,14 @@ where
         logic_sep_challenge: F,
         fixed_base_sep_challenge: F,
         var_base_sep_challenge: F,
+        poseidon_sep_challenge: F,
         lookup_sep_challenge: F,
+        custom_gates: &CustomGates<F>,
+        custom_sep_challenges: &[F],
         z_challenge: F,
         l1_eval: F,
         plonk_verifier_key: &PlonkVerifierKey<F, PC>,
-    ) -> PC::Commitment
+    ) -> Result<PC::Commitment, Error>
     where
         P: TEModelParameters<BaseField = F>,
     {
@@ -512,13 +709,16 @@ where
         // +  1 for logic
         // +  1 for fixed base mul
         // +  1 for curve add
+        // +  1 for poseidon round
+        // +  1 for each custom gate
         // +  3 for lookups
         // +  2 for permutation
-        // +  4 for each piece of the quotient poly
-        // = 19 total scalars and points
+        // +  5 for each piece of the quotient poly
+        // = 21 total scalars and points, and one per custom gate
 
-        let mut scalars = Vec::with_capacity(19);
-        let mut points = Vec::with_capacity(19);
+        let capacity = 21 + plonk_verifier_key.custom_gates.len();
+        let mut scalars = Vec::with_capacity(capacity);
+        let mut points = Vec::with_capacity(capacity);
 
         plonk_verifier_key
             .arithmetic
@@ -527,7 +727,7 @@ where
                 &mut points,
                 &self.evaluations,
             );
-        Range::extend_linearisation_commitment::<PC>(
+        Range::expression().extend_linearisation_commitment::<PC>(
             &plonk_verifier_key.range_selector_commitment,
             range_sep_challenge,
             &self.evaluations,
@@ -535,7 +735,7 @@ where
             &mut points,
         );
 
-        Logic::extend_linearisation_commitment::<PC>(
+        Logic::expression().extend_linearisation_commitment::<PC>(
             &plonk_verifier_key.logic_selector_commitment,
             logic_sep_challenge,
             &self.evaluations,
@@ -543,20 +743,42 @@ where
             &mut points,
         );
 
-        FixedBaseScalarMul::<_, P>::extend_linearisation_commitment::<PC>(
-            &plonk_verifier_key.fixed_group_add_selector_commitment,
-            fixed_base_sep_challenge,
-            &self.evaluations,
-            &mut scalars,
-            &mut points,
-        );
-        CurveAddition::<_, P>::extend_linearisation_commitment::<PC>(
-            &plonk_verifier_key.variable_group_add_selector_commitment,
-            var_base_sep_challenge,
+        FixedBaseScalarMul::<_, P>::expression()
+            .extend_linearisation_commitment::<PC>(
+                &plonk_verifier_key.fixed_group_add_selector_commitment,
+                fixed_base_sep_challenge,
+                &self.evaluations,
+                &mut scalars,
+                &mut points,
+            );
+        CurveAddition::<_, P>::expression()
+            .extend_linearisation_commitment::<PC>(
+                &plonk_verifier_key.variable_group_add_selector_commitment,
+                var_base_sep_challenge,
+                &self.evaluations,
+                &mut scalars,
+                &mut points,
+            );
+        PoseidonRound::extend_linearisation_commitment::<PC>(
+            &plonk_verifier_key.poseidon_selector_commitment,
+            poseidon_sep_challenge,
             &self.evaluations,
             &mut scalars,
             &mut points,
         );
+        for (custom_gate, challenge) in plonk_verifier_key
+            .custom_gates
+            .iter()
+            .zip(custom_sep_challenges)
+        {
+            custom_gate.compute_linearisation_commitment(
+                custom_gates.get(&custom_gate.name)?,
+                *challenge,
+                &mut scalars,
+                &mut points,
+                &self.evaluations,
+            );
+        }
         plonk_verifier_key.lookup.compute_linearisation_commitment(
             &mut scalars,
             &mut points,
@@ -564,8 +786,7 @@ where
             (delta, epsilon, zeta),
             lookup_sep_challenge,
             l1_eval,
-            self.z_2_comm.clone(),
-            self.h_1_comm.clone(),
+            &self.lookup_comms,
         );
         plonk_verifier_key
             .permutation
@@ -580,29 +801,135 @@ where
             );
 
         // Second part
-        let vanishing_poly_eval =
-            domain.evaluate_vanishing_polynomial(z_challenge);
-        // z_challenge ^ n
-        let z_challenge_to_n = vanishing_poly_eval + F::one();
-
-        let t_1_scalar = -vanishing_poly_eval;
-        let t_2_scalar = t_1_scalar * z_challenge_to_n;
-        let t_3_scalar = t_2_scalar * z_challenge_to_n;
-        let t_4_scalar = t_3_scalar * z_challenge_to_n;
-        scalars.extend_from_slice(&[
-            t_1_scalar, t_2_scalar, t_3_scalar, t_4_scalar,
-        ]);
-        points.extend_from_slice(&[
-            self.t_1_comm.clone(),
-            self.t_2_comm.clone(),
-            self.t_3_comm.clone(),
-            self.t_4_comm.clone(),
-        ]);
-
-        PC::multi_scalar_mul(&points, &scalars)
+        scalars.extend(compute_quotient_linearisation_scalars(
+            domain,
+            z_challenge,
+            self.t_comms.len(),
+        ));
+        points.extend_from_slice(&self.t_comms);
+
+        Ok(PC::multi_scalar_mul(&points, &scalars))
     }
 }
 
+/// Checks the two claims of a proof one at a time, returning
+/// [`Error::OpeningCheckFailure`] or [`Error::ShiftedOpeningCheckFailure`] for
+/// the first invalid one.
+pub(crate) fn check_opening_claims<F, PC>(
+    verifier_key: &PC::VerifierKey,
+    [aw_claim, saw_claim]: &[OpeningClaim<F, PC>; 2],
+) -> Result<(), Error>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    for (claim, error) in [
+        (aw_claim, Error::OpeningCheckFailure),
+        (saw_claim, Error::ShiftedOpeningCheckFailure),
+    ] {
+        let valid = PC::check(
+            verifier_key,
+            &[LabeledCommitment::new(
+                "claim".to_owned(),
+                claim.commitment.clone(),
+                None,
+            )],
+            &claim.point,
+            [claim.value],
+            &claim.proof,
+            F::one(),
+            None,
+        )
+        .map_err(to_pc_error::<F, PC>)?;
+        if !valid {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+/// Seeds an [`StdRng`] from the challenge drawn from `transcript` under
+/// `label`.
+fn transcript_rng<F, T>(transcript: &mut T, label: &'static [u8]) -> StdRng
+where
+    F: PrimeField,
+    T: TranscriptProtocol<F>,
+{
+    let seed = transcript.challenge_scalar(label);
+    let mut bytes = <StdRng as SeedableRng>::Seed::default();
+    for (byte, seed_byte) in
+        bytes.iter_mut().zip(seed.into_repr().to_bytes_le())
+    {
+        *byte = seed_byte;
+    }
+    StdRng::from_seed(bytes)
+}
+
+/// Checks all the `claims` at once with [`PolynomialCommitment::batch_check`],
+/// which folds them with a random linear combination drawn from `rng`.
+///
+/// [`PolynomialCommitment::batch_check`]: ark_poly_commit::PolynomialCommitment::batch_check
+pub(crate) fn batch_check_openings<'a, F, PC, R>(
+    verifier_key: &PC::VerifierKey,
+    claims: impl IntoIterator<Item = &'a OpeningClaim<F, PC>>,
+    rng: &mut R,
+) -> Result<bool, Error>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F> + 'a,
+    R: RngCore,
+{
+    let mut commitments = Vec::new();
+    let mut query_set = QuerySet::new();
+    let mut evaluations = Evaluations::new();
+    let mut proofs = Vec::new();
+    for (i, claim) in claims.into_iter().enumerate() {
+        // Every claim has its own point, and the points are sorted by label:
+        // pad the labels so that they are sorted as the proofs.
+        let label = format!("{:020}", i);
+        commitments.push(LabeledCommitment::new(
+            label.clone(),
+            claim.commitment.clone(),
+            None,
+        ));
+        query_set.insert((label.clone(), (label.clone(), claim.point)));
+        evaluations.insert((label, claim.point), claim.value);
+        proofs.push(claim.proof.clone());
+    }
+    PC::batch_check(
+        verifier_key,
+        &commitments,
+        &query_set,
+        &evaluations,
+        &proofs.into(),
+        F::one(),
+        rng,
+    )
+    .map_err(to_pc_error::<F, PC>)
+}
+
+/// Computes the scalars multiplying the `pieces` pieces of the quotient
+/// polynomial in the linearisation polynomial, that is `-Z_H(z) * z^(i * n)`
+/// for the `i`-th piece.
+pub(crate) fn compute_quotient_linearisation_scalars<F>(
+    domain: &GeneralEvaluationDomain<F>,
+    z_challenge: F,
+    pieces: usize,
+) -> Vec<F>
+where
+    F: PrimeField,
+{
+    let vanishing_poly_eval = domain.evaluate_vanishing_polynomial(z_challenge);
+    // z_challenge ^ n
+    let z_challenge_to_n = vanishing_poly_eval + F::one();
+
+    core::iter::successors(Some(-vanishing_poly_eval), |scalar| {
+        Some(*scalar * z_challenge_to_n)
+    })
+    .take(pieces)
+    .collect()
+}
+
 /// The first lagrange polynomial has the expression:
 ///
 /// ```text
@@ -706,16 +1033,85 @@ mod test {
         assert_eq!(proof, obtained_proof);
     }
 
+    fn test_serde_log_up_proof<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+        Proof<F, PC>: std::fmt::Debug + PartialEq,
+    {
+        let plookup_proof =
+            crate::constraint_system::helper::gadget_tester::<F, P, PC>(
+                |_: &mut crate::constraint_system::StandardComposer<F, P>| {},
+                200,
+            )
+            .expect("Empty circuit failed");
+        let proof =
+            crate::constraint_system::helper::gadget_tester::<F, P, PC>(
+                |composer: &mut crate::constraint_system::StandardComposer<
+                    F,
+                    P,
+                >| {
+                    composer.set_lookup_mode(crate::lookup::LookupMode::LogUp)
+                },
+                200,
+            )
+            .expect("Empty circuit failed");
+
+        let mut proof_bytes = vec![];
+        proof.serialize(&mut proof_bytes).unwrap();
+
+        let obtained_proof =
+            Proof::<F, PC>::deserialize(proof_bytes.as_slice()).unwrap();
+
+        assert_eq!(proof, obtained_proof);
+
+        // LogUp commits to one polynomial less than plookup and opens four
+        // evaluations less.
+        assert!(proof_bytes.len() < plookup_proof.serialized_size());
+    }
+
+    fn test_missing_custom_eval<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        use crate::constraint_system::helper::tampered_gadget_tester;
+
+        // A proof missing an evaluation is rejected instead of panicking.
+        let res = tampered_gadget_tester::<F, P, PC>(
+            |_| {},
+            200,
+            |proof| {
+                proof
+                    .evaluations
+                    .custom_evals
+                    .vals
+                    .retain(|(label, _)| label != "q_l_eval")
+            },
+        );
+        assert!(matches!(res, Err(Error::MalformedProof)));
+
+        // So is a proof whose evaluations are out of order.
+        let res = tampered_gadget_tester::<F, P, PC>(
+            |_| {},
+            200,
+            |proof| proof.evaluations.custom_evals.vals.swap(0, 1),
+        );
+        assert!(matches!(res, Err(Error::MalformedProof)));
+    }
+
     // Bls12-381 tests
     batch_test_kzg!(
-        [test_serde_proof],
+        [test_serde_proof, test_serde_log_up_proof, test_missing_custom_eval],
         [] => (
             Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
         )
     );
     // Bls12-377 tests
     batch_test_kzg!(
-        [test_serde_proof],
+        [test_serde_proof, test_serde_log_up_proof, test_missing_custom_eval],
         [] => (
             Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
         )
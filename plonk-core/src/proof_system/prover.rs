@@ -6,34 +6,63 @@
 
 //! Prover-side of the PLONK Proving System
 
-use crate::lookup::MultiSet;
+use crate::lookup::{LookupMode, MultiSet};
 use crate::{
     commitment::HomomorphicCommitment,
     constraint_system::{StandardComposer, Variable},
     error::{to_pc_error, Error},
     label_polynomial,
     proof_system::{
-        linearisation_poly, proof::Proof, quotient_poly, ProverKey,
+        linearisation_poly,
+        linearisation_poly::LookupArgumentEvaluations,
+        permutation,
+        proof::{
+            compute_first_lagrange_evaluation,
+            compute_quotient_linearisation_scalars, Proof,
+        },
+        quotient_poly,
+        widget::lookup::{
+            compute_argument_linearisation_scalars, LookupCommitments,
+            LookupPolynomials,
+        },
+        ProverKey,
     },
     transcript::TranscriptProtocol,
+    util::powers_of,
 };
 use ark_ec::{ModelParameters, TEModelParameters};
-use ark_ff::PrimeField;
+use ark_ff::{PrimeField, Zero};
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain,
     UVPolynomial,
 };
+use ark_poly_commit::{
+    LabeledCommitment, LabeledPolynomial, PCCommitterKey, PCRandomness,
+};
 use core::marker::PhantomData;
 use itertools::izip;
 use merlin::Transcript;
+use rand_core::RngCore;
+
+/// Hiding bound of the commitments made by [`Prover::prove_hiding`] and
+/// [`Prover::prove_with_preprocessed_hiding`].
+///
+/// Every hidden polynomial is opened at most at the evaluation challenge and
+/// at its shift, so the commit key must be trimmed with a supported hiding
+/// bound of at least `HIDING_BOUND`.
+pub const HIDING_BOUND: usize = 2;
 
 /// Abstraction structure designed to construct a circuit and generate
 /// [`Proof`]s for it.
-pub struct Prover<F, P, PC>
+///
+/// The challenges are derived from a transcript `T`, the [`Transcript`] of
+/// Merlin by default.
+pub struct Prover<F, P, PC, T = Transcript>
 where
     F: PrimeField,
     P: ModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol<F>,
 {
     /// Proving Key which is used to create proofs about a specific PLONK
     /// circuit.
@@ -45,22 +74,23 @@ where
     /// Store the messages exchanged during the preprocessing stage.
     ///
     /// This is copied each time, we make a proof.
-    pub preprocessed_transcript: Transcript,
+    pub preprocessed_transcript: T,
 
     _phantom: PhantomData<PC>,
 }
-impl<F, P, PC> Prover<F, P, PC>
+impl<F, P, PC, T> Prover<F, P, PC, T>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol<F>,
 {
     /// Creates a new `Prover` instance.
     pub fn new(label: &'static [u8]) -> Self {
         Self {
             prover_key: None,
             cs: StandardComposer::new(),
-            preprocessed_transcript: Transcript::new(label),
+            preprocessed_transcript: T::new(label),
             _phantom: PhantomData::<PC>,
         }
     }
@@ -70,7 +100,7 @@ where
         Self {
             prover_key: None,
             cs: StandardComposer::with_expected_size(size),
-            preprocessed_transcript: Transcript::new(label),
+            preprocessed_transcript: T::new(label),
             _phantom: PhantomData::<PC>,
         }
     }
@@ -85,6 +115,13 @@ where
         self.cs.circuit_bound()
     }
 
+    /// Returns the degree the commitment key must support to preprocess and
+    /// prove the circuit, as
+    /// [`StandardComposer::required_srs_degree`] does.
+    pub fn required_srs_degree(&self) -> usize {
+        self.cs.required_srs_degree()
+    }
+
     /// Preprocesses the underlying constraint system.
     pub fn preprocess(
         &mut self,
@@ -102,24 +139,21 @@ where
         Ok(())
     }
 
-    /// Split `t(X)` poly into 4 n-sized polynomials.
-    #[allow(clippy::type_complexity)] // NOTE: This is an ok type for internal use.
+    /// Split `t(X)` poly into `pieces` n-sized polynomials.
     fn split_tx_poly(
         &self,
         n: usize,
+        pieces: usize,
         t_x: &DensePolynomial<F>,
-    ) -> (
-        DensePolynomial<F>,
-        DensePolynomial<F>,
-        DensePolynomial<F>,
-        DensePolynomial<F>,
-    ) {
-        (
-            DensePolynomial::from_coefficients_vec(t_x[0..n].to_vec()),
-            DensePolynomial::from_coefficients_vec(t_x[n..2 * n].to_vec()),
-            DensePolynomial::from_coefficients_vec(t_x[2 * n..3 * n].to_vec()),
-            DensePolynomial::from_coefficients_vec(t_x[3 * n..].to_vec()),
-        )
+    ) -> Vec<DensePolynomial<F>> {
+        // `t(X)` may have a degree lower than `(pieces - 1)n` when the gates
+        // of the highest degree are not used, in which case the last pieces
+        // are zero.
+        let mut t_x = t_x.coeffs.clone();
+        t_x.resize(pieces * n, F::zero());
+        t_x.chunks(n)
+            .map(|piece| DensePolynomial::from_coefficients_vec(piece.to_vec()))
+            .collect()
     }
 
     /// Convert variables to their actual witness values.
@@ -142,14 +176,11 @@ where
     pub fn clear(&mut self) {
         self.clear_witness();
         self.prover_key = None;
-        self.preprocessed_transcript = Transcript::new(b"plonk");
+        self.preprocessed_transcript = T::new(b"plonk");
     }
 
-    /// Keys the [`Transcript`] with additional seed information
-    /// Wrapper around [`Transcript::append_message`].
-    ///
-    /// [`Transcript`]: merlin::Transcript
-    /// [`Transcript::append_message`]: merlin::Transcript::append_message
+    /// Keys the transcript with additional seed information
+    /// Wrapper around [`TranscriptProtocol::append_message`].
     pub fn key_transcript(&mut self, label: &'static [u8], message: &[u8]) {
         self.preprocessed_transcript.append_message(label, message);
     }
@@ -166,6 +197,52 @@ where
         prover_key: &ProverKey<F>,
         _data: PhantomData<PC>,
     ) -> Result<Proof<F, PC>, Error> {
+        self.prove_with_rng(commit_key, prover_key, None)
+    }
+
+    /// Creates a [`Proof`] that demonstrates that a circuit is satisfied,
+    /// with hiding commitments blinded by randomness drawn from `rng`.
+    ///
+    /// The commitments to the witness, permutation and quotient polynomials
+    /// have a hiding bound of [`HIDING_BOUND`], which the commit key must
+    /// support.
+    ///
+    /// Unlike the wires, which hold random blinding rows, the lookup
+    /// polynomials are not blinded, so their evaluations would reveal the
+    /// values looked up: an [`Error::HidingLookupsUnsupported`] is returned
+    /// for circuits with lookup gates.
+    ///
+    /// # Note
+    /// As for [`Prover::prove_with_preprocessed`], the user should call
+    /// [`Prover::clear_witness`] before constructing another [`Proof`].
+    pub fn prove_with_preprocessed_hiding<R>(
+        &self,
+        commit_key: &PC::CommitterKey,
+        prover_key: &ProverKey<F>,
+        rng: &mut R,
+    ) -> Result<Proof<F, PC>, Error>
+    where
+        R: RngCore,
+    {
+        self.prove_with_rng(commit_key, prover_key, Some(rng))
+    }
+
+    /// Creates a [`Proof`], with hiding commitments when an `rng` is given.
+    fn prove_with_rng(
+        &self,
+        commit_key: &PC::CommitterKey,
+        prover_key: &ProverKey<F>,
+        mut rng: Option<&mut dyn RngCore>,
+    ) -> Result<Proof<F, PC>, Error> {
+        let hiding_bound = rng.as_ref().map(|_| HIDING_BOUND);
+        if hiding_bound.is_some()
+            && self.cs.q_lookup.iter().any(|q_lookup| !q_lookup.is_zero())
+        {
+            return Err(Error::HidingLookupsUnsupported);
+        }
+
+        self.cs.check_srs_degree(commit_key.supported_degree())?;
+
         let domain =
             GeneralEvaluationDomain::new(self.cs.circuit_bound()).ok_or(Error::InvalidEvalDomainSize {
                 log_size_of_group: self.cs.circuit_bound().trailing_zeros(),
@@ -203,15 +280,16 @@ where
             DensePolynomial::from_coefficients_vec(domain.ifft(w_4_scalar));
 
         let w_polys = [
-            label_polynomial!(w_l_poly),
-            label_polynomial!(w_r_poly),
-            label_polynomial!(w_o_poly),
-            label_polynomial!(w_4_poly),
+            label_polynomial!(w_l_poly, hiding_bound),
+            label_polynomial!(w_r_poly, hiding_bound),
+            label_polynomial!(w_o_poly, hiding_bound),
+            label_polynomial!(w_4_poly, hiding_bound),
         ];
 
         // Commit to witness polynomials.
-        let (w_commits, w_rands) = PC::commit(commit_key, w_polys.iter(), None)
-            .map_err(to_pc_error::<F, PC>)?;
+        let (w_commits, w_rands) =
+            PC::commit(commit_key, w_polys.iter(), reborrow(&mut rng))
+                .map_err(to_pc_error::<F, PC>)?;
 
         // Add witness polynomial commitments to transcript.
         transcript.append(b"w_l", w_commits[0].commitment());
@@ -223,7 +301,7 @@ where
 
         // Generate table compression factor
         let zeta = transcript.challenge_scalar(b"zeta");
-        transcript.append(b"zeta", &zeta);
+        transcript.append_scalar(b"zeta", &zeta);
 
         // Compress lookup table into vector of single elements
         let compressed_t_multiset = MultiSet::compress(
@@ -232,6 +310,7 @@ where
                 prover_key.lookup.table_2.clone(),
                 prover_key.lookup.table_3.clone(),
                 prover_key.lookup.table_4.clone(),
+                prover_key.lookup.table_id.clone(),
             ],
             zeta,
         );
@@ -244,7 +323,8 @@ where
         // Compute query table f
         // When q_lookup[i] is zero the wire value is replaced with a dummy
         //   value currently set as the first row of the public table
-        // If q_lookup[i] is one the wire values are preserved
+        // If q_lookup[i] is one the wire values are preserved, along with the
+        //   id of the queried table held by q_table[i]
         // This ensures the ith element of the compressed query table
         //   is an element of the compressed lookup table even when
         //   q_lookup[i] is 0 so the lookup check will pass
@@ -252,12 +332,15 @@ where
         let q_lookup_pad = vec![F::zero(); n - self.cs.q_lookup.len()];
         let padded_q_lookup =
             &[self.cs.q_lookup.as_slice(), q_lookup_pad.as_slice()].concat();
+        let mut padded_q_table = self.cs.q_table.clone();
+        padded_q_table.resize(n, F::zero());
 
         let mut f_scalars: Vec<MultiSet<F>> =
-            vec![MultiSet::with_capacity(w_l_scalar.len()); 4];
+            vec![MultiSet::with_capacity(w_l_scalar.len()); 5];
 
-        for (q_lookup, w_l, w_r, w_o, w_4) in izip!(
+        for (q_lookup, q_table, w_l, w_r, w_o, w_4) in izip!(
             padded_q_lookup,
+            padded_q_table,
             w_l_scalar,
             w_r_scalar,
             w_o_scalar,
@@ -271,6 +354,7 @@ where
                 f_scalars[1].push(*w_r);
                 f_scalars[2].push(*w_o);
                 f_scalars[3].push(*w_4);
+                f_scalars[4].push(q_table);
             }
         }
 
@@ -286,63 +370,85 @@ where
         // let f_poly = Self::add_blinder(&f_poly, n, 1);
 
         // Commit to query polynomial
-        let (f_poly_commit, _) =
-            PC::commit(commit_key, &[label_polynomial!(f_poly)], None)
+        let f_polys = [label_polynomial!(f_poly, hiding_bound)];
+        let (f_poly_commit, f_rands) =
+            PC::commit(commit_key, &f_polys, reborrow(&mut rng))
                 .map_err(to_pc_error::<F, PC>)?;
 
         // Add f_poly commitment to transcript
         transcript.append(b"f", f_poly_commit[0].commitment());
 
-        // Compute s, as the sorted and concatenated version of f and t
-        let (h_1, h_2) = compressed_t_multiset
-            .combine_split(&compressed_f_multiset)
-            .unwrap();
-
-        // Compute h polys
-        let h_1_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(&h_1.0));
-        let h_2_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(&h_2.0));
-
-        // Add blinders to h polynomials
-        // let h_1_poly = Self::add_blinder(&h_1_poly, n, 1);
-        // let h_2_poly = Self::add_blinder(&h_2_poly, n, 1);
-
-        // Commit to h polys
-        let (h_1_poly_commit, _) =
-            PC::commit(commit_key, &[label_polynomial!(h_1_poly)], None)
-                .map_err(to_pc_error::<F, PC>)?;
-        let (h_2_poly_commit, _) =
-            PC::commit(commit_key, &[label_polynomial!(h_2_poly)], None)
+        // Compute the sets of the lookup argument committed to before its
+        // challenges, which fails if a query is not a row of the table it is
+        // made against: for plookup the two halves `h_1` and `h_2` of s, as
+        // the sorted and concatenated version of f and t, and for LogUp the
+        // multiplicities `m` of the rows of t in f
+        let (lookup_sets, lookup_labels): (_, &[(&str, &'static [u8])]) =
+            match prover_key.lookup.mode {
+                LookupMode::Plookup => {
+                    let (h_1, h_2) = compressed_t_multiset
+                        .combine_split(&compressed_f_multiset)?;
+                    (
+                        vec![h_1, h_2],
+                        &[("h_1_poly", b"h1"), ("h_2_poly", b"h2")],
+                    )
+                }
+                LookupMode::LogUp => (
+                    vec![compressed_t_multiset
+                        .multiplicities(&compressed_f_multiset)?],
+                    &[("m_poly", b"m")],
+                ),
+            };
+
+        // Compute and commit to the polynomials of the sets
+        let lookup_set_polys = lookup_sets
+            .iter()
+            .zip(lookup_labels)
+            .map(|(set, (label, _))| {
+                LabeledPolynomial::new(
+                    label.to_string(),
+                    set.to_polynomial(&domain),
+                    None,
+                    hiding_bound,
+                )
+            })
+            .collect::<Vec<_>>();
+        let (lookup_set_commits, lookup_set_rands) =
+            PC::commit(commit_key, &lookup_set_polys, reborrow(&mut rng))
                 .map_err(to_pc_error::<F, PC>)?;
 
-        // Add h polynomials to transcript
-        transcript.append(b"h1", h_1_poly_commit[0].commitment());
-        transcript.append(b"h2", h_2_poly_commit[0].commitment());
+        // Add the polynomials of the sets to transcript
+        for (commit, (_, label)) in lookup_set_commits.iter().zip(lookup_labels)
+        {
+            transcript.append(label, commit.commitment());
+        }
 
         // 3. Compute permutation polynomial
         //
         // Compute permutation challenge `beta`.
         let beta = transcript.challenge_scalar(b"beta");
-        transcript.append(b"beta", &beta);
+        transcript.append_scalar(b"beta", &beta);
         // Compute permutation challenge `gamma`.
         let gamma = transcript.challenge_scalar(b"gamma");
-        transcript.append(b"gamma", &gamma);
+        transcript.append_scalar(b"gamma", &gamma);
         // Compute permutation challenge `delta`.
         let delta = transcript.challenge_scalar(b"delta");
-        transcript.append(b"delta", &delta);
+        transcript.append_scalar(b"delta", &delta);
 
         // Compute permutation challenge `epsilon`.
         let epsilon = transcript.challenge_scalar(b"epsilon");
-        transcript.append(b"epsilon", &epsilon);
+        transcript.append_scalar(b"epsilon", &epsilon);
 
         // Challenges must be different
-        assert!(beta != gamma, "challenges must be different");
-        assert!(beta != delta, "challenges must be different");
-        assert!(beta != epsilon, "challenges must be different");
-        assert!(gamma != delta, "challenges must be different");
-        assert!(gamma != epsilon, "challenges must be different");
-        assert!(delta != epsilon, "challenges must be different");
+        if beta == gamma
+            || beta == delta
+            || beta == epsilon
+            || gamma == delta
+            || gamma == epsilon
+            || delta == epsilon
+        {
+            return Err(Error::DegenerateChallenges);
+        }
 
         let z_poly = self.cs.perm.compute_permutation_poly(
             &domain,
@@ -358,36 +464,74 @@ where
         );
 
         // Commit to permutation polynomial.
-        let (z_poly_commit, _) =
-            PC::commit(commit_key, &[label_polynomial!(z_poly)], None)
+        let z_polys = [label_polynomial!(z_poly, hiding_bound)];
+        let (z_poly_commit, z_rands) =
+            PC::commit(commit_key, &z_polys, reborrow(&mut rng))
                 .map_err(to_pc_error::<F, PC>)?;
 
         // Add permutation polynomial commitment to transcript.
         transcript.append(b"z", z_poly_commit[0].commitment());
 
-        // Compute mega permutation polynomial.
-        // Compute lookup permutation poly
-        let z_2_poly = DensePolynomial::from_coefficients_slice(
-            &self.cs.perm.compute_lookup_permutation_poly(
-                &domain,
-                &compressed_f_multiset.0,
-                &compressed_t_multiset.0,
-                &h_1.0,
-                &h_2.0,
-                delta,
-                epsilon,
-            ),
-        );
+        // Compute the polynomial of the lookup argument accumulating its
+        // checks over the domain: for plookup the lookup permutation
+        // polynomial `z_2`, and for LogUp the running sum `phi`
+        let (lookup_acc_poly, lookup_acc_label, lookup_polys) = match prover_key
+            .lookup
+            .mode
+        {
+            LookupMode::Plookup => {
+                let z_2_poly = self.cs.perm.compute_lookup_permutation_poly(
+                    &domain,
+                    &compressed_f_multiset.0,
+                    &compressed_t_multiset.0,
+                    &lookup_sets[0].0,
+                    &lookup_sets[1].0,
+                    delta,
+                    epsilon,
+                );
+                let lookup_polys = LookupPolynomials::Plookup {
+                    h_1: lookup_set_polys[0].polynomial().clone(),
+                    h_2: lookup_set_polys[1].polynomial().clone(),
+                    z_2: z_2_poly.clone(),
+                };
+                (
+                    label_polynomial!(z_2_poly, hiding_bound),
+                    b"z_2",
+                    lookup_polys,
+                )
+            }
+            LookupMode::LogUp => {
+                let phi_poly = self.cs.perm.compute_lookup_running_sum_poly(
+                    &domain,
+                    &compressed_f_multiset.0,
+                    &compressed_t_multiset.0,
+                    &lookup_sets[0].0,
+                    epsilon,
+                );
+                let lookup_polys = LookupPolynomials::LogUp {
+                    m: lookup_set_polys[0].polynomial().clone(),
+                    phi: phi_poly.clone(),
+                };
+                (
+                    label_polynomial!(phi_poly, hiding_bound),
+                    b"phi",
+                    lookup_polys,
+                )
+            }
+        };
 
-        // TODO: Find strategy for blinding lookups
-        // Add blinder for lookup permutation poly
-        // z_2_poly = Self::add_blinder(&z_2_poly, n, 2);
+        // TODO: Find strategy for blinding lookups, so that hiding proofs of
+        // circuits with lookup gates can be made.
 
-        // Commit to lookup permutation polynomial.
-        let (z_2_poly_commit, _) =
-            PC::commit(commit_key, &[label_polynomial!(z_2_poly)], None)
+        // Commit to the lookup accumulator polynomial.
+        let lookup_acc_polys = [lookup_acc_poly];
+        let (lookup_acc_commit, lookup_acc_rands) =
+            PC::commit(commit_key, &lookup_acc_polys, reborrow(&mut rng))
                 .map_err(to_pc_error::<F, PC>)?;
 
+        // Add lookup accumulator polynomial commitment to transcript.
+        transcript.append(lookup_acc_label, lookup_acc_commit[0].commitment());
+
         // 3. Compute public inputs polynomial.
         let pi_poly = self.cs.get_pi().into_dense_poly(n);
 
@@ -396,40 +540,62 @@ where
         // Compute quotient challenge; `alpha`, and gate-specific separation
         // challenges.
         let alpha = transcript.challenge_scalar(b"alpha");
-        transcript.append(b"alpha", &alpha);
+        transcript.append_scalar(b"alpha", &alpha);
 
         let range_sep_challenge =
             transcript.challenge_scalar(b"range separation challenge");
-        transcript.append(b"range seperation challenge", &range_sep_challenge);
+        transcript
+            .append_scalar(b"range seperation challenge", &range_sep_challenge);
 
         let logic_sep_challenge =
             transcript.challenge_scalar(b"logic separation challenge");
-        transcript.append(b"logic seperation challenge", &logic_sep_challenge);
+        transcript
+            .append_scalar(b"logic seperation challenge", &logic_sep_challenge);
 
         let fixed_base_sep_challenge =
             transcript.challenge_scalar(b"fixed base separation challenge");
-        transcript.append(
+        transcript.append_scalar(
             b"fixed base separation challenge",
             &fixed_base_sep_challenge,
         );
 
         let var_base_sep_challenge =
             transcript.challenge_scalar(b"variable base separation challenge");
-        transcript.append(
+        transcript.append_scalar(
             b"variable base separation challenge",
             &var_base_sep_challenge,
         );
 
+        let poseidon_sep_challenge =
+            transcript.challenge_scalar(b"poseidon separation challenge");
+        transcript.append_scalar(
+            b"poseidon separation challenge",
+            &poseidon_sep_challenge,
+        );
+
         let lookup_sep_challenge =
             transcript.challenge_scalar(b"lookup separation challenge");
-        transcript
-            .append(b"lookup separation challenge", &lookup_sep_challenge);
+        transcript.append_scalar(
+            b"lookup separation challenge",
+            &lookup_sep_challenge,
+        );
+
+        let custom_sep_challenges = prover_key
+            .custom_gates
+            .iter()
+            .map(|_| {
+                let challenge =
+                    transcript.challenge_scalar(b"custom separation challenge");
+                transcript
+                    .append_scalar(b"custom separation challenge", &challenge);
+                challenge
+            })
+            .collect::<Vec<_>>();
 
         let t_poly = quotient_poly::compute::<F, P>(
             &domain,
             prover_key,
             &z_poly,
-            &z_2_poly,
             &w_l_poly,
             &w_r_poly,
             &w_o_poly,
@@ -437,8 +603,7 @@ where
             &pi_poly,
             &f_poly,
             &table_poly,
-            &h_1_poly,
-            &h_2_poly,
+            &lookup_polys,
             &alpha,
             &beta,
             &gamma,
@@ -449,36 +614,44 @@ where
             &logic_sep_challenge,
             &fixed_base_sep_challenge,
             &var_base_sep_challenge,
+            &poseidon_sep_challenge,
             &lookup_sep_challenge,
+            &self.cs.custom_gates,
+            &custom_sep_challenges,
         )?;
 
-        let (t_1_poly, t_2_poly, t_3_poly, t_4_poly) =
-            self.split_tx_poly(n, &t_poly);
+        let t_polys =
+            self.split_tx_poly(n, prover_key.quotient_pieces, &t_poly);
 
         // Commit to splitted quotient polynomial
-        let (t_commits, _) = PC::commit(
+        let (t_commits, t_rands) = PC::commit(
             commit_key,
-            &[
-                label_polynomial!(t_1_poly),
-                label_polynomial!(t_2_poly),
-                label_polynomial!(t_3_poly),
-                label_polynomial!(t_4_poly),
-            ],
-            None,
+            &t_polys
+                .iter()
+                .enumerate()
+                .map(|(i, t_i_poly)| {
+                    LabeledPolynomial::new(
+                        format!("t_{}_poly", i + 1),
+                        t_i_poly.clone(),
+                        None,
+                        hiding_bound,
+                    )
+                })
+                .collect::<Vec<_>>(),
+            reborrow(&mut rng),
         )
         .map_err(to_pc_error::<F, PC>)?;
 
         // Add quotient polynomial commitments to transcript
-        transcript.append(b"t_1", t_commits[0].commitment());
-        transcript.append(b"t_2", t_commits[1].commitment());
-        transcript.append(b"t_3", t_commits[2].commitment());
-        transcript.append(b"t_4", t_commits[3].commitment());
+        for t_commit in &t_commits {
+            transcript.append(b"t", t_commit.commitment());
+        }
 
         // 4. Compute linearisation polynomial
         //
         // Compute evaluation challenge; `z`.
         let z_challenge = transcript.challenge_scalar(b"z");
-        transcript.append(b"z", &z_challenge);
+        transcript.append_scalar(b"z", &z_challenge);
 
         let (lin_poly, evaluations) = linearisation_poly::compute::<F, P>(
             &domain,
@@ -493,55 +666,73 @@ where
             &logic_sep_challenge,
             &fixed_base_sep_challenge,
             &var_base_sep_challenge,
+            &poseidon_sep_challenge,
             &lookup_sep_challenge,
+            &self.cs.custom_gates,
+            &custom_sep_challenges,
             &z_challenge,
             &w_l_poly,
             &w_r_poly,
             &w_o_poly,
             &w_4_poly,
-            &t_1_poly,
-            &t_2_poly,
-            &t_3_poly,
-            &t_4_poly,
+            &t_polys,
             &z_poly,
-            &z_2_poly,
             &f_poly,
-            &h_1_poly,
-            &h_2_poly,
             &table_poly,
+            &lookup_polys,
         )?;
 
         // Add evaluations to transcript.
         // First wire evals
-        transcript.append(b"a_eval", &evaluations.wire_evals.a_eval);
-        transcript.append(b"b_eval", &evaluations.wire_evals.b_eval);
-        transcript.append(b"c_eval", &evaluations.wire_evals.c_eval);
-        transcript.append(b"d_eval", &evaluations.wire_evals.d_eval);
+        transcript.append_scalar(b"a_eval", &evaluations.wire_evals.a_eval);
+        transcript.append_scalar(b"b_eval", &evaluations.wire_evals.b_eval);
+        transcript.append_scalar(b"c_eval", &evaluations.wire_evals.c_eval);
+        transcript.append_scalar(b"d_eval", &evaluations.wire_evals.d_eval);
 
         // Second permutation evals
-        transcript
-            .append(b"left_sig_eval", &evaluations.perm_evals.left_sigma_eval);
-        transcript.append(
+        transcript.append_scalar(
+            b"left_sig_eval",
+            &evaluations.perm_evals.left_sigma_eval,
+        );
+        transcript.append_scalar(
             b"right_sig_eval",
             &evaluations.perm_evals.right_sigma_eval,
         );
-        transcript
-            .append(b"out_sig_eval", &evaluations.perm_evals.out_sigma_eval);
-        transcript
-            .append(b"perm_eval", &evaluations.perm_evals.permutation_eval);
+        transcript.append_scalar(
+            b"out_sig_eval",
+            &evaluations.perm_evals.out_sigma_eval,
+        );
+        transcript.append_scalar(
+            b"perm_eval",
+            &evaluations.perm_evals.permutation_eval,
+        );
 
         // Third lookup evals
-        transcript.append(b"f_eval", &evaluations.lookup_evals.f_eval);
-        transcript
-            .append(b"q_lookup_eval", &evaluations.lookup_evals.q_lookup_eval);
-        transcript.append(
-            b"lookup_perm_eval",
-            &evaluations.lookup_evals.z2_next_eval,
+        transcript.append_scalar(b"f_eval", &evaluations.lookup_evals.f_eval);
+        transcript.append_scalar(
+            b"q_lookup_eval",
+            &evaluations.lookup_evals.q_lookup_eval,
         );
-        transcript.append(b"h_1_eval", &evaluations.lookup_evals.h1_eval);
         transcript
-            .append(b"h_1_next_eval", &evaluations.lookup_evals.h1_next_eval);
-        transcript.append(b"h_2_eval", &evaluations.lookup_evals.h2_eval);
+            .append_scalar(b"table_eval", &evaluations.lookup_evals.table_eval);
+        match evaluations.lookup_evals.argument_evals {
+            LookupArgumentEvaluations::Plookup {
+                z2_next_eval,
+                h1_eval,
+                h1_next_eval,
+                h2_eval,
+                table_next_eval,
+            } => {
+                transcript.append_scalar(b"lookup_perm_eval", &z2_next_eval);
+                transcript.append_scalar(b"h_1_eval", &h1_eval);
+                transcript.append_scalar(b"h_1_next_eval", &h1_next_eval);
+                transcript.append_scalar(b"h_2_eval", &h2_eval);
+                transcript.append_scalar(b"table_next_eval", &table_next_eval);
+            }
+            LookupArgumentEvaluations::LogUp { phi_next_eval } => {
+                transcript.append_scalar(b"phi_next_eval", &phi_next_eval);
+            }
+        }
 
         // Third, all evals needed for custom gates
         evaluations
@@ -550,7 +741,7 @@ where
             .iter()
             .for_each(|(label, eval)| {
                 let static_label = Box::leak(label.to_owned().into_boxed_str());
-                transcript.append(static_label.as_bytes(), eval);
+                transcript.append_scalar(static_label.as_bytes(), eval);
             });
 
         // 5. Compute Openings using KZG10
@@ -562,81 +753,272 @@ where
         // challenge `z`
         let aw_challenge: F = transcript.challenge_scalar(b"aggregate_witness");
 
+        // The verifier recomputes the commitment to the linearisation
+        // polynomial from the commitments of the proof, so that it carries the
+        // blinding randomness of the hidden polynomials it depends on,
+        // `z`, `z_2` and `h_1` or `phi` and `m`, and the pieces of the
+        // quotient polynomial, scaled by their coefficient in the
+        // linearisation polynomial.
+        let vanishing_poly_eval =
+            domain.evaluate_vanishing_polynomial(z_challenge);
+        let l1_eval = compute_first_lagrange_evaluation(
+            &domain,
+            &vanishing_poly_eval,
+            &z_challenge,
+        );
+        let t_scalars = compute_quotient_linearisation_scalars(
+            &domain,
+            z_challenge,
+            t_polys.len(),
+        );
+        let lin_scalars = [permutation::compute_z_linearisation_scalar(
+            &evaluations,
+            z_challenge,
+            (alpha, beta, gamma),
+            l1_eval,
+        )]
+        .into_iter()
+        .chain(compute_argument_linearisation_scalars(
+            &evaluations.lookup_evals,
+            (delta, epsilon),
+            lookup_sep_challenge,
+            l1_eval,
+        ))
+        .chain(t_scalars)
+        .collect::<Vec<_>>();
+        // The linearised polynomials of both lookup arguments are the
+        // accumulator followed by the first set
+        let lin_hidden_polys = [&z_poly]
+            .into_iter()
+            .chain(lookup_polys.linearised())
+            .chain(&t_polys)
+            .collect::<Vec<_>>();
+        let lin_hidden_commits = [
+            &z_poly_commit[0],
+            &lookup_acc_commit[0],
+            &lookup_set_commits[0],
+        ]
+        .into_iter()
+        .chain(&t_commits)
+        .map(|commit| commit.commitment().clone())
+        .collect::<Vec<_>>();
+        let lin_hidden_rands =
+            [&z_rands[0], &lookup_acc_rands[0], &lookup_set_rands[0]]
+                .into_iter()
+                .chain(&t_rands)
+                .cloned()
+                .collect::<Vec<_>>();
+
+        // Commit to the part of the linearisation polynomial which does not
+        // depend on the hidden polynomials, then add their commitments.
+        let mut lin_rest_poly = lin_poly.clone();
+        for (scalar, poly) in lin_scalars.iter().zip(lin_hidden_polys) {
+            lin_rest_poly += (-*scalar, poly);
+        }
+        let (lin_rest_commit, _) =
+            PC::commit(commit_key, &[label_polynomial!(lin_rest_poly)], None)
+                .map_err(to_pc_error::<F, PC>)?;
+        let lin_comm = PC::multi_scalar_mul(
+            &[
+                &[lin_rest_commit[0].commitment().clone()],
+                &lin_hidden_commits[..],
+            ]
+            .concat(),
+            &[&[F::one()], &lin_scalars[..]].concat(),
+        );
+        let lin_rand = PC::combine_randomness(&lin_hidden_rands, &lin_scalars);
+
         // XXX: The quotient polynmials is used here and then in the
         // opening poly. It is being left in for now but it may not
         // be necessary. Warrants further investigation.
         // Ditto with the out_sigma poly.
-        let aw_polys = [
-            label_polynomial!(lin_poly),
+        //
+        // The selectors whose evaluations are read by the gates are opened
+        // as well, so that the prover cannot choose them.
+        let public_polys = [
             label_polynomial!(prover_key.permutation.left_sigma.0.clone()),
             label_polynomial!(prover_key.permutation.right_sigma.0.clone()),
             label_polynomial!(prover_key.permutation.out_sigma.0.clone()),
-            label_polynomial!(f_poly),
-            label_polynomial!(h_2_poly),
-            label_polynomial!(table_poly),
+            label_polynomial!(prover_key.arithmetic.q_l.0.clone()),
+            label_polynomial!(prover_key.arithmetic.q_r.0.clone()),
+            label_polynomial!(prover_key.arithmetic.q_o.0.clone()),
+            label_polynomial!(prover_key.arithmetic.q_4.0.clone()),
+            label_polynomial!(prover_key.arithmetic.q_c.0.clone()),
+            label_polynomial!(prover_key.arithmetic.q_arith.0.clone()),
         ];
+        let (public_commits, _) = PC::commit(commit_key, &public_polys, None)
+            .map_err(to_pc_error::<F, PC>)?;
 
-        let (aw_commits, aw_rands) = PC::commit(commit_key, &aw_polys, None)
+        let table_polys = [label_polynomial!(table_poly)];
+        let (table_commits, _) = PC::commit(commit_key, &table_polys, None)
             .map_err(to_pc_error::<F, PC>)?;
 
-        let aw_opening = PC::open(
+        // Besides the accumulator, plookup opens `h_2` at `z` and `h_1` and
+        // the table at the shifted `z`, whereas LogUp opens no other
+        // polynomial of its argument.
+        let (lookup_aw_sets, lookup_saw_sets, lookup_saw_tables) =
+            match prover_key.lookup.mode {
+                LookupMode::Plookup => (1..2, 0..1, &table_polys[..]),
+                LookupMode::LogUp => (0..0, 0..0, &[][..]),
+            };
+        let table_commits_shifted = &table_commits[..lookup_saw_tables.len()];
+
+        let lin_polys = [label_polynomial!(lin_poly, hiding_bound)];
+        let lin_commits = [LabeledCommitment::new(
+            lin_polys[0].label().clone(),
+            lin_comm,
+            None,
+        )];
+        let empty_rand = PC::Randomness::empty();
+
+        let aw_opening = Self::open_aggregated(
             commit_key,
-            aw_polys.iter().chain(w_polys.iter()),
-            aw_commits.iter().chain(w_commits.iter()),
-            &z_challenge,
+            lin_polys
+                .iter()
+                .chain(&public_polys)
+                .chain(&f_polys)
+                .chain(&lookup_set_polys[lookup_aw_sets.clone()])
+                .chain(&table_polys)
+                .chain(&w_polys),
+            lin_commits
+                .iter()
+                .chain(&public_commits)
+                .chain(&f_poly_commit)
+                .chain(&lookup_set_commits[lookup_aw_sets.clone()])
+                .chain(&table_commits)
+                .chain(&w_commits),
+            [&lin_rand]
+                .into_iter()
+                .chain(public_polys.iter().map(|_| &empty_rand))
+                .chain(&f_rands)
+                .chain(&lookup_set_rands[lookup_aw_sets])
+                .chain([&empty_rand])
+                .chain(&w_rands),
+            z_challenge,
             aw_challenge,
-            aw_rands.iter().chain(w_rands.iter()),
-            None,
-        )
-        .map_err(to_pc_error::<F, PC>)?;
+            hiding_bound,
+            reborrow(&mut rng),
+        )?;
 
         let saw_challenge: F =
             transcript.challenge_scalar(b"aggregate_witness");
 
-        let saw_polys = [
-            label_polynomial!(z_poly),
-            label_polynomial!(w_l_poly),
-            label_polynomial!(w_r_poly),
-            label_polynomial!(w_4_poly),
-            label_polynomial!(h_1_poly),
-            label_polynomial!(z_2_poly),
-            label_polynomial!(table_poly),
-        ];
-
-        let (saw_commits, saw_rands) = PC::commit(commit_key, &saw_polys, None)
-            .map_err(to_pc_error::<F, PC>)?;
-
-        let saw_opening = PC::open(
+        let saw_opening = Self::open_aggregated(
             commit_key,
-            &saw_polys,
-            &saw_commits,
-            &(z_challenge * domain.element(1)),
+            z_polys
+                .iter()
+                .chain(&w_polys)
+                .chain(&lookup_set_polys[lookup_saw_sets.clone()])
+                .chain(&lookup_acc_polys)
+                .chain(lookup_saw_tables),
+            z_poly_commit
+                .iter()
+                .chain(&w_commits)
+                .chain(&lookup_set_commits[lookup_saw_sets.clone()])
+                .chain(&lookup_acc_commit)
+                .chain(table_commits_shifted),
+            z_rands
+                .iter()
+                .chain(&w_rands)
+                .chain(&lookup_set_rands[lookup_saw_sets])
+                .chain(&lookup_acc_rands)
+                .chain(table_commits_shifted.iter().map(|_| &empty_rand)),
+            z_challenge * domain.element(1),
             saw_challenge,
-            &saw_rands,
-            None,
-        )
-        .map_err(to_pc_error::<F, PC>)?;
+            hiding_bound,
+            rng,
+        )?;
 
         Ok(Proof {
             a_comm: w_commits[0].commitment().clone(),
             b_comm: w_commits[1].commitment().clone(),
             c_comm: w_commits[2].commitment().clone(),
             d_comm: w_commits[3].commitment().clone(),
-            z_comm: saw_commits[0].commitment().clone(),
+            z_comm: z_poly_commit[0].commitment().clone(),
             f_comm: f_poly_commit[0].commitment().clone(),
-            h_1_comm: h_1_poly_commit[0].commitment().clone(),
-            h_2_comm: h_2_poly_commit[0].commitment().clone(),
-            z_2_comm: z_2_poly_commit[0].commitment().clone(),
-            t_1_comm: t_commits[0].commitment().clone(),
-            t_2_comm: t_commits[1].commitment().clone(),
-            t_3_comm: t_commits[2].commitment().clone(),
-            t_4_comm: t_commits[3].commitment().clone(),
+            lookup_comms: match prover_key.lookup.mode {
+                LookupMode::Plookup => LookupCommitments::Plookup {
+                    h_1_comm: lookup_set_commits[0].commitment().clone(),
+                    h_2_comm: lookup_set_commits[1].commitment().clone(),
+                    z_2_comm: lookup_acc_commit[0].commitment().clone(),
+                },
+                LookupMode::LogUp => LookupCommitments::LogUp {
+                    m_comm: lookup_set_commits[0].commitment().clone(),
+                    phi_comm: lookup_acc_commit[0].commitment().clone(),
+                },
+            },
+            t_comms: t_commits
+                .iter()
+                .map(|commit| commit.commitment().clone())
+                .collect(),
             aw_opening,
             saw_opening,
             evaluations,
         })
     }
 
+    /// Opens at `point` the linear combination of `polynomials` by the powers
+    /// of `challenge`, which is checked against the same combination of their
+    /// `commitments`.
+    ///
+    /// Opening a single polynomial per point makes the opening independent of
+    /// how the commitment scheme combines the polynomials of an opening, so
+    /// that the openings of many proofs can be batched.
+    #[allow(clippy::too_many_arguments)]
+    fn open_aggregated<'a>(
+        commit_key: &PC::CommitterKey,
+        polynomials: impl IntoIterator<
+            Item = &'a LabeledPolynomial<F, DensePolynomial<F>>,
+        >,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<PC::Commitment>>,
+        rands: impl IntoIterator<Item = &'a PC::Randomness>,
+        point: F,
+        challenge: F,
+        hiding_bound: Option<usize>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<PC::Proof, Error>
+    where
+        PC::Commitment: 'a,
+        PC::Randomness: 'a,
+    {
+        let mut aggregated = DensePolynomial::zero();
+        let mut powers = Vec::new();
+        for (polynomial, power) in
+            polynomials.into_iter().zip(powers_of(challenge))
+        {
+            aggregated += (power, polynomial.polynomial());
+            powers.push(power);
+        }
+        let commitments = commitments
+            .into_iter()
+            .map(|commitment| commitment.commitment().clone())
+            .collect::<Vec<_>>();
+        let rands = rands.into_iter().cloned().collect::<Vec<_>>();
+
+        let polynomial = LabeledPolynomial::new(
+            "aggregated".to_owned(),
+            aggregated,
+            None,
+            hiding_bound,
+        );
+        let commitment = LabeledCommitment::new(
+            "aggregated".to_owned(),
+            PC::multi_scalar_mul(&commitments, &powers),
+            None,
+        );
+        let rand = PC::combine_randomness(&rands, &powers);
+        PC::open(
+            commit_key,
+            &[polynomial],
+            &[commitment],
+            &point,
+            challenge,
+            &[rand],
+            rng,
+        )
+        .map_err(to_pc_error::<F, PC>)
+    }
+
     /// Proves a circuit is satisfied, then clears the witness variables
     /// If the circuit is not pre-processed, then the preprocessed circuit will
     /// also be computed.
@@ -667,16 +1049,253 @@ where
 
         Ok(proof)
     }
+
+    /// Proves a circuit is satisfied with hiding commitments blinded by
+    /// randomness drawn from `rng`, then clears the witness variables.
+    /// If the circuit is not pre-processed, then the preprocessed circuit will
+    /// also be computed.
+    ///
+    /// The commit key must support a hiding bound of [`HIDING_BOUND`], and
+    /// the circuit must not have lookup gates, as described for
+    /// [`Prover::prove_with_preprocessed_hiding`].
+    pub fn prove_hiding<R>(
+        &mut self,
+        commit_key: &PC::CommitterKey,
+        rng: &mut R,
+    ) -> Result<Proof<F, PC>, Error>
+    where
+        R: RngCore,
+    {
+        if self.prover_key.is_none() {
+            // Preprocess circuit and store preprocessed circuit and transcript
+            // in the Prover.
+            self.prover_key = Some(self.cs.preprocess_prover(
+                commit_key,
+                &mut self.preprocessed_transcript,
+                PhantomData::<PC>,
+            )?);
+        }
+
+        let prover_key = self.prover_key.as_ref().unwrap();
+
+        let proof =
+            self.prove_with_preprocessed_hiding(commit_key, prover_key, rng)?;
+
+        // Clear witness and reset composer variables
+        self.clear_witness();
+
+        Ok(proof)
+    }
+}
+
+/// Reborrows an optional random number generator for a single use.
+fn reborrow<'a>(
+    rng: &'a mut Option<&mut dyn RngCore>,
+) -> Option<&'a mut dyn RngCore> {
+    rng.as_mut().map(|rng| &mut **rng as &mut dyn RngCore)
 }
 
-impl<F, P, PC> Default for Prover<F, P, PC>
+impl<F, P, PC, T> Default for Prover<F, P, PC, T>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol<F>,
 {
     #[inline]
     fn default() -> Self {
         Prover::new(b"plonk")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test, error::to_pc_error, lookup::LookupTable,
+        proof_system::Verifier,
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_serialize::CanonicalSerialize;
+    use rand_core::OsRng;
+
+    /// Proves that `a * b = 15`, for the witnesses `a` and `b`.
+    fn gadget<F, P>(composer: &mut StandardComposer<F, P>, a: u64, b: u64)
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let a_var = composer.add_input(F::from(a));
+        let b_var = composer.add_input(F::from(b));
+        let c = composer.arithmetic_gate(|gate| {
+            gate.witness(a_var, b_var, None)
+                .mul(F::one())
+                .pi(-F::from(15u64))
+        });
+        composer.constrain_to_constant(c, F::zero(), None);
+        composer.range_gate(a_var, 8);
+    }
+
+    /// Looks up `a xor b` on top of the constraints of [`gadget`].
+    fn lookup_gadget<F, P>(
+        composer: &mut StandardComposer<F, P>,
+        a: u64,
+        b: u64,
+    ) where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        gadget(composer, a, b);
+        let xor_table = composer
+            .add_lookup_table("xor", LookupTable::xor_table(0, 4))
+            .unwrap();
+        let a_var = composer.add_input(F::from(a));
+        let b_var = composer.add_input(F::from(b));
+        let xor = composer.add_input(F::from(a ^ b));
+        let negative_one = composer.add_input(-F::one());
+        composer.lookup_gate(
+            xor_table,
+            a_var,
+            b_var,
+            xor,
+            Some(negative_one),
+            None,
+        );
+    }
+
+    fn test_hiding_proofs<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = (|| -> Result<(), Error> {
+            let pp = PC::setup(2 * 512, None, &mut OsRng)
+                .map_err(to_pc_error::<F, PC>)?;
+
+            let mut prover = Prover::<F, P, PC>::new(b"hiding");
+            gadget(prover.mut_cs(), 3, 5);
+            let (ck, vk) =
+                PC::trim(&pp, prover.circuit_bound(), HIDING_BOUND, None)
+                    .map_err(to_pc_error::<F, PC>)?;
+            prover.preprocess(&ck)?;
+            let public_inputs = prover.mut_cs().get_pi().clone();
+
+            // Two different witnesses for the same public input.
+            let proof_1 = prover.prove_hiding(&ck, &mut OsRng)?;
+            gadget(prover.mut_cs(), 5, 3);
+            let proof_2 = prover.prove_hiding(&ck, &mut OsRng)?;
+
+            // The same witness gives different commitments.
+            gadget(prover.mut_cs(), 3, 5);
+            let proof_3 = prover.prove_hiding(&ck, &mut OsRng)?;
+            let bytes = |comm: &PC::Commitment| {
+                let mut bytes = Vec::new();
+                comm.serialize(&mut bytes).unwrap();
+                bytes
+            };
+            assert_ne!(bytes(&proof_1.a_comm), bytes(&proof_3.a_comm));
+            assert_ne!(bytes(&proof_1.z_comm), bytes(&proof_3.z_comm));
+            assert_ne!(bytes(&proof_1.t_comms[0]), bytes(&proof_3.t_comms[0]));
+
+            // Non-hiding proofs are still accepted by the same verifier.
+            gadget(prover.mut_cs(), 3, 5);
+            let proof_4 = prover.prove(&ck)?;
+
+            let mut verifier = Verifier::<F, P, PC>::new(b"hiding");
+            gadget(verifier.mut_cs(), 3, 5);
+            verifier.preprocess(&ck)?;
+            for proof in [&proof_1, &proof_2, &proof_3, &proof_4] {
+                verifier.verify(proof, &vk, &public_inputs)?;
+            }
+            Ok(())
+        })();
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_hiding_proof_wrong_witness<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = (|| -> Result<(), Error> {
+            let pp = PC::setup(2 * 512, None, &mut OsRng)
+                .map_err(to_pc_error::<F, PC>)?;
+
+            let mut prover = Prover::<F, P, PC>::new(b"hiding");
+            gadget(prover.mut_cs(), 3, 5);
+            let (ck, vk) =
+                PC::trim(&pp, prover.circuit_bound(), HIDING_BOUND, None)
+                    .map_err(to_pc_error::<F, PC>)?;
+            prover.preprocess(&ck)?;
+            let public_inputs = prover.mut_cs().get_pi().clone();
+            prover.clear_witness();
+            gadget(prover.mut_cs(), 3, 4);
+            let proof = prover.prove_hiding(&ck, &mut OsRng)?;
+
+            let mut verifier = Verifier::<F, P, PC>::new(b"hiding");
+            gadget(verifier.mut_cs(), 3, 5);
+            verifier.preprocess(&ck)?;
+            verifier.verify(&proof, &vk, &public_inputs)
+        })();
+        assert!(res.is_err());
+    }
+
+    fn test_hiding_proof_with_lookups<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = (|| -> Result<(), Error> {
+            let pp = PC::setup(2 * 512, None, &mut OsRng)
+                .map_err(to_pc_error::<F, PC>)?;
+
+            let mut prover = Prover::<F, P, PC>::new(b"hiding");
+            lookup_gadget(prover.mut_cs(), 3, 5);
+            let (ck, _) =
+                PC::trim(&pp, prover.circuit_bound(), HIDING_BOUND, None)
+                    .map_err(to_pc_error::<F, PC>)?;
+            prover.preprocess(&ck)?;
+
+            // The lookup polynomials are not blinded.
+            let res = prover.prove_hiding(&ck, &mut OsRng);
+            assert!(
+                matches!(res, Err(Error::HidingLookupsUnsupported)),
+                "{:?}",
+                res.err()
+            );
+
+            // Whereas non-hiding proofs of the circuit can be made.
+            prover.prove(&ck)?;
+            Ok(())
+        })();
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Bls12-381 tests
+    batch_test!(
+        [
+            test_hiding_proofs,
+            test_hiding_proof_wrong_witness,
+            test_hiding_proof_with_lookups
+        ],
+        [] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Bls12-377 tests
+    batch_test!(
+        [
+            test_hiding_proofs,
+            test_hiding_proof_wrong_witness,
+            test_hiding_proof_with_lookups
+        ],
+        [] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
@@ -7,10 +7,12 @@
 use crate::{
     error::Error,
     proof_system::{
+        custom::CustomGates,
         ecc::{CurveAddition, FixedBaseScalarMul},
         logic::Logic,
+        poseidon::PoseidonRound,
         range::Range,
-        widget::GateConstraint,
+        widget::{lookup::LookupPolynomials, GateConstraint},
         ProverKey,
     },
 };
@@ -22,11 +24,8 @@ use ark_poly::{
 };
 
 use super::{
-    ecc::{CAVals, FBSMVals},
-    linearisation_poly::CustomEvaluations,
-    logic::LogicVals,
-    range::RangeVals,
-    CustomValues, WitnessValues,
+    expression::ExpressionVals, linearisation_poly::CustomEvaluations,
+    poseidon::PoseidonVals, CustomValues, WitnessValues,
 };
 
 /// Computes the Quotient [`DensePolynomial`] given the [`EvaluationDomain`], a
@@ -35,7 +34,6 @@ pub fn compute<F, P>(
     domain: &GeneralEvaluationDomain<F>,
     prover_key: &ProverKey<F>,
     z_poly: &DensePolynomial<F>,
-    z2_poly: &DensePolynomial<F>,
     w_l_poly: &DensePolynomial<F>,
     w_r_poly: &DensePolynomial<F>,
     w_o_poly: &DensePolynomial<F>,
@@ -43,8 +41,7 @@ pub fn compute<F, P>(
     public_inputs_poly: &DensePolynomial<F>,
     f_poly: &DensePolynomial<F>,
     table_poly: &DensePolynomial<F>,
-    h1_poly: &DensePolynomial<F>,
-    h2_poly: &DensePolynomial<F>,
+    lookup_polys: &LookupPolynomials<F>,
     alpha: &F,
     beta: &F,
     gamma: &F,
@@ -55,69 +52,41 @@ pub fn compute<F, P>(
     logic_challenge: &F,
     fixed_base_challenge: &F,
     var_base_challenge: &F,
+    poseidon_challenge: &F,
     lookup_challenge: &F,
+    custom_gates: &CustomGates<F>,
+    custom_challenges: &[F],
 ) -> Result<DensePolynomial<F>, Error>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
 {
-    let domain_4n = GeneralEvaluationDomain::<F>::new(4 * domain.size())
-        .ok_or(Error::InvalidEvalDomainSize {
-        log_size_of_group: (4 * domain.size()).trailing_zeros(),
-        adicity:
-            <<F as FftField>::FftParams as ark_ff::FftParameters>::TWO_ADICITY,
-    })?;
+    let domain_ext = prover_key.extended_domain();
+    // Offset of the evaluations at the next element of `domain`
+    let next = domain_ext.size() / domain.size();
 
     let l1_poly = compute_first_lagrange_poly_scaled(domain, F::one());
-    let l1_eval_4n = domain_4n.coset_fft(&l1_poly);
+    let l1_eval_ext = domain_ext.coset_fft(&l1_poly);
 
-    let mut z_eval_4n = domain_4n.coset_fft(z_poly);
-    z_eval_4n.push(z_eval_4n[0]);
-    z_eval_4n.push(z_eval_4n[1]);
-    z_eval_4n.push(z_eval_4n[2]);
-    z_eval_4n.push(z_eval_4n[3]);
+    let mut z_eval_ext = domain_ext.coset_fft(z_poly);
+    z_eval_ext.extend_from_within(..next);
 
-    let mut wl_eval_4n = domain_4n.coset_fft(w_l_poly);
-    wl_eval_4n.push(wl_eval_4n[0]);
-    wl_eval_4n.push(wl_eval_4n[1]);
-    wl_eval_4n.push(wl_eval_4n[2]);
-    wl_eval_4n.push(wl_eval_4n[3]);
+    let mut wl_eval_ext = domain_ext.coset_fft(w_l_poly);
+    wl_eval_ext.extend_from_within(..next);
 
-    let mut wr_eval_4n = domain_4n.coset_fft(w_r_poly);
-    wr_eval_4n.push(wr_eval_4n[0]);
-    wr_eval_4n.push(wr_eval_4n[1]);
-    wr_eval_4n.push(wr_eval_4n[2]);
-    wr_eval_4n.push(wr_eval_4n[3]);
+    let mut wr_eval_ext = domain_ext.coset_fft(w_r_poly);
+    wr_eval_ext.extend_from_within(..next);
 
-    let wo_eval_4n = domain_4n.coset_fft(w_o_poly);
+    let mut wo_eval_ext = domain_ext.coset_fft(w_o_poly);
+    wo_eval_ext.extend_from_within(..next);
 
-    let mut w4_eval_4n = domain_4n.coset_fft(w_4_poly);
-    w4_eval_4n.push(w4_eval_4n[0]);
-    w4_eval_4n.push(w4_eval_4n[1]);
-    w4_eval_4n.push(w4_eval_4n[2]);
-    w4_eval_4n.push(w4_eval_4n[3]);
+    let mut w4_eval_ext = domain_ext.coset_fft(w_4_poly);
+    w4_eval_ext.extend_from_within(..next);
 
-    let mut z2_eval_4n = domain_4n.coset_fft(z2_poly);
-    z2_eval_4n.push(z2_eval_4n[0]);
-    z2_eval_4n.push(z2_eval_4n[1]);
-    z2_eval_4n.push(z2_eval_4n[2]);
-    z2_eval_4n.push(z2_eval_4n[3]);
+    let f_eval_ext = domain_ext.coset_fft(f_poly);
 
-    let f_eval_4n = domain_4n.coset_fft(f_poly);
-
-    let mut table_eval_4n = domain_4n.coset_fft(table_poly);
-    table_eval_4n.push(table_eval_4n[0]);
-    table_eval_4n.push(table_eval_4n[1]);
-    table_eval_4n.push(table_eval_4n[2]);
-    table_eval_4n.push(table_eval_4n[3]);
-
-    let mut h1_eval_4n = domain_4n.coset_fft(h1_poly);
-    h1_eval_4n.push(h1_eval_4n[0]);
-    h1_eval_4n.push(h1_eval_4n[1]);
-    h1_eval_4n.push(h1_eval_4n[2]);
-    h1_eval_4n.push(h1_eval_4n[3]);
-
-    let h2_eval_4n = domain_4n.coset_fft(h2_poly);
+    let mut table_eval_ext = domain_ext.coset_fft(table_poly);
+    table_eval_ext.extend_from_within(..next);
 
     let gate_constraints = compute_gate_constraint_satisfiability::<F, P>(
         domain,
@@ -125,55 +94,57 @@ where
         *logic_challenge,
         *fixed_base_challenge,
         *var_base_challenge,
+        *poseidon_challenge,
+        custom_gates,
+        custom_challenges,
         prover_key,
-        &wl_eval_4n,
-        &wr_eval_4n,
-        &wo_eval_4n,
-        &w4_eval_4n,
+        &wl_eval_ext,
+        &wr_eval_ext,
+        &wo_eval_ext,
+        &w4_eval_ext,
         public_inputs_poly,
     )?;
 
     let permutation = compute_permutation_checks::<F>(
         domain,
         prover_key,
-        &wl_eval_4n,
-        &wr_eval_4n,
-        &wo_eval_4n,
-        &w4_eval_4n,
-        &z_eval_4n,
+        &wl_eval_ext,
+        &wr_eval_ext,
+        &wo_eval_ext,
+        &w4_eval_ext,
+        &z_eval_ext,
         *alpha,
         *beta,
         *gamma,
-    )?;
+    );
 
     let lookup = prover_key.lookup.compute_lookup_quotient_term(
         domain,
-        &wl_eval_4n,
-        &wr_eval_4n,
-        &wo_eval_4n,
-        &w4_eval_4n,
-        &f_eval_4n,
-        &table_eval_4n,
-        &h1_eval_4n,
-        &h2_eval_4n,
-        &z2_eval_4n,
-        &l1_eval_4n,
+        &domain_ext,
+        &wl_eval_ext,
+        &wr_eval_ext,
+        &wo_eval_ext,
+        &w4_eval_ext,
+        &f_eval_ext,
+        &table_eval_ext,
+        lookup_polys,
+        &l1_eval_ext,
         *delta,
         *epsilon,
         *zeta,
         *lookup_challenge,
-    )?;
+    );
 
-    let quotient = (0..domain_4n.size())
+    let quotient = (0..domain_ext.size())
         .map(|i| {
             let numerator = gate_constraints[i] + permutation[i] + lookup[i];
-            let denominator = prover_key.v_h_coset_4n()[i];
+            let denominator = prover_key.v_h_coset_ext()[i];
             numerator * denominator.inverse().unwrap()
         })
         .collect::<Vec<_>>();
 
     Ok(DensePolynomial::from_coefficients_vec(
-        domain_4n.coset_ifft(&quotient),
+        domain_ext.coset_ifft(&quotient),
     ))
 }
 
@@ -185,42 +156,53 @@ fn compute_gate_constraint_satisfiability<F, P>(
     logic_challenge: F,
     fixed_base_challenge: F,
     var_base_challenge: F,
+    poseidon_challenge: F,
+    custom_gates: &CustomGates<F>,
+    custom_challenges: &[F],
     prover_key: &ProverKey<F>,
-    wl_eval_4n: &[F],
-    wr_eval_4n: &[F],
-    wo_eval_4n: &[F],
-    w4_eval_4n: &[F],
+    wl_eval_ext: &[F],
+    wr_eval_ext: &[F],
+    wo_eval_ext: &[F],
+    w4_eval_ext: &[F],
     pi_poly: &DensePolynomial<F>,
 ) -> Result<Vec<F>, Error>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
 {
-    let domain_4n = GeneralEvaluationDomain::<F>::new(4 * domain.size())
-        .ok_or(Error::InvalidEvalDomainSize {
-        log_size_of_group: (4 * domain.size()).trailing_zeros(),
-        adicity:
-            <<F as FftField>::FftParams as ark_ff::FftParameters>::TWO_ADICITY,
-    })?;
-    let pi_eval_4n = domain_4n.coset_fft(pi_poly);
+    let domain_ext = prover_key.extended_domain();
+    let next = domain_ext.size() / domain.size();
+    let pi_eval_ext = domain_ext.coset_fft(pi_poly);
+    let custom_gates = prover_key
+        .custom_gates
+        .iter()
+        .map(|key| Ok((key, custom_gates.get(&key.name)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let range = Range::expression();
+    let logic = Logic::expression();
+    let fixed_base_scalar_mul = FixedBaseScalarMul::<_, P>::expression();
+    let curve_addition = CurveAddition::<_, P>::expression();
 
     // TODO Eliminate contribution of unused gates
-    Ok((0..domain_4n.size())
+    Ok((0..domain_ext.size())
         .map(|i| {
             let wit_vals = WitnessValues {
-                a_val: wl_eval_4n[i],
-                b_val: wr_eval_4n[i],
-                c_val: wo_eval_4n[i],
-                d_val: w4_eval_4n[i],
+                a_val: wl_eval_ext[i],
+                b_val: wr_eval_ext[i],
+                c_val: wo_eval_ext[i],
+                d_val: w4_eval_ext[i],
             };
 
             let custom_vals = CustomEvaluations {
                 vals: vec![
-                    ("a_next_eval".to_string(), wl_eval_4n[i + 4]),
-                    ("b_next_eval".to_string(), wr_eval_4n[i + 4]),
-                    ("d_next_eval".to_string(), w4_eval_4n[i + 4]),
+                    ("a_next_eval".to_string(), wl_eval_ext[i + next]),
+                    ("b_next_eval".to_string(), wr_eval_ext[i + next]),
+                    ("c_next_eval".to_string(), wo_eval_ext[i + next]),
+                    ("d_next_eval".to_string(), w4_eval_ext[i + next]),
                     ("q_l_eval".to_string(), prover_key.arithmetic.q_l.1[i]),
                     ("q_r_eval".to_string(), prover_key.arithmetic.q_r.1[i]),
+                    ("q_o_eval".to_string(), prover_key.arithmetic.q_o.1[i]),
+                    ("q_4_eval".to_string(), prover_key.arithmetic.q_4.1[i]),
                     ("q_c_eval".to_string(), prover_key.arithmetic.q_c.1[i]),
                 ],
             };
@@ -228,40 +210,74 @@ where
             let arithmetic =
                 prover_key.arithmetic.compute_quotient_i(i, wit_vals);
 
-            let range = Range::quotient_term(
+            let vals = ExpressionVals {
+                a_next_val: wl_eval_ext[i + next],
+                b_next_val: wr_eval_ext[i + next],
+                c_next_val: wo_eval_ext[i + next],
+                d_next_val: w4_eval_ext[i + next],
+                q_l_val: prover_key.arithmetic.q_l.1[i],
+                q_r_val: prover_key.arithmetic.q_r.1[i],
+                q_o_val: prover_key.arithmetic.q_o.1[i],
+                q_4_val: prover_key.arithmetic.q_4.1[i],
+                q_c_val: prover_key.arithmetic.q_c.1[i],
+            };
+
+            let range = range.quotient_term(
                 prover_key.range_selector.1[i],
                 range_challenge,
                 wit_vals,
-                RangeVals::from_evaluations(&custom_vals),
+                &vals,
             );
 
-            let logic = Logic::quotient_term(
+            let logic = logic.quotient_term(
                 prover_key.logic_selector.1[i],
                 logic_challenge,
                 wit_vals,
-                LogicVals::from_evaluations(&custom_vals),
+                &vals,
             );
 
-            let fixed_base_scalar_mul =
-                FixedBaseScalarMul::<_, P>::quotient_term(
-                    prover_key.fixed_group_add_selector.1[i],
-                    fixed_base_challenge,
-                    wit_vals,
-                    FBSMVals::from_evaluations(&custom_vals),
-                );
+            let fixed_base_scalar_mul = fixed_base_scalar_mul.quotient_term(
+                prover_key.fixed_group_add_selector.1[i],
+                fixed_base_challenge,
+                wit_vals,
+                &vals,
+            );
 
-            let curve_addition = CurveAddition::<_, P>::quotient_term(
+            let curve_addition = curve_addition.quotient_term(
                 prover_key.variable_group_add_selector.1[i],
                 var_base_challenge,
                 wit_vals,
-                CAVals::from_evaluations(&custom_vals),
+                &vals,
+            );
+
+            let poseidon = PoseidonRound::quotient_term(
+                prover_key.poseidon_selector.1[i],
+                poseidon_challenge,
+                wit_vals,
+                PoseidonVals::from_evaluations(&custom_vals),
             );
 
-            (arithmetic + pi_eval_4n[i])
+            let custom = custom_gates
+                .iter()
+                .zip(custom_challenges)
+                .map(|((key, gate), challenge)| {
+                    key.compute_quotient_i(
+                        *gate,
+                        i,
+                        *challenge,
+                        wit_vals,
+                        &custom_vals,
+                    )
+                })
+                .sum::<F>();
+
+            (arithmetic + pi_eval_ext[i])
                 + range
                 + logic
                 + fixed_base_scalar_mul
                 + curve_addition
+                + poseidon
+                + custom
         })
         .collect())
 }
@@ -271,45 +287,41 @@ where
 fn compute_permutation_checks<F>(
     domain: &GeneralEvaluationDomain<F>,
     prover_key: &ProverKey<F>,
-    wl_eval_4n: &[F],
-    wr_eval_4n: &[F],
-    wo_eval_4n: &[F],
-    w4_eval_4n: &[F],
-    z_eval_4n: &[F],
+    wl_eval_ext: &[F],
+    wr_eval_ext: &[F],
+    wo_eval_ext: &[F],
+    w4_eval_ext: &[F],
+    z_eval_ext: &[F],
     alpha: F,
     beta: F,
     gamma: F,
-) -> Result<Vec<F>, Error>
+) -> Vec<F>
 where
     F: PrimeField,
 {
-    let domain_4n = GeneralEvaluationDomain::<F>::new(4 * domain.size())
-        .ok_or(Error::InvalidEvalDomainSize {
-        log_size_of_group: (4 * domain.size()).trailing_zeros(),
-        adicity:
-            <<F as FftField>::FftParams as ark_ff::FftParameters>::TWO_ADICITY,
-    })?;
+    let domain_ext = prover_key.extended_domain();
+    let next = domain_ext.size() / domain.size();
     let l1_poly_alpha =
         compute_first_lagrange_poly_scaled(domain, alpha.square());
-    let l1_alpha_sq_evals = domain_4n.coset_fft(&l1_poly_alpha.coeffs);
+    let l1_alpha_sq_evals = domain_ext.coset_fft(&l1_poly_alpha.coeffs);
 
-    Ok((0..domain_4n.size())
+    (0..domain_ext.size())
         .map(|i| {
             prover_key.permutation.compute_quotient_i(
                 i,
-                wl_eval_4n[i],
-                wr_eval_4n[i],
-                wo_eval_4n[i],
-                w4_eval_4n[i],
-                z_eval_4n[i],
-                z_eval_4n[i + 4],
+                wl_eval_ext[i],
+                wr_eval_ext[i],
+                wo_eval_ext[i],
+                w4_eval_ext[i],
+                z_eval_ext[i],
+                z_eval_ext[i + next],
                 alpha,
                 l1_alpha_sq_evals[i],
                 beta,
                 gamma,
             )
         })
-        .collect())
+        .collect()
 }
 
 /// Computes the first lagrange polynomial with the given `scale` over `domain`.
@@ -11,21 +11,32 @@ use crate::{
     commitment::HomomorphicCommitment,
     constraint_system::StandardComposer,
     error::Error,
-    proof_system::{widget::VerifierKey as PlonkVerifierKey, Proof},
+    proof_system::{
+        custom::CustomGates,
+        proof::{batch_check_openings, check_opening_claims},
+        widget::VerifierKey as PlonkVerifierKey,
+        Proof,
+    },
+    transcript::TranscriptProtocol,
 };
 use ark_ec::TEModelParameters;
 use ark_ff::PrimeField;
 use core::marker::PhantomData;
 use merlin::Transcript;
+use rand_core::RngCore;
 
 use super::pi::PublicInputs;
 
 /// Abstraction structure designed verify [`Proof`]s.
-pub struct Verifier<F, P, PC>
+///
+/// The challenges are derived from a transcript `T`, which must be the one
+/// the [`Prover`](crate::proof_system::Prover) used.
+pub struct Verifier<F, P, PC, T = Transcript>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol<F>,
 {
     /// VerificationKey which is used to verify a specific PLONK circuit
     pub verifier_key: Option<PlonkVerifierKey<F, PC>>,
@@ -39,21 +50,22 @@ where
     /// verifier to verify multiple proofs from the same circuit. If this is
     /// not copied, then the verification procedure will modify the transcript,
     /// making it unusable for future proofs.
-    pub preprocessed_transcript: Transcript,
+    pub preprocessed_transcript: T,
 }
 
-impl<F, P, PC> Verifier<F, P, PC>
+impl<F, P, PC, T> Verifier<F, P, PC, T>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol<F>,
 {
     /// Creates a new `Verifier` instance.
     pub fn new(label: &'static [u8]) -> Self {
         Self {
             verifier_key: None,
             cs: StandardComposer::new(),
-            preprocessed_transcript: Transcript::new(label),
+            preprocessed_transcript: T::new(label),
         }
     }
 
@@ -62,7 +74,7 @@ where
         Self {
             verifier_key: None,
             cs: StandardComposer::with_expected_size(size),
-            preprocessed_transcript: Transcript::new(label),
+            preprocessed_transcript: T::new(label),
         }
     }
 
@@ -71,6 +83,12 @@ where
         self.cs.circuit_bound()
     }
 
+    /// Returns the degree the commitment key must support to preprocess the
+    /// circuit, as [`StandardComposer::required_srs_degree`] does.
+    pub fn required_srs_degree(&self) -> usize {
+        self.cs.required_srs_degree()
+    }
+
     /// Returns a mutable copy of the underlying composer.
     pub fn mut_cs(&mut self) -> &mut StandardComposer<F, P> {
         &mut self.cs
@@ -93,24 +111,25 @@ where
         Ok(())
     }
 
-    /// Keys the [`Transcript`] with additional seed information
-    /// Wrapper around [`Transcript::append_message`].
-    ///
-    /// [`Transcript`]: merlin::Transcript
-    /// [`Transcript::append_message`]: merlin::Transcript::append_message
+    /// Keys the transcript with additional seed information
+    /// Wrapper around [`TranscriptProtocol::append_message`].
     pub fn key_transcript(&mut self, label: &'static [u8], message: &[u8]) {
         self.preprocessed_transcript.append_message(label, message);
     }
 
     /// Verifies a [`Proof`] using `pc_verifier_key` and `public_inputs`.
+    ///
+    /// The custom gates of the circuit must be registered to the underlying
+    /// composer.
     pub fn verify(
         &self,
         proof: &Proof<F, PC>,
         pc_verifier_key: &PC::VerifierKey,
         public_inputs: &PublicInputs<F>,
     ) -> Result<(), Error> {
-        proof.verify::<P>(
+        proof.verify::<P, T>(
             self.verifier_key.as_ref().unwrap(),
+            &self.cs.custom_gates,
             &mut self.preprocessed_transcript.clone(),
             pc_verifier_key,
             public_inputs,
@@ -118,14 +137,396 @@ where
     }
 }
 
-impl<F, P, PC> Default for Verifier<F, P, PC>
+impl<F, P, PC, T> Default for Verifier<F, P, PC, T>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol<F>,
 {
     #[inline]
-    fn default() -> Verifier<F, P, PC> {
+    fn default() -> Verifier<F, P, PC, T> {
         Verifier::new(b"plonk")
     }
 }
+
+/// Verifies a batch of [`Proof`]s, each given with the
+/// [`PlonkVerifierKey`] of its circuit and its [`PublicInputs`].
+///
+/// The openings of all the proofs are folded with a random linear combination
+/// drawn from `rng` into a single
+/// [`PolynomialCommitment::batch_check`](ark_poly_commit::PolynomialCommitment::batch_check),
+/// that is one pairing product for KZG and one multi-scalar multiplication for
+/// IPA. As with [`verify_proof`](crate::circuit::verify_proof), the transcript
+/// `T` of every proof is initialized with `transcript_init`. Every proof is
+/// checked against `pc_verifier_key`, so with IPA the circuits must share the
+/// same padded size, and against `custom_gates`, which must register the
+/// custom gates of all the circuits.
+///
+/// When the batch is rejected, the proofs are checked one at a time to return
+/// an [`Error::BatchVerificationError`] with the index of the first invalid
+/// proof.
+#[allow(clippy::type_complexity)] // NOTE: Tuples of references.
+pub fn verify_batch<F, P, PC, T, R>(
+    pc_verifier_key: &PC::VerifierKey,
+    instances: &[(&PlonkVerifierKey<F, PC>, &Proof<F, PC>, &PublicInputs<F>)],
+    custom_gates: &CustomGates<F>,
+    transcript_init: &'static [u8],
+    rng: &mut R,
+) -> Result<(), Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol<F>,
+    R: RngCore,
+{
+    let claims = instances
+        .iter()
+        .enumerate()
+        .map(|(index, (plonk_verifier_key, proof, public_inputs))| {
+            proof
+                .opening_claims::<P, T>(
+                    plonk_verifier_key,
+                    custom_gates,
+                    &mut T::new(transcript_init),
+                    pc_verifier_key,
+                    public_inputs,
+                )
+                .map_err(|error| Error::BatchVerificationError {
+                    index,
+                    error: Box::new(error),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if claims.is_empty()
+        || batch_check_openings(pc_verifier_key, claims.iter().flatten(), rng)?
+    {
+        return Ok(());
+    }
+
+    for (index, proof_claims) in claims.iter().enumerate() {
+        check_opening_claims(pc_verifier_key, proof_claims).map_err(
+            |error| Error::BatchVerificationError {
+                index,
+                error: Box::new(error),
+            },
+        )?;
+    }
+    // The proofs are all valid, only their random combination was rejected.
+    Err(Error::ProofVerificationError)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test,
+        circuit::Circuit,
+        error::to_pc_error,
+        proof_system::{ProverKey, VerifierKey},
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use rand_core::OsRng;
+
+    /// Checks that `a * b = c`, or `a + b = c` when `add` is set, for the
+    /// public input `c`.
+    #[derive(derivative::Derivative)]
+    #[derivative(Default(bound = ""))]
+    struct ArithCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        add: bool,
+        a: F,
+        b: F,
+        c: F,
+        _phantom: PhantomData<P>,
+    }
+
+    impl<F, P> ArithCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        fn new(add: bool, a: u64, b: u64, c: u64) -> Self {
+            Self {
+                add,
+                a: F::from(a),
+                b: F::from(b),
+                c: F::from(c),
+                _phantom: PhantomData,
+            }
+        }
+    }
+
+    impl<F, P> Circuit<F, P> for ArithCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        const CIRCUIT_ID: [u8; 32] = [0xff; 32];
+
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<F, P>,
+        ) -> Result<(), Error> {
+            let a = composer.add_input(self.a);
+            let b = composer.add_input(self.b);
+            let zero = composer.zero_var();
+            composer.arithmetic_gate(|gate| {
+                let gate = gate.witness(a, b, Some(zero)).pi(-self.c);
+                if self.add {
+                    gate.add(F::one(), F::one())
+                } else {
+                    gate.mul(F::one())
+                }
+            });
+            Ok(())
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn keys<F, P, PC>(
+        pp: &PC::UniversalParams,
+    ) -> Result<[(ProverKey<F>, VerifierKey<F, PC>); 2], Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        Ok([
+            ArithCircuit::<F, P>::new(false, 0, 0, 0).compile::<PC>(pp)?,
+            ArithCircuit::<F, P>::new(true, 0, 0, 0).compile::<PC>(pp)?,
+        ])
+    }
+
+    fn test_batch_verification<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = (|| -> Result<(), Error> {
+            let pp = PC::setup(1 << 5, None, &mut OsRng)
+                .map_err(to_pc_error::<F, PC>)?;
+            let [(mul_pk, mul_vk), (add_pk, add_vk)] = keys::<F, P, PC>(&pp)?;
+            let (_, pc_vk) =
+                PC::trim(&pp, mul_vk.padded_circuit_size(), 0, None)
+                    .map_err(to_pc_error::<F, PC>)?;
+
+            let circuits = [
+                (false, 3, 5, 15),
+                (false, 5, 3, 15),
+                (true, 7, 8, 15),
+                (false, 2, 6, 12),
+                (true, 1, 2, 3),
+            ];
+            let mut proofs = Vec::new();
+            for (add, a, b, c) in circuits {
+                let pk = if add { &add_pk } else { &mul_pk };
+                proofs.push(
+                    ArithCircuit::<F, P>::new(add, a, b, c)
+                        .gen_proof::<PC, Transcript>(
+                            &pp,
+                            pk.clone(),
+                            b"batch",
+                        )?,
+                );
+            }
+            let instances = circuits
+                .iter()
+                .zip(&proofs)
+                .map(|((add, ..), (proof, pi))| {
+                    (if *add { &add_vk } else { &mul_vk }, proof, pi)
+                })
+                .collect::<Vec<_>>();
+            verify_batch::<F, P, PC, Transcript, _>(
+                &pc_vk,
+                &instances,
+                &CustomGates::new(),
+                b"batch",
+                &mut OsRng,
+            )?;
+
+            // A single proof is a batch.
+            verify_batch::<F, P, PC, Transcript, _>(
+                &pc_vk,
+                &instances[..1],
+                &CustomGates::new(),
+                b"batch",
+                &mut OsRng,
+            )?;
+            verify_batch::<F, P, PC, Transcript, _>(
+                &pc_vk,
+                &[],
+                &CustomGates::new(),
+                b"batch",
+                &mut OsRng,
+            )
+        })();
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_batch_verification_failure<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = (|| -> Result<(), Error> {
+            let pp = PC::setup(1 << 5, None, &mut OsRng)
+                .map_err(to_pc_error::<F, PC>)?;
+            let [(mul_pk, mul_vk), (add_pk, add_vk)] = keys::<F, P, PC>(&pp)?;
+            let (_, pc_vk) =
+                PC::trim(&pp, mul_vk.padded_circuit_size(), 0, None)
+                    .map_err(to_pc_error::<F, PC>)?;
+
+            let (mul_proof, mul_pi) =
+                ArithCircuit::<F, P>::new(false, 3, 5, 15)
+                    .gen_proof::<PC, Transcript>(&pp, mul_pk, b"batch")?;
+            let (add_proof, add_pi) = ArithCircuit::<F, P>::new(true, 7, 8, 15)
+                .gen_proof::<PC, Transcript>(&pp, add_pk, b"batch")?;
+
+            // The proof of `3 * 5 = 15` does not prove `7 + 8 = 15`.
+            let instances = [
+                (&add_vk, &add_proof, &add_pi),
+                (&mul_vk, &mul_proof, &mul_pi),
+                (&add_vk, &mul_proof, &add_pi),
+                (&mul_vk, &mul_proof, &mul_pi),
+            ];
+            verify_batch::<F, P, PC, Transcript, _>(
+                &pc_vk,
+                &instances,
+                &CustomGates::new(),
+                b"batch",
+                &mut OsRng,
+            )
+        })();
+        match res {
+            Err(Error::BatchVerificationError { index, .. }) => {
+                assert_eq!(index, 2)
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    fn test_verification_errors<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = (|| -> Result<(), Error> {
+            let pp = PC::setup(1 << 5, None, &mut OsRng)
+                .map_err(to_pc_error::<F, PC>)?;
+            let [(pk, vk), _] = keys::<F, P, PC>(&pp)?;
+            let (_, pc_vk) = PC::trim(&pp, vk.padded_circuit_size(), 0, None)
+                .map_err(to_pc_error::<F, PC>)?;
+            let (proof, pi) =
+                ArithCircuit::<F, P>::new(false, 3, 5, 15)
+                    .gen_proof::<PC, Transcript>(&pp, pk, b"errors")?;
+            let verify = |pc_vk, proof: &Proof<F, PC>, pi| {
+                proof.verify::<P, Transcript>(
+                    &vk,
+                    &CustomGates::new(),
+                    &mut Transcript::new(b"errors"),
+                    pc_vk,
+                    pi,
+                )
+            };
+            verify(&pc_vk, &proof, &pi)?;
+
+            let position = vk.pi_positions[0];
+            let mut shifted_pi = PublicInputs::new();
+            shifted_pi.insert(position + 1, -F::from(15u64));
+            let res = verify(&pc_vk, &proof, &shifted_pi);
+            assert!(
+                matches!(
+                    res,
+                    Err(Error::PublicInputPositionMismatch { position: p })
+                        if p == position + 1
+                ),
+                "{:?}",
+                res
+            );
+
+            let mut extra_pi = pi.clone();
+            extra_pi.insert(position + 1, F::one());
+            let res = verify(&pc_vk, &proof, &extra_pi);
+            assert!(
+                matches!(
+                    res,
+                    Err(Error::PublicInputLengthMismatch {
+                        expected: 1,
+                        found: 2
+                    })
+                ),
+                "{:?}",
+                res
+            );
+
+            let circuit_size = vk.padded_circuit_size();
+            let (_, small_pc_vk) = PC::trim(&pp, circuit_size / 2, 0, None)
+                .map_err(to_pc_error::<F, PC>)?;
+            let res = verify(&small_pc_vk, &proof, &pi);
+            assert!(
+                matches!(
+                    res,
+                    Err(Error::CircuitSizeMismatch { circuit_size: size, .. })
+                        if size == circuit_size
+                ),
+                "{:?}",
+                res
+            );
+
+            // The proof of `3 * 5 = 15` does not prove `3 * 5 = 16`.
+            let mut wrong_pi = PublicInputs::new();
+            wrong_pi.insert(position, -F::from(16u64));
+            let res = verify(&pc_vk, &proof, &wrong_pi);
+            assert!(
+                matches!(res, Err(Error::OpeningCheckFailure)),
+                "{:?}",
+                res
+            );
+
+            let mut wrong_proof = proof.clone();
+            wrong_proof.saw_opening = proof.aw_opening.clone();
+            let res = verify(&pc_vk, &wrong_proof, &pi);
+            assert!(
+                matches!(res, Err(Error::ShiftedOpeningCheckFailure)),
+                "{:?}",
+                res
+            );
+            Ok(())
+        })();
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Bls12-381 tests
+    batch_test!(
+        [
+            test_batch_verification,
+            test_batch_verification_failure,
+            test_verification_errors
+        ],
+        [] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Bls12-377 tests
+    batch_test!(
+        [
+            test_batch_verification,
+            test_batch_verification_failure,
+            test_verification_errors
+        ],
+        [] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,319 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Custom Gates
+//!
+//! A gate defined outside of this crate is an implementation of
+//! [`GateConstraint`], or an [`Expression`], registered to the
+//! [`StandardComposer`](crate::constraint_system::StandardComposer) with
+//! [`register_gate`](crate::constraint_system::StandardComposer::register_gate)
+//! or
+//! [`register_expression_gate`](crate::constraint_system::StandardComposer::register_expression_gate)
+//! and added to the circuit with
+//! [`custom_gate`](crate::constraint_system::StandardComposer::custom_gate).
+//! Every registered gate has its own selector, committed to in the
+//! [`VerifierKey`](super::VerifierKey), and its own separation challenge, and
+//! its constraint is added to the quotient and linearisation polynomials as
+//! the ones of the built-in gates.
+//!
+//! Besides the wire values of the gate, the constraint can read the
+//! [`CustomEvaluations`] labelled `a_next_eval`, `b_next_eval`,
+//! `c_next_eval` and `d_next_eval`, the wire values of the next gate, and
+//! `q_l_eval`, `q_r_eval`, `q_o_eval`, `q_4_eval` and `q_c_eval`, the
+//! constants of the gate. All of them are opened against the commitments to
+//! the wires and selectors, so the prover cannot choose them.
+//!
+//! The quotient polynomial is computed over a coset large enough for the
+//! gate of the highest [`degree`](GateConstraint::degree) in the circuit and
+//! is split in as many pieces of degree `n` as needed, so a gate of any
+//! degree can be registered, at the cost of longer proofs for the circuits
+//! using a gate of a degree higher than 4.
+
+use crate::{
+    commitment::HomomorphicCommitment,
+    error::Error,
+    proof_system::{
+        expression::{Expression, ExpressionVals},
+        linearisation_poly::{CustomEvaluations, ProofEvaluations},
+        CustomValues, GateConstraint, WitnessValues,
+    },
+};
+use alloc::sync::Arc;
+use ark_ff::{FftField, PrimeField};
+use ark_poly::{polynomial::univariate::DensePolynomial, Evaluations};
+use ark_poly_commit::PolynomialCommitment;
+use ark_serialize::*;
+use core::marker::PhantomData;
+
+/// Constraint of a registered [`GateConstraint`], usable as a trait object.
+pub(crate) trait CustomConstraint<F>: Send + Sync
+where
+    F: PrimeField,
+{
+    /// Evaluates the constraint of the gate, as
+    /// [`GateConstraint::constraints`] does with the values read from
+    /// `custom_evals`.
+    fn constraints(
+        &self,
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        custom_evals: &CustomEvaluations<F>,
+    ) -> F;
+
+    /// Returns the degree of the constraint, as
+    /// [`GateConstraint::degree`].
+    fn degree(&self) -> usize;
+}
+
+/// [`CustomConstraint`] of the gate `G`.
+struct Registered<G>(PhantomData<fn() -> G>);
+
+impl<F, G> CustomConstraint<F> for Registered<G>
+where
+    F: PrimeField,
+    G: GateConstraint<F>,
+{
+    fn constraints(
+        &self,
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        custom_evals: &CustomEvaluations<F>,
+    ) -> F {
+        G::constraints(
+            separation_challenge,
+            wit_vals,
+            G::CustomVals::from_evaluations(custom_evals),
+        )
+    }
+
+    fn degree(&self) -> usize {
+        G::degree()
+    }
+}
+
+impl<F> CustomConstraint<F> for Expression<F>
+where
+    F: PrimeField,
+{
+    fn constraints(
+        &self,
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        custom_evals: &CustomEvaluations<F>,
+    ) -> F {
+        self.evaluate(
+            separation_challenge,
+            wit_vals,
+            &ExpressionVals::from_evaluations(custom_evals),
+        )
+    }
+
+    fn degree(&self) -> usize {
+        Expression::degree(self)
+    }
+}
+
+/// Handle to a custom gate registered to a
+/// [`StandardComposer`](crate::constraint_system::StandardComposer).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CustomGateId(pub(crate) usize);
+
+/// Set of custom gates identified by their names.
+///
+/// The gates registered to a
+/// [`StandardComposer`](crate::constraint_system::StandardComposer) are
+/// returned by
+/// [`custom_gates`](crate::constraint_system::StandardComposer::custom_gates).
+/// The [`Verifier`](crate::proof_system::Verifier) of a circuit with custom
+/// gates must know them under the same names, the circuit being checked
+/// against the gates named in its [`VerifierKey`](super::VerifierKey).
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Default(bound = ""))]
+pub struct CustomGates<F>
+where
+    F: PrimeField,
+{
+    /// Registered gates, in order of registration.
+    gates: Vec<(String, Arc<dyn CustomConstraint<F>>)>,
+}
+
+impl<F> CustomGates<F>
+where
+    F: PrimeField,
+{
+    /// Creates an empty set of custom gates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the gate `G` under `name`, returning an
+    /// [`Error::CustomGateAlreadyRegistered`] if the name is already taken.
+    pub fn register<G>(&mut self, name: &str) -> Result<CustomGateId, Error>
+    where
+        G: GateConstraint<F> + 'static,
+    {
+        self.insert(name, Arc::new(Registered::<G>(PhantomData)))
+    }
+
+    /// Registers the gate with the identity `expression` under `name`,
+    /// returning an [`Error::CustomGateAlreadyRegistered`] if the name is
+    /// already taken.
+    pub fn register_expression(
+        &mut self,
+        name: &str,
+        expression: Expression<F>,
+    ) -> Result<CustomGateId, Error> {
+        self.insert(name, Arc::new(expression))
+    }
+
+    /// Registers `gate` under `name` if the name is not taken.
+    fn insert(
+        &mut self,
+        name: &str,
+        gate: Arc<dyn CustomConstraint<F>>,
+    ) -> Result<CustomGateId, Error> {
+        if self.gates.iter().any(|(gate_name, _)| gate_name == name) {
+            return Err(Error::CustomGateAlreadyRegistered {
+                name: name.to_owned(),
+            });
+        }
+        self.gates.push((name.to_owned(), gate));
+        Ok(CustomGateId(self.gates.len() - 1))
+    }
+
+    /// Returns the number of registered gates.
+    pub fn len(&self) -> usize {
+        self.gates.len()
+    }
+
+    /// Returns `true` if no gate is registered.
+    pub fn is_empty(&self) -> bool {
+        self.gates.is_empty()
+    }
+
+    /// Returns the names of the registered gates, in order of registration.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.gates.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the constraint of the gate registered under `name`.
+    pub(crate) fn get(
+        &self,
+        name: &str,
+    ) -> Result<&dyn CustomConstraint<F>, Error> {
+        self.gates
+            .iter()
+            .find(|(gate_name, _)| gate_name == name)
+            .map(|(_, gate)| gate.as_ref())
+            .ok_or_else(|| Error::UnregisteredCustomGate {
+                name: name.to_owned(),
+            })
+    }
+}
+
+impl<F> core::fmt::Debug for CustomGates<F>
+where
+    F: PrimeField,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// Custom Gate Prover Key
+#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derivative(Clone, Debug, Eq, PartialEq)]
+pub struct ProverKey<F>
+where
+    F: FftField,
+{
+    /// Name of the Gate
+    pub name: String,
+
+    /// Gate Selector
+    pub q_custom: (DensePolynomial<F>, Evaluations<F>),
+}
+
+impl<F> ProverKey<F>
+where
+    F: PrimeField,
+{
+    /// Computes the contribution of `gate` to the quotient polynomial at the
+    /// element of the domain at the given `index`.
+    pub(crate) fn compute_quotient_i(
+        &self,
+        gate: &dyn CustomConstraint<F>,
+        index: usize,
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        custom_vals: &CustomEvaluations<F>,
+    ) -> F {
+        self.q_custom.1[index]
+            * gate.constraints(separation_challenge, wit_vals, custom_vals)
+    }
+
+    /// Computes the contribution of `gate` to the linearisation polynomial at
+    /// the given evaluations.
+    pub(crate) fn compute_linearisation(
+        &self,
+        gate: &dyn CustomConstraint<F>,
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        custom_evals: &CustomEvaluations<F>,
+    ) -> DensePolynomial<F> {
+        &self.q_custom.0
+            * gate.constraints(separation_challenge, wit_vals, custom_evals)
+    }
+}
+
+/// Custom Gate Verifier Key
+#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derivative(
+    Clone,
+    Debug(bound = "PC::Commitment: std::fmt::Debug"),
+    Eq(bound = "PC::Commitment: Eq"),
+    PartialEq(bound = "PC::Commitment: PartialEq")
+)]
+pub struct VerifierKey<F, PC>
+where
+    F: PrimeField,
+    PC: PolynomialCommitment<F, DensePolynomial<F>>,
+{
+    /// Name of the Gate
+    pub name: String,
+
+    /// Gate Selector Commitment
+    pub q_custom: PC::Commitment,
+}
+
+impl<F, PC> VerifierKey<F, PC>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    /// Computes the contribution of `gate` to the linearisation polynomial
+    /// commitment.
+    pub(crate) fn compute_linearisation_commitment(
+        &self,
+        gate: &dyn CustomConstraint<F>,
+        separation_challenge: F,
+        scalars: &mut Vec<F>,
+        points: &mut Vec<PC::Commitment>,
+        evaluations: &ProofEvaluations<F>,
+    ) {
+        scalars.push(gate.constraints(
+            separation_challenge,
+            WitnessValues {
+                a_val: evaluations.wire_evals.a_eval,
+                b_val: evaluations.wire_evals.b_eval,
+                c_val: evaluations.wire_evals.c_eval,
+                d_val: evaluations.wire_evals.d_eval,
+            },
+            &evaluations.custom_evals,
+        ));
+        points.push(self.q_custom.clone());
+    }
+}
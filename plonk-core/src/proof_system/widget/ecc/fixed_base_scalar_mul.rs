@@ -19,56 +19,13 @@
 //! base2 bit.
 
 use crate::proof_system::{
-    linearisation_poly::CustomEvaluations,
+    expression::{Expression, ExpressionVals, Value},
     widget::{GateConstraint, WitnessValues},
-    CustomValues,
 };
 use ark_ec::{ModelParameters, TEModelParameters};
 use ark_ff::PrimeField;
 use core::marker::PhantomData;
 
-/// Values needed for the computation of the Fixed Base Multiplication gate
-/// constraint.
-pub struct FBSMVals<F>
-where
-    F: PrimeField,
-{
-    /// Left wire value in the next position
-    pub a_next_val: F,
-    /// Right wire value in the next position
-    pub b_next_val: F,
-    /// Fourth wire value in the next position
-    pub d_next_val: F,
-    /// Left selector value
-    pub q_l_val: F,
-    /// Right selector value
-    pub q_r_val: F,
-    /// Constant selector value
-    pub q_c_val: F,
-}
-
-impl<F> CustomValues<F> for FBSMVals<F>
-where
-    F: PrimeField,
-{
-    fn from_evaluations(custom_evals: &CustomEvaluations<F>) -> Self {
-        let a_next_val = custom_evals.get("a_next_eval");
-        let b_next_val = custom_evals.get("b_next_eval");
-        let d_next_val = custom_evals.get("d_next_eval");
-        let q_l_val = custom_evals.get("q_l_eval");
-        let q_r_val = custom_evals.get("q_r_eval");
-        let q_c_val = custom_evals.get("q_c_eval");
-        FBSMVals {
-            a_next_val,
-            b_next_val,
-            d_next_val,
-            q_l_val,
-            q_r_val,
-            q_c_val,
-        }
-    }
-}
-
 /// Fixed-Base Scalar Multiplication Gate
 #[derive(derivative::Derivative)]
 #[derivative(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
@@ -77,57 +34,55 @@ where
     F: PrimeField,
     P: ModelParameters<BaseField = F>;
 
-impl<F, P> GateConstraint<F> for FixedBaseScalarMul<F, P>
+impl<F, P> FixedBaseScalarMul<F, P>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
 {
-    type CustomVals = FBSMVals<F>;
-
-    #[inline]
-    fn constraints(
-        separation_challenge: F,
-        wit_vals: WitnessValues<F>,
-        custom_vals: Self::CustomVals,
-    ) -> F {
+    /// Returns the identity of the fixed-base scalar multiplication gate,
+    /// checking that the accumulated point on the left and right wires of the
+    /// next gate adds to the one on the left and right wires the multiple of
+    /// the base selected by the next bit of the scalar.
+    pub fn expression() -> Expression<F> {
+        let one = Expression::constant(F::one());
+        let separation_challenge = Expression::challenge();
         let kappa = separation_challenge.square();
-        let kappa_sq = kappa.square();
-        let kappa_cu = kappa_sq * kappa;
 
-        let x_beta_eval = custom_vals.q_l_val;
-        let y_beta_eval = custom_vals.q_r_val;
+        let [x_beta_eval, y_beta_eval, q_c] =
+            [Value::Ql, Value::Qr, Value::Qc].map(Expression::from);
 
-        let acc_x = wit_vals.a_val;
-        let acc_x_next = custom_vals.a_next_val;
-        let acc_y = wit_vals.b_val;
-        let acc_y_next = custom_vals.b_next_val;
+        let [acc_x, acc_x_next, acc_y, acc_y_next] =
+            [Value::A, Value::ANext, Value::B, Value::BNext]
+                .map(Expression::from);
 
-        let xy_alpha = wit_vals.c_val;
+        let xy_alpha = Expression::from(Value::C);
 
-        let accumulated_bit = wit_vals.d_val;
-        let accumulated_bit_next = custom_vals.d_next_val;
-        let bit = extract_bit(accumulated_bit, accumulated_bit_next);
+        let accumulated_bit = Expression::from(Value::D);
+        let accumulated_bit_next = Expression::from(Value::DNext);
+        let bit = extract_bit(&accumulated_bit, &accumulated_bit_next);
 
         // Check bit consistency
-        let bit_consistency = check_bit_consistency(bit);
+        let bit_consistency = check_bit_consistency(&bit);
 
-        let y_alpha = bit.square() * (y_beta_eval - F::one()) + F::one();
-        let x_alpha = x_beta_eval * bit;
+        let y_alpha = bit.square() * (y_beta_eval - &one) + one;
+        let x_alpha = x_beta_eval * &bit;
 
         // xy_alpha consistency check
-        let xy_consistency = ((bit * custom_vals.q_c_val) - xy_alpha) * kappa;
+        let xy_consistency = ((bit * q_c) - &xy_alpha) * &kappa;
 
         // x accumulator consistency check
+        let acc_product =
+            xy_alpha * &acc_x * &acc_y * Expression::constant(P::COEFF_D);
         let x_3 = acc_x_next;
-        let lhs = x_3 + (x_3 * xy_alpha * acc_x * acc_y * P::COEFF_D);
-        let rhs = (x_alpha * acc_y) + (y_alpha * acc_x);
-        let x_acc_consistency = (lhs - rhs) * kappa_sq;
+        let lhs = &x_3 + (&x_3 * &acc_product);
+        let rhs = (&x_alpha * &acc_y) + (&y_alpha * &acc_x);
+        let x_acc_consistency = (lhs - rhs) * kappa.square();
 
         // y accumulator consistency check
         let y_3 = acc_y_next;
-        let lhs = y_3 - (y_3 * xy_alpha * acc_x * acc_y * P::COEFF_D);
+        let lhs = &y_3 - (&y_3 * acc_product);
         let rhs = (x_alpha * acc_x) + (y_alpha * acc_y);
-        let y_acc_consistency = (lhs - rhs) * kappa_cu;
+        let y_acc_consistency = (lhs - rhs) * kappa.pow(3);
 
         let checks = bit_consistency
             + x_acc_consistency
@@ -138,8 +93,36 @@ where
     }
 }
 
+impl<F, P> GateConstraint<F> for FixedBaseScalarMul<F, P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    type CustomVals = ExpressionVals<F>;
+
+    fn degree() -> usize {
+        Self::expression().degree()
+    }
+
+    #[inline]
+    fn constraints(
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        custom_vals: Self::CustomVals,
+    ) -> F {
+        Self::expression().evaluate(
+            separation_challenge,
+            wit_vals,
+            &custom_vals,
+        )
+    }
+}
+
 /// Extracts the bit value from the accumulated bit.
-pub(crate) fn extract_bit<F>(curr_acc: F, next_acc: F) -> F
+pub(crate) fn extract_bit<F>(
+    curr_acc: &Expression<F>,
+    next_acc: &Expression<F>,
+) -> Expression<F>
 where
     F: PrimeField,
 {
@@ -147,10 +130,10 @@ where
 }
 
 /// Ensures that the bit is either `+1`, `-1`, or `0`.
-pub(crate) fn check_bit_consistency<F>(bit: F) -> F
+pub(crate) fn check_bit_consistency<F>(bit: &Expression<F>) -> Expression<F>
 where
     F: PrimeField,
 {
-    let one = F::one();
-    bit * (bit - one) * (bit + one)
+    let one = Expression::constant(F::one());
+    bit * (bit - &one) * (bit + one)
 }
@@ -0,0 +1,504 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Gate Expressions
+//!
+//! An [`Expression`] is the polynomial identity of a gate, written once over
+//! the [`Value`]s of the gate and the separation challenge of its type. From
+//! it are derived the contribution of the gate to the quotient polynomial, its
+//! linearisation term, the scalar of its selector commitment in the
+//! linearisation commitment of the verifier, and a check that a gate of the
+//! circuit satisfies it.
+//!
+//! The [`Range`](super::range::Range), [`Logic`](super::logic::Logic),
+//! [`CurveAddition`](super::ecc::CurveAddition) and
+//! [`FixedBaseScalarMul`](super::ecc::FixedBaseScalarMul) gates are declared
+//! as expressions, and custom gates can be registered from one with
+//! [`register_expression_gate`](crate::constraint_system::StandardComposer::register_expression_gate).
+
+use crate::{
+    commitment::HomomorphicCommitment,
+    proof_system::{
+        linearisation_poly::{CustomEvaluations, ProofEvaluations},
+        CustomValues, WitnessValues,
+    },
+};
+use ark_ff::PrimeField;
+use ark_poly::polynomial::univariate::DensePolynomial;
+use core::ops::{Add, Mul, Neg, Sub};
+
+/// Value of a gate read by an [`Expression`].
+///
+/// The values of the next gate and of the selectors are read by the verifier
+/// from evaluations of the proof, which are opened against the commitments
+/// to the wires and selectors, so they can be used anywhere in an
+/// expression.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Value {
+    /// Left wire
+    A,
+    /// Right wire
+    B,
+    /// Output wire
+    C,
+    /// Fourth wire
+    D,
+    /// Left wire of the next gate
+    ANext,
+    /// Right wire of the next gate
+    BNext,
+    /// Output wire of the next gate
+    CNext,
+    /// Fourth wire of the next gate
+    DNext,
+    /// Left selector
+    Ql,
+    /// Right selector
+    Qr,
+    /// Output selector
+    Qo,
+    /// Fourth selector
+    Q4,
+    /// Constant selector
+    Qc,
+}
+
+/// Values of a gate read by an [`Expression`] besides its
+/// [`WitnessValues`], bound to the commitments of the circuit as described
+/// for [`Value`].
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ExpressionVals<F>
+where
+    F: PrimeField,
+{
+    /// Left wire value in the next position
+    pub a_next_val: F,
+    /// Right wire value in the next position
+    pub b_next_val: F,
+    /// Output wire value in the next position
+    pub c_next_val: F,
+    /// Fourth wire value in the next position
+    pub d_next_val: F,
+    /// Left selector value
+    pub q_l_val: F,
+    /// Right selector value
+    pub q_r_val: F,
+    /// Output selector value
+    pub q_o_val: F,
+    /// Fourth selector value
+    pub q_4_val: F,
+    /// Constant selector value
+    pub q_c_val: F,
+}
+
+impl<F> CustomValues<F> for ExpressionVals<F>
+where
+    F: PrimeField,
+{
+    fn from_evaluations(custom_evals: &CustomEvaluations<F>) -> Self {
+        ExpressionVals {
+            a_next_val: custom_evals.get("a_next_eval"),
+            b_next_val: custom_evals.get("b_next_eval"),
+            c_next_val: custom_evals.get("c_next_eval"),
+            d_next_val: custom_evals.get("d_next_eval"),
+            q_l_val: custom_evals.get("q_l_eval"),
+            q_r_val: custom_evals.get("q_r_eval"),
+            q_o_val: custom_evals.get("q_o_eval"),
+            q_4_val: custom_evals.get("q_4_eval"),
+            q_c_val: custom_evals.get("q_c_eval"),
+        }
+    }
+}
+
+/// Polynomial identity of a gate.
+///
+/// Expressions are built from [`Value`]s, constants and the separation
+/// challenge with the `+`, `-` and `*` operators, on owned expressions or
+/// references to them. A gate satisfies the identity if the expression
+/// vanishes at its values whatever the separation challenge, so the several
+/// constraints of a gate are combined with powers of the challenge.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Expression<F>
+where
+    F: PrimeField,
+{
+    /// Constant
+    Constant(F),
+
+    /// Value of the gate
+    Value(Value),
+
+    /// Separation challenge of the gate type
+    Challenge,
+
+    /// Sum of two expressions
+    Sum(Box<Expression<F>>, Box<Expression<F>>),
+
+    /// Product of two expressions
+    Product(Box<Expression<F>>, Box<Expression<F>>),
+
+    /// Negation of an expression
+    Negation(Box<Expression<F>>),
+}
+
+impl<F> Expression<F>
+where
+    F: PrimeField,
+{
+    /// Returns the constant expression `value`.
+    pub fn constant(value: F) -> Self {
+        Self::Constant(value)
+    }
+
+    /// Returns the separation challenge of the gate type.
+    pub fn challenge() -> Self {
+        Self::Challenge
+    }
+
+    /// Returns the square of the expression.
+    pub fn square(&self) -> Self {
+        self * self
+    }
+
+    /// Returns the expression raised to the power `exp`.
+    pub fn pow(&self, exp: usize) -> Self {
+        (1..exp).fold(
+            if exp == 0 {
+                Self::Constant(F::one())
+            } else {
+                self.clone()
+            },
+            |acc, _| acc * self,
+        )
+    }
+
+    /// Returns the degree of the expression in the wire and selector values,
+    /// that is the degree of the polynomial it defines over the evaluation
+    /// domain divided by the size of the domain.
+    pub fn degree(&self) -> usize {
+        match self {
+            Self::Constant(_) | Self::Challenge => 0,
+            Self::Value(_) => 1,
+            Self::Sum(lhs, rhs) => lhs.degree().max(rhs.degree()),
+            Self::Product(lhs, rhs) => lhs.degree() + rhs.degree(),
+            Self::Negation(expr) => expr.degree(),
+        }
+    }
+
+    /// Returns the degree of the expression in the separation challenge.
+    fn challenge_degree(&self) -> usize {
+        match self {
+            Self::Constant(_) | Self::Value(_) => 0,
+            Self::Challenge => 1,
+            Self::Sum(lhs, rhs) => {
+                lhs.challenge_degree().max(rhs.challenge_degree())
+            }
+            Self::Product(lhs, rhs) => {
+                lhs.challenge_degree() + rhs.challenge_degree()
+            }
+            Self::Negation(expr) => expr.challenge_degree(),
+        }
+    }
+
+    /// Evaluates the expression at the given values of a gate and
+    /// `separation_challenge`.
+    pub fn evaluate(
+        &self,
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        vals: &ExpressionVals<F>,
+    ) -> F {
+        match self {
+            Self::Constant(value) => *value,
+            Self::Value(value) => match value {
+                Value::A => wit_vals.a_val,
+                Value::B => wit_vals.b_val,
+                Value::C => wit_vals.c_val,
+                Value::D => wit_vals.d_val,
+                Value::ANext => vals.a_next_val,
+                Value::BNext => vals.b_next_val,
+                Value::CNext => vals.c_next_val,
+                Value::DNext => vals.d_next_val,
+                Value::Ql => vals.q_l_val,
+                Value::Qr => vals.q_r_val,
+                Value::Qo => vals.q_o_val,
+                Value::Q4 => vals.q_4_val,
+                Value::Qc => vals.q_c_val,
+            },
+            Self::Challenge => separation_challenge,
+            Self::Sum(lhs, rhs) => {
+                lhs.evaluate(separation_challenge, wit_vals, vals)
+                    + rhs.evaluate(separation_challenge, wit_vals, vals)
+            }
+            Self::Product(lhs, rhs) => {
+                lhs.evaluate(separation_challenge, wit_vals, vals)
+                    * rhs.evaluate(separation_challenge, wit_vals, vals)
+            }
+            Self::Negation(expr) => {
+                -expr.evaluate(separation_challenge, wit_vals, vals)
+            }
+        }
+    }
+
+    /// Returns `true` if the expression vanishes at the given values of a gate
+    /// for every separation challenge.
+    pub fn is_satisfied(
+        &self,
+        wit_vals: WitnessValues<F>,
+        vals: &ExpressionVals<F>,
+    ) -> bool {
+        // A polynomial of degree `d` in the challenge vanishing at `d + 1`
+        // points is zero.
+        (1..=self.challenge_degree() as u64 + 1).all(|challenge| {
+            self.evaluate(F::from(challenge), wit_vals, vals).is_zero()
+        })
+    }
+
+    /// Computes the quotient polynomial term of the gate at the given value
+    /// of its `selector`.
+    pub fn quotient_term(
+        &self,
+        selector: F,
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        vals: &ExpressionVals<F>,
+    ) -> F {
+        selector * self.evaluate(separation_challenge, wit_vals, vals)
+    }
+
+    /// Computes the linearisation polynomial term of the gate with its
+    /// `selector_polynomial`.
+    pub fn linearisation_term(
+        &self,
+        selector_polynomial: &DensePolynomial<F>,
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        vals: &ExpressionVals<F>,
+    ) -> DensePolynomial<F> {
+        selector_polynomial
+            * self.evaluate(separation_challenge, wit_vals, vals)
+    }
+
+    /// Extends `scalars` and `points` with the term of the gate in the
+    /// linearisation commitment, its `selector_commitment` scaled by the
+    /// expression at the `evaluations` of the proof.
+    pub fn extend_linearisation_commitment<PC>(
+        &self,
+        selector_commitment: &PC::Commitment,
+        separation_challenge: F,
+        evaluations: &ProofEvaluations<F>,
+        scalars: &mut Vec<F>,
+        points: &mut Vec<PC::Commitment>,
+    ) where
+        PC: HomomorphicCommitment<F>,
+    {
+        let coefficient = self.evaluate(
+            separation_challenge,
+            WitnessValues {
+                a_val: evaluations.wire_evals.a_eval,
+                b_val: evaluations.wire_evals.b_eval,
+                c_val: evaluations.wire_evals.c_eval,
+                d_val: evaluations.wire_evals.d_eval,
+            },
+            &ExpressionVals::from_evaluations(&evaluations.custom_evals),
+        );
+        scalars.push(coefficient);
+        points.push(selector_commitment.clone());
+    }
+}
+
+impl<F> From<Value> for Expression<F>
+where
+    F: PrimeField,
+{
+    fn from(value: Value) -> Self {
+        Self::Value(value)
+    }
+}
+
+impl<F> Neg for Expression<F>
+where
+    F: PrimeField,
+{
+    type Output = Expression<F>;
+
+    fn neg(self) -> Self::Output {
+        Expression::Negation(Box::new(self))
+    }
+}
+
+impl<F> Neg for &Expression<F>
+where
+    F: PrimeField,
+{
+    type Output = Expression<F>;
+
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+/// Implements the binary operator `$op` for owned expressions and references
+/// to them, with `$combine` building the result from the owned operands.
+macro_rules! impl_expression_op {
+    ($op:ident, $method:ident, $combine:expr) => {
+        impl<F> $op<Expression<F>> for Expression<F>
+        where
+            F: PrimeField,
+        {
+            type Output = Expression<F>;
+
+            fn $method(self, rhs: Expression<F>) -> Self::Output {
+                let combine: fn(Expression<F>, Expression<F>) -> Expression<F> =
+                    $combine;
+                combine(self, rhs)
+            }
+        }
+
+        impl<F> $op<&Expression<F>> for Expression<F>
+        where
+            F: PrimeField,
+        {
+            type Output = Expression<F>;
+
+            fn $method(self, rhs: &Expression<F>) -> Self::Output {
+                self.$method(rhs.clone())
+            }
+        }
+
+        impl<F> $op<Expression<F>> for &Expression<F>
+        where
+            F: PrimeField,
+        {
+            type Output = Expression<F>;
+
+            fn $method(self, rhs: Expression<F>) -> Self::Output {
+                self.clone().$method(rhs)
+            }
+        }
+
+        impl<F> $op<&Expression<F>> for &Expression<F>
+        where
+            F: PrimeField,
+        {
+            type Output = Expression<F>;
+
+            fn $method(self, rhs: &Expression<F>) -> Self::Output {
+                self.clone().$method(rhs.clone())
+            }
+        }
+    };
+}
+
+impl_expression_op!(Add, add, |lhs, rhs| Expression::Sum(
+    Box::new(lhs),
+    Box::new(rhs)
+));
+impl_expression_op!(Sub, sub, |lhs, rhs| Expression::Sum(
+    Box::new(lhs),
+    Box::new(-rhs)
+));
+impl_expression_op!(Mul, mul, |lhs, rhs| Expression::Product(
+    Box::new(lhs),
+    Box::new(rhs)
+));
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_field_test;
+    use ark_bls12_377::Fr as Bls12_377_scalar_field;
+    use ark_bls12_381::Fr as Bls12_381_scalar_field;
+    use rand_core::OsRng;
+
+    /// Returns the expression `(a * b - c) * ch + (a_next - q_c) * ch^2`.
+    fn example<F>() -> Expression<F>
+    where
+        F: PrimeField,
+    {
+        let [a, b, c, a_next, q_c] =
+            [Value::A, Value::B, Value::C, Value::ANext, Value::Qc]
+                .map(Expression::from);
+        let challenge = Expression::challenge();
+        (a * b - c) * &challenge + (a_next - q_c) * challenge.pow(2)
+    }
+
+    fn test_evaluate<F>()
+    where
+        F: PrimeField,
+    {
+        let challenge = F::rand(&mut OsRng);
+        let wit_vals = WitnessValues {
+            a_val: F::rand(&mut OsRng),
+            b_val: F::rand(&mut OsRng),
+            c_val: F::rand(&mut OsRng),
+            d_val: F::rand(&mut OsRng),
+        };
+        let vals = ExpressionVals {
+            a_next_val: F::rand(&mut OsRng),
+            q_c_val: F::rand(&mut OsRng),
+            ..Default::default()
+        };
+        assert_eq!(
+            example().evaluate(challenge, wit_vals, &vals),
+            (wit_vals.a_val * wit_vals.b_val - wit_vals.c_val) * challenge
+                + (vals.a_next_val - vals.q_c_val) * challenge.square()
+        );
+        assert_eq!(example::<F>().degree(), 2);
+        assert_eq!(example::<F>().challenge_degree(), 2);
+        assert_eq!(
+            Expression::from(Value::Qr)
+                .pow(5)
+                .evaluate(challenge, wit_vals, &vals),
+            F::zero()
+        );
+        assert_eq!(
+            Expression::constant(challenge)
+                .pow(0)
+                .evaluate(challenge, wit_vals, &vals),
+            F::one()
+        );
+    }
+
+    fn test_is_satisfied<F>()
+    where
+        F: PrimeField,
+    {
+        let a_val = F::rand(&mut OsRng);
+        let b_val = F::rand(&mut OsRng);
+        let wit_vals = WitnessValues {
+            a_val,
+            b_val,
+            c_val: a_val * b_val,
+            d_val: F::zero(),
+        };
+        let mut vals = ExpressionVals {
+            a_next_val: F::from(7u64),
+            q_c_val: F::from(7u64),
+            ..Default::default()
+        };
+        assert!(example().is_satisfied(wit_vals, &vals));
+
+        // `a_next != q_c`
+        vals.q_c_val.double_in_place();
+        assert!(!example().is_satisfied(wit_vals, &vals));
+    }
+
+    // Test on Bls12-381
+    batch_field_test!(
+        [test_evaluate, test_is_satisfied],
+        []
+        => Bls12_381_scalar_field
+    );
+
+    // Test on Bls12-377
+    batch_field_test!(
+        [test_evaluate, test_is_satisfied],
+        []
+        => Bls12_377_scalar_field
+    );
+}
@@ -7,59 +7,70 @@
 //! Logic Gates
 
 use crate::proof_system::{
-    linearisation_poly::CustomEvaluations,
-    widget::{GateConstraint, WitnessValues},
-    CustomValues,
+    expression::{Expression, ExpressionVals, Value},
+    GateConstraint, WitnessValues,
 };
 use ark_ff::PrimeField;
 use core::marker::PhantomData;
 
-/// Values needed for the computation of the logic gate constraint.
-pub struct LogicVals<F>
+/// Logic Gate
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Logic<F>(PhantomData<F>)
 where
-    F: PrimeField,
-{
-    /// Left wire value in the next position
-    pub a_next_val: F,
-    /// Right wire value in the next position
-    pub b_next_val: F,
-    /// Fourth wire value in the next position
-    pub d_next_val: F,
-    /// Constant selector value
-    pub q_c_val: F,
-}
+    F: PrimeField;
 
-impl<F> CustomValues<F> for LogicVals<F>
+impl<F> Logic<F>
 where
     F: PrimeField,
 {
-    fn from_evaluations(custom_evals: &CustomEvaluations<F>) -> Self {
-        // TODO: Subsitute labels
-        let a_next_val = custom_evals.get("a_next_eval");
-        let b_next_val = custom_evals.get("b_next_eval");
-        let d_next_val = custom_evals.get("d_next_eval");
-        let q_c_val = custom_evals.get("q_c_eval");
-        LogicVals {
-            a_next_val,
-            b_next_val,
-            d_next_val,
-            q_c_val,
-        }
+    /// Returns the identity of the logic gate, checking that the output wire
+    /// is the product of the base-4 digits added to the left and right
+    /// accumulators and that the digit added to the fourth accumulator is
+    /// their XOR or AND, as selected by the constant selector.
+    pub fn expression() -> Expression<F> {
+        let four = Expression::constant(F::from(4_u64));
+        let [a, b, c, d, a_next, b_next, d_next, q_c] = [
+            Value::A,
+            Value::B,
+            Value::C,
+            Value::D,
+            Value::ANext,
+            Value::BNext,
+            Value::DNext,
+            Value::Qc,
+        ]
+        .map(Expression::from);
+        let separation_challenge = Expression::challenge();
+        let kappa = separation_challenge.square();
+
+        let a = a_next - &four * a;
+        let c_0 = delta(&a);
+
+        let b = b_next - &four * b;
+        let c_1 = delta(&b) * &kappa;
+
+        let d = d_next - four * d;
+        let c_2 = delta(&d) * kappa.square();
+
+        let w = c;
+        let c_3 = (&w - &a * &b) * kappa.pow(3);
+
+        let c_4 = delta_xor_and(&a, &b, &w, &d, &q_c) * kappa.pow(4);
+
+        (c_0 + c_1 + c_2 + c_3 + c_4) * separation_challenge
     }
 }
 
-/// Logic Gate
-#[derive(derivative::Derivative)]
-#[derivative(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
-pub struct Logic<F>(PhantomData<F>)
-where
-    F: PrimeField;
-
 impl<F> GateConstraint<F> for Logic<F>
 where
     F: PrimeField,
 {
-    type CustomVals = LogicVals<F>;
+    type CustomVals = ExpressionVals<F>;
+
+    fn degree() -> usize {
+        Self::expression().degree()
+    }
 
     #[inline]
     fn constraints(
@@ -67,38 +78,22 @@ where
         wit_vals: WitnessValues<F>,
         custom_vals: Self::CustomVals,
     ) -> F {
-        let four = F::from(4_u64);
-        let kappa = separation_challenge.square();
-        let kappa_sq = kappa.square();
-        let kappa_cu = kappa_sq * kappa;
-        let kappa_qu = kappa_cu * kappa;
-
-        let a = custom_vals.a_next_val - four * wit_vals.a_val;
-        let c_0 = delta(a);
-
-        let b = custom_vals.b_next_val - four * wit_vals.b_val;
-        let c_1 = delta(b) * kappa;
-
-        let d = custom_vals.d_next_val - four * wit_vals.d_val;
-        let c_2 = delta(d) * kappa_sq;
-
-        let w = wit_vals.c_val;
-        let c_3 = (w - a * b) * kappa_cu;
-
-        let c_4 = delta_xor_and(a, b, w, d, custom_vals.q_c_val) * kappa_qu;
-
-        (c_0 + c_1 + c_2 + c_3 + c_4) * separation_challenge
+        Self::expression().evaluate(
+            separation_challenge,
+            wit_vals,
+            &custom_vals,
+        )
     }
 }
 
 /// Computes `f(f-1)(f-2)(f-3)`
-pub(crate) fn delta<F>(f: F) -> F
+pub(crate) fn delta<F>(f: &Expression<F>) -> Expression<F>
 where
     F: PrimeField,
 {
-    let f_1 = f - F::one();
-    let f_2 = f - F::from(2_u64);
-    let f_3 = f - F::from(3_u64);
+    let f_1 = f - Expression::constant(F::one());
+    let f_2 = f - Expression::constant(F::from(2_u64));
+    let f_3 = f - Expression::constant(F::from(3_u64));
     f * f_1 * f_2 * f_3
 }
 
@@ -111,23 +106,24 @@ where
 /// F = w[w(4w - 18(a+b) + 81) + 18(a^2 + b^2) - 81(a+b) + 83]
 /// ```
 #[allow(non_snake_case)]
-pub(crate) fn delta_xor_and<F>(a: F, b: F, w: F, c: F, q_c: F) -> F
+pub(crate) fn delta_xor_and<F>(
+    a: &Expression<F>,
+    b: &Expression<F>,
+    w: &Expression<F>,
+    c: &Expression<F>,
+    q_c: &Expression<F>,
+) -> Expression<F>
 where
     F: PrimeField,
 {
-    let nine = F::from(9_u64);
-    let two = F::from(2_u64);
-    let three = F::from(3_u64);
-    let four = F::from(4_u64);
-    let eighteen = F::from(18_u64);
-    let eighty_one = F::from(81_u64);
-    let eighty_three = F::from(83_u64);
+    let constant = |value: u64| Expression::constant(F::from(value));
+    let a_plus_b = a + b;
     let F = w
-        * (w * (four * w - eighteen * (a + b) + eighty_one)
-            + eighteen * (a.square() + b.square())
-            - eighty_one * (a + b)
-            + eighty_three);
-    let E = three * (a + b + c) - (two * F);
-    let B = q_c * ((nine * c) - three * (a + b));
+        * (w * (constant(4) * w - constant(18) * &a_plus_b + constant(81))
+            + constant(18) * (a.square() + b.square())
+            - constant(81) * &a_plus_b
+            + constant(83));
+    let E = constant(3) * (&a_plus_b + c) - (constant(2) * F);
+    let B = q_c * ((constant(9) * c) - constant(3) * a_plus_b);
     B + E
 }
@@ -5,11 +5,12 @@
 // Copyright (c) ZK-Garage. All rights reserved.
 //! Lookup gates
 
-use crate::error::Error;
-use crate::lookup::multiset::MultiSet;
-use crate::proof_system::linearisation_poly::ProofEvaluations;
+use crate::lookup::{multiset::MultiSet, LookupMode};
+use crate::proof_system::linearisation_poly::{
+    LookupArgumentEvaluations, LookupEvaluations, ProofEvaluations,
+};
 use crate::util::lc;
-use ark_ff::{FftField, PrimeField};
+use ark_ff::PrimeField;
 use ark_poly::polynomial::univariate::DensePolynomial;
 use ark_poly::{EvaluationDomain, Evaluations, GeneralEvaluationDomain};
 use ark_poly_commit::PolynomialCommitment;
@@ -24,6 +25,8 @@ where
 {
     /// Lookup selector
     pub q_lookup: (DensePolynomial<F>, Evaluations<F>),
+    /// Lookup table selector
+    pub q_table: (DensePolynomial<F>, Evaluations<F>),
     /// Column 1 of lookup table
     pub table_1: MultiSet<F>,
     /// Column 2 of lookup table
@@ -32,6 +35,10 @@ where
     pub table_3: MultiSet<F>,
     /// Column 4 of lookup table
     pub table_4: MultiSet<F>,
+    /// Table-id column of lookup table
+    pub table_id: MultiSet<F>,
+    /// Argument proving the lookups
+    pub mode: LookupMode,
 }
 
 impl<F> ProverKey<F>
@@ -39,58 +46,108 @@ where
     F: PrimeField,
 {
     /// Compute lookup portion of quotient polynomial
-    pub fn compute_lookup_quotient_term(
+    pub(crate) fn compute_lookup_quotient_term(
         &self,
         domain: &GeneralEvaluationDomain<F>,
-        wl_eval_4n: &[F],
-        wr_eval_4n: &[F],
-        wo_eval_4n: &[F],
-        w4_eval_4n: &[F],
-        f_eval_4n: &[F],
-        table_eval_4n: &[F],
-        h1_eval_4n: &[F],
-        h2_eval_4n: &[F],
-        z2_eval_4n: &[F],
-        l1_eval_4n: &[F],
+        domain_ext: &GeneralEvaluationDomain<F>,
+        wl_eval_ext: &[F],
+        wr_eval_ext: &[F],
+        wo_eval_ext: &[F],
+        w4_eval_ext: &[F],
+        f_eval_ext: &[F],
+        table_eval_ext: &[F],
+        lookup_polys: &LookupPolynomials<F>,
+        l1_eval_ext: &[F],
         delta: F,
         epsilon: F,
         zeta: F,
         lookup_sep: F,
-    ) -> Result<Vec<F>, Error>
-    where
-        F: PrimeField,
-    {
-        let domain_4n = GeneralEvaluationDomain::<F>::new(4 * domain.size())
-        .ok_or(Error::InvalidEvalDomainSize {
-        log_size_of_group: (4 * domain.size()).trailing_zeros(),
-        adicity:
-            <<F as FftField>::FftParams as ark_ff::FftParameters>::TWO_ADICITY,
-    })?;
-
-        Ok((0..domain_4n.size())
-            .map(|i| {
-                self.compute_quotient_i(
-                    i,
-                    wl_eval_4n[i],
-                    wr_eval_4n[i],
-                    wo_eval_4n[i],
-                    w4_eval_4n[i],
-                    f_eval_4n[i],
-                    table_eval_4n[i],
-                    table_eval_4n[i + 4],
-                    h1_eval_4n[i],
-                    h1_eval_4n[i + 4],
-                    h2_eval_4n[i],
-                    z2_eval_4n[i],
-                    z2_eval_4n[i + 4],
-                    l1_eval_4n[i],
-                    delta,
-                    epsilon,
-                    zeta,
-                    lookup_sep,
-                )
-            })
-            .collect())
+    ) -> Vec<F> {
+        // Offset of the evaluations at the next element of `domain`
+        let next = domain_ext.size() / domain.size();
+
+        match lookup_polys {
+            LookupPolynomials::Plookup { h_1, h_2, z_2 } => {
+                let mut h1_eval_ext = domain_ext.coset_fft(h_1);
+                h1_eval_ext.extend_from_within(..next);
+                let h2_eval_ext = domain_ext.coset_fft(h_2);
+                let mut z2_eval_ext = domain_ext.coset_fft(z_2);
+                z2_eval_ext.extend_from_within(..next);
+
+                (0..domain_ext.size())
+                    .map(|i| {
+                        self.compute_quotient_i(
+                            i,
+                            wl_eval_ext[i],
+                            wr_eval_ext[i],
+                            wo_eval_ext[i],
+                            w4_eval_ext[i],
+                            f_eval_ext[i],
+                            table_eval_ext[i],
+                            table_eval_ext[i + next],
+                            h1_eval_ext[i],
+                            h1_eval_ext[i + next],
+                            h2_eval_ext[i],
+                            z2_eval_ext[i],
+                            z2_eval_ext[i + next],
+                            l1_eval_ext[i],
+                            delta,
+                            epsilon,
+                            zeta,
+                            lookup_sep,
+                        )
+                    })
+                    .collect()
+            }
+            LookupPolynomials::LogUp { m, phi } => {
+                let m_eval_ext = domain_ext.coset_fft(m);
+                let mut phi_eval_ext = domain_ext.coset_fft(phi);
+                phi_eval_ext.extend_from_within(..next);
+
+                (0..domain_ext.size())
+                    .map(|i| {
+                        self.compute_log_derivative_quotient_i(
+                            i,
+                            wl_eval_ext[i],
+                            wr_eval_ext[i],
+                            wo_eval_ext[i],
+                            w4_eval_ext[i],
+                            f_eval_ext[i],
+                            table_eval_ext[i],
+                            m_eval_ext[i],
+                            phi_eval_ext[i],
+                            phi_eval_ext[i + next],
+                            epsilon,
+                            zeta,
+                            lookup_sep,
+                        )
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Computes the evaluation of the query portion of the quotient
+    /// polynomial, shared by both lookup arguments.
+    fn compute_query_quotient_i(
+        &self,
+        index: usize,
+        w_l_i: F,
+        w_r_i: F,
+        w_o_i: F,
+        w_4_i: F,
+        f_i: F,
+        zeta: F,
+        lookup_sep: F,
+    ) -> F {
+        // (q_lookup(X) * (a(X) + zeta * b(X) + (zeta^2 * c(X)) + (zeta^3 * d(X)
+        // - f(X))) + zeta^4 * q_table(X)) * α_1
+        let q_lookup_i = self.q_lookup.1[index];
+        let q_table_i = self.q_table.1[index];
+        let compressed_tuple = lc(&[w_l_i, w_r_i, w_o_i, w_4_i], &zeta);
+        (q_lookup_i * (compressed_tuple - f_i)
+            + zeta.square().square() * q_table_i)
+            * lookup_sep
     }
 
     /// Compute evals of lookup portion of quotient polynomial
@@ -115,18 +172,14 @@ where
         zeta: F,
         lookup_sep: F,
     ) -> F {
-        // q_lookup(X) * (a(X) + zeta * b(X) + (zeta^2 * c(X)) + (zeta^3 * d(X)
-        // - f(X))) * α_1
         let lookup_sep_sq = lookup_sep.square();
         let lookup_sep_cu = lookup_sep_sq * lookup_sep;
         let one_plus_delta = delta + F::one();
         let epsilon_one_plus_delta = epsilon * one_plus_delta;
 
-        let a = {
-            let q_lookup_i = self.q_lookup.1[index];
-            let compressed_tuple = lc(&[w_l_i, w_r_i, w_o_i, w_4_i], &zeta);
-            q_lookup_i * (compressed_tuple - f_i) * lookup_sep
-        };
+        let a = self.compute_query_quotient_i(
+            index, w_l_i, w_r_i, w_o_i, w_4_i, f_i, zeta, lookup_sep,
+        );
 
         // z2(X) * (1+δ) * (ε+f(X)) * (ε*(1+δ) + t(X) + δt(Xω)) * lookup_sep^2
         let b = {
@@ -150,6 +203,41 @@ where
         a + b + c + d
     }
 
+    /// Compute evals of lookup portion of quotient polynomial for the LogUp
+    /// argument
+    pub fn compute_log_derivative_quotient_i(
+        &self,
+        index: usize,
+        w_l_i: F,
+        w_r_i: F,
+        w_o_i: F,
+        w_4_i: F,
+        f_i: F,
+        table_i: F,
+        m_i: F,
+        phi_i: F,
+        phi_i_next: F,
+        epsilon: F,
+        zeta: F,
+        lookup_sep: F,
+    ) -> F {
+        let a = self.compute_query_quotient_i(
+            index, w_l_i, w_r_i, w_o_i, w_4_i, f_i, zeta, lookup_sep,
+        );
+
+        // ((phi(Xω) - phi(X)) * (ε + t(X)) * (ε + f(X)) - (ε + t(X))
+        // + m(X) * (ε + f(X))) * lookup_sep^2
+        let b = {
+            let b_0 = epsilon + f_i;
+            let b_1 = epsilon + table_i;
+
+            ((phi_i_next - phi_i) * b_1 * b_0 - b_1 + m_i * b_0)
+                * lookup_sep.square()
+        };
+
+        a + b
+    }
+
     /// Compute linearization for lookup gates
     pub(crate) fn compute_linearisation(
         &self,
@@ -158,48 +246,27 @@ where
         b_eval: F,
         c_eval: F,
         d_eval: F,
-        f_eval: F,
-        table_eval: F,
-        table_next_eval: F,
-        h1_next_eval: F,
-        h2_eval: F,
-        z2_next_eval: F,
-        delta: F,
-        epsilon: F,
-        zeta: F,
-        z2_poly: &DensePolynomial<F>,
-        h1_poly: &DensePolynomial<F>,
+        lookup_evals: &LookupEvaluations<F>,
+        (delta, epsilon, zeta): (F, F, F),
+        lookup_polys: &LookupPolynomials<F>,
         lookup_sep: F,
     ) -> DensePolynomial<F> {
-        let lookup_sep_sq = lookup_sep.square();
-        let lookup_sep_cu = lookup_sep * lookup_sep_sq;
-        let one_plus_delta = delta + F::one();
-        let epsilon_one_plus_delta = epsilon * one_plus_delta;
-
         let a = {
             let compressed_tuple = lc(&[a_eval, b_eval, c_eval, d_eval], &zeta);
-            &self.q_lookup.0 * ((compressed_tuple - f_eval) * lookup_sep)
+            &(&self.q_lookup.0
+                * ((compressed_tuple - lookup_evals.f_eval) * lookup_sep))
+                + &(&self.q_table.0 * (zeta.square().square() * lookup_sep))
         };
 
-        // z2(X) * (1 + δ) * (ε + f_bar) * (ε(1+δ) + t_bar + δ*tω_bar) *
-        // lookup_sep^2
-        let b = {
-            let b_0 = epsilon + f_eval;
-            let b_1 =
-                epsilon_one_plus_delta + table_eval + delta * table_next_eval;
-            let b_2 = l1_eval * lookup_sep_cu;
-
-            z2_poly * (one_plus_delta * b_0 * b_1 * lookup_sep_sq + b_2)
-        };
-
-        // h1(X) * (−z2ω_bar) * (ε(1+δ) + h2_bar  + δh1ω_bar) * lookup_sep^2
-        let c = {
-            let c_0 = -z2_next_eval * lookup_sep_sq;
-            let c_1 = epsilon_one_plus_delta + h2_eval + delta * h1_next_eval;
-
-            h1_poly * (c_0 * c_1)
-        };
-        a + b + c
+        compute_argument_linearisation_scalars(
+            lookup_evals,
+            (delta, epsilon),
+            lookup_sep,
+            l1_eval,
+        )
+        .into_iter()
+        .zip(lookup_polys.linearised())
+        .fold(a, |acc, (scalar, poly)| &acc + &(poly * scalar))
     }
 }
 
@@ -219,6 +286,8 @@ where
 {
     /// Lookup Selector Commitment
     pub q_lookup: PC::Commitment,
+    /// Lookup Table Selector Commitment
+    pub q_table: PC::Commitment,
     /// Commitment to first table column
     pub table_1: PC::Commitment,
     /// Commitment to second table column
@@ -227,6 +296,10 @@ where
     pub table_3: PC::Commitment,
     /// Commitment to fourth table column
     pub table_4: PC::Commitment,
+    /// Commitment to table-id column
+    pub table_id: PC::Commitment,
+    /// Argument proving the lookups
+    pub mode: LookupMode,
 }
 
 impl<F, PC> VerifierKey<F, PC>
@@ -235,7 +308,7 @@ where
     PC: PolynomialCommitment<F, DensePolynomial<F>>,
 {
     /// Computes the linearisation commitments.
-    pub fn compute_linearisation_commitment(
+    pub(crate) fn compute_linearisation_commitment(
         &self,
         scalars: &mut Vec<F>,
         points: &mut Vec<PC::Commitment>,
@@ -243,14 +316,8 @@ where
         (delta, epsilon, zeta): (F, F, F),
         lookup_sep: F,
         l1_eval: F,
-        z2_comm: PC::Commitment,
-        h1_comm: PC::Commitment,
+        lookup_comms: &LookupCommitments<PC::Commitment>,
     ) {
-        let one_plus_delta = F::one() + delta;
-        let epsilon_one_plus_delta = epsilon * one_plus_delta;
-        let lookup_sep_sq = lookup_sep.square();
-        let lookup_sep_cu = lookup_sep_sq * lookup_sep;
-
         let a = {
             let compressed_eval = lc(
                 &[
@@ -269,27 +336,278 @@ where
         scalars.push(a);
         points.push(self.q_lookup.clone());
 
-        // (1 + δ) * (ε + f_bar) * (ε(1+δ) + t_bar + δ*tω_bar) *  lookup_sep^2
-        let b = {
-            let b_0 = epsilon + evaluations.lookup_evals.f_eval;
-            let b_1 = epsilon_one_plus_delta
-                + evaluations.lookup_evals.table_eval
-                + delta * evaluations.lookup_evals.table_next_eval;
-            let b_2 = l1_eval * lookup_sep_cu;
-            one_plus_delta * b_0 * b_1 * lookup_sep_sq + b_2
-        };
+        scalars.push(zeta.square().square() * lookup_sep);
+        points.push(self.q_table.clone());
 
-        scalars.push(b);
-        points.push(z2_comm);
+        scalars.extend(compute_argument_linearisation_scalars(
+            &evaluations.lookup_evals,
+            (delta, epsilon),
+            lookup_sep,
+            l1_eval,
+        ));
+        points.extend(lookup_comms.linearised().into_iter().cloned());
+    }
+}
 
-        let c = {
-            let c_0 = -evaluations.lookup_evals.z2_next_eval * lookup_sep_sq;
-            let c_1 = epsilon_one_plus_delta
-                + evaluations.lookup_evals.h2_eval
-                + delta * evaluations.lookup_evals.h1_next_eval;
-            c_0 * c_1
-        };
-        scalars.push(c);
-        points.push(h1_comm);
+/// Polynomials of the lookup argument committed to by the prover, besides the
+/// query polynomial, which depend on its [`LookupMode`].
+pub(crate) enum LookupPolynomials<F>
+where
+    F: PrimeField,
+{
+    /// Polynomials of the plookup argument.
+    Plookup {
+        /// First half of the sorted polynomial
+        h_1: DensePolynomial<F>,
+        /// Second half of the sorted polynomial
+        h_2: DensePolynomial<F>,
+        /// Lookup permutation polynomial
+        z_2: DensePolynomial<F>,
+    },
+
+    /// Polynomials of the LogUp argument.
+    LogUp {
+        /// Multiplicities of the rows of the table among the queries
+        m: DensePolynomial<F>,
+        /// Running sum of the logarithmic derivatives
+        phi: DensePolynomial<F>,
+    },
+}
+
+impl<F> LookupPolynomials<F>
+where
+    F: PrimeField,
+{
+    /// Returns the polynomials which are not evaluated in the linearisation
+    /// polynomial, in the order of their
+    /// [scalars](compute_argument_linearisation_scalars).
+    pub(crate) fn linearised(&self) -> [&DensePolynomial<F>; 2] {
+        match self {
+            Self::Plookup { h_1, z_2, .. } => [z_2, h_1],
+            Self::LogUp { m, phi } => [phi, m],
+        }
+    }
+}
+
+/// Commitments to the [`LookupPolynomials`] of a
+/// [`Proof`](crate::proof_system::Proof).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum LookupCommitments<C> {
+    /// Commitments to the polynomials of the plookup argument.
+    Plookup {
+        /// Commitment to first half of sorted polynomial
+        h_1_comm: C,
+        /// Commitment to second half of sorted polynomial
+        h_2_comm: C,
+        /// Commitment to the lookup permutation polynomial.
+        z_2_comm: C,
+    },
+
+    /// Commitments to the polynomials of the LogUp argument.
+    LogUp {
+        /// Commitment to the multiplicities polynomial.
+        m_comm: C,
+        /// Commitment to the running sum polynomial.
+        phi_comm: C,
+    },
+}
+
+impl<C> LookupCommitments<C> {
+    /// Returns the argument of the commitments.
+    pub(crate) fn mode(&self) -> LookupMode {
+        match self {
+            Self::Plookup { .. } => LookupMode::Plookup,
+            Self::LogUp { .. } => LookupMode::LogUp,
+        }
+    }
+
+    /// Returns the commitments to the polynomials which are not evaluated in
+    /// the linearisation polynomial, in the order of
+    /// [`LookupPolynomials::linearised`].
+    pub(crate) fn linearised(&self) -> [&C; 2] {
+        match self {
+            Self::Plookup {
+                h_1_comm, z_2_comm, ..
+            } => [z_2_comm, h_1_comm],
+            Self::LogUp { m_comm, phi_comm } => [phi_comm, m_comm],
+        }
+    }
+}
+
+impl<C> Default for LookupCommitments<C>
+where
+    C: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::Plookup {
+            h_1_comm: C::default(),
+            h_2_comm: C::default(),
+            z_2_comm: C::default(),
+        }
+    }
+}
+
+impl<C> CanonicalSerialize for LookupCommitments<C>
+where
+    C: CanonicalSerialize,
+{
+    fn serialize<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        self.mode().serialize(&mut writer)?;
+        match self {
+            Self::Plookup {
+                h_1_comm,
+                h_2_comm,
+                z_2_comm,
+            } => {
+                h_1_comm.serialize(&mut writer)?;
+                h_2_comm.serialize(&mut writer)?;
+                z_2_comm.serialize(&mut writer)
+            }
+            Self::LogUp { m_comm, phi_comm } => {
+                m_comm.serialize(&mut writer)?;
+                phi_comm.serialize(&mut writer)
+            }
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.mode().serialized_size()
+            + match self {
+                Self::Plookup {
+                    h_1_comm,
+                    h_2_comm,
+                    z_2_comm,
+                } => {
+                    h_1_comm.serialized_size()
+                        + h_2_comm.serialized_size()
+                        + z_2_comm.serialized_size()
+                }
+                Self::LogUp { m_comm, phi_comm } => {
+                    m_comm.serialized_size() + phi_comm.serialized_size()
+                }
+            }
+    }
+}
+
+impl<C> CanonicalDeserialize for LookupCommitments<C>
+where
+    C: CanonicalDeserialize,
+{
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        match LookupMode::deserialize(&mut reader)? {
+            LookupMode::Plookup => Ok(Self::Plookup {
+                h_1_comm: C::deserialize(&mut reader)?,
+                h_2_comm: C::deserialize(&mut reader)?,
+                z_2_comm: C::deserialize(&mut reader)?,
+            }),
+            LookupMode::LogUp => Ok(Self::LogUp {
+                m_comm: C::deserialize(&mut reader)?,
+                phi_comm: C::deserialize(&mut reader)?,
+            }),
+        }
+    }
+}
+
+/// Computes the scalars multiplying the polynomials of the lookup argument
+/// which are not evaluated in the linearisation polynomial, in the order of
+/// [`LookupPolynomials::linearised`]: `z_2` and `h_1` for plookup, `phi` and
+/// `m` for LogUp.
+pub(crate) fn compute_argument_linearisation_scalars<F>(
+    lookup_evals: &LookupEvaluations<F>,
+    (delta, epsilon): (F, F),
+    lookup_sep: F,
+    l1_eval: F,
+) -> [F; 2]
+where
+    F: PrimeField,
+{
+    let lookup_sep_sq = lookup_sep.square();
+    match lookup_evals.argument_evals {
+        LookupArgumentEvaluations::Plookup {
+            z2_next_eval,
+            h1_next_eval,
+            h2_eval,
+            table_next_eval,
+            ..
+        } => {
+            let one_plus_delta = F::one() + delta;
+            let epsilon_one_plus_delta = epsilon * one_plus_delta;
+            let lookup_sep_cu = lookup_sep_sq * lookup_sep;
+
+            // (1 + δ) * (ε + f_bar) * (ε(1+δ) + t_bar + δ*tω_bar) *
+            // lookup_sep^2 + l_1(z) * lookup_sep^3
+            let z2_scalar = {
+                let b_0 = epsilon + lookup_evals.f_eval;
+                let b_1 = epsilon_one_plus_delta
+                    + lookup_evals.table_eval
+                    + delta * table_next_eval;
+                let b_2 = l1_eval * lookup_sep_cu;
+                one_plus_delta * b_0 * b_1 * lookup_sep_sq + b_2
+            };
+
+            // (−z2ω_bar) * (ε(1+δ) + h2_bar  + δh1ω_bar) * lookup_sep^2
+            let h1_scalar = {
+                let c_0 = -z2_next_eval * lookup_sep_sq;
+                let c_1 =
+                    epsilon_one_plus_delta + h2_eval + delta * h1_next_eval;
+                c_0 * c_1
+            };
+
+            [z2_scalar, h1_scalar]
+        }
+        LookupArgumentEvaluations::LogUp { .. } => {
+            let f_term = epsilon + lookup_evals.f_eval;
+            let t_term = epsilon + lookup_evals.table_eval;
+
+            // −(ε + t_bar) * (ε + f_bar) * lookup_sep^2 for phi and
+            // (ε + f_bar) * lookup_sep^2 for m
+            [-t_term * f_term * lookup_sep_sq, f_term * lookup_sep_sq]
+        }
+    }
+}
+
+/// Computes the part of the lookup argument constraint at the evaluation
+/// challenge which is not multiplied by a committed polynomial in the
+/// linearisation polynomial.
+pub(crate) fn compute_argument_linearisation_constant<F>(
+    lookup_evals: &LookupEvaluations<F>,
+    (delta, epsilon): (F, F),
+    lookup_sep: F,
+    l1_eval: F,
+) -> F
+where
+    F: PrimeField,
+{
+    let lookup_sep_sq = lookup_sep.square();
+    match lookup_evals.argument_evals {
+        LookupArgumentEvaluations::Plookup {
+            z2_next_eval,
+            h1_next_eval,
+            h2_eval,
+            ..
+        } => {
+            let epsilon_one_plus_delta = epsilon * (F::one() + delta);
+
+            // −z2ω_bar * (ε(1+δ) + δ*h2_bar) * (ε(1+δ) + h2_bar + δ*h1ω_bar)
+            // * lookup_sep^2 − l_1(z) * lookup_sep^3
+            let d_0 = lookup_sep_sq * z2_next_eval;
+            let d_1 = epsilon_one_plus_delta + delta * h2_eval;
+            let d_2 = epsilon_one_plus_delta + h2_eval + delta * h1_next_eval;
+            let e = lookup_sep_sq * lookup_sep * l1_eval;
+
+            -d_0 * d_1 * d_2 - e
+        }
+        LookupArgumentEvaluations::LogUp { phi_next_eval } => {
+            let f_term = epsilon + lookup_evals.f_eval;
+            let t_term = epsilon + lookup_evals.table_eval;
+
+            // (phiω_bar * (ε + t_bar) * (ε + f_bar) − (ε + t_bar)) *
+            // lookup_sep^2
+            (phi_next_eval * t_term * f_term - t_term) * lookup_sep_sq
+        }
     }
 }
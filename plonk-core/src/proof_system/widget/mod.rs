@@ -7,22 +7,28 @@
 //! Proof System Widgets
 
 pub mod arithmetic;
+pub mod custom;
 pub mod ecc;
+pub mod expression;
 pub mod logic;
 pub mod lookup;
+pub mod poseidon;
 pub mod range;
 
 use crate::{
     commitment::HomomorphicCommitment,
-    lookup::MultiSet,
+    error::Error,
+    lookup::{LookupMode, MultiSet},
     proof_system::{
         linearisation_poly::CustomEvaluations,
-        linearisation_poly::ProofEvaluations, permutation,
+        linearisation_poly::ProofEvaluations, permutation, pi::PublicInputs,
     },
     transcript::TranscriptProtocol,
 };
 use ark_ff::PrimeField;
-use ark_poly::{univariate::DensePolynomial, Evaluations};
+use ark_poly::{
+    univariate::DensePolynomial, Evaluations, GeneralEvaluationDomain,
+};
 use ark_serialize::*;
 
 /// Set of values needed for a custom gate
@@ -65,6 +71,16 @@ where
     /// Custom values needed for the gate
     type CustomVals: CustomValues<F>;
 
+    /// Returns the degree of [`constraints`](Self::constraints) in the wire
+    /// and custom values.
+    ///
+    /// The quotient polynomial is split in one piece less than the largest
+    /// degree of a selector times its constraint, so a gate of a higher
+    /// degree makes the proofs of the circuits using it longer.
+    fn degree() -> usize {
+        4
+    }
+
     /// Returns the coefficient of the quotient polynomial for this gate given
     /// an instantiation of the gate at `values` and a
     /// `separation_challenge` if this gate requires it for soundness.
@@ -153,6 +169,12 @@ where
     /// Circuit size (not padded to a power of two).
     pub(crate) n: usize,
 
+    /// Positions of the public inputs, in increasing order.
+    pub(crate) pi_positions: Vec<usize>,
+
+    /// Number of pieces of degree `n` the quotient polynomial is split in.
+    pub(crate) quotient_pieces: usize,
+
     /// Arithmetic Verifier Key
     pub(crate) arithmetic: arithmetic::VerifierKey<F, PC>,
 
@@ -168,6 +190,12 @@ where
     /// Variable Group Addition Selector Commitment
     pub(crate) variable_group_add_selector_commitment: PC::Commitment,
 
+    /// Poseidon Round Gate Selector Commitment
+    pub(crate) poseidon_selector_commitment: PC::Commitment,
+
+    /// Custom Gate Verifier Keys, in order of registration
+    pub(crate) custom_gates: Vec<custom::VerifierKey<F, PC>>,
+
     /// VerifierKey for permutation checks
     pub(crate) permutation: permutation::VerifierKey<PC::Commitment>,
 
@@ -185,6 +213,8 @@ where
     /// sigma polynomial commitments.
     pub(crate) fn from_polynomial_commitments(
         n: usize,
+        pi_positions: Vec<usize>,
+        quotient_pieces: usize,
         q_m: PC::Commitment,
         q_l: PC::Commitment,
         q_r: PC::Commitment,
@@ -195,8 +225,10 @@ where
         q_range: PC::Commitment,
         q_logic: PC::Commitment,
         q_lookup: PC::Commitment,
+        q_table: PC::Commitment,
         q_fixed_group_add: PC::Commitment,
         q_variable_group_add: PC::Commitment,
+        q_poseidon: PC::Commitment,
         left_sigma: PC::Commitment,
         right_sigma: PC::Commitment,
         out_sigma: PC::Commitment,
@@ -205,9 +237,14 @@ where
         table_2: PC::Commitment,
         table_3: PC::Commitment,
         table_4: PC::Commitment,
+        table_id: PC::Commitment,
+        lookup_mode: LookupMode,
+        custom_gates: Vec<custom::VerifierKey<F, PC>>,
     ) -> Self {
         Self {
             n,
+            pi_positions,
+            quotient_pieces,
             arithmetic: arithmetic::VerifierKey {
                 q_m,
                 q_l,
@@ -221,6 +258,8 @@ where
             logic_selector_commitment: q_logic,
             fixed_group_add_selector_commitment: q_fixed_group_add,
             variable_group_add_selector_commitment: q_variable_group_add,
+            poseidon_selector_commitment: q_poseidon,
+            custom_gates,
             permutation: permutation::VerifierKey {
                 left_sigma,
                 right_sigma,
@@ -229,10 +268,13 @@ where
             },
             lookup: lookup::VerifierKey {
                 q_lookup,
+                q_table,
                 table_1,
                 table_2,
                 table_3,
                 table_4,
+                table_id,
+                mode: lookup_mode,
             },
         }
     }
@@ -241,6 +283,28 @@ where
     pub fn padded_circuit_size(&self) -> usize {
         self.n.next_power_of_two()
     }
+
+    /// Checks that `pub_inputs` only sets public inputs of the circuit.
+    pub(crate) fn check_public_inputs(
+        &self,
+        pub_inputs: &PublicInputs<F>,
+    ) -> Result<(), Error> {
+        if pub_inputs.len() > self.pi_positions.len() {
+            return Err(Error::PublicInputLengthMismatch {
+                expected: self.pi_positions.len(),
+                found: pub_inputs.len(),
+            });
+        }
+        match pub_inputs
+            .positions()
+            .find(|position| self.pi_positions.binary_search(position).is_err())
+        {
+            Some(&position) => {
+                Err(Error::PublicInputPositionMismatch { position })
+            }
+            None => Ok(()),
+        }
+    }
 }
 
 impl<F, PC> VerifierKey<F, PC>
@@ -251,7 +315,7 @@ where
     /// Adds the circuit description to the transcript.
     pub(crate) fn seed_transcript<T>(&self, transcript: &mut T)
     where
-        T: TranscriptProtocol,
+        T: TranscriptProtocol<F>,
     {
         transcript.append(b"q_m", &self.arithmetic.q_m);
         transcript.append(b"q_l", &self.arithmetic.q_l);
@@ -270,10 +334,24 @@ where
             b"q_fixed_group_add",
             &self.fixed_group_add_selector_commitment,
         );
+        transcript.append(b"q_poseidon", &self.poseidon_selector_commitment);
+        transcript.append(b"q_lookup", &self.lookup.q_lookup);
+        transcript.append(b"q_table", &self.lookup.q_table);
+        for custom_gate in &self.custom_gates {
+            transcript
+                .append_message(b"custom gate", custom_gate.name.as_bytes());
+            transcript.append(b"q_custom", &custom_gate.q_custom);
+        }
         transcript.append(b"left_sigma", &self.permutation.left_sigma);
         transcript.append(b"right_sigma", &self.permutation.right_sigma);
         transcript.append(b"out_sigma", &self.permutation.out_sigma);
         transcript.append(b"fourth_sigma", &self.permutation.fourth_sigma);
+        transcript.append(b"table_1", &self.lookup.table_1);
+        transcript.append(b"table_2", &self.lookup.table_2);
+        transcript.append(b"table_3", &self.lookup.table_3);
+        transcript.append(b"table_4", &self.lookup.table_4);
+        transcript.append(b"table_id", &self.lookup.table_id);
+        transcript.append(b"lookup_mode", &self.lookup.mode);
         transcript.circuit_domain_sep(self.n as u64);
     }
 }
@@ -296,6 +374,9 @@ where
     /// Circuit size
     pub(crate) n: usize,
 
+    /// Number of pieces of degree `n` the quotient polynomial is split in.
+    pub(crate) quotient_pieces: usize,
+
     /// Arithmetic Prover Key
     pub(crate) arithmetic: arithmetic::ProverKey<F>,
 
@@ -315,24 +396,38 @@ where
     pub(crate) variable_group_add_selector:
         (DensePolynomial<F>, Evaluations<F>),
 
+    /// Poseidon Round Gate Selector
+    pub(crate) poseidon_selector: (DensePolynomial<F>, Evaluations<F>),
+
+    /// Custom Gate Prover Keys, in order of registration
+    pub(crate) custom_gates: Vec<custom::ProverKey<F>>,
+
     /// ProverKey for permutation checks
     pub(crate) permutation: permutation::ProverKey<F>,
 
-    /// Pre-processes the 4n Evaluations for the vanishing polynomial, so
-    /// they do not need to be computed at the proving stage.
+    /// Pre-processes the Evaluations for the vanishing polynomial over the
+    /// extended coset, so they do not need to be computed at the proving
+    /// stage.
     ///
     /// NOTE: With this, we can combine all parts of the quotient polynomial
     /// in their evaluation phase and divide by the quotient
     /// polynomial without having to perform IFFT
-    pub(crate) v_h_coset_4n: Evaluations<F>,
+    pub(crate) v_h_coset_ext: Evaluations<F>,
 }
 
 impl<F> ProverKey<F>
 where
     F: PrimeField,
 {
-    pub(crate) fn v_h_coset_4n(&self) -> &Evaluations<F> {
-        &self.v_h_coset_4n
+    pub(crate) fn v_h_coset_ext(&self) -> &Evaluations<F> {
+        &self.v_h_coset_ext
+    }
+
+    /// Returns the domain of the coset over which the quotient polynomial is
+    /// computed, the smallest one with a power of two times `n` elements
+    /// holding its [`quotient_pieces`](Self::quotient_pieces).
+    pub(crate) fn extended_domain(&self) -> GeneralEvaluationDomain<F> {
+        self.v_h_coset_ext.domain()
     }
 
     /// Constructs a [`ProverKey`] from the widget ProverKey's that are
@@ -340,6 +435,7 @@ where
     /// sigma polynomials and it's evaluations.
     pub(crate) fn from_polynomials_and_evals(
         n: usize,
+        quotient_pieces: usize,
         q_m: (DensePolynomial<F>, Evaluations<F>),
         q_l: (DensePolynomial<F>, Evaluations<F>),
         q_r: (DensePolynomial<F>, Evaluations<F>),
@@ -350,21 +446,27 @@ where
         q_range: (DensePolynomial<F>, Evaluations<F>),
         q_logic: (DensePolynomial<F>, Evaluations<F>),
         q_lookup: (DensePolynomial<F>, Evaluations<F>),
+        q_table: (DensePolynomial<F>, Evaluations<F>),
         q_fixed_group_add: (DensePolynomial<F>, Evaluations<F>),
         q_variable_group_add: (DensePolynomial<F>, Evaluations<F>),
+        q_poseidon: (DensePolynomial<F>, Evaluations<F>),
         left_sigma: (DensePolynomial<F>, Evaluations<F>),
         right_sigma: (DensePolynomial<F>, Evaluations<F>),
         out_sigma: (DensePolynomial<F>, Evaluations<F>),
         fourth_sigma: (DensePolynomial<F>, Evaluations<F>),
         linear_evaluations: Evaluations<F>,
-        v_h_coset_4n: Evaluations<F>,
+        v_h_coset_ext: Evaluations<F>,
         table_1: MultiSet<F>,
         table_2: MultiSet<F>,
         table_3: MultiSet<F>,
         table_4: MultiSet<F>,
+        table_id: MultiSet<F>,
+        lookup_mode: LookupMode,
+        custom_gates: Vec<custom::ProverKey<F>>,
     ) -> Self {
         Self {
             n,
+            quotient_pieces,
             arithmetic: arithmetic::ProverKey {
                 q_m,
                 q_l,
@@ -378,12 +480,17 @@ where
             logic_selector: q_logic,
             fixed_group_add_selector: q_fixed_group_add,
             variable_group_add_selector: q_variable_group_add,
+            poseidon_selector: q_poseidon,
+            custom_gates,
             lookup: lookup::ProverKey {
                 q_lookup,
+                q_table,
                 table_1,
                 table_2,
                 table_3,
                 table_4,
+                table_id,
+                mode: lookup_mode,
             },
             permutation: permutation::ProverKey {
                 left_sigma,
@@ -392,7 +499,7 @@ where
                 fourth_sigma,
                 linear_evaluations,
             },
-            v_h_coset_4n,
+            v_h_coset_ext,
         }
     }
 }
@@ -420,7 +527,7 @@ mod test {
     where
         F: PrimeField,
     {
-        let domain = GeneralEvaluationDomain::new(4 * n).unwrap();
+        let domain = GeneralEvaluationDomain::new(8 * n).unwrap();
         let values: Vec<_> = (0..8 * n).map(|_| F::rand(&mut OsRng)).collect();
         Evaluations::from_vec_and_domain(values, domain)
     }
@@ -450,8 +557,10 @@ mod test {
         let q_range = rand_poly_eval(n);
         let q_logic = rand_poly_eval(n);
         let q_lookup = rand_poly_eval(n);
+        let q_table = rand_poly_eval(n);
         let q_fixed_group_add = rand_poly_eval(n);
         let q_variable_group_add = rand_poly_eval(n);
+        let q_poseidon = rand_poly_eval(n);
 
         let left_sigma = rand_poly_eval(n);
         let right_sigma = rand_poly_eval(n);
@@ -459,14 +568,16 @@ mod test {
         let fourth_sigma = rand_poly_eval(n);
 
         let linear_evaluations = rand_evaluations(n);
-        let v_h_coset_8n = rand_evaluations(n);
+        let v_h_coset_ext = rand_evaluations(n);
         let table_1 = rand_multiset(n);
         let table_2 = rand_multiset(n);
         let table_3 = rand_multiset(n);
         let table_4 = rand_multiset(n);
+        let table_id = rand_multiset(n);
 
         let prover_key = ProverKey::from_polynomials_and_evals(
             n,
+            4,
             q_m,
             q_l,
             q_r,
@@ -477,18 +588,26 @@ mod test {
             q_range,
             q_logic,
             q_lookup,
+            q_table,
             q_fixed_group_add,
             q_variable_group_add,
+            q_poseidon,
             left_sigma,
             right_sigma,
             out_sigma,
             fourth_sigma,
             linear_evaluations,
-            v_h_coset_8n,
+            v_h_coset_ext,
             table_1,
             table_2,
             table_3,
             table_4,
+            table_id,
+            LookupMode::LogUp,
+            vec![custom::ProverKey {
+                name: "custom".to_owned(),
+                q_custom: rand_poly_eval(n),
+            }],
         );
 
         let mut prover_key_bytes = vec![];
@@ -522,8 +641,10 @@ mod test {
         let q_range = PC::Commitment::default();
         let q_logic = PC::Commitment::default();
         let q_lookup = PC::Commitment::default();
+        let q_table = PC::Commitment::default();
         let q_fixed_group_add = PC::Commitment::default();
         let q_variable_group_add = PC::Commitment::default();
+        let q_poseidon = PC::Commitment::default();
 
         let left_sigma = PC::Commitment::default();
         let right_sigma = PC::Commitment::default();
@@ -534,9 +655,12 @@ mod test {
         let table_2 = PC::Commitment::default();
         let table_3 = PC::Commitment::default();
         let table_4 = PC::Commitment::default();
+        let table_id = PC::Commitment::default();
 
         let verifier_key = VerifierKey::<F, PC>::from_polynomial_commitments(
             n,
+            vec![0, 3, 4],
+            4,
             q_m,
             q_l,
             q_r,
@@ -547,8 +671,10 @@ mod test {
             q_range,
             q_logic,
             q_lookup,
+            q_table,
             q_fixed_group_add,
             q_variable_group_add,
+            q_poseidon,
             left_sigma,
             right_sigma,
             out_sigma,
@@ -557,6 +683,12 @@ mod test {
             table_2,
             table_3,
             table_4,
+            table_id,
+            LookupMode::LogUp,
+            vec![custom::VerifierKey {
+                name: "custom".to_owned(),
+                q_custom: PC::Commitment::default(),
+            }],
         );
 
         let mut verifier_key_bytes = vec![];
@@ -0,0 +1,177 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Poseidon Round Gate
+//!
+//! Constrains a full round of a width-4 Poseidon permutation with the `x^5`
+//! S-box: the state is held in the four wires of the gate, the four round
+//! constants in the `q_l`, `q_r`, `q_o` and `q_4` selectors, and the state
+//! after the round in the four wires of the next gate.
+
+use crate::proof_system::{
+    linearisation_poly::CustomEvaluations, CustomValues, GateConstraint,
+    WitnessValues,
+};
+use ark_ff::PrimeField;
+use core::marker::PhantomData;
+
+/// Width of the Poseidon permutation constrained by the [`PoseidonRound`]
+/// gate.
+pub const WIDTH: usize = 4;
+
+/// Returns the MDS matrix of the [`PoseidonRound`] gate.
+///
+/// This is the Cauchy matrix `1 / (i + j + 4)` scaled by `2520`, the least
+/// common multiple of its denominators, so that its entries are integers and
+/// the gate can be evaluated without any inversion.
+pub fn mds_matrix<F>() -> [[F; WIDTH]; WIDTH]
+where
+    F: PrimeField,
+{
+    let mut matrix = [[F::zero(); WIDTH]; WIDTH];
+    for (i, row) in matrix.iter_mut().enumerate() {
+        for (j, entry) in row.iter_mut().enumerate() {
+            *entry = F::from(2520 / (i + j + WIDTH) as u64);
+        }
+    }
+    matrix
+}
+
+/// Computes one full round on `state` with the given `round_constants`.
+pub fn round<F>(state: [F; WIDTH], round_constants: [F; WIDTH]) -> [F; WIDTH]
+where
+    F: PrimeField,
+{
+    let mut sboxed = [F::zero(); WIDTH];
+    for ((s, x), c) in sboxed.iter_mut().zip(state).zip(round_constants) {
+        *s = sbox(x + c);
+    }
+    let mut output = [F::zero(); WIDTH];
+    for (out, row) in output.iter_mut().zip(mds_matrix::<F>()) {
+        *out = row.iter().zip(sboxed).map(|(m, s)| *m * s).sum();
+    }
+    output
+}
+
+/// Computes `x^5`.
+fn sbox<F>(x: F) -> F
+where
+    F: PrimeField,
+{
+    x.square().square() * x
+}
+
+/// Values needed for the computation of the Poseidon round gate constraint.
+pub struct PoseidonVals<F>
+where
+    F: PrimeField,
+{
+    /// Left wire value in the next position
+    pub a_next_val: F,
+
+    /// Right wire value in the next position
+    pub b_next_val: F,
+
+    /// Output wire value in the next position
+    pub c_next_val: F,
+
+    /// Fourth wire value in the next position
+    pub d_next_val: F,
+
+    /// Round constant of the first element of the state
+    pub q_l_val: F,
+
+    /// Round constant of the second element of the state
+    pub q_r_val: F,
+
+    /// Round constant of the third element of the state
+    pub q_o_val: F,
+
+    /// Round constant of the fourth element of the state
+    pub q_4_val: F,
+}
+
+impl<F> CustomValues<F> for PoseidonVals<F>
+where
+    F: PrimeField,
+{
+    #[inline]
+    fn from_evaluations(custom_evals: &CustomEvaluations<F>) -> Self {
+        let a_next_val = custom_evals.get("a_next_eval");
+        let b_next_val = custom_evals.get("b_next_eval");
+        let c_next_val = custom_evals.get("c_next_eval");
+        let d_next_val = custom_evals.get("d_next_eval");
+        let q_l_val = custom_evals.get("q_l_eval");
+        let q_r_val = custom_evals.get("q_r_eval");
+        let q_o_val = custom_evals.get("q_o_eval");
+        let q_4_val = custom_evals.get("q_4_eval");
+        PoseidonVals {
+            a_next_val,
+            b_next_val,
+            c_next_val,
+            d_next_val,
+            q_l_val,
+            q_r_val,
+            q_o_val,
+            q_4_val,
+        }
+    }
+}
+
+/// Poseidon Round Gate
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PoseidonRound<F>(PhantomData<F>)
+where
+    F: PrimeField;
+
+impl<F> GateConstraint<F> for PoseidonRound<F>
+where
+    F: PrimeField,
+{
+    type CustomVals = PoseidonVals<F>;
+
+    fn degree() -> usize {
+        // The S-box of a full round raises the wires to the fifth power.
+        5
+    }
+
+    #[inline]
+    fn constraints(
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        custom_vals: Self::CustomVals,
+    ) -> F {
+        let kappa = separation_challenge.square();
+        let expected = round(
+            [
+                wit_vals.a_val,
+                wit_vals.b_val,
+                wit_vals.c_val,
+                wit_vals.d_val,
+            ],
+            [
+                custom_vals.q_l_val,
+                custom_vals.q_r_val,
+                custom_vals.q_o_val,
+                custom_vals.q_4_val,
+            ],
+        );
+        let next = [
+            custom_vals.a_next_val,
+            custom_vals.b_next_val,
+            custom_vals.c_next_val,
+            custom_vals.d_next_val,
+        ];
+        let mut kappa_pow = F::one();
+        let mut sum = F::zero();
+        for (n, e) in next.iter().zip(expected) {
+            sum += (*n - e) * kappa_pow;
+            kappa_pow *= kappa;
+        }
+        sum * separation_challenge
+    }
+}
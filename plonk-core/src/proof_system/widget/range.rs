@@ -7,68 +7,72 @@
 //! Range Gate
 
 use crate::proof_system::{
-    linearisation_poly::CustomEvaluations, CustomValues, GateConstraint,
-    WitnessValues,
+    expression::{Expression, ExpressionVals, Value},
+    GateConstraint, WitnessValues,
 };
 use ark_ff::PrimeField;
 use core::marker::PhantomData;
 
-/// Values needed for the computation of the range gate constraint.
-pub struct RangeVals<F>
+/// Range Gate
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Range<F>(PhantomData<F>)
 where
-    F: PrimeField,
-{
-    /// Fourth wire value in the next position
-    pub d_next_val: F,
-}
+    F: PrimeField;
 
-impl<F> CustomValues<F> for RangeVals<F>
+impl<F> Range<F>
 where
     F: PrimeField,
 {
-    fn from_evaluations(custom_vals: &CustomEvaluations<F>) -> Self {
-        let d_next_val = custom_vals.get("d_next_eval");
-        RangeVals { d_next_val }
+    /// Returns the identity of the range gate, checking that the wires of the
+    /// gate and the fourth wire of the next gate each add a base-4 digit to
+    /// the accumulated value of the previous wire.
+    pub fn expression() -> Expression<F> {
+        let four = Expression::constant(F::from(4u64));
+        let [a, b, c, d, d_next] =
+            [Value::A, Value::B, Value::C, Value::D, Value::DNext]
+                .map(Expression::from);
+        let separation_challenge = Expression::challenge();
+        let kappa = separation_challenge.square();
+        let b_1 = delta(&c - &four * &d);
+        let b_2 = delta(&b - &four * &c) * &kappa;
+        let b_3 = delta(&a - &four * &b) * kappa.square();
+        let b_4 = delta(d_next - four * a) * kappa.pow(3);
+        (b_1 + b_2 + b_3 + b_4) * separation_challenge
     }
 }
-/// Range Gate
-#[derive(derivative::Derivative)]
-#[derivative(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
-pub struct Range<F>(PhantomData<F>)
-where
-    F: PrimeField;
 
 impl<F> GateConstraint<F> for Range<F>
 where
     F: PrimeField,
 {
-    type CustomVals = RangeVals<F>;
+    type CustomVals = ExpressionVals<F>;
+
+    fn degree() -> usize {
+        Self::expression().degree()
+    }
+
     #[inline]
     fn constraints(
         separation_challenge: F,
         wit_vals: WitnessValues<F>,
         custom_vals: Self::CustomVals,
     ) -> F {
-        let four = F::from(4u64);
-        let kappa = separation_challenge.square();
-        let kappa_sq = kappa.square();
-        let kappa_cu = kappa_sq * kappa;
-        let b_1 = delta(wit_vals.c_val - four * wit_vals.d_val);
-        let b_2 = delta(wit_vals.b_val - four * wit_vals.c_val) * kappa;
-        let b_3 = delta(wit_vals.a_val - four * wit_vals.b_val) * kappa_sq;
-        let b_4 =
-            delta(custom_vals.d_next_val - four * wit_vals.a_val) * kappa_cu;
-        (b_1 + b_2 + b_3 + b_4) * separation_challenge
+        Self::expression().evaluate(
+            separation_challenge,
+            wit_vals,
+            &custom_vals,
+        )
     }
 }
 
 /// Computes `f(f-1)(f-2)(f-3)`.
-fn delta<F>(f: F) -> F
+fn delta<F>(f: Expression<F>) -> Expression<F>
 where
     F: PrimeField,
 {
-    let f_1 = f - F::one();
-    let f_2 = f - F::from(2_u64);
-    let f_3 = f - F::from(3_u64);
+    let f_1 = &f - Expression::constant(F::one());
+    let f_2 = &f - Expression::constant(F::from(2_u64));
+    let f_3 = &f - Expression::constant(F::from(3_u64));
     f * f_1 * f_2 * f_3
 }
@@ -4,47 +4,176 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-//! This is an extension over the [Merlin Transcript](Transcript) which adds a
-//! few extra functionalities.
+//! Fiat-Shamir transcripts used by the
+//! [`Prover`](crate::proof_system::Prover) and the
+//! [`Verifier`](crate::proof_system::Verifier) to derive the challenges of the
+//! protocol.
+//!
+//! The default transcript is the [Merlin Transcript](Transcript). Any other
+//! hash, such as an algebraic sponge which is cheaper to recompute inside a
+//! circuit, can be plugged in by implementing [`TranscriptProtocol`].
 
 use ark_ff::PrimeField;
 use ark_serialize::CanonicalSerialize;
-use merlin::Transcript;
+pub use merlin::Transcript;
+
+/// Number of bytes drawn from the [`Transcript`] for every challenge.
+///
+/// The challenge is the reduction of these 512 bits modulo the order of the
+/// field, which is within a statistical distance of `2^(b - 512)` from the
+/// uniform distribution for a field of `b` bits, that is less than `2^-128`
+/// for every field of at most 384 bits.
+const CHALLENGE_BYTES: usize = 64;
+
+/// Transcript of the messages exchanged between the prover and the verifier,
+/// from which the challenges over the field `F` are derived.
+pub trait TranscriptProtocol<F>: Clone
+where
+    F: PrimeField,
+{
+    /// Creates a new transcript for the protocol identified by `label`.
+    fn new(label: &'static [u8]) -> Self;
+
+    /// Append the raw `message` with the given `label`.
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]);
 
-/// Transcript adds an abstraction over the Merlin transcript
-/// For convenience
-pub(crate) trait TranscriptProtocol {
     /// Append an `item` with the given `label`.
     fn append(&mut self, label: &'static [u8], item: &impl CanonicalSerialize);
 
+    /// Append a `scalar` with the given `label`.
+    fn append_scalar(&mut self, label: &'static [u8], scalar: &F) {
+        self.append(label, scalar)
+    }
+
     /// Compute a `label`ed challenge variable.
-    fn challenge_scalar<F: PrimeField>(&mut self, label: &'static [u8]) -> F;
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> F;
 
     /// Append domain separator for the circuit size.
     fn circuit_domain_sep(&mut self, n: u64);
 }
 
-impl TranscriptProtocol for Transcript {
+impl<F> TranscriptProtocol<F> for Transcript
+where
+    F: PrimeField,
+{
+    fn new(label: &'static [u8]) -> Self {
+        Transcript::new(label)
+    }
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        Transcript::append_message(self, label, message)
+    }
+
     fn append(&mut self, label: &'static [u8], item: &impl CanonicalSerialize) {
         let mut bytes = Vec::new();
         item.serialize(&mut bytes).unwrap();
-        self.append_message(label, &bytes)
+        Transcript::append_message(self, label, &bytes)
     }
 
-    fn challenge_scalar<F>(&mut self, label: &'static [u8]) -> F
-    where
-        F: PrimeField,
-    {
-        // XXX: review this: assure from_random_bytes returnes a valid Field
-        // element
-        let size = F::size_in_bits() / 8;
-        let mut buf = vec![0u8; size];
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> F {
+        let mut buf = [0u8; CHALLENGE_BYTES];
         self.challenge_bytes(label, &mut buf);
-        F::from_random_bytes(&buf).unwrap()
+        F::from_le_bytes_mod_order(&buf)
     }
 
     fn circuit_domain_sep(&mut self, n: u64) {
-        self.append_message(b"dom-sep", b"circuit_size");
+        Transcript::append_message(self, b"dom-sep", b"circuit_size");
         self.append_u64(b"n", n);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_ff::{BigInteger, FpParameters};
+
+    /// Draws `N` challenges over `F`, checking that they are evenly split
+    /// between both halves of the field and that their four lowest bits are
+    /// uniform, with a chi-squared test.
+    fn test_challenge_distribution<F>()
+    where
+        F: PrimeField,
+    {
+        const N: usize = 4096;
+        let half = F::Params::MODULUS_MINUS_ONE_DIV_TWO;
+        let mut transcript = Transcript::new(b"challenge distribution");
+        let mut lower_half = 0;
+        let mut buckets = [0usize; 16];
+        for _ in 0..N {
+            let challenge: F = transcript.challenge_scalar(b"challenge");
+            let repr = challenge.into_repr();
+            if repr <= half {
+                lower_half += 1;
+            }
+            buckets[(repr.as_ref()[0] & 0xf) as usize] += 1;
+        }
+
+        // The number of challenges in the lower half is within four standard
+        // deviations, `sqrt(N) / 2`, of `N / 2`.
+        assert!((lower_half as i64 - N as i64 / 2).abs() <= 128);
+
+        // 37.7 is the 0.999 quantile of the chi-squared distribution with 15
+        // degrees of freedom.
+        let expected = (N / 16) as f64;
+        let chi_squared = buckets
+            .iter()
+            .map(|count| (*count as f64 - expected).powi(2) / expected)
+            .sum::<f64>();
+        assert!(chi_squared < 37.7, "chi-squared: {}", chi_squared);
+    }
+
+    /// The 64 bytes drawn from the [`Transcript`] labelled `plonk` after
+    /// appending the message `message`, whose integer exceeds the orders of
+    /// the scalar fields by more than 200 bits, so that the challenges depend
+    /// on every byte.
+    const VECTOR_BYTES: &str = "212371e457eacbd79e47acebeb500aa826cfd32e7b2727c0922104aa928e4c1d\
+                                8622a465e0cc25271b86f7fa1ee346f8b3d502bc5699ce9398490afcd3715a1d";
+
+    /// Parses a hexadecimal string.
+    fn hex_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Checks the challenge drawn from the bytes [`VECTOR_BYTES`], read as a
+    /// little-endian integer and reduced modulo the order of `F`, against
+    /// `expected` in big-endian.
+    fn test_challenge_vector<F>(expected: &str)
+    where
+        F: PrimeField,
+    {
+        let mut transcript = Transcript::new(b"plonk");
+        transcript.append_message(b"m", b"message");
+        let mut bytes = [0u8; CHALLENGE_BYTES];
+        transcript.clone().challenge_bytes(b"c", &mut bytes);
+        assert_eq!(bytes.to_vec(), hex_bytes(VECTOR_BYTES));
+
+        let challenge: F = transcript.challenge_scalar(b"c");
+        let mut expected = hex_bytes(expected);
+        expected.reverse();
+        assert_eq!(challenge.into_repr().to_bytes_le(), expected);
+    }
+
+    #[test]
+    fn test_challenges_bls12_381() {
+        test_challenge_distribution::<ark_bls12_381::Fr>();
+        test_challenge_vector::<ark_bls12_381::Fr>(
+            "5a22aaa0dffa3592972a8932ac5e3213e91e6475e4df11ce8929f2f4a28317eb",
+        );
+    }
+
+    #[test]
+    fn test_challenges_bls12_377() {
+        test_challenge_distribution::<ark_bls12_377::Fr>();
+        test_challenge_vector::<ark_bls12_377::Fr>(
+            "0f4681c0ac01a9efe8c1ee4a38630dbaf2b084830aedfb89e22ba906ff2a9b51",
+        );
+    }
+
+    #[test]
+    fn test_challenges_vesta() {
+        test_challenge_distribution::<ark_vesta::Fr>();
+    }
+}
@@ -170,7 +170,7 @@ where
         .fold(kth_val, |acc, val| acc * *challenge + val.clone())
 }
 
-/// Macro to quickly label polynomials
+/// Macro to quickly label polynomials, with an optional hiding bound
 #[macro_export]
 macro_rules! label_polynomial {
     ($poly:expr) => {
@@ -181,6 +181,14 @@ macro_rules! label_polynomial {
             None,
         )
     };
+    ($poly:expr, $hiding_bound:expr) => {
+        ark_poly_commit::LabeledPolynomial::new(
+            stringify!($poly).to_owned(),
+            $poly.clone(),
+            None,
+            $hiding_bound,
+        )
+    };
 }
 
 /// Macro to quickly label polynomial commitments
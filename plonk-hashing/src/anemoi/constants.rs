@@ -0,0 +1,348 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Anemoi Parameters
+
+use crate::{
+    anemoi::AnemoiError,
+    arithmetic::{binomial, inverse_exponent},
+    poseidon::smallest_alpha,
+};
+use ark_ff::PrimeField;
+use ark_std::{vec, vec::Vec};
+use num_bigint::BigUint;
+
+/// First hundred decimals of `pi`, from which the round constants are
+/// derived.
+const PI_0: &str = "1415926535897932384626433832795028841971693993751058209749445923078164062862089986280348253421170679";
+
+/// Next hundred decimals of `pi`.
+const PI_1: &str = "8214808651328230664709384460955058223172535940812848111745028410270193852110555964462294895493038196";
+
+/// Parameters of an Anemoi permutation over `2 * columns` field elements.
+///
+/// The Flystel S-box is built from the quadratic maps `g * x^2` and
+/// `g * x^2 + g^-1` and from the inverse power map `x^(1/alpha)`, where `g`
+/// is the generator of the parameters.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug, PartialEq, Eq)]
+pub struct AnemoiConstants<F>
+where
+    F: PrimeField,
+{
+    /// Number of elements in each of the `x` and `y` columns of the state.
+    pub columns: usize,
+    /// S-box exponent.
+    pub alpha: u64,
+    /// Inverse of `alpha` modulo `p - 1`, as little-endian limbs.
+    pub alpha_inv: Vec<u64>,
+    /// Generator `g` used in the quadratic maps of the Flystel.
+    pub generator: F,
+    /// Inverse of the generator.
+    pub generator_inv: F,
+    /// Number of rounds.
+    pub rounds: usize,
+    /// MDS matrix applied to each column, stored row by row.
+    pub mds_matrix: Vec<Vec<F>>,
+    /// Round constants of the `x` column, `columns` per round.
+    pub c_constants: Vec<F>,
+    /// Round constants of the `y` column, `columns` per round.
+    pub d_constants: Vec<F>,
+}
+
+impl<F> AnemoiConstants<F>
+where
+    F: PrimeField,
+{
+    /// Builds a new set of Anemoi parameters, checking that they are
+    /// consistent with each other.
+    pub fn new(
+        columns: usize,
+        alpha: u64,
+        generator: F,
+        rounds: usize,
+        mds_matrix: Vec<Vec<F>>,
+        c_constants: Vec<F>,
+        d_constants: Vec<F>,
+    ) -> Result<Self, AnemoiError> {
+        if columns == 0 {
+            return Err(AnemoiError::InvalidColumns);
+        }
+        let alpha_inv = match inverse_exponent::<F>(alpha) {
+            Some(alpha_inv) if alpha >= 3 => alpha_inv,
+            _ => return Err(AnemoiError::InvalidAlpha),
+        };
+        let generator_inv =
+            generator.inverse().ok_or(AnemoiError::InvalidGenerator)?;
+        if rounds == 0 {
+            return Err(AnemoiError::InvalidRounds);
+        }
+        if mds_matrix.len() != columns
+            || mds_matrix.iter().any(|row| row.len() != columns)
+        {
+            return Err(AnemoiError::InvalidMdsMatrix);
+        }
+        if c_constants.len() != columns * rounds
+            || d_constants.len() != columns * rounds
+        {
+            return Err(AnemoiError::InvalidRoundConstants);
+        }
+        Ok(Self {
+            columns,
+            alpha,
+            alpha_inv,
+            generator,
+            generator_inv,
+            rounds,
+            mds_matrix,
+            c_constants,
+            d_constants,
+        })
+    }
+
+    /// Generates the parameters of a permutation of `2 * columns` elements
+    /// over `F` with 128 bits of security, the same way as the reference
+    /// implementation of the specification.
+    ///
+    /// The S-box exponent is the smallest `alpha` for which `x^alpha` is a
+    /// permutation of `F`, the generator is the multiplicative generator of
+    /// `F` and the round constants are derived from the decimals of `pi`.
+    /// Only one or two columns are supported.
+    pub fn generate(columns: usize) -> Result<Self, AnemoiError> {
+        const SECURITY_LEVEL: usize = 128;
+        let g = F::multiplicative_generator();
+        let mds_matrix = match columns {
+            1 => vec![vec![F::one()]],
+            2 => vec![vec![F::one(), g], vec![g, F::one() + g.square()]],
+            _ => return Err(AnemoiError::InvalidColumns),
+        };
+        let alpha = smallest_alpha::<F>();
+        let rounds = rounds(columns, alpha, SECURITY_LEVEL)
+            .ok_or(AnemoiError::InvalidAlpha)?;
+        let pi_0 = PI_0.parse::<F>().ok().expect("valid decimal string");
+        let pi_1 = PI_1.parse::<F>().ok().expect("valid decimal string");
+        let g_inv = g.inverse().expect("generator is non-zero");
+        let mut c_constants = Vec::with_capacity(columns * rounds);
+        let mut d_constants = Vec::with_capacity(columns * rounds);
+        for r in 0..rounds {
+            let pi_0_r = pi_0.pow([r as u64]);
+            for i in 0..columns {
+                let pi_1_i = pi_1.pow([i as u64]);
+                let pow_alpha = (pi_0_r + pi_1_i).pow([alpha]);
+                c_constants.push(g * pi_0_r.square() + pow_alpha);
+                d_constants.push(g * pi_1_i.square() + pow_alpha + g_inv);
+            }
+        }
+        Self::new(
+            columns,
+            alpha,
+            g,
+            rounds,
+            mds_matrix,
+            c_constants,
+            d_constants,
+        )
+    }
+
+    /// Round constants added to the `x` and `y` columns at the start of
+    /// `round`.
+    pub fn round_constants(&self, round: usize) -> (&[F], &[F]) {
+        let range = round * self.columns..(round + 1) * self.columns;
+        (&self.c_constants[range.clone()], &self.d_constants[range])
+    }
+
+    /// Applies the linear layer to `state`: the MDS matrix is applied to the
+    /// `x` column and to the `y` column rotated by one position, followed by
+    /// a pseudo-Hadamard transform mixing both columns.
+    pub fn linear_layer(&self, state: &mut [F]) {
+        let columns = self.columns;
+        let (x, y) = state.split_at_mut(columns);
+        let mul = |v: &[F], offset: usize| {
+            self.mds_matrix
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .enumerate()
+                        .map(|(j, m)| *m * v[(j + offset) % columns])
+                        .sum::<F>()
+                })
+                .collect::<Vec<F>>()
+        };
+        let new_x = mul(x, 0);
+        let new_y = mul(y, 1);
+        for i in 0..columns {
+            y[i] = new_y[i] + new_x[i];
+            x[i] = new_x[i] + y[i];
+        }
+    }
+}
+
+/// Returns the number of rounds of a permutation of `2 * columns` elements
+/// with the S-box exponent `alpha`, reaching `security_level` bits of
+/// security against algebraic attacks, with a security margin, or `None` if
+/// `alpha` is not supported.
+fn rounds(columns: usize, alpha: u64, security_level: usize) -> Option<usize> {
+    let kappa = match alpha {
+        3 => 1,
+        5 => 2,
+        7 => 4,
+        9 => 7,
+        11 => 9,
+        _ => return None,
+    };
+    let l = columns as u64;
+    let target = BigUint::from(1u64) << security_level;
+    let secure_rounds = (1..)
+        .find(|r| {
+            let binom = binomial(4 * l * r + kappa, 2 * l * r);
+            &binom * &binom >= target
+        })
+        .expect("the complexity grows with the number of rounds");
+    let rounds = secure_rounds as usize + 2 + (columns + 1).min(5);
+    Some(rounds.max(8))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::anemoi::{AnemoiSpec, NativeSpec};
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_parameter_checks() {
+        let g = Fr::from(7u64);
+        let mds = vec![vec![Fr::from(1u64)]];
+        let rc = vec![Fr::from(2u64); 4];
+        assert!(AnemoiConstants::new(
+            1,
+            5,
+            g,
+            4,
+            mds.clone(),
+            rc.clone(),
+            rc.clone()
+        )
+        .is_ok());
+        assert_eq!(
+            AnemoiConstants::new(
+                0,
+                5,
+                g,
+                4,
+                mds.clone(),
+                rc.clone(),
+                rc.clone()
+            ),
+            Err(AnemoiError::InvalidColumns)
+        );
+        assert_eq!(
+            AnemoiConstants::new(
+                1,
+                3,
+                g,
+                4,
+                mds.clone(),
+                rc.clone(),
+                rc.clone()
+            ),
+            Err(AnemoiError::InvalidAlpha)
+        );
+        assert_eq!(
+            AnemoiConstants::new(
+                1,
+                5,
+                Fr::from(0u64),
+                4,
+                mds.clone(),
+                rc.clone(),
+                rc.clone()
+            ),
+            Err(AnemoiError::InvalidGenerator)
+        );
+        assert_eq!(
+            AnemoiConstants::new(1, 5, g, 0, mds.clone(), vec![], vec![]),
+            Err(AnemoiError::InvalidRounds)
+        );
+        assert_eq!(
+            AnemoiConstants::new(1, 5, g, 4, vec![], rc.clone(), rc.clone()),
+            Err(AnemoiError::InvalidMdsMatrix)
+        );
+        assert_eq!(
+            AnemoiConstants::new(1, 5, g, 4, mds, rc[..3].to_vec(), rc),
+            Err(AnemoiError::InvalidRoundConstants)
+        );
+        assert_eq!(
+            AnemoiConstants::<Fr>::generate(3),
+            Err(AnemoiError::InvalidColumns)
+        );
+    }
+
+    /// Parses a big-endian hexadecimal string.
+    fn from_hex(hex: &str) -> Fr {
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+        Fr::from_be_bytes_mod_order(&bytes)
+    }
+
+    #[test]
+    fn test_reference_parameters() {
+        // Known-answer vectors computed with a transcription of the
+        // reference `anemoi.sage` algorithms of the specification.
+        let constants = AnemoiConstants::<Fr>::generate(1).unwrap();
+        assert_eq!((constants.alpha, constants.rounds), (5, 21));
+        assert_eq!(constants.generator, Fr::from(7u64));
+        assert_eq!(
+            constants.c_constants[1],
+            from_hex(
+                "5b7255448a8ae544b0b8709bfbdb374a309e1e91747aedfd7408afc1cfd01efd"
+            )
+        );
+        assert_eq!(
+            constants.d_constants[0],
+            from_hex(
+                "211f5460e751918257c7624b7077624aaa362edc49241a48db6db6db2492494c"
+            )
+        );
+        let mut state = vec![Fr::from(0u64), Fr::from(1u64)];
+        NativeSpec::permute(&mut (), &constants, &mut state);
+        assert_eq!(
+            state,
+            [
+                "019ea09bf18332c14411e27d2a654837a188f8b718d13faa824730fa20350684",
+                "68ae6629a63203e1fc2c8ecbfc72eb940a63a0f7ed9bf9d64bec32dec5217cc0",
+            ]
+            .iter()
+            .map(|h| from_hex(h))
+            .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            NativeSpec::compress(
+                &mut (),
+                &constants,
+                &[Fr::from(0u64), Fr::from(1u64)]
+            ),
+            [from_hex(
+                "6a4d06c597b536a3403e714926d833cbabec99af066d3980ce3363d8e5568345"
+            )]
+        );
+
+        let constants = AnemoiConstants::<Fr>::generate(2).unwrap();
+        assert_eq!(constants.rounds, 14);
+        let inputs = (0..4u64).map(Fr::from).collect::<Vec<_>>();
+        assert_eq!(
+            NativeSpec::compress(&mut (), &constants, &inputs),
+            [
+                "1aefd15c29821229933933ca4a637634b9017d9fd4cf68a195d58b1f81cb79e2",
+                "43abf1c41bba3169970b9d8617cf56f1ba3846e91fa0c370055faecc8d8f1af9",
+            ]
+            .iter()
+            .map(|h| from_hex(h))
+            .collect::<Vec<_>>()
+        );
+    }
+}
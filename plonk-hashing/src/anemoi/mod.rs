@@ -0,0 +1,127 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Anemoi Hash Function
+//!
+//! Native and in-circuit implementations of the Anemoi permutation and of the
+//! Jive compression mode built on top of it (see
+//! <https://eprint.iacr.org/2022/840>). As for Poseidon, both
+//! implementations share the same [`AnemoiConstants`] and the same
+//! compression logic through the [`AnemoiSpec`] trait.
+//!
+//! The state is made of `2 * columns` elements, the `x` column followed by
+//! the `y` column. Every round adds round constants to both columns, applies
+//! the linear layer and the Flystel S-box, whose open form involves the
+//! inverse power map `x^(1/alpha)`. The gadget never computes the latter: it
+//! witnesses the root and checks that raising it to the power `alpha` gives
+//! back the input.
+
+mod constants;
+mod native;
+mod plonk;
+
+pub use constants::AnemoiConstants;
+pub use native::NativeSpec;
+pub use plonk::PlonkSpec;
+
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use core::fmt;
+
+/// Arithmetic backend over which the Anemoi permutation is evaluated.
+///
+/// `COM` is the context the computation happens in, `()` for the native
+/// implementation and a
+/// [`StandardComposer`](plonk_core::constraint_system::StandardComposer) for
+/// the gadget.
+pub trait AnemoiSpec<COM, F>
+where
+    F: PrimeField,
+{
+    /// Representation of a state element.
+    type Field: Clone;
+
+    /// Returns `x + y`.
+    fn add(c: &mut COM, x: &Self::Field, y: &Self::Field) -> Self::Field;
+
+    /// Applies the Anemoi permutation defined by `constants` to `state`.
+    fn permute(
+        c: &mut COM,
+        constants: &AnemoiConstants<F>,
+        state: &mut [Self::Field],
+    );
+
+    /// Compresses the `2 * columns` elements of `inputs` into `columns`
+    /// elements with the Jive mode.
+    ///
+    /// The `i`-th output is the sum of the `i`-th element of both columns of
+    /// the input and of its image by the permutation.
+    fn compress(
+        c: &mut COM,
+        constants: &AnemoiConstants<F>,
+        inputs: &[Self::Field],
+    ) -> Vec<Self::Field> {
+        let columns = constants.columns;
+        assert_eq!(inputs.len(), 2 * columns);
+        let mut state = inputs.to_vec();
+        Self::permute(c, constants, &mut state);
+        (0..columns)
+            .map(|i| {
+                let sum = Self::add(c, &inputs[i], &inputs[columns + i]);
+                let sum = Self::add(c, &sum, &state[i]);
+                Self::add(c, &sum, &state[columns + i])
+            })
+            .collect()
+    }
+}
+
+/// Anemoi Errors
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AnemoiError {
+    /// The number of columns must be non-zero, and parameters can only be
+    /// generated for one or two columns.
+    InvalidColumns,
+    /// The S-box exponent must be at least three and `x^alpha` must be a
+    /// permutation of the field.
+    InvalidAlpha,
+    /// The generator of the Flystel must be non-zero.
+    InvalidGenerator,
+    /// The number of rounds must be non-zero.
+    InvalidRounds,
+    /// The MDS matrix is not a `columns x columns` matrix.
+    InvalidMdsMatrix,
+    /// The number of round constants of each column does not match
+    /// `columns * rounds`.
+    InvalidRoundConstants,
+}
+
+impl fmt::Display for AnemoiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidColumns => {
+                write!(f, "Unsupported number of Anemoi columns")
+            }
+            Self::InvalidAlpha => {
+                write!(f, "S-box exponent must be >= 3 and invertible")
+            }
+            Self::InvalidGenerator => {
+                write!(f, "Flystel generator must be non-zero")
+            }
+            Self::InvalidRounds => {
+                write!(f, "Number of rounds must be non-zero")
+            }
+            Self::InvalidMdsMatrix => {
+                write!(f, "MDS matrix must be square, of size columns")
+            }
+            Self::InvalidRoundConstants => {
+                write!(f, "Wrong number of round constants")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AnemoiError {}
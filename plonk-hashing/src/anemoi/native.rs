@@ -0,0 +1,48 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Native Anemoi Permutation
+
+use crate::anemoi::{AnemoiConstants, AnemoiSpec};
+use ark_ff::PrimeField;
+
+/// Out-of-circuit Anemoi, evaluated directly over the field `F`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeSpec;
+
+impl<F> AnemoiSpec<(), F> for NativeSpec
+where
+    F: PrimeField,
+{
+    type Field = F;
+
+    fn add(_: &mut (), x: &F, y: &F) -> F {
+        *x + y
+    }
+
+    fn permute(_: &mut (), constants: &AnemoiConstants<F>, state: &mut [F]) {
+        let columns = constants.columns;
+        assert_eq!(state.len(), 2 * columns);
+        let g = constants.generator;
+        for round in 0..constants.rounds {
+            let (c, d) = constants.round_constants(round);
+            for (s, rc) in state.iter_mut().zip(c.iter().chain(d)) {
+                *s += rc;
+            }
+            constants.linear_layer(state);
+            // Open Flystel on every (x, y) pair.
+            for i in 0..columns {
+                let (mut x, mut y) = (state[i], state[columns + i]);
+                x -= g * y.square();
+                y -= x.pow(&constants.alpha_inv);
+                x += g * y.square() + constants.generator_inv;
+                state[i] = x;
+                state[columns + i] = y;
+            }
+        }
+        constants.linear_layer(state);
+    }
+}
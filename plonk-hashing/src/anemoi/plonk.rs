@@ -0,0 +1,203 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Anemoi Permutation Gadget
+
+use crate::{
+    anemoi::{AnemoiConstants, AnemoiSpec},
+    arithmetic::{linear_combination, root},
+};
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use ark_std::{vec, vec::Vec};
+use plonk_core::constraint_system::{StandardComposer, Variable};
+
+/// In-circuit Anemoi, evaluated with the arithmetic gates of a
+/// [`StandardComposer`].
+///
+/// The round constants and the linear layer are merged into a single affine
+/// map, and every Flystel takes six gates for `alpha = 5`: the root
+/// `x^(1/alpha)` is witnessed and checked with a single `poly_gate` once
+/// raised to the power `alpha - 1`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlonkSpec;
+
+impl<F, P> AnemoiSpec<StandardComposer<F, P>, F> for PlonkSpec
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    type Field = Variable;
+
+    fn add(
+        c: &mut StandardComposer<F, P>,
+        x: &Variable,
+        y: &Variable,
+    ) -> Variable {
+        c.arithmetic_gate(|gate| {
+            gate.witness(*x, *y, None).add(F::one(), F::one())
+        })
+    }
+
+    fn permute(
+        c: &mut StandardComposer<F, P>,
+        constants: &AnemoiConstants<F>,
+        state: &mut [Variable],
+    ) {
+        let columns = constants.columns;
+        let width = 2 * columns;
+        assert_eq!(state.len(), width);
+        // The linear layer is linear, so its matrix is made of the images of
+        // the unit vectors.
+        let mut matrix = vec![vec![F::zero(); width]; width];
+        for k in 0..width {
+            let mut unit = vec![F::zero(); width];
+            unit[k] = F::one();
+            constants.linear_layer(&mut unit);
+            for (row, value) in matrix.iter_mut().zip(unit) {
+                row[k] = value;
+            }
+        }
+        let affine = |c: &mut StandardComposer<F, P>,
+                      state: &mut [Variable],
+                      offsets: &[F]| {
+            let mut constant = offsets.to_vec();
+            constants.linear_layer(&mut constant);
+            let mixed = matrix
+                .iter()
+                .zip(constant)
+                .map(|(row, constant)| {
+                    let terms = row
+                        .iter()
+                        .copied()
+                        .zip(state.iter().copied())
+                        .filter(|(m, _)| !m.is_zero())
+                        .collect::<Vec<_>>();
+                    linear_combination(c, &terms, constant)
+                })
+                .collect::<Vec<_>>();
+            state.copy_from_slice(&mixed);
+        };
+        let g = constants.generator;
+        for round in 0..constants.rounds {
+            let (rc_c, rc_d) = constants.round_constants(round);
+            let offsets = rc_c.iter().chain(rc_d).copied().collect::<Vec<_>>();
+            affine(c, state, &offsets);
+            for i in 0..columns {
+                let (x, y) = (state[i], state[columns + i]);
+                // w = x - g * y^2
+                let w = c.arithmetic_gate(|gate| {
+                    gate.witness(y, y, None).mul(-g).fan_in_3(F::one(), x)
+                });
+                let t = root(c, w, constants.alpha, &constants.alpha_inv);
+                let v = c.arithmetic_gate(|gate| {
+                    gate.witness(y, t, None).add(F::one(), -F::one())
+                });
+                // u = w + g * v^2 + g^-1
+                let u = c.arithmetic_gate(|gate| {
+                    gate.witness(v, v, None)
+                        .mul(g)
+                        .fan_in_3(F::one(), w)
+                        .constant(constants.generator_inv)
+                });
+                state[i] = u;
+                state[columns + i] = v;
+            }
+        }
+        affine(c, state, &vec![F::zero(); width]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{anemoi::NativeSpec, batch_test, test::gadget_tester};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_std::test_rng;
+    use plonk_core::commitment::HomomorphicCommitment;
+
+    fn test_permutation_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        for columns in [1, 2] {
+            let constants = AnemoiConstants::<F>::generate(columns).unwrap();
+            let input = (0..2 * columns)
+                .map(|_| F::rand(&mut rng))
+                .collect::<Vec<_>>();
+            let mut expected = input.clone();
+            NativeSpec::permute(&mut (), &constants, &mut expected);
+
+            let res = gadget_tester::<F, P, PC>(
+                |composer: &mut StandardComposer<F, P>| {
+                    let mut state = input
+                        .iter()
+                        .map(|x| composer.add_input(*x))
+                        .collect::<Vec<_>>();
+                    PlonkSpec::permute(composer, &constants, &mut state);
+                    for (var, value) in state.iter().zip(expected.iter()) {
+                        assert_eq!(composer.value_of_var(*var), *value);
+                        composer.constrain_to_constant(*var, *value, None);
+                    }
+                },
+                1000,
+            );
+            assert!(res.is_ok(), "{:?}", res.err().unwrap());
+        }
+    }
+
+    fn test_compress_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        let constants = AnemoiConstants::<F>::generate(1).unwrap();
+        let input = (0..2).map(|_| F::rand(&mut rng)).collect::<Vec<_>>();
+        let expected = NativeSpec::compress(&mut (), &constants, &input);
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let input = input
+                    .iter()
+                    .map(|x| composer.add_input(*x))
+                    .collect::<Vec<_>>();
+                let output = PlonkSpec::compress(composer, &constants, &input);
+                for (var, value) in output.iter().zip(expected.iter()) {
+                    assert_eq!(composer.value_of_var(*var), *value);
+                    composer.constrain_to_constant(*var, *value, None);
+                }
+            },
+            500,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_permutation_gadget,
+            test_compress_gadget
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_permutation_gadget,
+            test_compress_gadget
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,480 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Arithmetic Helpers
+//!
+//! Power maps, linear combinations and bit decompositions shared by the
+//! gadgets of this crate, both natively and with the arithmetic gates of a
+//! [`StandardComposer`].
+
+use ark_ec::TEModelParameters;
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_std::{vec, vec::Vec};
+use num_bigint::BigUint;
+use plonk_core::constraint_system::{StandardComposer, Variable};
+
+/// Returns the inverse of `alpha` modulo `p - 1`, where `p` is the modulus
+/// of `F`, so that `x^alpha_inv` is the inverse of the power map `x^alpha`,
+/// or `None` if `x^alpha` is not a permutation of `F`.
+pub(crate) fn inverse_exponent<F>(alpha: u64) -> Option<Vec<u64>>
+where
+    F: PrimeField,
+{
+    let p_minus_one: BigUint = (-F::one()).into();
+    // alpha * alpha_inv = k * (p - 1) + 1 for some k < alpha.
+    (0..alpha)
+        .map(|k| &p_minus_one * k + 1u64)
+        .find(|n| (n % alpha) == BigUint::from(0u64))
+        .map(|n| (n / alpha).to_u64_digits())
+}
+
+/// Returns the binomial coefficient `n choose k`.
+pub(crate) fn binomial(n: u64, k: u64) -> BigUint {
+    let k = k.min(n - k);
+    (0..k).fold(BigUint::from(1u64), |acc, i| acc * (n - i) / (i + 1))
+}
+
+/// Computes `(x + constant)^alpha` with one gate per squaring and per
+/// multiplication of a square-and-multiply chain.
+pub(crate) fn pow<F, P>(
+    c: &mut StandardComposer<F, P>,
+    x: Variable,
+    constant: F,
+    alpha: u64,
+) -> Variable
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let num_bits = 64 - alpha.leading_zeros();
+    // `None` stands for `x + constant`, which is never computed on its own.
+    let mut acc: Option<Variable> = None;
+    for i in (0..num_bits - 1).rev() {
+        let square = match acc {
+            // x^2 + 2 * constant * x + constant^2
+            None => c.arithmetic_gate(|gate| {
+                gate.witness(x, x, None)
+                    .mul(F::one())
+                    .add(constant.double(), F::zero())
+                    .constant(constant.square())
+            }),
+            Some(a) => {
+                c.arithmetic_gate(|gate| gate.witness(a, a, None).mul(F::one()))
+            }
+        };
+        acc = Some(if (alpha >> i) & 1 == 1 {
+            // square * x + constant * square
+            c.arithmetic_gate(|gate| {
+                gate.witness(square, x, None)
+                    .mul(F::one())
+                    .add(constant, F::zero())
+            })
+        } else {
+            square
+        });
+    }
+    acc.expect("alpha is at least 2")
+}
+
+/// Returns the `alpha`-th root `y = x^alpha_inv` of `x`, where `alpha_inv`
+/// is given by [`inverse_exponent`].
+///
+/// The root is witnessed rather than computed in-circuit, and constrained
+/// with [`assert_root`], so that the inverse power map costs as many gates
+/// as the power map itself.
+pub(crate) fn root<F, P>(
+    c: &mut StandardComposer<F, P>,
+    x: Variable,
+    alpha: u64,
+    alpha_inv: &[u64],
+) -> Variable
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let y = c.add_input(c.value_of_var(x).pow(alpha_inv));
+    assert_root(c, x, y, alpha);
+    y
+}
+
+/// Constrains `y^alpha = x`, computing `y^(alpha - 1)` with [`pow`] and
+/// checking `y^(alpha - 1) * y = x` with a single `poly_gate`.
+pub(crate) fn assert_root<F, P>(
+    c: &mut StandardComposer<F, P>,
+    x: Variable,
+    y: Variable,
+    alpha: u64,
+) where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    // x^alpha is a permutation, so alpha is odd and at least 3.
+    let y_pow = pow(c, y, F::zero(), alpha - 1);
+    c.poly_gate(
+        y_pow,
+        y,
+        x,
+        F::one(),
+        F::zero(),
+        F::zero(),
+        -F::one(),
+        F::zero(),
+        None,
+    );
+}
+
+/// Computes `sum(q_i * w_i) + constant`, using fan-in-3 gates to absorb three
+/// terms in the first gate and two more in every following gate.
+pub(crate) fn linear_combination<F, P>(
+    c: &mut StandardComposer<F, P>,
+    terms: &[(F, Variable)],
+    constant: F,
+) -> Variable
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let zero = (F::zero(), c.zero_var());
+    let term = |i: usize| terms.get(i).copied().unwrap_or(zero);
+    let ((q_l, a), (q_r, b), (q_4, d)) = (term(0), term(1), term(2));
+    let mut acc = c.arithmetic_gate(|gate| {
+        gate.witness(a, b, None)
+            .add(q_l, q_r)
+            .fan_in_3(q_4, d)
+            .constant(constant)
+    });
+    for chunk in terms[terms.len().min(3)..].chunks(2) {
+        let (q_r, b) = chunk[0];
+        let (q_4, d) = chunk.get(1).copied().unwrap_or(zero);
+        let prev = acc;
+        acc = c.arithmetic_gate(|gate| {
+            gate.witness(prev, b, None)
+                .add(F::one(), q_r)
+                .fan_in_3(q_4, d)
+        });
+    }
+    acc
+}
+
+/// Constrains `sum(q_i * w_i) + constant` to be zero, absorbing four terms
+/// in the last gate.
+pub(crate) fn assert_linear_combination<F, P>(
+    c: &mut StandardComposer<F, P>,
+    terms: &[(F, Variable)],
+    constant: F,
+) where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let (mut last, constant) = if terms.len() > 4 {
+        let (head, tail) = terms.split_at(terms.len() - 3);
+        let acc = linear_combination(c, head, constant);
+        let mut last = tail.to_vec();
+        last.push((F::one(), acc));
+        (last, F::zero())
+    } else {
+        (terms.to_vec(), constant)
+    };
+    last.resize(4, (F::zero(), c.zero_var()));
+    let [(q_l, a), (q_r, b), (q_o, o), (q_4, d)] =
+        [last[0], last[1], last[2], last[3]];
+    c.arithmetic_gate(|gate| {
+        gate.witness(a, b, Some(o))
+            .add(q_l, q_r)
+            .out(q_o)
+            .fan_in_3(q_4, d)
+            .constant(constant)
+    });
+}
+
+/// Decomposes `x` into `num_bits` boolean variables, least significant
+/// first.
+///
+/// Below the number of bits of the modulus, `x` is constrained to be smaller
+/// than `2^num_bits`. Otherwise, the bits beyond those of the modulus are
+/// the zero variable and the decomposition is constrained to be the
+/// canonical one, smaller than the modulus, so that no other decomposition
+/// of `x` satisfies the circuit.
+pub(crate) fn to_bits_le<F, P>(
+    c: &mut StandardComposer<F, P>,
+    x: Variable,
+    num_bits: usize,
+) -> Vec<Variable>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let modulus_bits = F::Params::MODULUS_BITS as usize;
+    let len = num_bits.min(modulus_bits);
+    let value = c.value_of_var(x).into_repr().to_bits_le();
+    let mut bits = value[..len]
+        .iter()
+        .map(|bit| {
+            let bit = c.add_input(F::from(*bit as u64));
+            c.boolean_gate(bit)
+        })
+        .collect::<Vec<_>>();
+    // sum(2^i b_i) - x = 0
+    let mut terms = Vec::with_capacity(len + 1);
+    let mut power = F::one();
+    for bit in &bits {
+        terms.push((power, *bit));
+        power.double_in_place();
+    }
+    terms.push((-F::one(), x));
+    assert_linear_combination(c, &terms, F::zero());
+    if len == modulus_bits {
+        assert_below_modulus(c, &bits);
+    }
+    bits.resize(num_bits, c.zero_var());
+    bits
+}
+
+/// Constrains the integer whose boolean little-endian decomposition is
+/// `bits`, of as many bits as the modulus, to be smaller than the modulus.
+///
+/// Going from the most significant bit, `eq` tracks whether the bits seen so
+/// far are those of `p - 1`: a bit set where `p - 1` has a zero is forbidden
+/// while `eq` holds, and a bit unset where `p - 1` has a one clears `eq`.
+fn assert_below_modulus<F, P>(c: &mut StandardComposer<F, P>, bits: &[Variable])
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let max = (-F::one()).into_repr().to_bits_le();
+    let zero = c.zero_var();
+    // `None` stands for `eq` being the constant one.
+    let mut eq: Option<Variable> = None;
+    for (bit, max_bit) in bits.iter().zip(max).rev() {
+        match (eq, max_bit) {
+            (None, true) => eq = Some(*bit),
+            (None, false) => c.constrain_to_constant(*bit, F::zero(), None),
+            (Some(e), true) => {
+                eq = Some(c.arithmetic_gate(|gate| {
+                    gate.witness(e, *bit, None).mul(F::one())
+                }))
+            }
+            (Some(e), false) => {
+                c.arithmetic_gate(|gate| {
+                    gate.witness(e, *bit, Some(zero)).mul(F::one())
+                });
+            }
+        }
+    }
+}
+
+/// Linear combination `sum(q_i * w_i) + constant` of variables, which only
+/// adds gates to the circuit once evaluated or constrained.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug)]
+pub(crate) struct LinearCombination<F>
+where
+    F: PrimeField,
+{
+    /// Coefficients and variables of the combination, every variable
+    /// appearing at most once.
+    pub terms: Vec<(F, Variable)>,
+    /// Constant term.
+    pub constant: F,
+}
+
+impl<F> LinearCombination<F>
+where
+    F: PrimeField,
+{
+    /// Returns the constant combination `constant`.
+    pub fn constant(constant: F) -> Self {
+        Self {
+            terms: Vec::new(),
+            constant,
+        }
+    }
+
+    /// Returns the combination made of the single variable `var`.
+    pub fn variable(var: Variable) -> Self {
+        Self {
+            terms: vec![(F::one(), var)],
+            constant: F::zero(),
+        }
+    }
+
+    /// Adds `scalar * other` to `self`, merging the terms holding the same
+    /// variable.
+    pub fn add_scaled(&mut self, scalar: F, other: &Self) {
+        for (q, var) in &other.terms {
+            match self.terms.iter_mut().find(|(_, v)| v == var) {
+                Some((coeff, _)) => *coeff += scalar * q,
+                None => self.terms.push((scalar * q, *var)),
+            }
+        }
+        self.terms.retain(|(q, _)| !q.is_zero());
+        self.constant += scalar * other.constant;
+    }
+
+    /// Returns the value of the combination in `c`.
+    pub fn value<P>(&self, c: &StandardComposer<F, P>) -> F
+    where
+        P: TEModelParameters<BaseField = F>,
+    {
+        self.terms.iter().fold(self.constant, |acc, (q, var)| {
+            acc + *q * c.value_of_var(*var)
+        })
+    }
+
+    /// Returns a variable constrained to hold the value of the combination.
+    pub fn evaluate<P>(&self, c: &mut StandardComposer<F, P>) -> Variable
+    where
+        P: TEModelParameters<BaseField = F>,
+    {
+        match self.terms.as_slice() {
+            [] => c.add_witness_to_circuit_description(self.constant),
+            [(q, var)] if q.is_one() && self.constant.is_zero() => *var,
+            terms => linear_combination(c, terms, self.constant),
+        }
+    }
+
+    /// Constrains the combination to be zero.
+    pub fn assert_zero<P>(&self, c: &mut StandardComposer<F, P>)
+    where
+        P: TEModelParameters<BaseField = F>,
+    {
+        assert_linear_combination(c, &self.terms, self.constant)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, test::gadget_tester};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ff::Field;
+    use plonk_core::commitment::HomomorphicCommitment;
+
+    #[test]
+    fn test_inverse_exponent() {
+        let x = Fr::from(1234567u64);
+        for alpha in [5, 7, 13] {
+            let alpha_inv = inverse_exponent::<Fr>(alpha).unwrap();
+            assert_eq!(x.pow(&alpha_inv).pow([alpha]), x);
+            assert_eq!(x.pow([alpha]).pow(&alpha_inv), x);
+        }
+        // 3 divides p - 1.
+        assert_eq!(inverse_exponent::<Fr>(3), None);
+        assert_eq!(binomial(10, 3), BigUint::from(120u64));
+        assert_eq!(binomial(66, 32), BigUint::from(7007092303604022630u64));
+    }
+
+    fn test_root<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // x^7 is not a permutation of every field.
+        let alpha = crate::poseidon::smallest_alpha::<F>();
+        let alpha_inv = inverse_exponent::<F>(alpha).unwrap();
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let x = composer.add_input(F::from(5u64));
+                let y = root(composer, x, alpha, &alpha_inv);
+                let expected = F::from(5u64).pow(&alpha_inv);
+                assert_eq!(composer.value_of_var(y), expected);
+                composer.constrain_to_constant(y, expected, None);
+            },
+            200,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_wrong_root<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let x = composer.add_input(F::from(5u64));
+                let y = composer.add_input(F::from(2u64));
+                assert_root(composer, x, y, 7);
+            },
+            200,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_to_bits_le<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let modulus_bits = F::Params::MODULUS_BITS as usize;
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let x = composer.add_input(F::from(0b1011u64));
+                let bits = to_bits_le(composer, x, 6);
+                let values = bits
+                    .iter()
+                    .map(|bit| composer.value_of_var(*bit))
+                    .collect::<Vec<_>>();
+                let expected = [1u64, 1, 0, 1, 0, 0].map(F::from);
+                assert_eq!(values, expected);
+
+                // p - 1 is the largest canonical decomposition.
+                let max = composer.add_input(-F::one());
+                let bits = to_bits_le(composer, max, modulus_bits + 1);
+                assert_eq!(bits.len(), modulus_bits + 1);
+                let top = composer.value_of_var(bits[modulus_bits - 1]);
+                assert_eq!(top, F::one());
+                assert_eq!(bits[modulus_bits], composer.zero_var());
+            },
+            1024,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_to_bits_le_overflow<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let x = composer.add_input(F::from(64u64));
+                to_bits_le(composer, x, 6);
+            },
+            200,
+        );
+        assert!(res.is_err());
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_root,
+            test_wrong_root,
+            test_to_bits_le,
+            test_to_bits_le_overflow
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_root,
+            test_wrong_root,
+            test_to_bits_le,
+            test_to_bits_le_overflow
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,323 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! BLAKE2s Gadget
+//!
+//! In-circuit BLAKE2s-256 (RFC 7693), unkeyed, over a message of a fixed
+//! number of bytes, with an optional personalization as used to derive
+//! nullifiers.
+//!
+//! Words are held as their eight nibbles, every nibble having been looked
+//! up in the table returned by [`lookup_table`], added to the circuit under
+//! the name [`LOOKUP_TABLE`] by the first hash:
+//!
+//! - the XOR of two words is read nibble by nibble from the table,
+//! - the rotations by 16, 12 and 8 bits of the mixing function only reorder the
+//!   nibbles, while the rotation by 7 bits splits the second nibble and
+//!   witnesses the rotated word,
+//! - additions modulo `2^32` witness the nibbles of the result and a carry of
+//!   at most four bits.
+//!
+//! A 64-byte block takes about 11,000 gates, on top of the 266 rows of the
+//! lookup table.
+
+mod word;
+
+use crate::arithmetic::LinearCombination;
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use plonk_core::{
+    constraint_system::{StandardComposer, Variable},
+    lookup::LookupTable,
+};
+use word::{Constants, Word};
+
+/// Number of bytes of a block.
+pub const BLOCK_BYTES: usize = 64;
+
+/// Number of bytes of the digest.
+pub const DIGEST_BYTES: usize = 32;
+
+/// Initialization vector.
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c,
+    0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Message word permutations of the ten rounds.
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Name of the lookup table used by [`blake2s`].
+pub const LOOKUP_TABLE: &str = "blake2s nibbles";
+
+/// Returns the lookup table used by [`blake2s`], which it adds to the circuit
+/// under the name [`LOOKUP_TABLE`] when it is not already added.
+pub fn lookup_table<F>() -> LookupTable<F>
+where
+    F: PrimeField,
+{
+    word::lookup_table()
+}
+
+/// Hashes `message` with BLAKE2s-256, returning the 32 bytes of the digest.
+///
+/// # Note
+///
+/// The bytes of `message` should have previously been constrained to be
+/// bytes, for instance with [`StandardComposer::range_gate`].
+pub fn blake2s<F, P>(
+    c: &mut StandardComposer<F, P>,
+    message: &[Variable],
+) -> [Variable; DIGEST_BYTES]
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    blake2s_personalized(c, message, &[0; 8])
+}
+
+/// Hashes `message` with BLAKE2s-256 personalized with `personalization`,
+/// returning the 32 bytes of the digest.
+///
+/// # Note
+///
+/// The bytes of `message` should have previously been constrained to be
+/// bytes, for instance with [`StandardComposer::range_gate`].
+pub fn blake2s_personalized<F, P>(
+    c: &mut StandardComposer<F, P>,
+    message: &[Variable],
+    personalization: &[u8; 8],
+) -> [Variable; DIGEST_BYTES]
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let constants = Constants::new(c);
+    let mut params = [0u32; 8];
+    params[0] = 0x0101_0000 | DIGEST_BYTES as u32;
+    for (i, bytes) in personalization.chunks(4).enumerate() {
+        params[6 + i] =
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    let mut h = [Word::constant(&constants, 0); 8];
+    for (i, word) in h.iter_mut().enumerate() {
+        *word = Word::constant(&constants, IV[i] ^ params[i]);
+    }
+
+    let blocks =
+        message.len() / BLOCK_BYTES + (message.len() % BLOCK_BYTES).min(1);
+    for i in 0..blocks.max(1) {
+        let start = i * BLOCK_BYTES;
+        let end = message.len().min(start + BLOCK_BYTES);
+        let mut words = Vec::with_capacity(16);
+        for j in 0..16 {
+            let mut word = LinearCombination::constant(F::zero());
+            for k in 0..4 {
+                if let Some(byte) = message[start..end].get(4 * j + k) {
+                    word.add_scaled(
+                        F::from(1u64 << (8 * k)),
+                        &LinearCombination::variable(*byte),
+                    );
+                }
+            }
+            words.push(word);
+        }
+        let last = i + 1 >= blocks;
+        h = compress(c, &constants, &h, &words, end as u64, last);
+    }
+
+    let mut digest = [c.zero_var(); DIGEST_BYTES];
+    for (bytes, word) in digest.chunks_mut(4).zip(h) {
+        bytes.copy_from_slice(&word.bytes(c));
+    }
+    digest
+}
+
+/// Compresses the block `m` into the chaining value `h`, `t` being the
+/// number of bytes hashed so far and `last` telling whether the block is the
+/// last one.
+fn compress<F, P>(
+    c: &mut StandardComposer<F, P>,
+    constants: &Constants,
+    h: &[Word; 8],
+    m: &[LinearCombination<F>],
+    t: u64,
+    last: bool,
+) -> [Word; 8]
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let mut iv = IV;
+    iv[4] ^= t as u32;
+    iv[5] ^= (t >> 32) as u32;
+    if last {
+        iv[6] ^= u32::MAX;
+    }
+    let mut v = [h[0]; 16];
+    v[..8].copy_from_slice(h);
+    for (word, iv) in v[8..].iter_mut().zip(iv) {
+        *word = Word::constant(constants, iv);
+    }
+
+    for s in SIGMA {
+        for (i, [a, b, d, e]) in [
+            [0, 4, 8, 12],
+            [1, 5, 9, 13],
+            [2, 6, 10, 14],
+            [3, 7, 11, 15],
+            [0, 5, 10, 15],
+            [1, 6, 11, 12],
+            [2, 7, 8, 13],
+            [3, 4, 9, 14],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let (x, y) = (&m[s[2 * i]], &m[s[2 * i + 1]]);
+            mix(c, constants, &mut v, [a, b, d, e], x, y);
+        }
+    }
+
+    let mut output = *h;
+    for (i, word) in output.iter_mut().enumerate() {
+        *word = word.xor(c, constants, &v[i]).xor(c, constants, &v[i + 8]);
+    }
+    output
+}
+
+/// Mixing function `G` applied to the words `[a, b, c, d]` of `v` with the
+/// message words `x` and `y`.
+fn mix<F, P>(
+    c: &mut StandardComposer<F, P>,
+    constants: &Constants,
+    v: &mut [Word; 16],
+    [a, b, d, e]: [usize; 4],
+    x: &LinearCombination<F>,
+    y: &LinearCombination<F>,
+) where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let zero = LinearCombination::constant(F::zero());
+    v[a] = Word::add_mod(c, constants, &[&v[a], &v[b]], x);
+    v[e] = v[e].xor(c, constants, &v[a]).rotr_nibbles(16);
+    v[d] = Word::add_mod(c, constants, &[&v[d], &v[e]], &zero);
+    v[b] = v[b].xor(c, constants, &v[d]).rotr_nibbles(12);
+    v[a] = Word::add_mod(c, constants, &[&v[a], &v[b]], y);
+    v[e] = v[e].xor(c, constants, &v[a]).rotr_nibbles(8);
+    v[d] = Word::add_mod(c, constants, &[&v[d], &v[e]], &zero);
+    v[b] = v[b].xor(c, constants, &v[d]).rotr7(c, constants);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, test::gadget_tester};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_std::{test_rng, UniformRand};
+    use blake2::{Blake2s, Digest};
+    use plonk_core::commitment::HomomorphicCommitment;
+
+    /// Hashes `message` in `composer`, checking the digest against
+    /// `expected`.
+    fn hash_and_check<F, P>(
+        composer: &mut StandardComposer<F, P>,
+        message: &[u8],
+        personalization: &[u8; 8],
+        expected: &[u8],
+    ) where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let message = message
+            .iter()
+            .map(|byte| {
+                let byte = composer.add_input(F::from(*byte));
+                composer.range_gate(byte, 8);
+                byte
+            })
+            .collect::<Vec<_>>();
+        let digest = blake2s_personalized(composer, &message, personalization);
+        for (var, byte) in digest.iter().zip(expected) {
+            assert_eq!(composer.value_of_var(*var), F::from(*byte));
+            composer.constrain_to_constant(*var, F::from(*byte), None);
+        }
+    }
+
+    #[test]
+    fn test_blake2s_values() {
+        // Random messages around the block boundaries, checked without
+        // proving the circuit.
+        let mut rng = test_rng();
+        let mut composer = StandardComposer::<
+            ark_bls12_381::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+        >::new();
+        for len in [0, 1, 63, 64, 65, 130] {
+            let message =
+                (0..len).map(|_| u8::rand(&mut rng)).collect::<Vec<_>>();
+            let expected = Blake2s::digest(&message);
+            hash_and_check(&mut composer, &message, &[0; 8], &expected);
+        }
+        let mut hasher = Blake2s::with_params(&[], &[], b"Zcash_nf");
+        hasher.update(b"nullifier");
+        let expected = hasher.finalize();
+        hash_and_check(&mut composer, b"nullifier", b"Zcash_nf", &expected);
+    }
+
+    fn test_blake2s_random<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        let message = (0..50).map(|_| u8::rand(&mut rng)).collect::<Vec<_>>();
+        let expected = Blake2s::digest(&message);
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let start = composer.circuit_size();
+                hash_and_check(composer, &message, &[0; 8], &expected);
+                let gates = composer.circuit_size() - start;
+                assert!(gates < 12000, "{} gates for one block", gates);
+            },
+            16384,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_blake2s_random
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_blake2s_random
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
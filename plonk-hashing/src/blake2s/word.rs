@@ -0,0 +1,288 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! 32-bit Words Split into Nibbles
+//!
+//! Every word is held as its eight nibbles, least significant first, each
+//! of them having been looked up in a table of the XOR of two nibbles. The
+//! XOR of two words is then read nibble by nibble from the table, and their
+//! rotations by a multiple of four bits only reorder the nibbles.
+
+use crate::arithmetic::LinearCombination;
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use plonk_core::{
+    constraint_system::{StandardComposer, Variable},
+    lookup::{LookupTable, LookupTableId},
+};
+
+/// Lengths of the pieces, other than nibbles, which are range-checked
+/// through the lookup table.
+const RANGE_BITS: [u64; 2] = [1, 3];
+
+/// Returns the lookup table holding the rows `(a, b, a ^ b, 0)` for all
+/// nibbles `a` and `b`, and the rows `(x, 0, 0, k)` for every length `k` of
+/// [`RANGE_BITS`] and every `x < 2^k`.
+pub(super) fn lookup_table<F>() -> LookupTable<F>
+where
+    F: PrimeField,
+{
+    let mut table = LookupTable::new();
+    for a in 0..16u64 {
+        for b in 0..16u64 {
+            table.insert_row(F::from(a), F::from(b), F::from(a ^ b), F::zero());
+        }
+    }
+    for bits in RANGE_BITS {
+        for x in 0..1u64 << bits {
+            table.insert_row(F::from(x), F::zero(), F::zero(), F::from(bits));
+        }
+    }
+    table
+}
+
+/// Constants shared by the words of a circuit: the handle to the lookup
+/// table, the sixteen nibbles and the lengths of [`RANGE_BITS`].
+pub(super) struct Constants {
+    table: LookupTableId,
+    nibbles: Vec<Variable>,
+    range_tags: Vec<Variable>,
+}
+
+impl Constants {
+    /// Adds the constant variables to the circuit, along with the lookup
+    /// table if it is not already added.
+    pub fn new<F, P>(c: &mut StandardComposer<F, P>) -> Self
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let table =
+            c.get_or_add_lookup_table(super::LOOKUP_TABLE, lookup_table);
+        let mut constant =
+            |x: u64| c.add_witness_to_circuit_description(F::from(x));
+        let nibbles = (0..16).map(&mut constant).collect();
+        let range_tags =
+            RANGE_BITS.iter().map(|bits| constant(*bits)).collect();
+        Self {
+            table,
+            nibbles,
+            range_tags,
+        }
+    }
+
+    /// Looks up `x` in the range of `bits` bits.
+    fn range<F, P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        x: Variable,
+        bits: u64,
+    ) where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let index = RANGE_BITS.iter().position(|b| *b == bits);
+        let tag = self.range_tags[index.expect("range in the table")];
+        let zero = c.zero_var();
+        c.lookup_gate(self.table, x, zero, zero, Some(tag), None);
+    }
+}
+
+/// 32-bit word held as its eight nibbles, least significant first.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Word {
+    nibbles: [Variable; 8],
+}
+
+impl Word {
+    /// Returns the constant word `value`.
+    pub fn constant(constants: &Constants, value: u32) -> Self {
+        let mut nibbles = [constants.nibbles[0]; 8];
+        for (i, nibble) in nibbles.iter_mut().enumerate() {
+            *nibble = constants.nibbles[((value >> (4 * i)) & 0xf) as usize];
+        }
+        Self { nibbles }
+    }
+
+    /// Witnesses the word `value`, looking up every nibble.
+    fn witness<F, P>(
+        c: &mut StandardComposer<F, P>,
+        constants: &Constants,
+        value: u32,
+    ) -> Self
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut nibbles = [c.zero_var(); 8];
+        for (i, nibble) in nibbles.iter_mut().enumerate() {
+            *nibble = c.add_input(F::from((value >> (4 * i)) & 0xf));
+            c.lookup_gate(
+                constants.table,
+                *nibble,
+                constants.nibbles[0],
+                *nibble,
+                None,
+                None,
+            );
+        }
+        Self { nibbles }
+    }
+
+    /// Returns the value of the word.
+    pub fn value<F, P>(&self, c: &StandardComposer<F, P>) -> u32
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        self.nibbles.iter().rev().fold(0, |acc, nibble| {
+            (acc << 4) | c.value_of_var(*nibble).into_repr().as_ref()[0] as u32
+        })
+    }
+
+    /// Returns the word as a linear combination of its nibbles.
+    pub fn dense<F>(&self) -> LinearCombination<F>
+    where
+        F: PrimeField,
+    {
+        let mut dense = LinearCombination::constant(F::zero());
+        for (i, nibble) in self.nibbles.iter().enumerate() {
+            dense.add_scaled(
+                F::from(1u64 << (4 * i)),
+                &LinearCombination::variable(*nibble),
+            );
+        }
+        dense
+    }
+
+    /// Returns the variables holding the bytes of the word, least
+    /// significant first.
+    pub fn bytes<F, P>(&self, c: &mut StandardComposer<F, P>) -> [Variable; 4]
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut bytes = [c.zero_var(); 4];
+        for (byte, nibbles) in bytes.iter_mut().zip(self.nibbles.chunks(2)) {
+            let mut sum = LinearCombination::variable(nibbles[0]);
+            sum.add_scaled(
+                F::from(16u64),
+                &LinearCombination::variable(nibbles[1]),
+            );
+            *byte = sum.evaluate(c);
+        }
+        bytes
+    }
+
+    /// Returns `self ^ other`.
+    pub fn xor<F, P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        constants: &Constants,
+        other: &Self,
+    ) -> Self
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut nibbles = [c.zero_var(); 8];
+        for (i, nibble) in nibbles.iter_mut().enumerate() {
+            let (a, b) = (self.nibbles[i], other.nibbles[i]);
+            let value = c.value_of_var(a).into_repr().as_ref()[0]
+                ^ c.value_of_var(b).into_repr().as_ref()[0];
+            *nibble = c.add_input(F::from(value));
+            c.lookup_gate(constants.table, a, b, *nibble, None, None);
+        }
+        Self { nibbles }
+    }
+
+    /// Returns the word rotated right by `r` bits, which must be a multiple
+    /// of four.
+    pub fn rotr_nibbles(&self, r: u32) -> Self {
+        debug_assert_eq!(r % 4, 0);
+        let mut nibbles = self.nibbles;
+        nibbles.rotate_left((r / 4) as usize);
+        Self { nibbles }
+    }
+
+    /// Returns the word rotated right by seven bits.
+    ///
+    /// The seven low bits `l` of the word `x` are split off its first two
+    /// nibbles, and the rotation `y` is witnessed and constrained by
+    /// `2^7 y = x + (2^32 - 1) l`.
+    pub fn rotr7<F, P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        constants: &Constants,
+    ) -> Self
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let value = self.value(c);
+        // Second nibble = low + 8 high
+        let low = c.add_input(F::from((value >> 4) & 0b111));
+        let high = c.add_input(F::from((value >> 7) & 1));
+        constants.range(c, low, 3);
+        constants.range(c, high, 1);
+        let mut check = LinearCombination::variable(self.nibbles[1]);
+        check.add_scaled(-F::one(), &LinearCombination::variable(low));
+        check.add_scaled(-F::from(8u64), &LinearCombination::variable(high));
+        check.assert_zero(c);
+
+        let rotated = Self::witness(c, constants, value.rotate_right(7));
+        // 2^7 y - x - (2^32 - 1) (nibble_0 + 16 low) = 0
+        let wrap = F::from(u64::from(u32::MAX));
+        let mut check = LinearCombination::constant(F::zero());
+        check.add_scaled(F::from(128u64), &rotated.dense());
+        check.add_scaled(-F::one(), &self.dense());
+        check.add_scaled(-wrap, &LinearCombination::variable(self.nibbles[0]));
+        check.add_scaled(
+            -wrap * F::from(16u64),
+            &LinearCombination::variable(low),
+        );
+        check.assert_zero(c);
+        rotated
+    }
+
+    /// Returns the sum of `words` and `extra` modulo `2^32`, the sum being
+    /// at most `2^36`.
+    pub fn add_mod<F, P>(
+        c: &mut StandardComposer<F, P>,
+        constants: &Constants,
+        words: &[&Self],
+        extra: &LinearCombination<F>,
+    ) -> Self
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut sum = extra.clone();
+        for word in words {
+            sum.add_scaled(F::one(), &word.dense());
+        }
+        let value = sum.value(c).into_repr().as_ref()[0];
+        let word = Self::witness(c, constants, value as u32);
+        let carry = c.add_input(F::from(value >> 32));
+        c.lookup_gate(
+            constants.table,
+            carry,
+            constants.nibbles[0],
+            carry,
+            None,
+            None,
+        );
+        // sum - word - 2^32 carry = 0
+        sum.add_scaled(-F::one(), &word.dense());
+        sum.add_scaled(
+            -F::from(1u64 << 32),
+            &LinearCombination::variable(carry),
+        );
+        sum.assert_zero(c);
+        word
+    }
+}
@@ -0,0 +1,84 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Keccak Digests
+//!
+//! Byte-oriented hashes built on the Keccak-f\[1600\] permutation, used to
+//! derive the constants of some of the hash functions of this crate.
+
+use ark_std::{vec, vec::Vec};
+
+/// Returns the first `len` bytes output by a Keccak sponge of `rate` bytes
+/// absorbing `input`, padded with the domain separation bits `suffix`
+/// followed by the `10*1` padding.
+fn keccak_sponge(input: &[u8], rate: usize, suffix: u8, len: usize) -> Vec<u8> {
+    let mut state = [0u64; 25];
+    let absorb = |state: &mut [u64; 25], block: &[u8]| {
+        for (i, byte) in block.iter().enumerate() {
+            state[i / 8] ^= (*byte as u64) << (8 * (i % 8));
+        }
+        keccak::f1600(state);
+    };
+    let mut blocks = input.chunks_exact(rate);
+    for block in &mut blocks {
+        absorb(&mut state, block);
+    }
+    let mut last = vec![0u8; rate];
+    let remainder = blocks.remainder();
+    last[..remainder.len()].copy_from_slice(remainder);
+    last[remainder.len()] ^= suffix;
+    last[rate - 1] ^= 0x80;
+    absorb(&mut state, &last);
+    let mut output = Vec::with_capacity(len);
+    loop {
+        for lane in state.iter().take(rate / 8) {
+            output.extend_from_slice(&lane.to_le_bytes());
+        }
+        if output.len() >= len {
+            output.truncate(len);
+            return output;
+        }
+        keccak::f1600(&mut state);
+    }
+}
+
+/// Returns the first `len` bytes of the SHAKE256 output for `input`.
+pub(crate) fn shake256(input: &[u8], len: usize) -> Vec<u8> {
+    keccak_sponge(input, 136, 0x1f, len)
+}
+
+/// Returns the Keccak-256 digest of `input`, as used by Ethereum, which
+/// differs from SHA3-256 by its padding.
+pub(crate) fn keccak256(input: &[u8]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&keccak_sponge(input, 136, 0x01, 32));
+    digest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_shake256() {
+        // Test vectors of FIPS 202.
+        assert_eq!(
+            shake256(b"", 8),
+            [0x46, 0xb9, 0xdd, 0x2b, 0x0b, 0xa8, 0x8d, 0x13]
+        );
+        let long = shake256(&[0xa3; 200], 300);
+        assert_eq!(&long[..4], &[0xcd, 0x8a, 0x92, 0x0e]);
+        assert_eq!(&long[296..], &[0x78, 0x39, 0x06, 0x4c]);
+    }
+
+    #[test]
+    fn test_keccak256() {
+        assert_eq!(
+            keccak256(b"")[..8],
+            [0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c]
+        );
+    }
+}
@@ -0,0 +1,481 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Keccak Gadget
+//!
+//! In-circuit Keccak-f\[1600\] permutation and Keccak-256 hash, as used by
+//! Ethereum, that is with the original `0x01` padding of Keccak rather than
+//! the `0x06` padding of SHA3-256.
+//!
+//! The state is kept in a sparse form, every lane being written in base 13
+//! so that the five lanes of a column add up without carry. The steps of a
+//! round are then computed by normalizing linear combinations of lanes
+//! through the lookup table returned by [`lookup_table`], added to the
+//! circuit under the name [`LOOKUP_TABLE`] by the first permutation:
+//!
+//! - theta sums every column, normalizes the sum to its parity and adds the
+//!   parities of the two neighbouring columns to every lane, the rotation of
+//!   one of them being a linear combination of its chunks,
+//! - the lanes are then normalized to base 5, rho and pi being free since the
+//!   chunks are cut at the rotation of the lane and recombined at their new
+//!   position,
+//! - chi is computed lane by lane from the digits of `2 a + b - c + 1` in base
+//!   5, where `a`, `b` and `c` are three consecutive lanes of a row, which are
+//!   mapped back to base 13,
+//! - iota adds the round constant in base 13.
+//!
+//! A permutation takes about 46,000 gates, on top of the 4,431 rows of the
+//! lookup table.
+//!
+//! The [`KeccakTranscript`] derives the Fiat-Shamir challenges of PLONK
+//! proofs with Keccak-256 over a fixed byte encoding, so that they can be
+//! verified on Ethereum.
+
+mod sparse;
+mod transcript;
+
+pub use transcript::KeccakTranscript;
+
+use crate::arithmetic::LinearCombination;
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use plonk_core::{
+    constraint_system::{StandardComposer, Variable},
+    lookup::LookupTable,
+};
+use sparse::{
+    lookup, normalize, recombine, sparse, tags, Normalize, Tags, CHI_BASE,
+    LANE_BITS, THETA_BASE,
+};
+
+/// Number of rounds of Keccak-f\[1600\].
+pub const ROUNDS: usize = 24;
+
+/// Number of bytes absorbed by every permutation of Keccak-256.
+pub const RATE: usize = 136;
+
+/// Round constants.
+const RC: [u64; ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation of the lane `(x, y)` by rho, indexed by `x + 5 y`.
+const RHO: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8,
+    18, 2, 61, 56, 14,
+];
+
+/// Layout of a column sum, whose last digit is kept apart to rotate it.
+const COLUMN_LAYOUT: [u32; 22] = [
+    3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 1,
+];
+
+/// Layout of the lanes normalized through chi or to binary.
+const NIBBLE_LAYOUT: [u32; 16] = [4; 16];
+
+/// Layout of the lanes normalized from bytes.
+const BYTE_LAYOUT: [u32; 8] = [8; 8];
+
+/// Name of the lookup table used by [`keccak_f1600`] and [`keccak256`].
+pub const LOOKUP_TABLE: &str = "keccak sparse";
+
+/// Returns the lookup table used by [`keccak_f1600`] and [`keccak256`],
+/// which they add to the circuit under the name [`LOOKUP_TABLE`] when it is
+/// not already added.
+pub fn lookup_table<F>() -> LookupTable<F>
+where
+    F: PrimeField,
+{
+    sparse::lookup_table()
+}
+
+/// Applies Keccak-f\[1600\] to the 25 lanes of `state`, the lane `(x, y)`
+/// being at the index `x + 5 y`.
+///
+/// The lanes of the state are constrained to 64 bits.
+pub fn keccak_f1600<F, P>(
+    c: &mut StandardComposer<F, P>,
+    state: &[Variable; 25],
+) -> [Variable; 25]
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    keccak_p1600(c, state, ROUNDS)
+}
+
+/// Applies the last `rounds` rounds of Keccak-f\[1600\] to `state`.
+fn keccak_p1600<F, P>(
+    c: &mut StandardComposer<F, P>,
+    state: &[Variable; 25],
+    rounds: usize,
+) -> [Variable; 25]
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let tags = tags(c);
+    let mut lanes = [c.zero_var(); 25];
+    for (sparse, lane) in lanes.iter_mut().zip(state) {
+        let lane = LinearCombination::variable(*lane);
+        let bytes = normalize(c, &tags, Normalize::Input, &lane, &BYTE_LAYOUT);
+        *sparse = recombine(&bytes, THETA_BASE, 0).evaluate(c);
+    }
+    permute(c, &tags, &mut lanes, rounds);
+    let mut output = [c.zero_var(); 25];
+    for (dense, lane) in output.iter_mut().zip(lanes) {
+        let lane = LinearCombination::variable(lane);
+        let bits =
+            normalize(c, &tags, Normalize::Output, &lane, &NIBBLE_LAYOUT);
+        *dense = recombine(&bits, 2, 0).evaluate(c);
+    }
+    output
+}
+
+/// Hashes `message` with Keccak-256, returning the 32 bytes of the digest.
+///
+/// The message is padded inside the gadget, its length being part of the
+/// circuit description. Every byte of the message is looked up, which
+/// constrains it to be a byte.
+pub fn keccak256<F, P>(
+    c: &mut StandardComposer<F, P>,
+    message: &[Variable],
+) -> [Variable; 32]
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let tags = tags(c);
+    let mut bytes = message
+        .iter()
+        .map(|byte| {
+            let sparse = lookup(c, &tags, Normalize::Input, 8, *byte);
+            LinearCombination::variable(sparse)
+        })
+        .collect::<Vec<_>>();
+    let constant =
+        |byte: u8| LinearCombination::constant(sparse(byte.into(), THETA_BASE));
+    let padding = RATE - message.len() % RATE;
+    for i in 0..padding {
+        let first = if i == 0 { 0x01 } else { 0x00 };
+        let last = if i + 1 == padding { 0x80 } else { 0x00 };
+        bytes.push(constant(first | last));
+    }
+
+    let mut state = [c.zero_var(); 25];
+    for block in bytes.chunks(RATE) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            let mut sum = LinearCombination::variable(*lane);
+            for (i, byte) in bytes.iter().enumerate() {
+                sum.add_scaled(F::from(THETA_BASE).pow([8 * i as u64]), byte);
+            }
+            *lane = sum.evaluate(c);
+        }
+        permute(c, &tags, &mut state, ROUNDS);
+    }
+
+    let mut digest = [c.zero_var(); 32];
+    for (bytes, lane) in digest.chunks_mut(8).zip(state) {
+        let lane = LinearCombination::variable(lane);
+        let nibbles =
+            normalize(c, &tags, Normalize::Output, &lane, &NIBBLE_LAYOUT);
+        for (byte, nibbles) in bytes.iter_mut().zip(nibbles.chunks(2)) {
+            let (_, low) = nibbles[0];
+            let (_, high) = nibbles[1];
+            let mut sum = LinearCombination::variable(low);
+            sum.add_scaled(F::from(16u64), &LinearCombination::variable(high));
+            *byte = sum.evaluate(c);
+        }
+    }
+    digest
+}
+
+/// Applies the last `rounds` rounds of Keccak-f\[1600\] to `state`, whose lanes
+/// are in base 13.
+///
+/// The digits of the lanes must be at most 2, except those of the first lane
+/// which may be 3.
+fn permute<F, P>(
+    c: &mut StandardComposer<F, P>,
+    tags: &Tags,
+    state: &mut [Variable; 25],
+    rounds: usize,
+) where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    for rc in &RC[ROUNDS - rounds..] {
+        *state = round(c, tags, state, *rc);
+    }
+}
+
+/// Applies a round of Keccak-f\[1600\] with the round constant `rc` to `a`.
+fn round<F, P>(
+    c: &mut StandardComposer<F, P>,
+    tags: &Tags,
+    a: &[Variable; 25],
+    rc: u64,
+) -> [Variable; 25]
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let var = LinearCombination::variable;
+
+    // Theta: parities of the columns, and of their last bits.
+    let mut parities = Vec::with_capacity(5);
+    for x in 0..5 {
+        let mut sum = LinearCombination::constant(F::zero());
+        for y in 0..5 {
+            sum.add_scaled(F::one(), &var(a[x + 5 * y]));
+        }
+        let chunks =
+            normalize(c, tags, Normalize::ThetaColumn, &sum, &COLUMN_LAYOUT);
+        let (_, last) = chunks[chunks.len() - 1];
+        parities.push((recombine(&chunks, THETA_BASE, 0).evaluate(c), last));
+    }
+
+    // Theta, rho and pi: every lane plus the parity of the column on its
+    // left and the parity of the column on its right rotated by one bit,
+    // normalized to base 5 and moved to its new position.
+    let wrap = F::from(THETA_BASE).pow([LANE_BITS.into()]) - F::one();
+    let mut b = [c.zero_var(); 25];
+    for x in 0..5 {
+        let (left, _) = parities[(x + 4) % 5];
+        let (right, right_last) = parities[(x + 1) % 5];
+        for y in 0..5 {
+            let mut lane = var(a[x + 5 * y]);
+            lane.add_scaled(F::one(), &var(left));
+            lane.add_scaled(F::from(THETA_BASE), &var(right));
+            lane.add_scaled(-wrap, &var(right_last));
+            let r = RHO[x + 5 * y];
+            let chunks = normalize(
+                c,
+                tags,
+                Normalize::ThetaLane,
+                &lane,
+                &rotation_layout(r),
+            );
+            b[y + 5 * ((2 * x + 3 * y) % 5)] =
+                recombine(&chunks, CHI_BASE, r).evaluate(c);
+        }
+    }
+
+    // Chi and iota.
+    let ones = sparse::<F>(u64::MAX, CHI_BASE);
+    let mut output = [c.zero_var(); 25];
+    for y in 0..5 {
+        for x in 0..5 {
+            let mut lane = LinearCombination::constant(ones);
+            lane.add_scaled(F::from(2u64), &var(b[x + 5 * y]));
+            lane.add_scaled(F::one(), &var(b[(x + 1) % 5 + 5 * y]));
+            lane.add_scaled(-F::one(), &var(b[(x + 2) % 5 + 5 * y]));
+            let chunks =
+                normalize(c, tags, Normalize::Chi, &lane, &NIBBLE_LAYOUT);
+            let mut lane = recombine(&chunks, THETA_BASE, 0);
+            if x + y == 0 {
+                lane.add_scaled(
+                    F::one(),
+                    &LinearCombination::constant(sparse(rc, THETA_BASE)),
+                );
+            }
+            output[x + 5 * y] = lane.evaluate(c);
+        }
+    }
+    output
+}
+
+/// Returns the layout of a lane rotated left by `r` bits: chunks of at most
+/// four digits, cut at the digit which the rotation brings back to zero.
+fn rotation_layout(r: u32) -> Vec<u32> {
+    let cut = (LANE_BITS - r) % LANE_BITS;
+    let mut layout = Vec::new();
+    for mut length in [cut, LANE_BITS - cut] {
+        while length > 0 {
+            let size = length.min(4);
+            layout.push(size);
+            length -= size;
+        }
+    }
+    layout
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, digest, test::gadget_tester};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_std::{test_rng, vec, UniformRand};
+    use plonk_core::commitment::HomomorphicCommitment;
+
+    /// Parses a hexadecimal digest.
+    fn digest_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Hashes `message` in `composer`, checking the digest against
+    /// `expected`.
+    fn hash_and_check<F, P>(
+        composer: &mut StandardComposer<F, P>,
+        message: &[u8],
+        expected: &str,
+    ) where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let expected = digest_bytes(expected);
+        assert_eq!(digest::keccak256(message), expected.as_slice());
+        let message = message
+            .iter()
+            .map(|byte| composer.add_input(F::from(*byte)))
+            .collect::<Vec<_>>();
+        let digest = keccak256(composer, &message);
+        for (var, byte) in digest.iter().zip(expected) {
+            assert_eq!(composer.value_of_var(*var), F::from(byte));
+            composer.constrain_to_constant(*var, F::from(byte), None);
+        }
+    }
+
+    #[test]
+    fn test_rotation_layout() {
+        for r in RHO {
+            let layout = rotation_layout(r);
+            assert_eq!(layout.iter().sum::<u32>(), LANE_BITS);
+            assert!(layout.iter().all(|size| (1..=4).contains(size)));
+        }
+        assert_eq!(rotation_layout(0), vec![4; 16]);
+        assert_eq!(rotation_layout(62), [&[2][..], &[4; 15], &[2]].concat());
+    }
+
+    #[test]
+    fn test_keccak256_values() {
+        // Digests of the empty message, of a message whose padding is a
+        // single byte and of a message spanning two blocks, checked without
+        // proving the circuit.
+        let mut composer = StandardComposer::<
+            ark_bls12_381::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+        >::new();
+        for (message, expected) in [
+            (
+                &[][..],
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            ),
+            (
+                &[0x61; 135],
+                "34367dc248bbd832f4e3e69dfaac2f92638bd0bbd18f2912ba4ef454919cf446",
+            ),
+            (
+                &[0x61; 200],
+                "96ea54061def936c4be90b518992fdc6f12f535068a256229aca54267b4d084d",
+            ),
+        ] {
+            hash_and_check(&mut composer, message, expected);
+        }
+    }
+
+    fn test_keccak_p1600<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // Two rounds of the permutation on a random state, the last round
+        // constant exercising the top bit of the lanes.
+        let mut rng = test_rng();
+        let mut lanes = [0u64; 25];
+        lanes
+            .iter_mut()
+            .for_each(|lane| *lane = u64::rand(&mut rng));
+        let mut expected = lanes;
+        keccak::p1600(&mut expected, 2);
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let mut state = [composer.zero_var(); 25];
+                for (var, lane) in state.iter_mut().zip(lanes) {
+                    *var = composer.add_input(F::from(lane));
+                }
+                let output = keccak_p1600(composer, &state, 2);
+                for (var, lane) in output.iter().zip(expected) {
+                    assert_eq!(composer.value_of_var(*var), F::from(lane));
+                    composer.constrain_to_constant(*var, F::from(lane), None);
+                }
+            },
+            8192,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_keccak256_abc<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // Ethereum's keccak256("abc").
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let start = composer.circuit_size();
+                hash_and_check(
+                    composer,
+                    b"abc",
+                    "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+                );
+                let gates = composer.circuit_size() - start;
+                assert!(gates < 50000, "{} gates for one block", gates);
+            },
+            65536,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_keccak_p1600,
+            test_keccak256_abc
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_keccak_p1600
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
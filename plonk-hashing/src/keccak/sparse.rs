@@ -0,0 +1,327 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Sparse Representations of 64-bit Lanes
+//!
+//! A lane is written in base `b` by giving its `i`-th bit the weight `b^i`.
+//! Adding lanes in base `b` then adds their bits digit by digit, without any
+//! carry as long as the digits stay below `b`, and a function of the bits of
+//! several lanes is computed by normalizing a linear combination of the lanes:
+//! the combination is split into chunks of a few digits, every chunk being
+//! looked up along with the image of its digits.
+
+use crate::arithmetic::LinearCombination;
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use ark_std::{vec, vec::Vec};
+use num_bigint::BigUint;
+use plonk_core::{
+    constraint_system::{StandardComposer, Variable},
+    lookup::{LookupTable, LookupTableId},
+};
+
+/// Number of bits of a lane.
+pub(super) const LANE_BITS: u32 = 64;
+
+/// Base in which the state is kept, large enough for the sum of the five
+/// lanes of a column.
+pub(super) const THETA_BASE: u64 = 13;
+
+/// Base in which the input of chi is computed.
+pub(super) const CHI_BASE: u64 = 5;
+
+/// Normalization read from the lookup table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Normalize {
+    /// Parity of the digits of a sum of five lanes in base 13, to base 13.
+    ThetaColumn,
+    /// Parity of the digits of a lane after theta in base 13, to base 5.
+    ThetaLane,
+    /// Output of chi from the digits of `2 a + b - c + 1` in base 5, where
+    /// `a`, `b` and `c` are the bits of three consecutive lanes, to base 13.
+    Chi,
+    /// Parity of the digits of a lane in base 13, to base 2.
+    Output,
+    /// Bits of a byte, to base 13.
+    Input,
+}
+
+impl Normalize {
+    /// Every normalization.
+    const ALL: [Self; 5] = [
+        Self::ThetaColumn,
+        Self::ThetaLane,
+        Self::Chi,
+        Self::Output,
+        Self::Input,
+    ];
+
+    /// Base of the input.
+    pub fn base_in(self) -> u64 {
+        match self {
+            Self::ThetaColumn | Self::ThetaLane | Self::Output => THETA_BASE,
+            Self::Chi => CHI_BASE,
+            Self::Input => 2,
+        }
+    }
+
+    /// Base of the output.
+    pub fn base_out(self) -> u64 {
+        match self {
+            Self::ThetaColumn | Self::Chi | Self::Input => THETA_BASE,
+            Self::ThetaLane => CHI_BASE,
+            Self::Output => 2,
+        }
+    }
+
+    /// Largest digit of the input.
+    fn max_digit(self) -> u64 {
+        match self {
+            Self::ThetaColumn => 11,
+            Self::ThetaLane => 5,
+            Self::Chi => 4,
+            Self::Output => 3,
+            Self::Input => 1,
+        }
+    }
+
+    /// Numbers of digits of the chunks stored in the table.
+    fn chunk_sizes(self) -> &'static [u32] {
+        match self {
+            Self::ThetaColumn => &[1, 3],
+            Self::ThetaLane => &[1, 2, 3, 4],
+            Self::Chi | Self::Output => &[4],
+            Self::Input => &[8],
+        }
+    }
+
+    /// Maps a digit of the input to a digit of the output.
+    fn map(self, digit: u64) -> u64 {
+        match self {
+            Self::Chi => [1, 0, 0, 1, 1][digit as usize],
+            Self::Input => digit,
+            _ => digit & 1,
+        }
+    }
+
+    /// Tag of the chunks of `size` digits, which tells them apart from the
+    /// chunks of every other normalization and size.
+    fn tag(self, size: u32) -> usize {
+        let index = Self::ALL.iter().position(|kind| *kind == self);
+        9 * index.expect("listed normalization") + size as usize
+    }
+
+    /// Returns the output of the chunk holding `digits`, least significant
+    /// first.
+    fn image(self, digits: &[u64]) -> (u64, u64) {
+        digits.iter().rev().fold((0, 0), |(input, output), digit| {
+            (
+                input * self.base_in() + digit,
+                output * self.base_out() + self.map(*digit),
+            )
+        })
+    }
+}
+
+/// Returns the lookup table holding the rows `(x, tag, y, 0)` for every
+/// normalization, every chunk size and every chunk `x` whose digits are
+/// within the range of the normalization, `y` being the image of `x`.
+pub(super) fn lookup_table<F>() -> LookupTable<F>
+where
+    F: PrimeField,
+{
+    let mut table = LookupTable::new();
+    for kind in Normalize::ALL {
+        for &size in kind.chunk_sizes() {
+            let mut digits = vec![0; size as usize];
+            loop {
+                let (input, output) = kind.image(&digits);
+                table.insert_row(
+                    F::from(input),
+                    F::from(kind.tag(size) as u64),
+                    F::from(output),
+                    F::zero(),
+                );
+                // Moves on to the next chunk, as an odometer.
+                match digits.iter().position(|d| *d < kind.max_digit()) {
+                    Some(i) => {
+                        digits[i] += 1;
+                        digits[..i].iter_mut().for_each(|d| *d = 0);
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+    table
+}
+
+/// Lookup table of the normalizations and the variables with which the
+/// chunks are looked up.
+pub(super) struct Tags {
+    /// Handle to the table returned by [`lookup_table`].
+    table: LookupTableId,
+    /// Variables holding the tags of the chunks, indexed by tag.
+    vars: Vec<Variable>,
+}
+
+/// Returns the [`Tags`] with which the chunks are looked up, adding the
+/// lookup table to the circuit if it is not already added.
+pub(super) fn tags<F, P>(c: &mut StandardComposer<F, P>) -> Tags
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let table = c.get_or_add_lookup_table(super::LOOKUP_TABLE, lookup_table);
+    let mut vars = vec![c.zero_var(); 9 * Normalize::ALL.len()];
+    for kind in Normalize::ALL {
+        for &size in kind.chunk_sizes() {
+            let tag = kind.tag(size);
+            vars[tag] =
+                c.add_witness_to_circuit_description(F::from(tag as u64));
+        }
+    }
+    Tags { table, vars }
+}
+
+/// Returns the value of `x` in base `base`, least significant digit first,
+/// padded with zeros to `LANE_BITS` digits.
+fn digits<F>(x: F, base: u64) -> Vec<u64>
+where
+    F: PrimeField,
+{
+    let x: BigUint = x.into();
+    let mut digits = x
+        .to_radix_le(base as u32)
+        .into_iter()
+        .map(u64::from)
+        .collect::<Vec<_>>();
+    debug_assert!(digits.len() <= LANE_BITS as usize);
+    digits.resize(LANE_BITS as usize, 0);
+    digits
+}
+
+/// Looks up the chunk `input` of `size` digits along with its image through
+/// `kind`, returning the variable holding the image.
+pub(super) fn lookup<F, P>(
+    c: &mut StandardComposer<F, P>,
+    tags: &Tags,
+    kind: Normalize,
+    size: u32,
+    input: Variable,
+) -> Variable
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let digits = digits(c.value_of_var(input), kind.base_in());
+    let (_, image) = kind.image(&digits[..size as usize]);
+    let output = c.add_input(F::from(image));
+    c.lookup_gate(
+        tags.table,
+        input,
+        tags.vars[kind.tag(size)],
+        output,
+        None,
+        None,
+    );
+    output
+}
+
+/// Normalizes `lane` through `kind`, splitting it into chunks whose numbers
+/// of digits are given by `layout`. Returns the position of the first digit
+/// of every chunk along with the variable holding its image.
+///
+/// The chunks are constrained to add up to `lane`, which they can only do in
+/// one way since their digits are below the base.
+pub(super) fn normalize<F, P>(
+    c: &mut StandardComposer<F, P>,
+    tags: &Tags,
+    kind: Normalize,
+    lane: &LinearCombination<F>,
+    layout: &[u32],
+) -> Vec<(u32, Variable)>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    debug_assert_eq!(layout.iter().sum::<u32>(), LANE_BITS);
+    let base = kind.base_in();
+    let digits = digits(lane.value(c), base);
+    let mut check = lane.clone();
+    let mut offset = 0;
+    let chunks = layout
+        .iter()
+        .map(|&size| {
+            let range = offset as usize..(offset + size) as usize;
+            let (chunk, _) = kind.image(&digits[range]);
+            let input = c.add_input(F::from(chunk));
+            let output = lookup(c, tags, kind, size, input);
+            check.add_scaled(
+                -F::from(base).pow([offset.into()]),
+                &LinearCombination::variable(input),
+            );
+            let chunk = (offset, output);
+            offset += size;
+            chunk
+        })
+        .collect();
+    check.assert_zero(c);
+    chunks
+}
+
+/// Returns the lane in base `base` made of `chunks`, rotated left by `r`
+/// bits.
+pub(super) fn recombine<F>(
+    chunks: &[(u32, Variable)],
+    base: u64,
+    r: u32,
+) -> LinearCombination<F>
+where
+    F: PrimeField,
+{
+    let mut lane = LinearCombination::constant(F::zero());
+    for (offset, chunk) in chunks {
+        let offset = (offset + r) % LANE_BITS;
+        lane.add_scaled(
+            F::from(base).pow([offset.into()]),
+            &LinearCombination::variable(*chunk),
+        );
+    }
+    lane
+}
+
+/// Returns the lane holding `bits` in base `base`.
+pub(super) fn sparse<F>(bits: u64, base: u64) -> F
+where
+    F: PrimeField,
+{
+    (0..LANE_BITS).rev().fold(F::zero(), |acc, i| {
+        acc * F::from(base) + F::from((bits >> i) & 1)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_chi_digits() {
+        // The digit 2 a + b - c + 1 determines a ^ (!b & c).
+        for bits in 0..8u64 {
+            let (a, b, c) = (bits & 1, (bits >> 1) & 1, bits >> 2);
+            let digit = 2 * a + b + 1 - c;
+            assert_eq!(Normalize::Chi.map(digit), a ^ ((1 ^ b) & c));
+        }
+    }
+
+    #[test]
+    fn test_table_size() {
+        let table = lookup_table::<Fr>();
+        assert_eq!(table.0.len(), 12 + 1728 + 6 + 36 + 216 + 1296 + 625 + 512);
+    }
+}
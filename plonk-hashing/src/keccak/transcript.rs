@@ -0,0 +1,722 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Keccak Transcript
+
+use crate::digest::keccak256;
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, SWModelParameters};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{marker::PhantomData, vec, vec::Vec};
+use plonk_core::{
+    proof_system::pi::PublicInputs, transcript::TranscriptProtocol,
+};
+
+/// Number of bytes of an EVM word.
+const WORD: usize = 32;
+
+/// Fiat-Shamir transcript over Keccak-256, whose challenges can be
+/// recomputed by an EVM verifier of KZG10 proofs over the curve whose G1
+/// group is given by `P`, such as `ark_bls12_381::g1::Parameters`.
+///
+/// The transcript holds a 32-byte state, initialized to the Keccak-256 digest
+/// of the label of [`TranscriptProtocol::new`], and the bytes appended since
+/// the last challenge. Every challenge replaces the state with
+/// `keccak256(state || appended bytes)` and clears the appended bytes. The
+/// challenge is then the 64 bytes `keccak256(state || 0x00) ||
+/// keccak256(state || 0x01)` read as a big-endian integer reduced modulo the
+/// order `r` of the scalar field, that is `addmod(mulmod(hi, 2^256 % r, r),
+/// lo, r)` in Solidity for the two digests `hi` and `lo`. Reducing 512 bits
+/// keeps the challenges within a statistical distance of `2^(b - 512)` from
+/// the uniform distribution for a field of `b` bits, where the 256 bits of a
+/// single digest would be biased by up to `2^(b - 256)`, more than `2^-3` for
+/// the scalar field of BLS12-381.
+///
+/// The labels of the appended values and of the challenges are not hashed,
+/// the order of the messages being fixed by the protocol. The values are
+/// encoded with the integers in big-endian, left-padded to a multiple of 32
+/// bytes, which is a single `uint256` for the scalar fields of BLS12-381 and
+/// BLS12-377 and two for their base fields, as the field elements of the
+/// EIP-2537 precompiles:
+///
+/// - a scalar, appended with [`TranscriptProtocol::append_scalar`], is its
+///   canonical integer,
+/// - the circuit size `n` is a `uint256`,
+/// - a raw message, appended with [`TranscriptProtocol::append_message`] as
+///   done by `key_transcript`, is its bytes as they are,
+/// - the [`LookupMode`](plonk_core::lookup::LookupMode) is a `uint256`,
+///   `0` for plookup and `1` for LogUp,
+/// - the public inputs are their number as a `uint256` followed, for every
+///   non-zero public input, by its position as a `uint256` and its value,
+/// - an opening proof is its point `w` followed, for hiding proofs only, by
+///   its scalar `random_v`,
+/// - any other item, appended with [`TranscriptProtocol::append`], is a
+///   KZG10 commitment, whose G1 point is encoded as its `x` coordinate
+///   followed by its `y` coordinate, the point at infinity being encoded as
+///   `(0, 0)`.
+///
+/// The [`Prover`](plonk_core::proof_system::Prover) and the
+/// [`Verifier`](plonk_core::proof_system::Verifier) append, in this order:
+///
+/// 1. when preprocessing, the commitments to the selectors `q_m`, `q_l`,
+///    `q_r`, `q_o`, `q_c`, `q_4`, `q_arith`, `q_range`, `q_logic`,
+///    `q_variable_group_add`, `q_fixed_group_add`, `q_poseidon`, `q_lookup`
+///    and `q_table`, the name of every custom gate followed by the commitment
+///    to its selector, the commitments to the permutation polynomials
+///    `left_sigma`, `right_sigma`, `out_sigma` and `fourth_sigma`, the
+///    commitments to the columns of the lookup table `table_1`, `table_2`,
+///    `table_3`, `table_4` and `table_id`, the lookup mode and the circuit
+///    size,
+/// 2. the public inputs and the commitments to the four wire polynomials,
+///    then draw and append the challenge `zeta`,
+/// 3. the commitment to `f`, followed by the commitments to `h_1` and `h_2`
+///    for plookup or to the multiplicities `m` for LogUp, then draw and
+///    append the challenges `beta`, `gamma`, `delta` and `epsilon`,
+/// 4. the commitment to the permutation polynomial `z`, followed by the
+///    commitment to the lookup permutation polynomial `z_2` for plookup or to
+///    the running sum `phi` for LogUp, then draw and append the challenge
+///    `alpha` and the range, logic, fixed base, variable base, Poseidon and
+///    lookup separation challenges, followed by the separation challenge of
+///    every custom gate,
+/// 5. the commitments to the pieces of the quotient polynomial, as many as
+///    the verifier key sets, then draw and append the evaluation challenge,
+/// 6. the evaluations of `a`, `b`, `c`, `d`, `left_sigma`, `right_sigma`,
+///    `out_sigma`, `z` at the shifted point, `f`, `q_lookup` and `table`,
+///    followed for plookup by the evaluations of `z_2` at the shifted point,
+///    `h_1`, `h_1` at the shifted point, `h_2` and `table` at the shifted
+///    point, or for LogUp by the evaluation of `phi` at the shifted point,
+///    followed by the evaluations needed by the custom gates, which are
+///    `q_arith`, `q_c`, `q_l`, `q_r`, `q_o`, `q_4` and `a`, `b`, `c`, `d` at
+///    the shifted point, then draw the two challenges aggregating the
+///    openings, without appending them,
+/// 7. on the verifier side only, the two opening proofs, then draw the
+///    challenge seeding the randomizer which batches the two openings into a
+///    single check.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct KeccakTranscript<P>
+where
+    P: SWModelParameters,
+{
+    state: [u8; WORD],
+    buffer: Vec<u8>,
+    __: PhantomData<P>,
+}
+
+impl<P> KeccakTranscript<P>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+{
+    /// Appends `integer` in big-endian, left-padded to a multiple of 32
+    /// bytes.
+    fn append_integer<B>(&mut self, integer: B)
+    where
+        B: BigInteger,
+    {
+        let bytes = integer.to_bytes_be();
+        let padding = (WORD - bytes.len() % WORD) % WORD;
+        self.buffer.extend(vec![0u8; padding]);
+        self.buffer.extend(bytes);
+    }
+
+    /// Appends the coordinates of `point`, or `(0, 0)` for the point at
+    /// infinity.
+    fn append_point(&mut self, point: &GroupAffine<P>) {
+        if point.infinity {
+            self.append_integer(P::BaseField::zero().into_repr());
+            self.append_integer(P::BaseField::zero().into_repr());
+        } else {
+            self.append_integer(point.x.into_repr());
+            self.append_integer(point.y.into_repr());
+        }
+    }
+}
+
+impl<P> TranscriptProtocol<P::ScalarField> for KeccakTranscript<P>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+{
+    fn new(label: &'static [u8]) -> Self {
+        Self {
+            state: keccak256(label),
+            buffer: Vec::new(),
+            __: PhantomData,
+        }
+    }
+
+    fn append_message(&mut self, _: &'static [u8], message: &[u8]) {
+        self.buffer.extend_from_slice(message);
+    }
+
+    fn append(&mut self, label: &'static [u8], item: &impl CanonicalSerialize) {
+        // The item is decoded back from its uncompressed encoding, its type
+        // being given by its label.
+        let mut bytes = Vec::new();
+        item.serialize_uncompressed(&mut bytes).unwrap();
+        let mut reader = bytes.as_slice();
+        match label {
+            b"pi" => {
+                let pi = PublicInputs::<P::ScalarField>::deserialize_unchecked(
+                    &mut reader,
+                )
+                .expect("the public inputs are encoded as such");
+                self.append_integer(ark_ff::BigInteger256::from(
+                    pi.len() as u64
+                ));
+                for (position, value) in pi.iter() {
+                    self.append_integer(ark_ff::BigInteger256::from(
+                        *position as u64,
+                    ));
+                    self.append_integer(value.into_repr());
+                }
+            }
+            b"lookup_mode" => {
+                let mode = u8::deserialize_unchecked(&mut reader)
+                    .expect("the lookup mode is encoded as a byte");
+                self.append_integer(ark_ff::BigInteger256::from(mode as u64));
+            }
+            b"aw_opening" | b"saw_opening" => {
+                let w = GroupAffine::<P>::deserialize_unchecked(&mut reader)
+                    .expect("the opening proof starts with a G1 point");
+                let random_v = Option::<P::ScalarField>::deserialize_unchecked(
+                    &mut reader,
+                )
+                .expect("the opening proof ends with an optional scalar");
+                self.append_point(&w);
+                if let Some(random_v) = random_v {
+                    self.append_integer(random_v.into_repr());
+                }
+            }
+            _ => {
+                let point =
+                    GroupAffine::<P>::deserialize_unchecked(&mut reader)
+                        .expect("the commitment is a G1 point");
+                self.append_point(&point);
+            }
+        }
+        assert!(reader.is_empty(), "the item is decoded entirely");
+    }
+
+    fn append_scalar(&mut self, _: &'static [u8], scalar: &P::ScalarField) {
+        self.append_integer(scalar.into_repr());
+    }
+
+    fn challenge_scalar(&mut self, _: &'static [u8]) -> P::ScalarField {
+        let mut input = Vec::with_capacity(WORD + self.buffer.len());
+        input.extend_from_slice(&self.state);
+        input.append(&mut self.buffer);
+        self.state = keccak256(&input);
+
+        let mut wide = [0u8; 2 * WORD];
+        for (i, half) in wide.chunks_mut(WORD).enumerate() {
+            let mut input = self.state.to_vec();
+            input.push(i as u8);
+            half.copy_from_slice(&keccak256(&input));
+        }
+        P::ScalarField::from_be_bytes_mod_order(&wide)
+    }
+
+    fn circuit_domain_sep(&mut self, n: u64) {
+        self.append_integer(ark_ff::BigInteger256::from(n));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::gadget_tester_with_transcript;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::{g1::Parameters as G1Parameters, Bls12_381, Fr};
+    use ark_ec::{AffineCurve, TEModelParameters};
+    use ark_poly_commit::kzg10;
+    use plonk_core::{
+        commitment::{HomomorphicCommitment, KZG10},
+        constraint_system::StandardComposer,
+        lookup::{LookupMode, LookupTable},
+    };
+
+    type Transcript = KeccakTranscript<G1Parameters>;
+
+    /// Returns the challenge drawn from `state`.
+    fn challenge(state: &[u8]) -> Fr {
+        let mut wide = keccak256(&[state, &[0]].concat()).to_vec();
+        wide.extend(keccak256(&[state, &[1]].concat()));
+        Fr::from_be_bytes_mod_order(&wide)
+    }
+
+    /// Returns `bytes` left-padded to `len` bytes.
+    fn padded(bytes: &[u8], len: usize) -> Vec<u8> {
+        [vec![0u8; len - bytes.len()], bytes.to_vec()].concat()
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut transcript =
+            <Transcript as TranscriptProtocol<Fr>>::new(b"plonk");
+        transcript.append_scalar(b"x", &Fr::from(0x0102u64));
+        TranscriptProtocol::<Fr>::circuit_domain_sep(&mut transcript, 8);
+        let c: Fr = transcript.challenge_scalar(b"c");
+
+        // keccak256(keccak256("plonk") || uint256(0x0102) || uint256(8))
+        let mut input = keccak256(b"plonk").to_vec();
+        input.extend(padded(&[0x01, 0x02], WORD));
+        input.extend(padded(&[8], WORD));
+        let state = keccak256(&input);
+        assert_eq!(c, challenge(&state));
+        assert_ne!(c, Fr::from_be_bytes_mod_order(&state));
+
+        // The next challenge only hashes the state.
+        let next: Fr = transcript.challenge_scalar(b"c");
+        assert_eq!(next, challenge(&keccak256(&state)));
+    }
+
+    #[test]
+    fn test_item_encoding() {
+        let g = ark_bls12_381::G1Affine::prime_subgroup_generator();
+        let mut point = padded(&g.x.into_repr().to_bytes_be(), 2 * WORD);
+        point.extend(padded(&g.y.into_repr().to_bytes_be(), 2 * WORD));
+        let mut transcript =
+            <Transcript as TranscriptProtocol<Fr>>::new(b"plonk");
+
+        // The lookup mode is a word.
+        TranscriptProtocol::<Fr>::append(
+            &mut transcript,
+            b"lookup_mode",
+            &LookupMode::LogUp,
+        );
+        assert_eq!(transcript.buffer, padded(&[1], WORD));
+        transcript.buffer.clear();
+
+        // A commitment is `x || y`, each coordinate on two words.
+        TranscriptProtocol::<Fr>::append(
+            &mut transcript,
+            b"w_l",
+            &kzg10::Commitment::<Bls12_381>(g),
+        );
+        assert_eq!(transcript.buffer, point);
+        transcript.buffer.clear();
+
+        // The point at infinity is `(0, 0)`.
+        TranscriptProtocol::<Fr>::append(
+            &mut transcript,
+            b"w_l",
+            &kzg10::Commitment::<Bls12_381>(ark_bls12_381::G1Affine::zero()),
+        );
+        assert_eq!(transcript.buffer, vec![0u8; 4 * WORD]);
+        transcript.buffer.clear();
+
+        // The public inputs are their number, then the position and value of
+        // every one of them.
+        let mut pi = PublicInputs::<Fr>::new();
+        pi.insert(3, Fr::from(7u64));
+        pi.insert(5, -Fr::from(1u64));
+        TranscriptProtocol::<Fr>::append(&mut transcript, b"pi", &pi);
+        let mut expected = padded(&[2], WORD);
+        expected.extend(padded(&[3], WORD));
+        expected.extend(padded(&[7], WORD));
+        expected.extend(padded(&[5], WORD));
+        expected.extend((-Fr::from(1u64)).into_repr().to_bytes_be());
+        assert_eq!(transcript.buffer, expected);
+        transcript.buffer.clear();
+
+        // An opening proof is its point, followed by its scalar when hiding.
+        let mut proof = kzg10::Proof::<Bls12_381> {
+            w: g,
+            random_v: None,
+        };
+        TranscriptProtocol::<Fr>::append(
+            &mut transcript,
+            b"aw_opening",
+            &proof,
+        );
+        assert_eq!(transcript.buffer, point);
+        transcript.buffer.clear();
+        proof.random_v = Some(Fr::from(9u64));
+        TranscriptProtocol::<Fr>::append(
+            &mut transcript,
+            b"saw_opening",
+            &proof,
+        );
+        assert_eq!(transcript.buffer, [point, padded(&[9], WORD)].concat());
+    }
+
+    /// Call made on a [`Recorder`].
+    #[derive(Clone, Debug)]
+    enum Call {
+        New,
+        Message,
+        Item(&'static [u8], Vec<u8>),
+        Scalar(&'static [u8], Fr),
+        CircuitSize(u64),
+        Challenge(Fr),
+    }
+
+    std::thread_local! {
+        /// Calls made on the [`Recorder`]s of the current test.
+        static CALLS: std::cell::RefCell<Vec<Call>> = Default::default();
+    }
+
+    /// [`KeccakTranscript`] recording the calls made on it in [`CALLS`].
+    #[derive(Clone)]
+    struct Recorder(Transcript);
+
+    impl Recorder {
+        fn record(call: Call) {
+            CALLS.with(|calls| calls.borrow_mut().push(call));
+        }
+    }
+
+    impl TranscriptProtocol<Fr> for Recorder {
+        fn new(label: &'static [u8]) -> Self {
+            Self::record(Call::New);
+            Self(TranscriptProtocol::<Fr>::new(label))
+        }
+
+        fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+            Self::record(Call::Message);
+            TranscriptProtocol::<Fr>::append_message(
+                &mut self.0,
+                label,
+                message,
+            )
+        }
+
+        fn append(
+            &mut self,
+            label: &'static [u8],
+            item: &impl CanonicalSerialize,
+        ) {
+            let mut bytes = Vec::new();
+            item.serialize_uncompressed(&mut bytes).unwrap();
+            Self::record(Call::Item(label, bytes));
+            TranscriptProtocol::<Fr>::append(&mut self.0, label, item)
+        }
+
+        fn append_scalar(&mut self, label: &'static [u8], scalar: &Fr) {
+            Self::record(Call::Scalar(label, *scalar));
+            self.0.append_scalar(label, scalar)
+        }
+
+        fn challenge_scalar(&mut self, label: &'static [u8]) -> Fr {
+            let challenge = self.0.challenge_scalar(label);
+            Self::record(Call::Challenge(challenge));
+            challenge
+        }
+
+        fn circuit_domain_sep(&mut self, n: u64) {
+            Self::record(Call::CircuitSize(n));
+            TranscriptProtocol::<Fr>::circuit_domain_sep(&mut self.0, n)
+        }
+    }
+
+    /// Item whose uncompressed encoding is the bytes it holds.
+    struct Encoded(Vec<u8>);
+
+    impl CanonicalSerialize for Encoded {
+        fn serialize<W: ark_serialize::Write>(
+            &self,
+            mut writer: W,
+        ) -> Result<(), ark_serialize::SerializationError> {
+            Ok(writer.write_all(&self.0)?)
+        }
+
+        fn serialized_size(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    /// [`KeccakTranscript`] fed by hand with the values recorded by a
+    /// verifier, taken by label in the documented order.
+    struct Replay {
+        calls: Vec<Call>,
+        transcript: Transcript,
+        challenges: Vec<Fr>,
+    }
+
+    impl Replay {
+        /// Removes the first recorded call matched by `take`.
+        fn take<T>(&mut self, take: impl Fn(&Call) -> Option<T>) -> T {
+            let index = self
+                .calls
+                .iter()
+                .position(|call| take(call).is_some())
+                .expect("the call was recorded");
+            take(&self.calls.remove(index)).unwrap()
+        }
+
+        fn item(&mut self, label: &'static [u8]) {
+            let bytes = self.take(|call| match call {
+                Call::Item(l, bytes) if *l == label => Some(bytes.clone()),
+                _ => None,
+            });
+            TranscriptProtocol::<Fr>::append(
+                &mut self.transcript,
+                label,
+                &Encoded(bytes),
+            );
+        }
+
+        fn items(&mut self, labels: &[&'static [u8]]) {
+            labels.iter().for_each(|label| self.item(label));
+        }
+
+        fn scalars(&mut self, labels: &[&'static [u8]]) {
+            for label in labels {
+                let scalar = self.take(|call| match call {
+                    Call::Scalar(l, scalar) if l == label => Some(*scalar),
+                    _ => None,
+                });
+                self.transcript.append_scalar(label, &scalar);
+            }
+        }
+
+        fn challenge(&mut self) -> Fr {
+            let challenge = self.transcript.challenge_scalar(b"");
+            self.challenges.push(challenge);
+            challenge
+        }
+
+        /// Draws a challenge and appends it, as the recorded value labelled
+        /// `label`.
+        fn appended_challenges(&mut self, labels: &[&'static [u8]]) {
+            for label in labels {
+                let challenge = self.challenge();
+                let recorded = self.take(|call| match call {
+                    Call::Scalar(l, scalar) if l == label => Some(*scalar),
+                    _ => None,
+                });
+                assert_eq!(challenge, recorded);
+                self.transcript.append_scalar(label, &challenge);
+            }
+        }
+    }
+
+    fn test_documented_order(mode: LookupMode) {
+        CALLS.with(|calls| calls.borrow_mut().clear());
+        let res = gadget_tester_with_transcript::<
+            Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            KZG10<Bls12_381>,
+            Recorder,
+        >(
+            |composer| {
+                composer.set_lookup_mode(mode);
+                let table = composer
+                    .add_lookup_table("xor", LookupTable::xor_table(0, 4))
+                    .unwrap();
+                let a = composer.add_input(Fr::from(3u64));
+                let b = composer.add_input(Fr::from(5u64));
+                let c = composer.add_input(Fr::from(6u64));
+                let d = composer.add_input(-Fr::from(1u64));
+                composer.lookup_gate(table, a, b, c, Some(d), None);
+                let product = composer.arithmetic_gate(|gate| {
+                    gate.witness(a, b, None)
+                        .mul(Fr::from(1u64))
+                        .pi(-Fr::from(15u64))
+                });
+                composer.constrain_to_constant(product, Fr::from(0u64), None);
+            },
+            200,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+
+        // The prover then the verifier create their transcript.
+        let calls = CALLS.with(|calls| calls.borrow().clone());
+        let mut runs = calls.split(|call| matches!(call, Call::New)).skip(1);
+        let challenges = |calls: &[Call]| {
+            calls
+                .iter()
+                .filter_map(|call| match call {
+                    Call::Challenge(challenge) => Some(*challenge),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let prover_challenges = challenges(runs.next().unwrap());
+        let verifier_calls = runs.next().unwrap().to_vec();
+        let verifier_challenges = challenges(&verifier_calls);
+
+        let mut replay = Replay {
+            calls: verifier_calls
+                .into_iter()
+                .filter(|call| !matches!(call, Call::Challenge(_)))
+                .collect(),
+            transcript: TranscriptProtocol::<Fr>::new(b"demo"),
+            challenges: Vec::new(),
+        };
+
+        // 1. The verifier key.
+        replay.items(&[
+            b"q_m",
+            b"q_l",
+            b"q_r",
+            b"q_o",
+            b"q_c",
+            b"q_4",
+            b"q_arith",
+            b"q_range",
+            b"q_logic",
+            b"q_variable_group_add",
+            b"q_fixed_group_add",
+            b"q_poseidon",
+            b"q_lookup",
+            b"q_table",
+            b"left_sigma",
+            b"right_sigma",
+            b"out_sigma",
+            b"fourth_sigma",
+            b"table_1",
+            b"table_2",
+            b"table_3",
+            b"table_4",
+            b"table_id",
+            b"lookup_mode",
+        ]);
+        let n = replay.take(|call| match call {
+            Call::CircuitSize(n) => Some(*n),
+            _ => None,
+        });
+        TranscriptProtocol::<Fr>::circuit_domain_sep(&mut replay.transcript, n);
+
+        // 2. The public inputs and the wires.
+        replay.items(&[b"pi", b"w_l", b"w_r", b"w_o", b"w_4"]);
+        replay.appended_challenges(&[b"zeta"]);
+
+        // 3. The query and the sets of the lookup argument.
+        replay.item(b"f");
+        match mode {
+            LookupMode::Plookup => replay.items(&[b"h1", b"h2"]),
+            LookupMode::LogUp => replay.item(b"m"),
+        }
+        replay.appended_challenges(&[b"beta", b"gamma", b"delta", b"epsilon"]);
+
+        // 4. The permutation and lookup accumulators.
+        replay.item(b"z");
+        match mode {
+            LookupMode::Plookup => replay.item(b"z_2"),
+            LookupMode::LogUp => replay.item(b"phi"),
+        }
+        replay.appended_challenges(&[
+            b"alpha",
+            b"range seperation challenge",
+            b"logic seperation challenge",
+            b"fixed base separation challenge",
+            b"variable base separation challenge",
+            b"poseidon separation challenge",
+            b"lookup separation challenge",
+        ]);
+
+        // 5. The four pieces of the quotient polynomial of a circuit without
+        // Poseidon round gates.
+        replay.items(&[b"t", b"t", b"t", b"t"]);
+        replay.appended_challenges(&[b"z"]);
+
+        // 6. The evaluations.
+        replay.scalars(&[
+            b"a_eval",
+            b"b_eval",
+            b"c_eval",
+            b"d_eval",
+            b"left_sig_eval",
+            b"right_sig_eval",
+            b"out_sig_eval",
+            b"perm_eval",
+            b"f_eval",
+            b"q_lookup_eval",
+            b"table_eval",
+        ]);
+        match mode {
+            LookupMode::Plookup => replay.scalars(&[
+                b"lookup_perm_eval",
+                b"h_1_eval",
+                b"h_1_next_eval",
+                b"h_2_eval",
+                b"table_next_eval",
+            ]),
+            LookupMode::LogUp => replay.scalars(&[b"phi_next_eval"]),
+        }
+        replay.scalars(&[
+            b"q_arith_eval",
+            b"q_c_eval",
+            b"q_l_eval",
+            b"q_r_eval",
+            b"q_o_eval",
+            b"q_4_eval",
+            b"a_next_eval",
+            b"b_next_eval",
+            b"c_next_eval",
+            b"d_next_eval",
+        ]);
+        replay.challenge();
+        replay.challenge();
+
+        // 7. The opening proofs.
+        replay.items(&[b"aw_opening", b"saw_opening"]);
+        replay.challenge();
+
+        assert!(replay.calls.is_empty(), "{:?}", replay.calls);
+        assert_eq!(replay.challenges, verifier_challenges);
+        assert_eq!(
+            replay.challenges[..replay.challenges.len() - 1],
+            prover_challenges
+        );
+    }
+
+    #[test]
+    fn test_documented_order_plookup() {
+        test_documented_order(LookupMode::Plookup);
+    }
+
+    #[test]
+    fn test_documented_order_log_up() {
+        test_documented_order(LookupMode::LogUp);
+    }
+
+    fn test_proof<F, P, PC, G>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+        G: SWModelParameters<ScalarField = F>,
+        G::BaseField: PrimeField,
+    {
+        let res = gadget_tester_with_transcript::<F, P, PC, KeccakTranscript<G>>(
+            |composer: &mut StandardComposer<F, P>| {
+                let a = composer.add_input(F::from(3u64));
+                let b = composer.add_input(F::from(5u64));
+                let c = composer.arithmetic_gate(|gate| {
+                    gate.witness(a, b, None).mul(F::one()).pi(-F::from(15u64))
+                });
+                composer.constrain_to_constant(c, F::zero(), None);
+                composer.range_gate(a, 8);
+            },
+            200,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    #[test]
+    fn test_proof_on_bls12_381() {
+        test_proof::<
+            Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            KZG10<Bls12_381>,
+            G1Parameters,
+        >()
+    }
+
+    #[test]
+    fn test_proof_on_bls12_377() {
+        test_proof::<
+            ark_bls12_377::Fr,
+            ark_ed_on_bls12_377::EdwardsParameters,
+            KZG10<Bls12_377>,
+            ark_bls12_377::g1::Parameters,
+        >()
+    }
+}
@@ -10,3 +10,18 @@
 #![cfg_attr(doc_cfg, feature(doc_cfg))]
 #![forbid(rustdoc::broken_intra_doc_links)]
 #![forbid(missing_docs)]
+
+pub mod anemoi;
+mod arithmetic;
+pub mod blake2s;
+mod digest;
+pub mod keccak;
+pub mod merkle;
+pub mod mimc;
+pub mod pedersen;
+pub mod poseidon;
+pub mod rescue;
+pub mod sha256;
+
+#[cfg(test)]
+mod test;
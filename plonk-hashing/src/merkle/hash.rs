@@ -0,0 +1,152 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Two-to-One Hashes
+
+use crate::{
+    merkle::TwoToOneHash,
+    mimc::{self, MimcConstants, MimcSpec, MimcSponge},
+    poseidon::{self, PoseidonConstants, PoseidonError, PoseidonSponge},
+};
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use plonk_core::constraint_system::{StandardComposer, Variable};
+
+/// Poseidon sponge of rate two hashing the pair of nodes with
+/// [`PoseidonSponge::hash`].
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug, PartialEq, Eq)]
+pub struct PoseidonHash<F>
+where
+    F: PrimeField,
+{
+    constants: PoseidonConstants<F>,
+}
+
+impl<F> PoseidonHash<F>
+where
+    F: PrimeField,
+{
+    /// Creates the hash over the permutation defined by `constants`, whose
+    /// width must be at least three.
+    pub fn new(constants: PoseidonConstants<F>) -> Result<Self, PoseidonError> {
+        if constants.width < 3 {
+            return Err(PoseidonError::InvalidRate);
+        }
+        Ok(Self { constants })
+    }
+}
+
+impl<F> TwoToOneHash<(), F> for PoseidonHash<F>
+where
+    F: PrimeField,
+{
+    type Field = F;
+
+    fn compress(&self, c: &mut (), left: &F, right: &F) -> F {
+        PoseidonSponge::<_, poseidon::NativeSpec, _>::hash(
+            c,
+            self.constants.clone(),
+            2,
+            &[*left, *right],
+        )
+        .expect("width checked on creation")
+    }
+}
+
+impl<F, P> TwoToOneHash<StandardComposer<F, P>, F> for PoseidonHash<F>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    type Field = Variable;
+
+    fn compress(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        left: &Variable,
+        right: &Variable,
+    ) -> Variable {
+        PoseidonSponge::<_, poseidon::PlonkSpec, _>::hash(
+            c,
+            self.constants.clone(),
+            2,
+            &[*left, *right],
+        )
+        .expect("width checked on creation")
+    }
+}
+
+/// MiMC-Feistel sponge with a zero key hashing the pair of nodes into one
+/// element, as the `hashLeftRight` of the Tornado Cash Merkle trees.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug, PartialEq, Eq)]
+pub struct MimcHash<F>
+where
+    F: PrimeField,
+{
+    constants: MimcConstants<F>,
+}
+
+impl<F> MimcHash<F>
+where
+    F: PrimeField,
+{
+    /// Creates the hash over the permutation defined by `constants`.
+    pub fn new(constants: MimcConstants<F>) -> Self {
+        Self { constants }
+    }
+
+    /// Hashes `left` and `right` with the sponge of the backend `S`.
+    fn sponge<COM, S>(
+        &self,
+        c: &mut COM,
+        left: &S::Field,
+        right: &S::Field,
+    ) -> S::Field
+    where
+        S: MimcSpec<COM, F>,
+    {
+        let key = S::zero(c);
+        let inputs = [left.clone(), right.clone()];
+        MimcSponge::<COM, S, F>::hash(
+            c,
+            self.constants.clone(),
+            &inputs,
+            &key,
+            1,
+        )
+        .remove(0)
+    }
+}
+
+impl<F> TwoToOneHash<(), F> for MimcHash<F>
+where
+    F: PrimeField,
+{
+    type Field = F;
+
+    fn compress(&self, c: &mut (), left: &F, right: &F) -> F {
+        self.sponge::<_, mimc::NativeSpec>(c, left, right)
+    }
+}
+
+impl<F, P> TwoToOneHash<StandardComposer<F, P>, F> for MimcHash<F>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    type Field = Variable;
+
+    fn compress(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        left: &Variable,
+        right: &Variable,
+    ) -> Variable {
+        self.sponge::<_, mimc::PlonkSpec>(c, left, right)
+    }
+}
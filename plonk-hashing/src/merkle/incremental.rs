@@ -0,0 +1,238 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Incremental Merkle Tree
+
+use crate::merkle::{
+    native::empty_roots, MerkleError, MerklePath, TwoToOneHash,
+};
+use ark_ff::PrimeField;
+use ark_std::{vec, vec::Vec};
+
+/// Append-only Merkle tree of a fixed depth, whose leaves are filled from
+/// left to right, the leaves not appended yet holding an empty value.
+///
+/// Every node computed so far is kept, so that the path of any appended leaf
+/// can be exported for the [`MerkleGadget`](crate::merkle::MerkleGadget).
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = "H: Clone"), Debug(bound = "H: core::fmt::Debug"))]
+pub struct IncrementalMerkleTree<F, H>
+where
+    F: PrimeField,
+{
+    hash: H,
+    depth: usize,
+    /// Roots of the empty subtrees, by height.
+    empty_roots: Vec<F>,
+    /// Nodes computed so far, by height, the leftmost first.
+    levels: Vec<Vec<F>>,
+}
+
+impl<F, H> IncrementalMerkleTree<F, H>
+where
+    F: PrimeField,
+    H: TwoToOneHash<(), F, Field = F>,
+{
+    /// Creates an empty tree of `depth` levels above the leaves, whose empty
+    /// leaves hold zero.
+    pub fn new(hash: H, depth: usize) -> Self {
+        Self::with_empty_leaf(hash, depth, F::zero())
+    }
+
+    /// Creates an empty tree of `depth` levels above the leaves, whose empty
+    /// leaves hold `empty_leaf`.
+    pub fn with_empty_leaf(hash: H, depth: usize, empty_leaf: F) -> Self {
+        let empty_roots = empty_roots(&hash, depth, empty_leaf);
+        Self {
+            hash,
+            depth,
+            empty_roots,
+            levels: vec![Vec::new(); depth + 1],
+        }
+    }
+
+    /// Returns the number of levels above the leaves.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of leaves appended so far.
+    pub fn len(&self) -> u64 {
+        self.levels[0].len() as u64
+    }
+
+    /// Returns `true` if no leaf has been appended yet.
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Returns the node at `index` on the level of height `level`.
+    fn node(&self, level: usize, index: u64) -> F {
+        self.levels[level]
+            .get(index as usize)
+            .copied()
+            .unwrap_or(self.empty_roots[level])
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> F {
+        self.node(self.depth, 0)
+    }
+
+    /// Returns the leaf at `index`, if it has been appended.
+    pub fn leaf(&self, index: u64) -> Option<F> {
+        self.levels[0].get(index as usize).copied()
+    }
+
+    /// Appends `leaf` to the tree, returning its index.
+    pub fn append(&mut self, leaf: F) -> Result<u64, MerkleError> {
+        let index = self.len();
+        if index.checked_shr(self.depth as u32).unwrap_or(0) != 0 {
+            return Err(MerkleError::TreeFull);
+        }
+        self.levels[0].push(leaf);
+        let mut i = index;
+        for level in 0..self.depth {
+            let parent = i >> 1;
+            let left = self.node(level, parent << 1);
+            let right = self.node(level, (parent << 1) | 1);
+            let node = self.hash.compress(&mut (), &left, &right);
+            let nodes = &mut self.levels[level + 1];
+            if (parent as usize) < nodes.len() {
+                nodes[parent as usize] = node;
+            } else {
+                nodes.push(node);
+            }
+            i = parent;
+        }
+        Ok(index)
+    }
+
+    /// Returns the path of the leaf at `index`, which must have been
+    /// appended.
+    pub fn path(&self, index: u64) -> Result<MerklePath<bool, F>, MerkleError> {
+        if index >= self.len() {
+            return Err(MerkleError::IndexOutOfRange);
+        }
+        let mut directions = Vec::with_capacity(self.depth);
+        let mut siblings = Vec::with_capacity(self.depth);
+        let mut i = index;
+        for level in 0..self.depth {
+            directions.push(i & 1 == 1);
+            siblings.push(self.node(level, i ^ 1));
+            i >>= 1;
+        }
+        MerklePath::new(directions, siblings)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test,
+        merkle::{MerkleGadget, PoseidonHash},
+        poseidon::PoseidonConstants,
+        test::gadget_tester,
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::TEModelParameters;
+    use ark_std::{test_rng, UniformRand};
+    use plonk_core::{
+        commitment::HomomorphicCommitment, constraint_system::StandardComposer,
+    };
+
+    #[test]
+    fn test_append() {
+        use ark_bls12_381::Fr;
+        let mut rng = test_rng();
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<Fr>::generate(3).unwrap())
+                .unwrap();
+        let mut tree = IncrementalMerkleTree::new(hash.clone(), 3);
+        let empty_root = tree.root();
+        assert_eq!(tree.path(0).err(), Some(MerkleError::IndexOutOfRange));
+
+        let leaves = (0..5).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
+        for (i, leaf) in leaves.iter().enumerate() {
+            assert_eq!(tree.append(*leaf), Ok(i as u64));
+        }
+        assert_ne!(tree.root(), empty_root);
+        for (i, leaf) in leaves.iter().enumerate() {
+            let path = tree.path(i as u64).unwrap();
+            assert_eq!(path.index(), i as u64);
+            assert_eq!(path.root(&hash, *leaf), tree.root());
+        }
+
+        // Root recomputed level by level from the padded leaves.
+        let mut level = leaves.clone();
+        level.resize(8, Fr::from(0u64));
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| hash.compress(&mut (), &pair[0], &pair[1]))
+                .collect();
+        }
+        assert_eq!(level[0], tree.root());
+
+        for _ in 5..8 {
+            tree.append(Fr::rand(&mut rng)).unwrap();
+        }
+        assert_eq!(tree.append(Fr::rand(&mut rng)), Err(MerkleError::TreeFull));
+    }
+
+    fn test_path_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<F>::generate(3).unwrap())
+                .unwrap();
+        let mut tree = IncrementalMerkleTree::new(hash.clone(), 4);
+        for _ in 0..6 {
+            tree.append(F::rand(&mut rng)).unwrap();
+        }
+        let leaf = tree.leaf(5).unwrap();
+        let path = tree.path(5).unwrap();
+        let root = tree.root();
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let gadget = MerkleGadget::new(hash.clone(), 4);
+                let leaf = composer.add_input(leaf);
+                let root = composer.add_input(root);
+                let path = path.witness(composer);
+                gadget
+                    .assert_membership(composer, root, leaf, &path)
+                    .unwrap();
+            },
+            4096,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_path_gadget
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_path_gadget
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
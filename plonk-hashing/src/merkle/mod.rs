@@ -0,0 +1,139 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Merkle Trees
+//!
+//! Gadgets proving that a leaf belongs to a binary Merkle tree of fixed
+//! depth, and that replacing a leaf moves the tree from one root to another,
+//! generic over the [`TwoToOneHash`] compressing two nodes into their parent.
+//!
+//! A leaf is authenticated by a [`MerklePath`], which holds for every level,
+//! from the leaf up to the root, the sibling of the current node and the
+//! direction bit telling whether the current node is the right child. The
+//! gadget orders each pair of nodes with
+//! [`StandardComposer::conditional_select`](plonk_core::constraint_system::StandardComposer::conditional_select)
+//! before hashing it.
+//!
+//! The [`SparseMerkleGadget`] addresses the leaves by a key, whose bits are
+//! the direction bits of its path, to prove that a key is present in a tree
+//! or, as required by nullifier sets, that it is absent.
+//!
+//! Services can maintain the same trees natively with the append-only
+//! [`IncrementalMerkleTree`] and the key-indexed [`SparseMerkleTree`], whose
+//! paths are the ones consumed by the gadget.
+
+mod hash;
+mod incremental;
+mod native;
+mod plonk;
+mod sparse;
+
+pub use hash::{MimcHash, PoseidonHash};
+pub use incremental::IncrementalMerkleTree;
+pub use plonk::{MerkleGadget, SparseMerkleGadget};
+pub use sparse::SparseMerkleTree;
+
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use core::fmt;
+
+/// Hash function compressing two nodes of a Merkle tree into their parent.
+///
+/// `COM` is the context the computation happens in, `()` for the native
+/// implementation and a
+/// [`StandardComposer`](plonk_core::constraint_system::StandardComposer) for
+/// the gadget. Implementing both contexts on the same type guarantees that
+/// native trees and circuits hash their nodes identically.
+pub trait TwoToOneHash<COM, F>
+where
+    F: PrimeField,
+{
+    /// Representation of a node.
+    type Field: Clone;
+
+    /// Returns the parent of the nodes `left` and `right`.
+    fn compress(
+        &self,
+        c: &mut COM,
+        left: &Self::Field,
+        right: &Self::Field,
+    ) -> Self::Field;
+}
+
+/// Authentication path of a leaf, from the leaf up to the root.
+///
+/// `B` is the representation of the direction bits, `bool` natively and a
+/// boolean [`Variable`](plonk_core::constraint_system::Variable) in a
+/// circuit, and `T` the representation of the nodes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MerklePath<B, T> {
+    /// Whether the node at each level is the right child of its parent.
+    pub directions: Vec<B>,
+    /// Sibling of the node at each level.
+    pub siblings: Vec<T>,
+}
+
+impl<B, T> MerklePath<B, T> {
+    /// Creates a path from its direction bits and siblings, both ordered from
+    /// the leaf up to the root.
+    pub fn new(
+        directions: Vec<B>,
+        siblings: Vec<T>,
+    ) -> Result<Self, MerkleError> {
+        if directions.len() != siblings.len() {
+            return Err(MerkleError::InvalidPathLength);
+        }
+        Ok(Self {
+            directions,
+            siblings,
+        })
+    }
+
+    /// Returns the number of levels of the path.
+    pub fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+}
+
+impl<T> MerklePath<bool, T> {
+    /// Returns the index of the leaf authenticated by the path, whose bits
+    /// are the direction bits, least significant first.
+    pub fn index(&self) -> u64 {
+        self.directions
+            .iter()
+            .rev()
+            .fold(0, |acc, bit| (acc << 1) | *bit as u64)
+    }
+}
+
+/// Merkle Tree Errors
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MerkleError {
+    /// A path must hold as many direction bits as siblings, one per level of
+    /// the tree.
+    InvalidPathLength,
+    /// Every leaf of the tree has already been appended.
+    TreeFull,
+    /// The index does not address a leaf of the tree.
+    IndexOutOfRange,
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPathLength => {
+                write!(f, "Path length does not match the tree depth")
+            }
+            Self::TreeFull => write!(f, "Merkle tree is full"),
+            Self::IndexOutOfRange => {
+                write!(f, "Index does not address a leaf of the tree")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MerkleError {}
@@ -0,0 +1,50 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Native Merkle Paths
+
+use crate::merkle::{MerklePath, TwoToOneHash};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+/// Returns the roots of the empty subtrees of heights `0` to `depth`, whose
+/// leaves all hold `empty_leaf`.
+pub(super) fn empty_roots<F, H>(hash: &H, depth: usize, empty_leaf: F) -> Vec<F>
+where
+    F: PrimeField,
+    H: TwoToOneHash<(), F, Field = F>,
+{
+    let mut roots = Vec::with_capacity(depth + 1);
+    roots.push(empty_leaf);
+    for level in 0..depth {
+        let root = hash.compress(&mut (), &roots[level], &roots[level]);
+        roots.push(root);
+    }
+    roots
+}
+
+impl<F> MerklePath<bool, F>
+where
+    F: PrimeField,
+{
+    /// Returns the root of the tree in which `leaf` sits at the end of the
+    /// path.
+    pub fn root<H>(&self, hash: &H, leaf: F) -> F
+    where
+        H: TwoToOneHash<(), F, Field = F>,
+    {
+        self.directions.iter().zip(&self.siblings).fold(
+            leaf,
+            |node, (is_right, sibling)| {
+                if *is_right {
+                    hash.compress(&mut (), sibling, &node)
+                } else {
+                    hash.compress(&mut (), &node, sibling)
+                }
+            },
+        )
+    }
+}
@@ -0,0 +1,584 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Merkle Tree Gadget
+
+use crate::{
+    arithmetic::to_bits_le,
+    merkle::{MerkleError, MerklePath, TwoToOneHash},
+};
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use plonk_core::constraint_system::{StandardComposer, Variable};
+
+impl<F> MerklePath<bool, F>
+where
+    F: PrimeField,
+{
+    /// Witnesses the path in `c`, constraining every direction bit to be
+    /// boolean.
+    pub fn witness<P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+    ) -> MerklePath<Variable, Variable>
+    where
+        P: TEModelParameters<BaseField = F>,
+    {
+        let directions = self
+            .directions
+            .iter()
+            .map(|bit| {
+                let bit = c.add_input(F::from(*bit as u64));
+                c.boolean_gate(bit)
+            })
+            .collect();
+        let siblings = self.siblings.iter().map(|s| c.add_input(*s)).collect();
+        MerklePath {
+            directions,
+            siblings,
+        }
+    }
+}
+
+/// Merkle tree gadget for trees of a fixed depth, hashing their nodes with
+/// `H`.
+///
+/// # Note
+///
+/// The direction bits of the paths given to the gadget should have
+/// previously been constrained to be boolean, as done by
+/// [`MerklePath::witness`].
+#[derive(Clone, Debug)]
+pub struct MerkleGadget<H> {
+    hash: H,
+    depth: usize,
+}
+
+impl<H> MerkleGadget<H> {
+    /// Creates a gadget for trees with `depth` levels above the leaves.
+    pub fn new(hash: H, depth: usize) -> Self {
+        Self { hash, depth }
+    }
+
+    /// Returns the depth of the trees.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the hash compressing the nodes.
+    pub fn hash(&self) -> &H {
+        &self.hash
+    }
+
+    /// Returns the root of the tree in which `leaf` sits at the end of
+    /// `path`.
+    pub fn root<F, P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        leaf: Variable,
+        path: &MerklePath<Variable, Variable>,
+    ) -> Result<Variable, MerkleError>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        H: TwoToOneHash<StandardComposer<F, P>, F, Field = Variable>,
+    {
+        if path.depth() != self.depth
+            || path.directions.len() != path.siblings.len()
+        {
+            return Err(MerkleError::InvalidPathLength);
+        }
+        let mut node = leaf;
+        for (is_right, sibling) in path.directions.iter().zip(&path.siblings) {
+            let left = c.conditional_select(*is_right, *sibling, node);
+            let right = c.conditional_select(*is_right, node, *sibling);
+            node = self.hash.compress(c, &left, &right);
+        }
+        Ok(node)
+    }
+
+    /// Constrains `leaf` to sit at the end of `path` in the tree of root
+    /// `root`.
+    pub fn assert_membership<F, P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        root: Variable,
+        leaf: Variable,
+        path: &MerklePath<Variable, Variable>,
+    ) -> Result<(), MerkleError>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        H: TwoToOneHash<StandardComposer<F, P>, F, Field = Variable>,
+    {
+        let computed = self.root(c, leaf, path)?;
+        c.assert_equal(computed, root);
+        Ok(())
+    }
+
+    /// Constrains `old_leaf` to sit at the end of `path` in the tree of root
+    /// `old_root`, and returns the root of the tree in which it has been
+    /// replaced by `new_leaf`.
+    pub fn update<F, P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        old_root: Variable,
+        old_leaf: Variable,
+        new_leaf: Variable,
+        path: &MerklePath<Variable, Variable>,
+    ) -> Result<Variable, MerkleError>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        H: TwoToOneHash<StandardComposer<F, P>, F, Field = Variable>,
+    {
+        self.assert_membership(c, old_root, old_leaf, path)?;
+        self.root(c, new_leaf, path)
+    }
+}
+
+/// Sparse Merkle tree gadget, for trees whose leaves are addressed by a key
+/// and hold an empty value unless set, proving both the presence and the
+/// absence of a key.
+///
+/// The direction bits of the path of a key are its bits, derived in-circuit
+/// with a canonical decomposition, so that the leaf at index `key` of a
+/// [`SparseMerkleTree`](crate::merkle::SparseMerkleTree) of the same depth,
+/// `key.into_repr().into()`, is the only one a key can be proven against.
+/// When the depth is below the number of bits of the modulus, keys must be
+/// smaller than `2^depth`.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = "H: Clone"), Debug(bound = "H: core::fmt::Debug"))]
+pub struct SparseMerkleGadget<F, H>
+where
+    F: PrimeField,
+{
+    merkle: MerkleGadget<H>,
+    empty_leaf: F,
+}
+
+impl<F, H> SparseMerkleGadget<F, H>
+where
+    F: PrimeField,
+{
+    /// Creates a gadget for trees with `depth` levels above the leaves,
+    /// whose empty leaves hold zero.
+    pub fn new(hash: H, depth: usize) -> Self {
+        Self::with_empty_leaf(hash, depth, F::zero())
+    }
+
+    /// Creates a gadget for trees with `depth` levels above the leaves,
+    /// whose empty leaves hold `empty_leaf`.
+    pub fn with_empty_leaf(hash: H, depth: usize, empty_leaf: F) -> Self {
+        Self {
+            merkle: MerkleGadget::new(hash, depth),
+            empty_leaf,
+        }
+    }
+
+    /// Returns the depth of the trees.
+    pub fn depth(&self) -> usize {
+        self.merkle.depth()
+    }
+
+    /// Returns the path of `key` in a tree where its siblings are
+    /// `siblings`, from the leaf up to the root.
+    pub fn key_path<P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        key: Variable,
+        siblings: &[Variable],
+    ) -> Result<MerklePath<Variable, Variable>, MerkleError>
+    where
+        P: TEModelParameters<BaseField = F>,
+    {
+        if siblings.len() != self.depth() {
+            return Err(MerkleError::InvalidPathLength);
+        }
+        let directions = to_bits_le(c, key, self.depth());
+        MerklePath::new(directions, siblings.to_vec())
+    }
+
+    /// Constrains `key` to be set to `value` in the tree of root `root`.
+    pub fn assert_membership<P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        root: Variable,
+        key: Variable,
+        value: Variable,
+        siblings: &[Variable],
+    ) -> Result<(), MerkleError>
+    where
+        P: TEModelParameters<BaseField = F>,
+        H: TwoToOneHash<StandardComposer<F, P>, F, Field = Variable>,
+    {
+        let path = self.key_path(c, key, siblings)?;
+        self.merkle.assert_membership(c, root, value, &path)
+    }
+
+    /// Constrains `key` to be absent from the tree of root `root`, its leaf
+    /// holding the empty value.
+    pub fn assert_non_membership<P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        root: Variable,
+        key: Variable,
+        siblings: &[Variable],
+    ) -> Result<(), MerkleError>
+    where
+        P: TEModelParameters<BaseField = F>,
+        H: TwoToOneHash<StandardComposer<F, P>, F, Field = Variable>,
+    {
+        let path = self.key_path(c, key, siblings)?;
+        let empty = c.add_witness_to_circuit_description(self.empty_leaf);
+        self.merkle.assert_membership(c, root, empty, &path)
+    }
+
+    /// Constrains `key` to be absent from the tree of root `old_root`, and
+    /// returns the root of the tree in which it has been set to `value`, as
+    /// when adding a nullifier to a set.
+    pub fn insert<P>(
+        &self,
+        c: &mut StandardComposer<F, P>,
+        old_root: Variable,
+        key: Variable,
+        value: Variable,
+        siblings: &[Variable],
+    ) -> Result<Variable, MerkleError>
+    where
+        P: TEModelParameters<BaseField = F>,
+        H: TwoToOneHash<StandardComposer<F, P>, F, Field = Variable>,
+    {
+        let path = self.key_path(c, key, siblings)?;
+        let empty = c.add_witness_to_circuit_description(self.empty_leaf);
+        self.merkle.update(c, old_root, empty, value, &path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test,
+        merkle::{MimcHash, PoseidonHash, SparseMerkleTree},
+        mimc::MimcConstants,
+        poseidon::{smallest_alpha, PoseidonConstants},
+        test::gadget_tester,
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_std::{test_rng, vec::Vec, UniformRand};
+    use num_bigint::BigUint;
+    use plonk_core::commitment::HomomorphicCommitment;
+
+    /// Returns a random path of `depth` levels.
+    fn random_path<F>(depth: usize) -> MerklePath<bool, F>
+    where
+        F: PrimeField,
+    {
+        let mut rng = test_rng();
+        let directions = (0..depth).map(|_| bool::rand(&mut rng)).collect();
+        let siblings = (0..depth).map(|_| F::rand(&mut rng)).collect();
+        MerklePath::new(directions, siblings).unwrap()
+    }
+
+    fn test_membership<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<F>::generate(3).unwrap())
+                .unwrap();
+        let path = random_path::<F>(4);
+        let leaf = F::rand(&mut test_rng());
+        let root = path.root(&hash, leaf);
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let gadget = MerkleGadget::new(hash.clone(), 4);
+                let leaf = composer.add_input(leaf);
+                let path = path.witness(composer);
+                let computed = gadget.root(composer, leaf, &path).unwrap();
+                assert_eq!(composer.value_of_var(computed), root);
+                composer.constrain_to_constant(computed, root, None);
+            },
+            4096,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_membership_wrong_leaf<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<F>::generate(3).unwrap())
+                .unwrap();
+        let path = random_path::<F>(4);
+        let root = path.root(&hash, F::rand(&mut test_rng()));
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let gadget = MerkleGadget::new(hash.clone(), 4);
+                let leaf = composer.add_input(F::one());
+                let root = composer.add_input(root);
+                let path = path.witness(composer);
+                gadget
+                    .assert_membership(composer, root, leaf, &path)
+                    .unwrap();
+            },
+            4096,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_update<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        let constants = MimcConstants::<F>::from_seed(
+            "mimcsponge",
+            220,
+            smallest_alpha::<F>(),
+        )
+        .unwrap();
+        let hash = MimcHash::new(constants);
+        let path = random_path::<F>(3);
+        let (old_leaf, new_leaf) = (F::rand(&mut rng), F::rand(&mut rng));
+        let old_root = path.root(&hash, old_leaf);
+        let new_root = path.root(&hash, new_leaf);
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let gadget = MerkleGadget::new(hash.clone(), 3);
+                let old_root = composer.add_input(old_root);
+                let old_leaf = composer.add_input(old_leaf);
+                let new_leaf = composer.add_input(new_leaf);
+                let path = path.witness(composer);
+                let computed = gadget
+                    .update(composer, old_root, old_leaf, new_leaf, &path)
+                    .unwrap();
+                assert_eq!(composer.value_of_var(computed), new_root);
+                composer.constrain_to_constant(computed, new_root, None);
+            },
+            16384,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    /// Returns a sparse tree of depth `depth` in which the keys 3 and 200 are
+    /// set to random values.
+    fn sparse_tree<F>(
+        hash: &PoseidonHash<F>,
+        depth: usize,
+    ) -> SparseMerkleTree<F, PoseidonHash<F>>
+    where
+        F: PrimeField,
+    {
+        let mut rng = test_rng();
+        let mut tree = SparseMerkleTree::new(hash.clone(), depth);
+        for key in [3u64, 200] {
+            tree.insert(&BigUint::from(key), F::rand(&mut rng)).unwrap();
+        }
+        tree
+    }
+
+    fn test_sparse_membership<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<F>::generate(3).unwrap())
+                .unwrap();
+        let tree = sparse_tree(&hash, 8);
+        let (present, absent) = (BigUint::from(200u64), BigUint::from(17u64));
+        let value = tree.leaf(&present).unwrap();
+        let present_path = tree.path(&present).unwrap();
+        let absent_path = tree.path(&absent).unwrap();
+        let root = tree.root();
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let gadget = SparseMerkleGadget::new(hash.clone(), 8);
+                let root = composer.add_input(root);
+                let key = composer.add_input(F::from(200u64));
+                let value = composer.add_input(value);
+                let siblings = present_path.witness(composer).siblings;
+                gadget
+                    .assert_membership(composer, root, key, value, &siblings)
+                    .unwrap();
+                let key = composer.add_input(F::from(17u64));
+                let siblings = absent_path.witness(composer).siblings;
+                gadget
+                    .assert_non_membership(composer, root, key, &siblings)
+                    .unwrap();
+            },
+            16384,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_sparse_non_membership_present_key<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<F>::generate(3).unwrap())
+                .unwrap();
+        let tree = sparse_tree(&hash, 8);
+        let path = tree.path(&BigUint::from(3u64)).unwrap();
+        let root = tree.root();
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let gadget = SparseMerkleGadget::new(hash.clone(), 8);
+                let root = composer.add_input(root);
+                let key = composer.add_input(F::from(3u64));
+                let siblings = path.witness(composer).siblings;
+                gadget
+                    .assert_non_membership(composer, root, key, &siblings)
+                    .unwrap();
+            },
+            8192,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_sparse_insert<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<F>::generate(3).unwrap())
+                .unwrap();
+        let mut tree = sparse_tree(&hash, 8);
+        let key = BigUint::from(17u64);
+        let path = tree.path(&key).unwrap();
+        let old_root = tree.root();
+        tree.insert(&key, F::one()).unwrap();
+        let new_root = tree.root();
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let gadget = SparseMerkleGadget::new(hash.clone(), 8);
+                let old_root = composer.add_input(old_root);
+                let key = composer.add_input(F::from(17u64));
+                let value = composer.add_input(F::one());
+                let siblings = path.witness(composer).siblings;
+                let computed = gadget
+                    .insert(composer, old_root, key, value, &siblings)
+                    .unwrap();
+                assert_eq!(composer.value_of_var(computed), new_root);
+                composer.constrain_to_constant(computed, new_root, None);
+            },
+            16384,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    #[test]
+    fn test_sparse_full_depth() {
+        // Keys spanning the whole field in a tree of depth 256, checked
+        // without proving the circuit.
+        use ark_bls12_381::Fr;
+        use ark_ff::{BigInteger, PrimeField};
+        let mut rng = test_rng();
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<Fr>::generate(3).unwrap())
+                .unwrap();
+        let mut tree = SparseMerkleTree::new(hash.clone(), 256);
+        let (present, absent) = (Fr::rand(&mut rng), -Fr::from(1u64));
+        tree.insert(&present.into_repr().into(), Fr::from(1u64))
+            .unwrap();
+        let path = tree.path(&absent.into_repr().into()).unwrap();
+
+        let mut composer = StandardComposer::<
+            Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+        >::new();
+        let gadget = SparseMerkleGadget::new(hash, 256);
+        let key = composer.add_input(absent);
+        let siblings = path.witness(&mut composer).siblings;
+        let path = gadget.key_path(&mut composer, key, &siblings).unwrap();
+        let bits = absent.into_repr().to_bits_le();
+        for (var, bit) in path.directions.iter().zip(bits) {
+            assert_eq!(composer.value_of_var(*var), Fr::from(bit as u64));
+        }
+        let empty = composer.add_input(Fr::from(0u64));
+        let root = gadget.merkle.root(&mut composer, empty, &path).unwrap();
+        assert_eq!(composer.value_of_var(root), tree.root());
+    }
+
+    #[test]
+    fn test_path_length() {
+        use ark_bls12_381::Fr;
+        assert_eq!(
+            MerklePath::<bool, Fr>::new(vec![true], Vec::new()).err(),
+            Some(MerkleError::InvalidPathLength)
+        );
+        let path = random_path::<Fr>(3);
+        assert_eq!(path.index(), {
+            let d = &path.directions;
+            d[0] as u64 | (d[1] as u64) << 1 | (d[2] as u64) << 2
+        });
+
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<Fr>::generate(3).unwrap())
+                .unwrap();
+        let mut composer = StandardComposer::<
+            Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+        >::new();
+        let gadget = MerkleGadget::new(hash, 4);
+        let leaf = composer.add_input(Fr::from(1u64));
+        let path = path.witness(&mut composer);
+        assert_eq!(
+            gadget.root(&mut composer, leaf, &path).err(),
+            Some(MerkleError::InvalidPathLength)
+        );
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_membership,
+            test_membership_wrong_leaf,
+            test_update,
+            test_sparse_membership,
+            test_sparse_non_membership_present_key,
+            test_sparse_insert
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_membership,
+            test_membership_wrong_leaf,
+            test_update,
+            test_sparse_membership,
+            test_sparse_non_membership_present_key,
+            test_sparse_insert
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
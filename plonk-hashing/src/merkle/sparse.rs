@@ -0,0 +1,214 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Sparse Merkle Tree
+
+use crate::merkle::{
+    native::empty_roots, MerkleError, MerklePath, TwoToOneHash,
+};
+use ark_ff::PrimeField;
+use ark_std::{collections::BTreeMap, vec::Vec};
+use num_bigint::BigUint;
+
+/// Merkle tree of a fixed depth whose leaves are addressed by an index of up
+/// to `depth` bits, all leaves holding an empty value until they are set.
+///
+/// Only the nodes which differ from the root of an empty subtree of the same
+/// height are stored, so that the depth can be as large as the number of
+/// bits of the keys of a key-value map.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = "H: Clone"), Debug(bound = "H: core::fmt::Debug"))]
+pub struct SparseMerkleTree<F, H>
+where
+    F: PrimeField,
+{
+    hash: H,
+    depth: usize,
+    /// Roots of the empty subtrees, by height.
+    empty_roots: Vec<F>,
+    /// Non-empty nodes, keyed by their height and their index on that level.
+    nodes: BTreeMap<(usize, BigUint), F>,
+}
+
+impl<F, H> SparseMerkleTree<F, H>
+where
+    F: PrimeField,
+    H: TwoToOneHash<(), F, Field = F>,
+{
+    /// Creates an empty tree of `depth` levels above the leaves, whose empty
+    /// leaves hold zero.
+    pub fn new(hash: H, depth: usize) -> Self {
+        Self::with_empty_leaf(hash, depth, F::zero())
+    }
+
+    /// Creates an empty tree of `depth` levels above the leaves, whose empty
+    /// leaves hold `empty_leaf`.
+    pub fn with_empty_leaf(hash: H, depth: usize, empty_leaf: F) -> Self {
+        let empty_roots = empty_roots(&hash, depth, empty_leaf);
+        Self {
+            hash,
+            depth,
+            empty_roots,
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of levels above the leaves.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the value held by empty leaves.
+    pub fn empty_leaf(&self) -> F {
+        self.empty_roots[0]
+    }
+
+    /// Returns the node at `index` on the level of height `level`.
+    fn node(&self, level: usize, index: &BigUint) -> F {
+        self.nodes
+            .get(&(level, index.clone()))
+            .copied()
+            .unwrap_or(self.empty_roots[level])
+    }
+
+    /// Stores the node at `index` on the level of height `level`, dropping
+    /// it if it is the root of an empty subtree.
+    fn set_node(&mut self, level: usize, index: BigUint, node: F) {
+        if node == self.empty_roots[level] {
+            self.nodes.remove(&(level, index));
+        } else {
+            self.nodes.insert((level, index), node);
+        }
+    }
+
+    /// Checks that `index` addresses a leaf of the tree.
+    fn check_index(&self, index: &BigUint) -> Result<(), MerkleError> {
+        if index.bits() > self.depth as u64 {
+            return Err(MerkleError::IndexOutOfRange);
+        }
+        Ok(())
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> F {
+        self.node(self.depth, &BigUint::from(0u64))
+    }
+
+    /// Returns the leaf at `index`.
+    pub fn leaf(&self, index: &BigUint) -> Result<F, MerkleError> {
+        self.check_index(index)?;
+        Ok(self.node(0, index))
+    }
+
+    /// Sets the leaf at `index` to `leaf`. Setting a leaf to
+    /// [`empty_leaf`](Self::empty_leaf) removes it from the tree.
+    pub fn insert(
+        &mut self,
+        index: &BigUint,
+        leaf: F,
+    ) -> Result<(), MerkleError> {
+        self.check_index(index)?;
+        let mut i = index.clone();
+        self.set_node(0, i.clone(), leaf);
+        for level in 0..self.depth {
+            let parent = &i >> 1u32;
+            let left = self.node(level, &(&parent << 1u32));
+            let right = self.node(level, &((&parent << 1u32) + 1u32));
+            let node = self.hash.compress(&mut (), &left, &right);
+            self.set_node(level + 1, parent.clone(), node);
+            i = parent;
+        }
+        Ok(())
+    }
+
+    /// Returns the path of the leaf at `index`.
+    pub fn path(
+        &self,
+        index: &BigUint,
+    ) -> Result<MerklePath<bool, F>, MerkleError> {
+        self.check_index(index)?;
+        let mut directions = Vec::with_capacity(self.depth);
+        let mut siblings = Vec::with_capacity(self.depth);
+        let mut i = index.clone();
+        for level in 0..self.depth {
+            let is_right = i.bit(0);
+            let sibling = if is_right { &i - 1u32 } else { &i + 1u32 };
+            directions.push(is_right);
+            siblings.push(self.node(level, &sibling));
+            i >>= 1u32;
+        }
+        MerklePath::new(directions, siblings)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        merkle::{IncrementalMerkleTree, PoseidonHash},
+        poseidon::PoseidonConstants,
+    };
+    use ark_bls12_381::Fr;
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn test_insert() {
+        let mut rng = test_rng();
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<Fr>::generate(3).unwrap())
+                .unwrap();
+        let mut sparse = SparseMerkleTree::new(hash.clone(), 4);
+        let mut incremental = IncrementalMerkleTree::new(hash.clone(), 4);
+        let empty_root = sparse.root();
+        assert_eq!(empty_root, incremental.root());
+
+        // Filling the first leaves gives the incremental tree.
+        for i in 0..5u64 {
+            let leaf = Fr::rand(&mut rng);
+            sparse.insert(&BigUint::from(i), leaf).unwrap();
+            incremental.append(leaf).unwrap();
+        }
+        assert_eq!(sparse.root(), incremental.root());
+        for i in 0..5u64 {
+            let index = BigUint::from(i);
+            assert_eq!(sparse.path(&index), incremental.path(i));
+            let path = sparse.path(&index).unwrap();
+            assert_eq!(
+                path.root(&hash, sparse.leaf(&index).unwrap()),
+                sparse.root()
+            );
+        }
+
+        // Emptying the leaves again drops every node.
+        for i in 0..5u64 {
+            sparse.insert(&BigUint::from(i), Fr::from(0u64)).unwrap();
+        }
+        assert_eq!(sparse.root(), empty_root);
+        assert!(sparse.nodes.is_empty());
+
+        assert_eq!(
+            sparse.insert(&BigUint::from(16u64), Fr::from(1u64)),
+            Err(MerkleError::IndexOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_large_depth() {
+        let mut rng = test_rng();
+        let hash =
+            PoseidonHash::new(PoseidonConstants::<Fr>::generate(3).unwrap())
+                .unwrap();
+        let mut tree = SparseMerkleTree::new(hash.clone(), 256);
+        let index = (BigUint::from(1u64) << 255u32) + 12345u32;
+        let leaf = Fr::rand(&mut rng);
+        tree.insert(&index, leaf).unwrap();
+        let path = tree.path(&index).unwrap();
+        assert_eq!(path.depth(), 256);
+        assert_eq!(path.root(&hash, leaf), tree.root());
+        let absent = tree.path(&BigUint::from(7u64)).unwrap();
+        assert_eq!(path.root(&hash, leaf), absent.root(&hash, Fr::from(0u64)));
+    }
+}
@@ -0,0 +1,207 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! MiMC Parameters
+
+use crate::{arithmetic::inverse_exponent, digest::keccak256, mimc::MimcError};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+/// Parameters of the MiMC block cipher and of the MiMC-Feistel permutation:
+/// the exponent `e` of the round function and one round constant per round.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug, PartialEq, Eq)]
+pub struct MimcConstants<F>
+where
+    F: PrimeField,
+{
+    /// Exponent of the round function.
+    pub exponent: u64,
+    /// Round constants, one per round.
+    pub round_constants: Vec<F>,
+}
+
+impl<F> MimcConstants<F>
+where
+    F: PrimeField,
+{
+    /// Builds a new set of MiMC parameters, checking that `x^exponent` is a
+    /// permutation of `F`.
+    pub fn new(
+        exponent: u64,
+        round_constants: Vec<F>,
+    ) -> Result<Self, MimcError> {
+        if exponent < 3 || inverse_exponent::<F>(exponent).is_none() {
+            return Err(MimcError::InvalidExponent);
+        }
+        if round_constants.is_empty() {
+            return Err(MimcError::InvalidRounds);
+        }
+        Ok(Self {
+            exponent,
+            round_constants,
+        })
+    }
+
+    /// Derives `rounds` round constants from `seed` the same way as
+    /// circomlib: the first constant is zero and the following ones are the
+    /// successive Keccak-256 digests of the Keccak-256 digest of `seed`,
+    /// read as big-endian integers reduced modulo `p`.
+    pub fn from_seed(
+        seed: &str,
+        rounds: usize,
+        exponent: u64,
+    ) -> Result<Self, MimcError> {
+        let mut round_constants = Vec::with_capacity(rounds);
+        if rounds > 0 {
+            round_constants.push(F::zero());
+        }
+        let mut digest = keccak256(seed.as_bytes());
+        for _ in 1..rounds {
+            digest = keccak256(&digest);
+            round_constants.push(F::from_be_bytes_mod_order(&digest));
+        }
+        Self::new(exponent, round_constants)
+    }
+
+    /// Parameters of circomlib's `MiMC7`: 91 rounds of the block cipher with
+    /// the exponent 7 and the seed `mimc`.
+    pub fn mimc7() -> Result<Self, MimcError> {
+        Self::from_seed("mimc", 91, 7)
+    }
+
+    /// Parameters of circomlib's `MiMCSponge`: 220 rounds of the Feistel
+    /// permutation with the exponent 5 and the seed `mimcsponge`, the last
+    /// round constant being zero.
+    pub fn mimc_sponge() -> Result<Self, MimcError> {
+        let mut constants = Self::from_seed("mimcsponge", 220, 5)?;
+        *constants.round_constants.last_mut().expect("220 rounds") = F::zero();
+        Ok(constants)
+    }
+
+    /// Number of rounds.
+    pub fn rounds(&self) -> usize {
+        self.round_constants.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        mimc::{MimcSpec, MimcSponge, NativeSpec},
+        test::Bn254Fr,
+    };
+    use ark_std::vec;
+
+    /// Parses a big-endian hexadecimal string.
+    fn from_hex(hex: &str) -> Bn254Fr {
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+        Bn254Fr::from_be_bytes_mod_order(&bytes)
+    }
+
+    #[test]
+    fn test_parameter_checks() {
+        assert!(MimcConstants::new(5, vec![Bn254Fr::from(1u64)]).is_ok());
+        // 3 divides p - 1.
+        assert_eq!(
+            MimcConstants::new(3, vec![Bn254Fr::from(1u64)]),
+            Err(MimcError::InvalidExponent)
+        );
+        assert_eq!(
+            MimcConstants::<Bn254Fr>::new(7, vec![]),
+            Err(MimcError::InvalidRounds)
+        );
+    }
+
+    #[test]
+    fn test_mimc7_vectors() {
+        // Test vectors of circomlib.
+        let constants = MimcConstants::<Bn254Fr>::mimc7().unwrap();
+        for (x, k, expected) in [
+            (
+                1u64,
+                2u64,
+                "176c6eefc3fdf8d6136002d8e6f7a885bbd1c4e3957b93ddc1ec3ae7859f1a08",
+            ),
+            (
+                0,
+                0,
+                "19ef1644e8e5e6a0d7db0046d76324d7052eb1dcd7802ef5845f93cfeaa02179",
+            ),
+        ] {
+            let (x, k) = (Bn254Fr::from(x), Bn254Fr::from(k));
+            assert_eq!(
+                NativeSpec::encrypt(&mut (), &constants, &x, &k),
+                from_hex(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mimc_sponge_vectors() {
+        // Test vectors of circomlib.
+        let constants = MimcConstants::<Bn254Fr>::mimc_sponge().unwrap();
+        let (x_l, x_r) = NativeSpec::feistel(
+            &mut (),
+            &constants,
+            &Bn254Fr::from(1u64),
+            &Bn254Fr::from(2u64),
+            &Bn254Fr::from(3u64),
+        );
+        assert_eq!(
+            (x_l, x_r),
+            (
+                from_hex("28c6f78ee3ed6b336280d3e522b03efc49eeb5a2a3af1075ccf6f64e5d867e53"),
+                from_hex("05d9ff7555e18007f7809e5977e6dd41ff93ca7c8d11249d51eaeb0b4f727d37"),
+            )
+        );
+        let inputs = (1..4u64).map(Bn254Fr::from).collect::<Vec<_>>();
+        assert_eq!(
+            MimcSponge::<(), NativeSpec, _>::hash(
+                &mut (),
+                constants,
+                &inputs,
+                &Bn254Fr::from(4u64),
+                3,
+            ),
+            [
+                "2412394e278d0839b708e1ccfaf4a7a536662ac9603282833ac78487dfddc5d2",
+                "20b073005d76fa5e23db0aeb635698fbd00142ad203486687c2fed2dee6b4ea0",
+                "118d10376042da6b752abca89d97759682cf63ade1439330e76bb57a229246f8",
+            ]
+            .iter()
+            .map(|h| from_hex(h))
+            .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_tornado_zeros() {
+        // Zero values of the Merkle tree of Tornado Cash, whose leaves are
+        // `keccak256("tornado")` and whose nodes are the MiMCSponge hash of
+        // their children.
+        let constants = MimcConstants::<Bn254Fr>::mimc_sponge().unwrap();
+        let mut zero = Bn254Fr::from_be_bytes_mod_order(&keccak256(b"tornado"));
+        for expected in [
+            "256a6135777eee2fd26f54b8b7037a25439d5235caee224154186d2b8a52e31d",
+            "1151949895e82ab19924de92c40a3d6f7bcb60d92b00504b8199613683f0c200",
+            "20121ee811489ff8d61f09fb89e313f14959a0f28bb428a20dba6b0b068b3bdb",
+        ] {
+            zero = MimcSponge::<(), NativeSpec, _>::hash(
+                &mut (),
+                constants.clone(),
+                &[zero, zero],
+                &Bn254Fr::from(0u64),
+                1,
+            )[0];
+            assert_eq!(zero, from_hex(expected));
+        }
+    }
+}
@@ -0,0 +1,115 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! MiMC Hash Functions
+//!
+//! Native and in-circuit implementations of the MiMC block cipher and of the
+//! MiMC-Feistel permutation (see <https://eprint.iacr.org/2016/492>), with
+//! the hash modes built on top of them by circomlib: the Miyaguchi-Preneel
+//! `MultiMiMC7` hash and the `MiMCSponge` sponge, used for instance by the
+//! Merkle trees of Tornado Cash.
+//!
+//! Both implementations share the same [`MimcConstants`] and the same hash
+//! logic through the [`MimcSpec`] trait.
+
+mod constants;
+mod native;
+mod plonk;
+mod sponge;
+
+pub use constants::MimcConstants;
+pub use native::NativeSpec;
+pub use plonk::PlonkSpec;
+pub use sponge::MimcSponge;
+
+use ark_ff::PrimeField;
+use core::fmt;
+
+/// Arithmetic backend over which the MiMC block cipher and the MiMC-Feistel
+/// permutation are evaluated.
+///
+/// `COM` is the context the computation happens in, `()` for the native
+/// implementation and a
+/// [`StandardComposer`](plonk_core::constraint_system::StandardComposer) for
+/// the gadget.
+pub trait MimcSpec<COM, F>
+where
+    F: PrimeField,
+{
+    /// Representation of a field element.
+    type Field: Clone;
+
+    /// Returns an element holding zero.
+    fn zero(c: &mut COM) -> Self::Field;
+
+    /// Returns `x + y`.
+    fn add(c: &mut COM, x: &Self::Field, y: &Self::Field) -> Self::Field;
+
+    /// Encrypts `x` under the key `k` with the MiMC block cipher, whose
+    /// round `i` maps `x` to `(x + k + c_i)^e`, the key being added once more
+    /// after the last round.
+    fn encrypt(
+        c: &mut COM,
+        constants: &MimcConstants<F>,
+        x: &Self::Field,
+        k: &Self::Field,
+    ) -> Self::Field;
+
+    /// Applies the MiMC-Feistel permutation with the key `k` to `(x_l, x_r)`,
+    /// whose round `i` maps `(x_l, x_r)` to `(x_r + (x_l + k + c_i)^e, x_l)`,
+    /// the last round leaving the branches in place.
+    fn feistel(
+        c: &mut COM,
+        constants: &MimcConstants<F>,
+        x_l: &Self::Field,
+        x_r: &Self::Field,
+        k: &Self::Field,
+    ) -> (Self::Field, Self::Field);
+
+    /// Hashes `inputs` with the Miyaguchi-Preneel construction over the MiMC
+    /// block cipher, as circomlib's `MultiMiMC7`: starting from `r = key`,
+    /// every input `x` updates `r` to `r + x + E_r(x)`.
+    fn hash(
+        c: &mut COM,
+        constants: &MimcConstants<F>,
+        inputs: &[Self::Field],
+        key: &Self::Field,
+    ) -> Self::Field {
+        let mut r = key.clone();
+        for x in inputs {
+            let e = Self::encrypt(c, constants, x, &r);
+            let sum = Self::add(c, &r, x);
+            r = Self::add(c, &sum, &e);
+        }
+        r
+    }
+}
+
+/// MiMC Errors
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MimcError {
+    /// The exponent must be at least three and `x^e` must be a permutation
+    /// of the field.
+    InvalidExponent,
+    /// There must be at least one round.
+    InvalidRounds,
+}
+
+impl fmt::Display for MimcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidExponent => {
+                write!(f, "MiMC exponent must be >= 3 and invertible")
+            }
+            Self::InvalidRounds => {
+                write!(f, "Number of rounds must be non-zero")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MimcError {}
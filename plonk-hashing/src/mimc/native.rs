@@ -0,0 +1,59 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Native MiMC
+
+use crate::mimc::{MimcConstants, MimcSpec};
+use ark_ff::PrimeField;
+
+/// Out-of-circuit MiMC, evaluated directly over the field `F`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeSpec;
+
+impl<F> MimcSpec<(), F> for NativeSpec
+where
+    F: PrimeField,
+{
+    type Field = F;
+
+    fn zero(_: &mut ()) -> F {
+        F::zero()
+    }
+
+    fn add(_: &mut (), x: &F, y: &F) -> F {
+        *x + y
+    }
+
+    fn encrypt(_: &mut (), constants: &MimcConstants<F>, x: &F, k: &F) -> F {
+        let mut r = *x;
+        for c in &constants.round_constants {
+            r = (r + k + c).pow([constants.exponent]);
+        }
+        r + k
+    }
+
+    fn feistel(
+        _: &mut (),
+        constants: &MimcConstants<F>,
+        x_l: &F,
+        x_r: &F,
+        k: &F,
+    ) -> (F, F) {
+        let (mut x_l, mut x_r) = (*x_l, *x_r);
+        let last = constants.rounds() - 1;
+        for (i, c) in constants.round_constants.iter().enumerate() {
+            let t = (x_l + k + c).pow([constants.exponent]);
+            if i < last {
+                let x = x_l;
+                x_l = x_r + t;
+                x_r = x;
+            } else {
+                x_r += t;
+            }
+        }
+        (x_l, x_r)
+    }
+}
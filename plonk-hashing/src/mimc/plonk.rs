@@ -0,0 +1,205 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! MiMC Gadget
+
+use crate::{
+    arithmetic::pow,
+    mimc::{MimcConstants, MimcSpec},
+};
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use plonk_core::constraint_system::{StandardComposer, Variable};
+
+/// In-circuit MiMC, evaluated with the arithmetic gates of a
+/// [`StandardComposer`].
+///
+/// Every round adds the key to the state in one gate and raises the sum,
+/// offset by the round constant, to the power `e` with a square-and-multiply
+/// chain. The last multiplication of the chain is fused with the addition of
+/// the other branch in the Feistel mode, so that a round of `MiMCSponge`
+/// takes four gates and a round of `MiMC7` five.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlonkSpec;
+
+impl<F, P> MimcSpec<StandardComposer<F, P>, F> for PlonkSpec
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    type Field = Variable;
+
+    fn zero(c: &mut StandardComposer<F, P>) -> Variable {
+        c.zero_var()
+    }
+
+    fn add(
+        c: &mut StandardComposer<F, P>,
+        x: &Variable,
+        y: &Variable,
+    ) -> Variable {
+        c.arithmetic_gate(|gate| {
+            gate.witness(*x, *y, None).add(F::one(), F::one())
+        })
+    }
+
+    fn encrypt(
+        c: &mut StandardComposer<F, P>,
+        constants: &MimcConstants<F>,
+        x: &Variable,
+        k: &Variable,
+    ) -> Variable {
+        let mut r = *x;
+        for rc in &constants.round_constants {
+            let sum = Self::add(c, &r, k);
+            r = pow(c, sum, *rc, constants.exponent);
+        }
+        Self::add(c, &r, k)
+    }
+
+    fn feistel(
+        c: &mut StandardComposer<F, P>,
+        constants: &MimcConstants<F>,
+        x_l: &Variable,
+        x_r: &Variable,
+        k: &Variable,
+    ) -> (Variable, Variable) {
+        let (mut x_l, mut x_r) = (*x_l, *x_r);
+        let last = constants.rounds() - 1;
+        for (i, rc) in constants.round_constants.iter().enumerate() {
+            let sum = Self::add(c, &x_l, k);
+            let power = pow(c, sum, *rc, constants.exponent - 1);
+            // power * (sum + rc) + x_r
+            let t = c.arithmetic_gate(|gate| {
+                gate.witness(power, sum, None)
+                    .mul(F::one())
+                    .add(*rc, F::zero())
+                    .fan_in_3(F::one(), x_r)
+            });
+            if i < last {
+                x_r = x_l;
+                x_l = t;
+            } else {
+                x_r = t;
+            }
+        }
+        (x_l, x_r)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test,
+        mimc::{MimcSponge, NativeSpec},
+        poseidon::smallest_alpha,
+        test::gadget_tester,
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_std::{test_rng, vec::Vec};
+    use plonk_core::commitment::HomomorphicCommitment;
+
+    fn test_hash_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        // circomlib's exponents are not permutations of every field.
+        let constants =
+            MimcConstants::<F>::from_seed("mimc", 91, smallest_alpha::<F>())
+                .unwrap();
+        let input = (0..2).map(|_| F::rand(&mut rng)).collect::<Vec<_>>();
+        let key = F::rand(&mut rng);
+        let expected = NativeSpec::hash(&mut (), &constants, &input, &key);
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let input = input
+                    .iter()
+                    .map(|x| composer.add_input(*x))
+                    .collect::<Vec<_>>();
+                let key = composer.add_input(key);
+                let output =
+                    PlonkSpec::hash(composer, &constants, &input, &key);
+                assert_eq!(composer.value_of_var(output), expected);
+                composer.constrain_to_constant(output, expected, None);
+            },
+            2000,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_sponge_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        let constants = MimcConstants::<F>::from_seed(
+            "mimcsponge",
+            220,
+            smallest_alpha::<F>(),
+        )
+        .unwrap();
+        let input = (0..2).map(|_| F::rand(&mut rng)).collect::<Vec<_>>();
+        let key = F::rand(&mut rng);
+        let expected = MimcSponge::<(), NativeSpec, _>::hash(
+            &mut (),
+            constants.clone(),
+            &input,
+            &key,
+            2,
+        );
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let input = input
+                    .iter()
+                    .map(|x| composer.add_input(*x))
+                    .collect::<Vec<_>>();
+                let key = composer.add_input(key);
+                let output = MimcSponge::<_, PlonkSpec, _>::hash(
+                    composer,
+                    constants.clone(),
+                    &input,
+                    &key,
+                    2,
+                );
+                for (var, value) in output.iter().zip(expected.iter()) {
+                    assert_eq!(composer.value_of_var(*var), *value);
+                    composer.constrain_to_constant(*var, *value, None);
+                }
+            },
+            4096,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_hash_gadget,
+            test_sponge_gadget
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_hash_gadget,
+            test_sponge_gadget
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,111 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! MiMC-Feistel Sponge
+
+use crate::mimc::{MimcConstants, MimcSpec};
+use ark_ff::PrimeField;
+use ark_std::{marker::PhantomData, vec::Vec};
+
+/// Last operation applied to the sponge.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SpongeMode {
+    /// Whether an element was absorbed since the last permutation.
+    Absorbing(bool),
+    /// An element was squeezed since the last permutation.
+    Squeezing,
+}
+
+/// Sponge over the MiMC-Feistel permutation, as circomlib's `MiMCSponge`.
+///
+/// The state is made of one rate element `R`, into which the inputs are
+/// absorbed and from which the outputs are squeezed, and of one capacity
+/// element `C`. Every permutation is keyed with the same key.
+#[derive(derivative::Derivative)]
+#[derivative(
+    Clone(bound = "S::Field: Clone"),
+    Debug(bound = "S::Field: core::fmt::Debug")
+)]
+pub struct MimcSponge<COM, S, F>
+where
+    S: MimcSpec<COM, F>,
+    F: PrimeField,
+{
+    constants: MimcConstants<F>,
+    key: S::Field,
+    rate: S::Field,
+    capacity: S::Field,
+    mode: SpongeMode,
+    __: PhantomData<COM>,
+}
+
+impl<COM, S, F> MimcSponge<COM, S, F>
+where
+    S: MimcSpec<COM, F>,
+    F: PrimeField,
+{
+    /// Creates a sponge with an all-zero state, permuted with `key`.
+    pub fn new(
+        c: &mut COM,
+        constants: MimcConstants<F>,
+        key: S::Field,
+    ) -> Self {
+        Self {
+            constants,
+            key,
+            rate: S::zero(c),
+            capacity: S::zero(c),
+            mode: SpongeMode::Absorbing(false),
+            __: PhantomData,
+        }
+    }
+
+    /// Applies the keyed permutation to the state.
+    fn permute(&mut self, c: &mut COM) {
+        let (rate, capacity) = S::feistel(
+            c,
+            &self.constants,
+            &self.rate,
+            &self.capacity,
+            &self.key,
+        );
+        self.rate = rate;
+        self.capacity = capacity;
+    }
+
+    /// Absorbs `inputs` into the sponge, permuting the state after every
+    /// element.
+    pub fn absorb(&mut self, c: &mut COM, inputs: &[S::Field]) {
+        for input in inputs {
+            if self.mode == SpongeMode::Absorbing(true) {
+                self.permute(c);
+            }
+            self.rate = S::add(c, &self.rate, input);
+            self.mode = SpongeMode::Absorbing(true);
+        }
+    }
+
+    /// Squeezes one element out of the sponge.
+    pub fn squeeze(&mut self, c: &mut COM) -> S::Field {
+        self.permute(c);
+        self.mode = SpongeMode::Squeezing;
+        self.rate.clone()
+    }
+
+    /// Hashes `inputs` into `outputs` field elements, as circomlib's
+    /// `MiMCSponge(nInputs, 220, nOutputs)`.
+    pub fn hash(
+        c: &mut COM,
+        constants: MimcConstants<F>,
+        inputs: &[S::Field],
+        key: &S::Field,
+        outputs: usize,
+    ) -> Vec<S::Field> {
+        let mut sponge = Self::new(c, constants, key.clone());
+        sponge.absorb(c, inputs);
+        (0..outputs).map(|_| sponge.squeeze(c)).collect()
+    }
+}
@@ -0,0 +1,295 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Pedersen Hash Parameters
+
+use crate::{
+    digest::keccak256,
+    pedersen::{PedersenError, CHUNK_BITS},
+};
+use ark_ec::{
+    twisted_edwards_extended::GroupAffine as TEGroupAffine, AffineCurve,
+    ProjectiveCurve, TEModelParameters,
+};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_std::{format, string::String, vec::Vec};
+use num_bigint::BigUint;
+use plonk_core::lookup::LookupTable;
+
+/// Parameters of a Pedersen hash: the generators `G_i` multiplied by the
+/// segments of the message, and the number of chunks in each segment.
+#[derive(derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Debug(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = "")
+)]
+pub struct PedersenConstants<P>
+where
+    P: TEModelParameters,
+{
+    /// Generator of every segment of the message.
+    pub generators: Vec<TEGroupAffine<P>>,
+    /// Number of chunks of [`CHUNK_BITS`] bits in a segment.
+    pub chunks_per_generator: usize,
+}
+
+impl<F, P> PedersenConstants<P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    /// Builds a new set of Pedersen hash parameters, checking that the
+    /// generators are points of the prime-order subgroup other than the
+    /// identity and that the encoding of a segment is injective modulo the
+    /// order of the subgroup.
+    pub fn new(
+        generators: Vec<TEGroupAffine<P>>,
+        chunks_per_generator: usize,
+    ) -> Result<Self, PedersenError> {
+        if generators.is_empty()
+            || generators.iter().any(|g| {
+                g.is_zero()
+                    || !g.is_on_curve()
+                    || !g.is_in_correct_subgroup_assuming_on_curve()
+            })
+        {
+            return Err(PedersenError::InvalidGenerators);
+        }
+        if chunks_per_generator == 0
+            || chunks_per_generator > max_chunks_per_generator::<P>()
+        {
+            return Err(PedersenError::InvalidChunksPerGenerator);
+        }
+        Ok(Self {
+            generators,
+            chunks_per_generator,
+        })
+    }
+
+    /// Generates the parameters of a Pedersen hash of messages of up to
+    /// `max_bits` bits, whose generators are derived from `personalization`.
+    ///
+    /// Segments hold as many chunks as the order of the subgroup allows, 63
+    /// on Jubjub as in Zcash Sapling. The `i`-th generator is found by
+    /// try-and-increment: the Keccak-256 digest of the length of
+    /// `personalization` as four little-endian bytes, of `personalization`,
+    /// of `i` as four little-endian bytes and of a one-byte counter is read
+    /// as a little-endian `x`-coordinate, the first counter for which it
+    /// lifts to a point whose multiple by the cofactor is not the identity
+    /// being used.
+    pub fn generate(
+        personalization: &[u8],
+        max_bits: usize,
+    ) -> Result<Self, PedersenError> {
+        let chunks_per_generator = max_chunks_per_generator::<P>();
+        let bits_per_generator = CHUNK_BITS * chunks_per_generator;
+        let count = max_bits / bits_per_generator
+            + (max_bits % bits_per_generator).min(1);
+        let generators = (0..count as u32)
+            .map(|i| group_hash(personalization, i))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(PedersenError::InvalidGenerators)?;
+        Self::new(generators, chunks_per_generator)
+    }
+
+    /// Maximum number of bits of a message.
+    pub fn max_bits(&self) -> usize {
+        self.generators.len() * self.chunks_per_generator * CHUNK_BITS
+    }
+
+    /// Number of chunks of a message of the maximum length.
+    pub fn windows(&self) -> usize {
+        self.generators.len() * self.chunks_per_generator
+    }
+
+    /// Returns `2^(4j) G_i`, by which the `window`-th chunk of the message
+    /// is multiplied, `i` and `j` being the segment of the window and its
+    /// position inside the segment.
+    pub fn window_base(&self, window: usize) -> TEGroupAffine<P> {
+        let generator = self.generators[window / self.chunks_per_generator];
+        let j = (window % self.chunks_per_generator) as u64;
+        let scalar = P::ScalarField::from(16u64).pow([j]);
+        generator.mul(scalar.into_repr()).into_affine()
+    }
+
+    /// Returns the contribution `enc(chunk) 2^(4j) G_i` of the `window`-th
+    /// chunk of the message, whose bits are those of `chunk`, least
+    /// significant first.
+    pub fn window_point(&self, window: usize, chunk: u8) -> TEGroupAffine<P> {
+        let magnitude = 1 + u64::from(chunk & 0b011);
+        let point = self
+            .window_base(window)
+            .mul(P::ScalarField::from(magnitude).into_repr())
+            .into_affine();
+        if chunk & 0b100 == 0 {
+            point
+        } else {
+            -point
+        }
+    }
+
+    /// Returns the lookup table from which the gadget reads the contribution
+    /// of every chunk: for each window `w` and each chunk `s` of the window,
+    /// the table holds the row `(8 w + s, x, y, 0)`, where `(x, y)` is the
+    /// contribution of `s`.
+    ///
+    /// The gadget adds the table to the circuit under the name returned by
+    /// [`lookup_table_name`](Self::lookup_table_name) when it is not already
+    /// added.
+    pub fn lookup_table(&self) -> LookupTable<F> {
+        let mut table = LookupTable::new();
+        for window in 0..self.windows() {
+            let base = self.window_base(window).into_projective();
+            let mut multiple = base;
+            for magnitude in 0..4u64 {
+                let point = multiple.into_affine();
+                for (sign, point) in [(0, point), (4, -point)] {
+                    table.insert_row(
+                        F::from(8 * window as u64 + magnitude + sign),
+                        point.x,
+                        point.y,
+                        F::zero(),
+                    );
+                }
+                multiple += base;
+            }
+        }
+        table
+    }
+
+    /// Returns the name of the [`lookup_table`](Self::lookup_table) of these
+    /// parameters, told apart from the tables of other parameters by their
+    /// generators.
+    pub fn lookup_table_name(&self) -> String {
+        format!(
+            "pedersen {} {}",
+            self.generators.len(),
+            self.generators[0].x
+        )
+    }
+}
+
+/// Returns the largest number of chunks `c` in a segment for which the
+/// encodings of two distinct segments never collide modulo the order `r` of
+/// the subgroup, that is such that `4 (16^c - 1) / 15 <= (r - 1) / 2`.
+fn max_chunks_per_generator<P>() -> usize
+where
+    P: TEModelParameters,
+{
+    let order_minus_one: BigUint = (-P::ScalarField::one()).into();
+    let half_order = order_minus_one / 2u64;
+    let mut chunks = 0;
+    let mut max_encoding = BigUint::from(0u64);
+    loop {
+        max_encoding = (max_encoding << 4) + 4u64;
+        if max_encoding > half_order {
+            return chunks;
+        }
+        chunks += 1;
+    }
+}
+
+/// Derives a point of the prime-order subgroup from `personalization` and
+/// `index`, as described in [`PedersenConstants::generate`].
+fn group_hash<F, P>(
+    personalization: &[u8],
+    index: u32,
+) -> Option<TEGroupAffine<P>>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let mut input = Vec::with_capacity(personalization.len() + 9);
+    input.extend_from_slice(&(personalization.len() as u32).to_le_bytes());
+    input.extend_from_slice(personalization);
+    input.extend_from_slice(&index.to_le_bytes());
+    (0..=u8::MAX).find_map(|counter| {
+        input.push(counter);
+        let x = F::from_le_bytes_mod_order(&keccak256(&input));
+        input.pop();
+        TEGroupAffine::<P>::get_point_from_x(x, false)
+            .map(|point| point.mul_by_cofactor())
+            .filter(|point| !point.is_zero())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pedersen::{NativeSpec, PedersenSpec};
+    use ark_ec::twisted_edwards_extended::GroupProjective as TEGroupProjective;
+    use ark_ed_on_bls12_381::{EdwardsParameters as JubJubParameters, Fr};
+    use ark_std::{test_rng, vec, UniformRand};
+
+    type Constants = PedersenConstants<JubJubParameters>;
+
+    #[test]
+    fn test_parameter_checks() {
+        let constants = Constants::generate(b"Zcash_PH", 510).unwrap();
+        assert_eq!(constants.chunks_per_generator, 63);
+        assert_eq!(constants.generators.len(), 3);
+        assert_eq!(constants.max_bits(), 567);
+        assert_eq!(
+            Constants::new(vec![], 63),
+            Err(PedersenError::InvalidGenerators)
+        );
+        assert_eq!(
+            Constants::new(vec![TEGroupAffine::zero()], 63),
+            Err(PedersenError::InvalidGenerators)
+        );
+        assert_eq!(
+            Constants::new(constants.generators.clone(), 64),
+            Err(PedersenError::InvalidChunksPerGenerator)
+        );
+        assert_eq!(
+            Constants::new(constants.generators, 0),
+            Err(PedersenError::InvalidChunksPerGenerator)
+        );
+    }
+
+    #[test]
+    fn test_personalization() {
+        let a = Constants::generate(b"Zcash_PH", 1).unwrap();
+        let b = Constants::generate(b"Zcash_PH", 1).unwrap();
+        let c = Constants::generate(b"Zcash_PI", 1).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a.generators[0], c.generators[0]);
+    }
+
+    #[test]
+    fn test_hash() {
+        // The hash is the sum of every segment multiplied by its generator.
+        let mut rng = test_rng();
+        let constants = Constants::generate(b"test", 300).unwrap();
+        let bits = (0..300).map(|_| bool::rand(&mut rng)).collect::<Vec<_>>();
+        let mut expected = TEGroupProjective::zero();
+        for (segment, generator) in bits
+            .chunks(CHUNK_BITS * constants.chunks_per_generator)
+            .zip(constants.generators.iter())
+        {
+            let mut scalar = Fr::zero();
+            let mut shift = Fr::one();
+            for chunk in segment.chunks(CHUNK_BITS) {
+                let enc =
+                    shift * Fr::from(1 + chunk[0] as u64 + 2 * chunk[1] as u64);
+                scalar += if chunk[2] { -enc } else { enc };
+                shift *= Fr::from(16u64);
+            }
+            expected += generator.mul(scalar);
+        }
+        assert_eq!(
+            NativeSpec::hash(&mut (), &constants, &bits),
+            Ok(expected.into_affine())
+        );
+        let bits = vec![false; constants.max_bits() + 1];
+        assert_eq!(
+            NativeSpec::hash(&mut (), &constants, &bits),
+            Err(PedersenError::MessageTooLong)
+        );
+    }
+}
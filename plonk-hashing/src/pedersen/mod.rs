@@ -0,0 +1,134 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Pedersen Hash
+//!
+//! Native and in-circuit implementations of the windowed Pedersen hash of
+//! Zcash Sapling (see section 5.4.1.7 of the Zcash protocol specification)
+//! over the embedded Twisted Edwards curve of a
+//! [`StandardComposer`](plonk_core::constraint_system::StandardComposer).
+//!
+//! The message is split into chunks of three bits `(s_0, s_1, s_2)`, each
+//! encoded as the signed integer `(1 - 2 s_2) (1 + s_0 + 2 s_1)`. The chunks
+//! are grouped into segments, the `j`-th chunk of the `i`-th segment being
+//! multiplied by `2^(4j) G_i`, where the generators `G_i` are derived from a
+//! personalization string, and the hash is the sum of all these points.
+//!
+//! Both implementations share the same [`PedersenConstants`] and the same
+//! hash logic through the [`PedersenSpec`] trait.
+
+mod constants;
+mod native;
+mod plonk;
+
+pub use constants::PedersenConstants;
+pub use native::NativeSpec;
+pub use plonk::PlonkSpec;
+
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use core::fmt;
+
+/// Number of bits of a chunk of the message.
+pub const CHUNK_BITS: usize = 3;
+
+/// Arithmetic backend over which the Pedersen hash is evaluated.
+///
+/// `COM` is the context the computation happens in, `()` for the native
+/// implementation and a
+/// [`StandardComposer`](plonk_core::constraint_system::StandardComposer) for
+/// the gadget.
+pub trait PedersenSpec<COM, F, P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    /// Representation of a bit of the message.
+    type Bit: Clone;
+
+    /// Representation of a point of the curve.
+    type Point;
+
+    /// Returns a bit holding zero, used to pad the message.
+    fn zero_bit(c: &mut COM) -> Self::Bit;
+
+    /// Returns the identity of the curve.
+    fn identity(c: &mut COM) -> Self::Point;
+
+    /// Returns `p + q`.
+    fn add(c: &mut COM, p: &Self::Point, q: &Self::Point) -> Self::Point;
+
+    /// Returns the contribution of the `window`-th chunk of the message,
+    /// `enc(chunk) 2^(4j) G_i`, where `i` and `j` are the segment of the
+    /// window and its position inside the segment.
+    fn window(
+        c: &mut COM,
+        constants: &PedersenConstants<P>,
+        window: usize,
+        chunk: &[Self::Bit; CHUNK_BITS],
+    ) -> Self::Point;
+
+    /// Hashes `bits` into a point of the curve, padding them with zeros to a
+    /// multiple of [`CHUNK_BITS`].
+    fn hash(
+        c: &mut COM,
+        constants: &PedersenConstants<P>,
+        bits: &[Self::Bit],
+    ) -> Result<Self::Point, PedersenError> {
+        if bits.len() > constants.max_bits() {
+            return Err(PedersenError::MessageTooLong);
+        }
+        let mut bits = bits.to_vec();
+        while bits.len() % CHUNK_BITS != 0 {
+            bits.push(Self::zero_bit(c));
+        }
+        let mut acc = None;
+        for (window, chunk) in bits.chunks(CHUNK_BITS).enumerate() {
+            let chunk = [chunk[0].clone(), chunk[1].clone(), chunk[2].clone()];
+            let point = Self::window(c, constants, window, &chunk);
+            acc = Some(match acc {
+                Some(acc) => Self::add(c, &acc, &point),
+                None => point,
+            });
+        }
+        Ok(match acc {
+            Some(acc) => acc,
+            None => Self::identity(c),
+        })
+    }
+}
+
+/// Pedersen Errors
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PedersenError {
+    /// There must be at least one generator, and the generators must be
+    /// points of the prime-order subgroup other than the identity.
+    InvalidGenerators,
+    /// A segment must hold at least one chunk and few enough chunks that the
+    /// encodings of distinct segments never wrap around the group order.
+    InvalidChunksPerGenerator,
+    /// The message is longer than the generators allow.
+    MessageTooLong,
+}
+
+impl fmt::Display for PedersenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidGenerators => {
+                write!(f, "Generators must be non-identity subgroup points")
+            }
+            Self::InvalidChunksPerGenerator => {
+                write!(f, "Invalid number of chunks per generator")
+            }
+            Self::MessageTooLong => {
+                write!(f, "Message is longer than the supported length")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PedersenError {}
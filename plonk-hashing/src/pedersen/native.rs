@@ -0,0 +1,55 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Native Pedersen Hash
+
+use crate::pedersen::{PedersenConstants, PedersenSpec, CHUNK_BITS};
+use ark_ec::{
+    twisted_edwards_extended::GroupAffine as TEGroupAffine, TEModelParameters,
+};
+use ark_ff::{PrimeField, Zero};
+
+/// Out-of-circuit Pedersen hash, evaluated directly over the curve.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeSpec;
+
+impl<F, P> PedersenSpec<(), F, P> for NativeSpec
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    type Bit = bool;
+    type Point = TEGroupAffine<P>;
+
+    fn zero_bit(_: &mut ()) -> bool {
+        false
+    }
+
+    fn identity(_: &mut ()) -> TEGroupAffine<P> {
+        TEGroupAffine::zero()
+    }
+
+    fn add(
+        _: &mut (),
+        p: &TEGroupAffine<P>,
+        q: &TEGroupAffine<P>,
+    ) -> TEGroupAffine<P> {
+        *p + *q
+    }
+
+    fn window(
+        _: &mut (),
+        constants: &PedersenConstants<P>,
+        window: usize,
+        chunk: &[bool; CHUNK_BITS],
+    ) -> TEGroupAffine<P> {
+        let chunk = chunk
+            .iter()
+            .rev()
+            .fold(0u8, |acc, bit| (acc << 1) | *bit as u8);
+        constants.window_point(window, chunk)
+    }
+}
@@ -0,0 +1,165 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Pedersen Hash Gadget
+
+use crate::pedersen::{PedersenConstants, PedersenSpec, CHUNK_BITS};
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use plonk_core::constraint_system::{ecc::Point, StandardComposer, Variable};
+
+/// In-circuit Pedersen hash, evaluated with the lookup and curve addition
+/// gates of a [`StandardComposer`].
+///
+/// The contribution of every chunk is read from the lookup table returned
+/// by [`PedersenConstants::lookup_table`], added to the circuit by the first
+/// hash with these parameters, at the index `8 w + s_0 + 2 s_1 + 4 s_2` computed in a single
+/// gate. A chunk therefore takes one arithmetic gate, one lookup gate and
+/// the two rows of a curve addition.
+///
+/// # Note
+///
+/// The bits of the message should have previously been constrained to be
+/// either `1` or `0` using a boolean constraint. See:
+/// [`StandardComposer::boolean_gate`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlonkSpec;
+
+impl<F, P> PedersenSpec<StandardComposer<F, P>, F, P> for PlonkSpec
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    type Bit = Variable;
+    type Point = Point<P>;
+
+    fn zero_bit(c: &mut StandardComposer<F, P>) -> Variable {
+        c.zero_var()
+    }
+
+    fn identity(c: &mut StandardComposer<F, P>) -> Point<P> {
+        Point::identity(c)
+    }
+
+    fn add(
+        c: &mut StandardComposer<F, P>,
+        p: &Point<P>,
+        q: &Point<P>,
+    ) -> Point<P> {
+        c.point_addition_gate(*p, *q)
+    }
+
+    fn window(
+        c: &mut StandardComposer<F, P>,
+        constants: &PedersenConstants<P>,
+        window: usize,
+        chunk: &[Variable; CHUNK_BITS],
+    ) -> Point<P> {
+        // 8 w + s_0 + 2 s_1 + 4 s_2
+        let index = c.arithmetic_gate(|gate| {
+            gate.witness(chunk[0], chunk[1], None)
+                .add(F::one(), F::from(2u64))
+                .fan_in_3(F::from(4u64), chunk[2])
+                .constant(F::from(8 * window as u64))
+        });
+        let value = c.value_of_var(index).into_repr().as_ref()[0];
+        let point =
+            constants.window_point(window, (value - 8 * window as u64) as u8);
+        let x = c.add_input(point.x);
+        let y = c.add_input(point.y);
+        let table = c
+            .get_or_add_lookup_table(&constants.lookup_table_name(), || {
+                constants.lookup_table()
+            });
+        c.lookup_gate(table, index, x, y, None, None);
+        Point::new(x, y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, pedersen::NativeSpec, test::gadget_tester};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_std::{test_rng, vec::Vec, UniformRand};
+    use plonk_core::commitment::HomomorphicCommitment;
+
+    fn test_hash_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        let constants =
+            PedersenConstants::<P>::generate(b"plonk_PH", 256).unwrap();
+        let bits = (0..256).map(|_| bool::rand(&mut rng)).collect::<Vec<_>>();
+        let expected = NativeSpec::hash(&mut (), &constants, &bits).unwrap();
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let bits = bits
+                    .iter()
+                    .map(|bit| {
+                        let bit = composer.add_input(F::from(*bit));
+                        composer.boolean_gate(bit);
+                        bit
+                    })
+                    .collect::<Vec<_>>();
+                let output =
+                    PlonkSpec::hash(composer, &constants, &bits).unwrap();
+                assert_eq!(composer.value_of_var(*output.x()), expected.x);
+                assert_eq!(composer.value_of_var(*output.y()), expected.y);
+                composer.assert_equal_public_point(output, expected);
+            },
+            2048,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_empty_message<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let constants =
+            PedersenConstants::<P>::generate(b"plonk_PH", 3).unwrap();
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let output =
+                    PlonkSpec::hash(composer, &constants, &[]).unwrap();
+                composer.assert_equal_public_point(
+                    output,
+                    NativeSpec::hash(&mut (), &constants, &[]).unwrap(),
+                );
+            },
+            32,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_hash_gadget,
+            test_empty_message
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_hash_gadget,
+            test_empty_message
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
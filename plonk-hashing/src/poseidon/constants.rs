@@ -0,0 +1,253 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Poseidon Parameters
+
+use crate::poseidon::{
+    grain_lfsr::GrainLfsr, mds, round_numbers, smallest_alpha, PoseidonError,
+};
+use ark_ff::{FpParameters, PrimeField};
+use ark_std::vec::Vec;
+
+/// Parameters of a Poseidon permutation of a given width.
+///
+/// The permutation is made of `full_rounds / 2` full rounds, followed by
+/// `partial_rounds` partial rounds and `full_rounds / 2` full rounds. Every
+/// round adds `width` round constants to the state, applies the S-box
+/// `x^alpha` (to the whole state for full rounds, to the first element only
+/// for partial rounds) and multiplies the state by the MDS matrix.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug, PartialEq, Eq)]
+pub struct PoseidonConstants<F>
+where
+    F: PrimeField,
+{
+    /// Number of field elements in the state.
+    pub width: usize,
+    /// Total number of full rounds.
+    pub full_rounds: usize,
+    /// Number of partial rounds.
+    pub partial_rounds: usize,
+    /// S-box exponent.
+    pub alpha: u64,
+    /// MDS matrix, stored row by row.
+    pub mds_matrix: Vec<Vec<F>>,
+    /// Round constants, `width` per round.
+    pub round_constants: Vec<F>,
+}
+
+impl<F> PoseidonConstants<F>
+where
+    F: PrimeField,
+{
+    /// Builds a new set of Poseidon parameters, checking that they are
+    /// consistent with each other.
+    pub fn new(
+        width: usize,
+        full_rounds: usize,
+        partial_rounds: usize,
+        alpha: u64,
+        mds_matrix: Vec<Vec<F>>,
+        round_constants: Vec<F>,
+    ) -> Result<Self, PoseidonError> {
+        if width < 2 {
+            return Err(PoseidonError::InvalidWidth);
+        }
+        if full_rounds == 0 || full_rounds % 2 != 0 {
+            return Err(PoseidonError::InvalidFullRounds);
+        }
+        if alpha < 2 {
+            return Err(PoseidonError::InvalidAlpha);
+        }
+        if mds_matrix.len() != width
+            || mds_matrix.iter().any(|row| row.len() != width)
+        {
+            return Err(PoseidonError::InvalidMdsMatrix);
+        }
+        if round_constants.len() != width * (full_rounds + partial_rounds) {
+            return Err(PoseidonError::InvalidRoundConstants);
+        }
+        Ok(Self {
+            width,
+            full_rounds,
+            partial_rounds,
+            alpha,
+            mds_matrix,
+            round_constants,
+        })
+    }
+
+    /// Generates parameters for a permutation of the given `width` over `F`
+    /// with 128 bits of security.
+    ///
+    /// The S-box exponent is the smallest `alpha` for which `x^alpha` is a
+    /// permutation of `F`, and the number of rounds is given by
+    /// [`round_numbers`](crate::poseidon::round_numbers).
+    pub fn generate(width: usize) -> Result<Self, PoseidonError> {
+        let alpha = smallest_alpha::<F>();
+        let (full_rounds, partial_rounds) =
+            round_numbers::<F>(width, alpha, 128);
+        Self::generate_with_rounds(width, full_rounds, partial_rounds, alpha)
+    }
+
+    /// Generates the round constants and the MDS matrix of a permutation
+    /// with the given number of rounds and S-box exponent, the same way as
+    /// the reference implementation.
+    ///
+    /// The round constants are sampled first out of a Grain LFSR seeded with
+    /// the parameters of the permutation, followed by the MDS matrix, which
+    /// is a Cauchy matrix resampled until it admits no invariant subspace
+    /// trail.
+    pub fn generate_with_rounds(
+        width: usize,
+        full_rounds: usize,
+        partial_rounds: usize,
+        alpha: u64,
+    ) -> Result<Self, PoseidonError> {
+        let mut lfsr = GrainLfsr::new(
+            F::Params::MODULUS_BITS as usize,
+            width,
+            full_rounds,
+            partial_rounds,
+        );
+        let round_constants = lfsr
+            .field_elements_rejection(width * (full_rounds + partial_rounds));
+        let mds_matrix = mds::generate(&mut lfsr, width);
+        Self::new(
+            width,
+            full_rounds,
+            partial_rounds,
+            alpha,
+            mds_matrix,
+            round_constants,
+        )
+    }
+
+    /// Total number of rounds of the permutation.
+    pub fn rounds(&self) -> usize {
+        self.full_rounds + self.partial_rounds
+    }
+
+    /// Returns `true` if `round` applies the S-box to the whole state.
+    pub fn is_full_round(&self, round: usize) -> bool {
+        let half_full = self.full_rounds / 2;
+        round < half_full || round >= half_full + self.partial_rounds
+    }
+
+    /// Round constants added to the state at the start of `round`.
+    pub fn round_constants(&self, round: usize) -> &[F] {
+        &self.round_constants[round * self.width..(round + 1) * self.width]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::poseidon::{NativeSpec, PoseidonSpec};
+    use ark_bls12_381::Fr;
+    use ark_std::vec;
+
+    #[test]
+    fn test_parameter_checks() {
+        let mds = vec![vec![Fr::from(1u64); 3]; 3];
+        let rc = vec![Fr::from(2u64); 3 * 12];
+        assert!(
+            PoseidonConstants::new(3, 8, 4, 5, mds.clone(), rc.clone()).is_ok()
+        );
+        assert_eq!(
+            PoseidonConstants::new(1, 8, 4, 5, mds.clone(), rc.clone()),
+            Err(PoseidonError::InvalidWidth)
+        );
+        assert_eq!(
+            PoseidonConstants::new(3, 7, 5, 5, mds.clone(), rc.clone()),
+            Err(PoseidonError::InvalidFullRounds)
+        );
+        assert_eq!(
+            PoseidonConstants::new(3, 8, 4, 1, mds.clone(), rc.clone()),
+            Err(PoseidonError::InvalidAlpha)
+        );
+        assert_eq!(
+            PoseidonConstants::new(3, 8, 4, 5, mds[..2].to_vec(), rc.clone()),
+            Err(PoseidonError::InvalidMdsMatrix)
+        );
+        assert_eq!(
+            PoseidonConstants::new(3, 8, 5, 5, mds, rc),
+            Err(PoseidonError::InvalidRoundConstants)
+        );
+    }
+
+    #[test]
+    fn test_round_layout() {
+        let constants = PoseidonConstants::new(
+            2,
+            4,
+            3,
+            5,
+            vec![vec![Fr::from(1u64); 2]; 2],
+            (0..14u64).map(Fr::from).collect(),
+        )
+        .unwrap();
+        let full = (0..constants.rounds())
+            .map(|r| constants.is_full_round(r))
+            .collect::<Vec<_>>();
+        assert_eq!(full, vec![true, true, false, false, false, true, true]);
+        assert_eq!(
+            constants.round_constants(3),
+            &[Fr::from(6u64), Fr::from(7u64)]
+        );
+    }
+
+    /// Parses a big-endian hexadecimal string.
+    fn from_hex(hex: &str) -> Fr {
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+        Fr::from_be_bytes_mod_order(&bytes)
+    }
+
+    #[test]
+    fn test_reference_parameters() {
+        // Test vector `poseidonperm_x5_255_3` of the reference
+        // implementation.
+        let constants =
+            PoseidonConstants::<Fr>::generate_with_rounds(3, 8, 57, 5).unwrap();
+        assert_eq!(
+            constants.round_constants[0],
+            from_hex(
+                "6c4ffa723eaf1a7bf74905cc7dae4ca9ff4a2c3bc81d42e09540d1f250910880"
+            )
+        );
+        assert_eq!(
+            constants.mds_matrix[0][0],
+            from_hex(
+                "3d955d6c02fe4d7cb500e12f2b55eff668a7b4386bd27413766713c93f2acfcd"
+            )
+        );
+        let mut state = vec![Fr::from(0u64), Fr::from(1u64), Fr::from(2u64)];
+        NativeSpec::permute(&mut (), &constants, &mut state);
+        assert_eq!(
+            state,
+            [
+                "28ce19420fc246a05553ad1e8c98f5c9d67166be2c18e9e4cb4b4e317dd2a78a",
+                "51f3e312c95343a896cfd8945ea82ba956c1118ce9b9859b6ea56637b4b1ddc4",
+                "3b2b69139b235626a0bfb56c9527ae66a7bf486ad8c11c14d1da0c69bbe0f79a",
+            ]
+            .iter()
+            .map(|h| from_hex(h))
+            .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let constants = PoseidonConstants::<Fr>::generate(5).unwrap();
+        assert_eq!(constants.width, 5);
+        assert_eq!(constants.alpha, 5);
+        assert_eq!((constants.full_rounds, constants.partial_rounds), (8, 56));
+        assert!(mds::is_secure(&constants.mds_matrix));
+    }
+}
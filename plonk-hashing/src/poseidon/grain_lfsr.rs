@@ -0,0 +1,125 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Grain LFSR
+//!
+//! Pseudo-random generator used by the reference implementation of Poseidon
+//! to derive the round constants and the MDS matrix from the parameters of
+//! the permutation (see Appendix F of <https://eprint.iacr.org/2019/458>).
+
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_std::vec::Vec;
+
+/// Self-shrinking Grain LFSR with an 80-bit state.
+#[derive(Clone, Debug)]
+pub(crate) struct GrainLfsr {
+    /// Current state, `state[0]` being the oldest bit.
+    state: [bool; 80],
+    /// Index of `state[0]` in `state`, which is used as a ring buffer.
+    head: usize,
+    /// Number of bits of the field modulus.
+    field_bits: usize,
+}
+
+impl GrainLfsr {
+    /// Initialises the LFSR for a prime field of `field_bits` bits and the
+    /// S-box `x^alpha`, then discards its first 160 output bits.
+    pub(crate) fn new(
+        field_bits: usize,
+        width: usize,
+        full_rounds: usize,
+        partial_rounds: usize,
+    ) -> Self {
+        let mut state = [true; 80];
+        let mut pos = 0;
+        // Field type (1: prime field) and S-box type (0: x^alpha), followed
+        // by the parameters of the permutation.
+        for (value, len) in [
+            (1, 2),
+            (0, 4),
+            (field_bits, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10),
+        ] {
+            for i in (0..len).rev() {
+                state[pos] = (value >> i) & 1 == 1;
+                pos += 1;
+            }
+        }
+        // The remaining 30 bits are set to one.
+        let mut lfsr = Self {
+            state,
+            head: 0,
+            field_bits,
+        };
+        for _ in 0..160 {
+            lfsr.clock();
+        }
+        lfsr
+    }
+
+    /// Updates the state and returns the new bit.
+    fn clock(&mut self) -> bool {
+        let bit = |i: usize| self.state[(self.head + i) % 80];
+        let new_bit = bit(62) ^ bit(51) ^ bit(38) ^ bit(23) ^ bit(13) ^ bit(0);
+        self.state[self.head] = new_bit;
+        self.head = (self.head + 1) % 80;
+        new_bit
+    }
+
+    /// Returns the next output bit. Bits are produced in pairs, the second
+    /// bit being output only if the first one is set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.clock();
+            let bit = self.clock();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    /// Returns the next `field_bits` output bits, most significant first.
+    fn next_bits(&mut self) -> Vec<bool> {
+        (0..self.field_bits).map(|_| self.next_bit()).collect()
+    }
+
+    /// Samples `num` field elements, rejecting the integers that are not
+    /// smaller than the modulus.
+    pub(crate) fn field_elements_rejection<F>(&mut self, num: usize) -> Vec<F>
+    where
+        F: PrimeField,
+    {
+        (0..num)
+            .map(|_| loop {
+                let bits = self.next_bits();
+                if let Some(f) = F::from_repr(F::BigInt::from_bits_be(&bits)) {
+                    break f;
+                }
+            })
+            .collect()
+    }
+
+    /// Samples `num` field elements, reducing the sampled integers modulo
+    /// the field modulus.
+    pub(crate) fn field_elements_mod_p<F>(&mut self, num: usize) -> Vec<F>
+    where
+        F: PrimeField,
+    {
+        (0..num)
+            .map(|_| {
+                let mut repr = F::BigInt::from_bits_be(&self.next_bits());
+                // The integer has as many bits as the modulus, so a single
+                // subtraction is enough to reduce it.
+                if repr >= F::Params::MODULUS {
+                    repr.sub_noborrow(&F::Params::MODULUS);
+                }
+                F::from_repr(repr).expect("value is reduced")
+            })
+            .collect()
+    }
+}
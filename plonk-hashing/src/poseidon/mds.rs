@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Poseidon MDS Matrix
+//!
+//! Generation of Cauchy MDS matrices which do not allow infinitely long
+//! invariant subspace trails through the partial rounds.
+
+use crate::poseidon::grain_lfsr::GrainLfsr;
+use ark_ff::PrimeField;
+use ark_std::{vec, vec::Vec};
+
+/// Samples Cauchy matrices `M[i][j] = 1 / (x_i + y_j)` out of `lfsr` until
+/// one of them passes [`is_secure`].
+pub(crate) fn generate<F>(lfsr: &mut GrainLfsr, width: usize) -> Vec<Vec<F>>
+where
+    F: PrimeField,
+{
+    loop {
+        let values = lfsr.field_elements_mod_p::<F>(2 * width);
+        let distinct = values
+            .iter()
+            .enumerate()
+            .all(|(i, v)| !values[..i].contains(v));
+        if !distinct {
+            continue;
+        }
+        let (xs, ys) = values.split_at(width);
+        let matrix = xs
+            .iter()
+            .map(|x| ys.iter().map(|y| (*x + y).inverse()).collect())
+            .collect::<Option<Vec<Vec<F>>>>();
+        if let Some(matrix) = matrix {
+            if is_secure(&matrix) {
+                return matrix;
+            }
+        }
+    }
+}
+
+/// Returns `true` if `M` admits no infinitely long subspace trail, following
+/// the algorithms of <https://eprint.iacr.org/2020/500> as implemented by the
+/// reference parameter generation script.
+///
+/// With a single S-box per partial round, such a trail exists for the
+/// period `r` if the first unit vector is not a cyclic vector of `M^r` or of
+/// its transpose, which is checked for every `r` up to `4 * width`.
+pub(crate) fn is_secure<F>(matrix: &[Vec<F>]) -> bool
+where
+    F: PrimeField,
+{
+    let mut power = matrix.to_vec();
+    for _ in 0..4 * matrix.len() {
+        if !is_cyclic(&power) || !is_cyclic(&transpose(&power)) {
+            return false;
+        }
+        power = mat_mul(&power, matrix);
+    }
+    true
+}
+
+/// Returns the product of two square matrices.
+fn mat_mul<F>(a: &[Vec<F>], b: &[Vec<F>]) -> Vec<Vec<F>>
+where
+    F: PrimeField,
+{
+    a.iter()
+        .map(|row| {
+            (0..b.len())
+                .map(|j| row.iter().zip(b).map(|(x, col)| *x * col[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the transpose of a square matrix.
+fn transpose<F>(m: &[Vec<F>]) -> Vec<Vec<F>>
+where
+    F: PrimeField,
+{
+    (0..m.len())
+        .map(|j| m.iter().map(|row| row[j]).collect())
+        .collect()
+}
+
+/// Returns `true` if `e_0, M e_0, ..., M^(t - 1) e_0` span the whole space.
+fn is_cyclic<F>(m: &[Vec<F>]) -> bool
+where
+    F: PrimeField,
+{
+    let t = m.len();
+    let mut v = vec![F::zero(); t];
+    v[0] = F::one();
+    let mut krylov = Vec::with_capacity(t);
+    for _ in 0..t {
+        let next = m
+            .iter()
+            .map(|row| row.iter().zip(&v).map(|(x, y)| *x * y).sum())
+            .collect();
+        krylov.push(v);
+        v = next;
+    }
+    rank(krylov) == t
+}
+
+/// Returns the rank of a list of vectors, by Gaussian elimination.
+fn rank<F>(mut rows: Vec<Vec<F>>) -> usize
+where
+    F: PrimeField,
+{
+    let mut rank = 0;
+    for col in 0..rows.first().map_or(0, Vec::len) {
+        let pivot = match (rank..rows.len()).find(|i| !rows[*i][col].is_zero())
+        {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+        let inv = rows[rank][col].inverse().expect("pivot is non-zero");
+        for i in rank + 1..rows.len() {
+            let factor = rows[i][col] * inv;
+            for j in col..rows[i].len() {
+                let sub = factor * rows[rank][j];
+                rows[i][j] -= sub;
+            }
+        }
+        rank += 1;
+    }
+    rank
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    fn poly(coeffs: &[i64]) -> Vec<Fr> {
+        coeffs
+            .iter()
+            .map(|c| {
+                let f = Fr::from(c.unsigned_abs());
+                if *c < 0 {
+                    -f
+                } else {
+                    f
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_subspace_trails() {
+        // A permutation matrix leaves the span of the last two unit vectors,
+        // on which the S-box of the partial rounds is inactive, invariant.
+        let m = vec![poly(&[0, 1, 0]), poly(&[1, 0, 0]), poly(&[0, 0, 1])];
+        assert!(!is_cyclic(&m));
+        assert!(!is_secure(&m));
+        let m = vec![poly(&[2, 3]), poly(&[5, 7])];
+        assert!(is_cyclic(&m));
+        assert!(is_secure(&m));
+        assert_eq!(rank(vec![poly(&[1, 2]), poly(&[2, 4])]), 1);
+        assert_eq!(rank(m), 2);
+    }
+}
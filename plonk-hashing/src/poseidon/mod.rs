@@ -0,0 +1,112 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Poseidon Hash Function
+//!
+//! Native and in-circuit implementations of the Poseidon permutation (see
+//! <https://eprint.iacr.org/2019/458>) and of a duplex sponge built on top of
+//! it. Both implementations share the same [`PoseidonConstants`] and the same
+//! sponge logic through the [`PoseidonSpec`] trait, so that the gadget
+//! computes exactly the same values as the native hash.
+//!
+//! Parameters for any prime field can be derived with
+//! [`PoseidonConstants::generate`], which picks the S-box and the number of
+//! rounds from the security analysis of the paper and samples the round
+//! constants and MDS matrix with the Grain LFSR of the reference
+//! implementation.
+//!
+//! The [`PoseidonTranscript`] derives the Fiat-Shamir challenges of PLONK
+//! proofs from the same sponge, making them cheap to recompute in a circuit
+//! verifying those proofs.
+
+mod constants;
+mod grain_lfsr;
+mod mds;
+mod native;
+mod plonk;
+mod round_numbers;
+mod sponge;
+mod transcript;
+
+pub use constants::PoseidonConstants;
+pub use native::NativeSpec;
+pub use plonk::PlonkSpec;
+pub use round_numbers::{round_numbers, smallest_alpha};
+pub use sponge::PoseidonSponge;
+pub use transcript::PoseidonTranscript;
+
+use ark_ff::PrimeField;
+use core::fmt;
+
+/// Arithmetic backend over which the Poseidon permutation is evaluated.
+///
+/// `COM` is the context the computation happens in, `()` for the native
+/// implementation and a
+/// [`StandardComposer`](plonk_core::constraint_system::StandardComposer) for
+/// the gadget.
+pub trait PoseidonSpec<COM, F>
+where
+    F: PrimeField,
+{
+    /// Representation of a state element.
+    type Field: Clone;
+
+    /// Returns a state element holding zero.
+    fn zero(c: &mut COM) -> Self::Field;
+
+    /// Returns a state element holding the fixed value `value`.
+    fn constant(c: &mut COM, value: F) -> Self::Field;
+
+    /// Returns `x + y`.
+    fn add(c: &mut COM, x: &Self::Field, y: &Self::Field) -> Self::Field;
+
+    /// Applies the Poseidon permutation defined by `constants` to `state`.
+    fn permute(
+        c: &mut COM,
+        constants: &PoseidonConstants<F>,
+        state: &mut [Self::Field],
+    );
+}
+
+/// Poseidon Errors
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PoseidonError {
+    /// The width of the permutation must be at least two.
+    InvalidWidth,
+    /// The number of full rounds must be even and non-zero.
+    InvalidFullRounds,
+    /// The S-box exponent must be greater than one.
+    InvalidAlpha,
+    /// The MDS matrix is not a `width x width` matrix.
+    InvalidMdsMatrix,
+    /// The number of round constants does not match
+    /// `width * (full_rounds + partial_rounds)`.
+    InvalidRoundConstants,
+    /// The rate must be between one and `width - 1`.
+    InvalidRate,
+}
+
+impl fmt::Display for PoseidonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidWidth => write!(f, "Poseidon width must be >= 2"),
+            Self::InvalidFullRounds => {
+                write!(f, "Number of full rounds must be even and non-zero")
+            }
+            Self::InvalidAlpha => write!(f, "S-box exponent must be > 1"),
+            Self::InvalidMdsMatrix => {
+                write!(f, "MDS matrix must be square, of size width")
+            }
+            Self::InvalidRoundConstants => {
+                write!(f, "Wrong number of round constants")
+            }
+            Self::InvalidRate => write!(f, "Rate must be in [1, width - 1]"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PoseidonError {}
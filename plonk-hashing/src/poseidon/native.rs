@@ -0,0 +1,59 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Native Poseidon Permutation
+
+use crate::poseidon::{PoseidonConstants, PoseidonSpec};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+/// Out-of-circuit Poseidon, evaluated directly over the field `F`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeSpec;
+
+impl<F> PoseidonSpec<(), F> for NativeSpec
+where
+    F: PrimeField,
+{
+    type Field = F;
+
+    fn zero(_: &mut ()) -> F {
+        F::zero()
+    }
+
+    fn constant(_: &mut (), value: F) -> F {
+        value
+    }
+
+    fn add(_: &mut (), x: &F, y: &F) -> F {
+        *x + y
+    }
+
+    fn permute(_: &mut (), constants: &PoseidonConstants<F>, state: &mut [F]) {
+        assert_eq!(state.len(), constants.width);
+        for round in 0..constants.rounds() {
+            for (s, c) in state.iter_mut().zip(constants.round_constants(round))
+            {
+                *s += c;
+            }
+            if constants.is_full_round(round) {
+                for s in state.iter_mut() {
+                    *s = s.pow([constants.alpha]);
+                }
+            } else {
+                state[0] = state[0].pow([constants.alpha]);
+            }
+            let mixed = constants
+                .mds_matrix
+                .iter()
+                .map(|row| {
+                    row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum()
+                })
+                .collect::<Vec<F>>();
+            state.copy_from_slice(&mixed);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Poseidon Permutation Gadget
+
+use crate::{
+    arithmetic::{linear_combination, pow},
+    poseidon::{PoseidonConstants, PoseidonSpec},
+};
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use ark_std::{vec, vec::Vec};
+use plonk_core::constraint_system::{StandardComposer, Variable};
+
+/// In-circuit Poseidon, evaluated with the arithmetic gates of a
+/// [`StandardComposer`].
+///
+/// Round constants are never added with a dedicated gate: they are folded
+/// into the selectors of the S-box gates, or into the constant selector of
+/// the linear layer for the lanes that skip the S-box in partial rounds.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlonkSpec;
+
+impl<F, P> PoseidonSpec<StandardComposer<F, P>, F> for PlonkSpec
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    type Field = Variable;
+
+    fn zero(c: &mut StandardComposer<F, P>) -> Variable {
+        c.zero_var()
+    }
+
+    fn constant(c: &mut StandardComposer<F, P>, value: F) -> Variable {
+        c.add_witness_to_circuit_description(value)
+    }
+
+    fn add(
+        c: &mut StandardComposer<F, P>,
+        x: &Variable,
+        y: &Variable,
+    ) -> Variable {
+        c.arithmetic_gate(|gate| {
+            gate.witness(*x, *y, None).add(F::one(), F::one())
+        })
+    }
+
+    fn permute(
+        c: &mut StandardComposer<F, P>,
+        constants: &PoseidonConstants<F>,
+        state: &mut [Variable],
+    ) {
+        assert_eq!(state.len(), constants.width);
+        for round in 0..constants.rounds() {
+            let round_constants = constants.round_constants(round);
+            // Constants that still have to be added to each lane before the
+            // linear layer.
+            let mut offsets = vec![F::zero(); constants.width];
+            if constants.is_full_round(round) {
+                for (s, rc) in state.iter_mut().zip(round_constants) {
+                    *s = pow(c, *s, *rc, constants.alpha);
+                }
+            } else {
+                state[0] =
+                    pow(c, state[0], round_constants[0], constants.alpha);
+                offsets[1..].copy_from_slice(&round_constants[1..]);
+            }
+            let mixed = constants
+                .mds_matrix
+                .iter()
+                .map(|row| {
+                    let constant = row
+                        .iter()
+                        .zip(offsets.iter())
+                        .map(|(m, o)| *m * o)
+                        .sum();
+                    let terms = row
+                        .iter()
+                        .copied()
+                        .zip(state.iter().copied())
+                        .collect::<Vec<_>>();
+                    linear_combination(c, &terms, constant)
+                })
+                .collect::<Vec<_>>();
+            state.copy_from_slice(&mixed);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Poseidon Round Numbers
+//!
+//! Computes the smallest secure number of rounds of a Poseidon permutation,
+//! following the security analysis of <https://eprint.iacr.org/2019/458>
+//! (with the additional bound of <https://eprint.iacr.org/2023/537>) as
+//! implemented by the reference `calc_round_numbers.py` script.
+//!
+//! The computations are done with `f64`s, whose logarithm is not available
+//! in `core`, so the few elementary functions needed here are implemented
+//! below.
+
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+
+/// Largest number of partial rounds considered by [`round_numbers`].
+const MAX_PARTIAL_ROUNDS: usize = 500;
+
+/// Largest number of full rounds considered by [`round_numbers`].
+const MAX_FULL_ROUNDS: usize = 100;
+
+/// Returns the smallest integer greater than or equal to `x`.
+fn ceil(x: f64) -> f64 {
+    let t = x as i64 as f64;
+    if t < x {
+        t + 1.0
+    } else {
+        t
+    }
+}
+
+/// Returns the largest integer less than or equal to `x`.
+fn floor(x: f64) -> f64 {
+    let t = x as i64 as f64;
+    if t > x {
+        t - 1.0
+    } else {
+        t
+    }
+}
+
+/// Returns the base 2 logarithm of the positive number `x`.
+fn log2(x: f64) -> f64 {
+    let bits = x.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    // Mantissa in [1, 2).
+    let m = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    // ln(m) = 2 * atanh((m - 1) / (m + 1)), with |y| <= 1/3.
+    let y = (m - 1.0) / (m + 1.0);
+    let y2 = y * y;
+    let mut term = y;
+    let mut ln = 0.0;
+    for k in 0..40 {
+        ln += term / (2 * k + 1) as f64;
+        term *= y2;
+    }
+    exponent as f64 + 2.0 * ln / core::f64::consts::LN_2
+}
+
+/// Returns the base 2 logarithm of the binomial coefficient `n choose k`.
+fn log2_binomial(n: usize, k: usize) -> f64 {
+    let k = k.min(n - k);
+    (1..=k)
+        .map(|i| log2((n - k + i) as f64) - log2(i as f64))
+        .sum()
+}
+
+/// Returns `true` if a Poseidon permutation of width `t` over a field of
+/// `log2(p) = field_bits` with S-box `x^alpha`, `full_rounds` full rounds
+/// and `partial_rounds` partial rounds reaches `security_level` bits of
+/// security, without security margin.
+fn is_secure(
+    field_bits: f64,
+    t: usize,
+    alpha: u64,
+    security_level: u32,
+    full_rounds: usize,
+    partial_rounds: usize,
+) -> bool {
+    let (n, m) = (ceil(field_bits), security_level as f64);
+    let (tf, rf, rp) = (t as f64, full_rounds as f64, partial_rounds as f64);
+    let log_alpha_2 = 1.0 / log2(alpha as f64);
+    // Statistical attacks.
+    let rf_1 = if m <= floor(field_bits - (alpha - 1) as f64 / 2.0) * (tf + 1.0)
+    {
+        6.0
+    } else {
+        10.0
+    };
+    // Interpolation attack.
+    let rf_2 =
+        1.0 + ceil(log_alpha_2 * m.min(n)) + ceil(log2(tf) * log_alpha_2) - rp;
+    // Groebner basis attacks.
+    let rf_3 = log_alpha_2 * m.min(field_bits) - rp;
+    let rf_4 =
+        tf - 1.0 + log_alpha_2 * (m / (tf + 1.0)).min(field_bits / 2.0) - rp;
+    let rf_5 = (tf - 2.0 + m / (2.0 * log2(alpha as f64)) - rp) / (tf - 1.0);
+    let rf_max = [rf_1, rf_2, rf_3, rf_4, rf_5]
+        .iter()
+        .map(|x| ceil(*x))
+        .fold(f64::MIN, f64::max);
+    if rf < rf_max {
+        return false;
+    }
+    // Groebner basis attack skipping the first full rounds.
+    let r = t / 3;
+    let over = (full_rounds - 1) * t
+        + 2 * partial_rounds
+        + r
+        + r * (full_rounds / 2)
+        + alpha as usize;
+    let under = r * (full_rounds / 2) + partial_rounds + alpha as usize;
+    ceil(2.0 * log2_binomial(over, under)) >= m
+}
+
+/// Returns the number of full and partial rounds of a Poseidon permutation
+/// of width `t` over `F` with the S-box `x^alpha`, reaching
+/// `security_level` bits of security.
+///
+/// Among all the secure pairs, two full rounds and 7.5% of partial rounds
+/// are added as a security margin, and the pair minimising the number of
+/// S-boxes is returned.
+pub fn round_numbers<F>(
+    t: usize,
+    alpha: u64,
+    security_level: u32,
+) -> (usize, usize)
+where
+    F: PrimeField,
+{
+    let field_bits = log2_modulus::<F>();
+    let mut best: Option<(usize, usize, usize)> = None;
+    for partial_rounds in 1..MAX_PARTIAL_ROUNDS {
+        let full_rounds = (4..MAX_FULL_ROUNDS).step_by(2).find(|rf| {
+            is_secure(field_bits, t, alpha, security_level, *rf, partial_rounds)
+        });
+        if let Some(rf) = full_rounds {
+            let rf = rf + 2;
+            let rp = ceil(partial_rounds as f64 * 1.075) as usize;
+            let cost = t * rf + rp;
+            match best {
+                Some((c, best_rf, _)) if (c, best_rf) <= (cost, rf) => {}
+                _ => best = Some((cost, rf, rp)),
+            }
+        }
+    }
+    let (_, rf, rp) = best.expect("some parameters are always secure");
+    (rf, rp)
+}
+
+/// Returns the smallest `alpha >= 3` such that `x^alpha` is a permutation of
+/// `F`, that is such that `gcd(alpha, p - 1) = 1`.
+pub fn smallest_alpha<F>() -> u64
+where
+    F: PrimeField,
+{
+    let mut p_minus_one = F::Params::MODULUS;
+    p_minus_one.sub_noborrow(&F::BigInt::from(1));
+    (3..)
+        .find(|alpha| {
+            // alpha is coprime with p - 1 iff none of its prime factors
+            // divides p - 1.
+            (2..=*alpha)
+                .filter(|d| alpha % d == 0 && (2..*d).all(|e| d % e != 0))
+                .all(|d| rem_u64(&p_minus_one, d) != 0)
+        })
+        .expect("x^alpha is a permutation for some alpha")
+}
+
+/// Returns `x mod d`.
+fn rem_u64<B>(x: &B, d: u64) -> u64
+where
+    B: BigInteger,
+{
+    x.as_ref().iter().rev().fold(0u64, |acc, limb| {
+        (((acc as u128) << 64 | *limb as u128) % d as u128) as u64
+    })
+}
+
+/// Returns `log2(p)`, computed from the 64 most significant bits of the
+/// modulus `p` of `F`.
+fn log2_modulus<F>() -> f64
+where
+    F: PrimeField,
+{
+    let num_bits = F::Params::MODULUS_BITS as usize;
+    let bits = F::Params::MODULUS.to_bits_be();
+    let top = bits[bits.len() - num_bits..]
+        .iter()
+        .take(64)
+        .fold(0u64, |acc, b| acc << 1 | *b as u64);
+    log2(top as f64) + num_bits.saturating_sub(64) as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_elementary_functions() {
+        assert_eq!(ceil(1.5), 2.0);
+        assert_eq!(ceil(-1.5), -1.0);
+        assert_eq!(ceil(3.0), 3.0);
+        assert_eq!(floor(1.5), 1.0);
+        assert_eq!(floor(-1.5), -2.0);
+        assert_eq!(floor(3.0), 3.0);
+        for (x, expected) in [(1.0, 0.0), (8.0, 3.0), (0.5, -1.0)] {
+            assert_eq!(log2(x), expected);
+        }
+        assert!((log2(5.0) - 2.321928094887362).abs() < 1e-12);
+        assert!((log2(3.0) - 1.584962500721156).abs() < 1e-12);
+        assert!((log2_binomial(10, 3) - 120f64.log2()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_bls12_381_round_numbers() {
+        use ark_bls12_381::Fr;
+        assert_eq!(smallest_alpha::<Fr>(), 5);
+        for t in [2, 3, 4, 5] {
+            assert_eq!(round_numbers::<Fr>(t, 5, 128), (8, 56));
+        }
+        for t in [9, 12] {
+            assert_eq!(round_numbers::<Fr>(t, 5, 128), (8, 57));
+        }
+        assert_eq!(round_numbers::<Fr>(3, 17, 128), (8, 31));
+    }
+
+    #[test]
+    fn test_bls12_377_round_numbers() {
+        use ark_bls12_377::Fr;
+        let alpha = smallest_alpha::<Fr>();
+        let mut p_minus_one = <Fr as PrimeField>::Params::MODULUS;
+        p_minus_one.sub_noborrow(&1u64.into());
+        assert_ne!(rem_u64(&p_minus_one, alpha), 0);
+        for smaller in 3..alpha {
+            assert!((2..=smaller)
+                .any(|d| smaller % d == 0 && rem_u64(&p_minus_one, d) == 0));
+        }
+        let (rf, rp) = round_numbers::<Fr>(3, alpha, 128);
+        assert_eq!(rf, 8);
+        assert!(is_secure(log2_modulus::<Fr>(), 3, alpha, 128, rf - 2, rp));
+        assert!(!is_secure(log2_modulus::<Fr>(), 3, alpha, 128, rf - 2, 1));
+    }
+}
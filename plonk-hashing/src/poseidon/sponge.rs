@@ -0,0 +1,311 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Poseidon Sponge
+
+use crate::poseidon::{PoseidonConstants, PoseidonError, PoseidonSpec};
+use ark_ff::PrimeField;
+use ark_std::{marker::PhantomData, vec::Vec};
+
+/// Position of the sponge inside the rate part of the state.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SpongeMode {
+    /// Number of elements absorbed since the last permutation.
+    Absorbing(usize),
+    /// Number of elements squeezed since the last permutation.
+    Squeezing(usize),
+}
+
+/// Duplex sponge over the Poseidon permutation.
+///
+/// The state is split into `width - rate` capacity elements, stored first,
+/// followed by `rate` elements into which the inputs are absorbed and from
+/// which the outputs are squeezed.
+#[derive(derivative::Derivative)]
+#[derivative(
+    Clone(bound = "S::Field: Clone"),
+    Debug(bound = "S::Field: core::fmt::Debug")
+)]
+pub struct PoseidonSponge<COM, S, F>
+where
+    S: PoseidonSpec<COM, F>,
+    F: PrimeField,
+{
+    constants: PoseidonConstants<F>,
+    rate: usize,
+    state: Vec<S::Field>,
+    mode: SpongeMode,
+    __: PhantomData<(COM, S)>,
+}
+
+impl<COM, S, F> PoseidonSponge<COM, S, F>
+where
+    S: PoseidonSpec<COM, F>,
+    F: PrimeField,
+{
+    /// Creates a sponge with an all-zero state absorbing `rate` elements per
+    /// permutation.
+    pub fn new(
+        c: &mut COM,
+        constants: PoseidonConstants<F>,
+        rate: usize,
+    ) -> Result<Self, PoseidonError> {
+        Self::with_domain_tag(c, constants, rate, F::zero())
+    }
+
+    /// Creates a sponge whose first capacity element is set to `tag`, the
+    /// rest of the state being zero.
+    pub fn with_domain_tag(
+        c: &mut COM,
+        constants: PoseidonConstants<F>,
+        rate: usize,
+        tag: F,
+    ) -> Result<Self, PoseidonError> {
+        if rate == 0 || rate >= constants.width {
+            return Err(PoseidonError::InvalidRate);
+        }
+        let mut state = Vec::with_capacity(constants.width);
+        state.push(S::constant(c, tag));
+        for _ in 1..constants.width {
+            state.push(S::zero(c));
+        }
+        Ok(Self {
+            constants,
+            rate,
+            state,
+            mode: SpongeMode::Absorbing(0),
+            __: PhantomData,
+        })
+    }
+
+    /// Number of capacity elements of the state.
+    fn capacity(&self) -> usize {
+        self.constants.width - self.rate
+    }
+
+    /// Absorbs `inputs` into the sponge, permuting the state every time the
+    /// rate is full.
+    pub fn absorb(&mut self, c: &mut COM, inputs: &[S::Field]) {
+        for input in inputs {
+            let pos = match self.mode {
+                SpongeMode::Absorbing(pos) if pos == self.rate => {
+                    S::permute(c, &self.constants, &mut self.state);
+                    0
+                }
+                SpongeMode::Absorbing(pos) => pos,
+                SpongeMode::Squeezing(_) => 0,
+            };
+            let i = self.capacity() + pos;
+            self.state[i] = S::add(c, &self.state[i], input);
+            self.mode = SpongeMode::Absorbing(pos + 1);
+        }
+    }
+
+    /// Squeezes one element out of the sponge.
+    pub fn squeeze(&mut self, c: &mut COM) -> S::Field {
+        let pos = match self.mode {
+            SpongeMode::Absorbing(_) => {
+                S::permute(c, &self.constants, &mut self.state);
+                0
+            }
+            SpongeMode::Squeezing(pos) if pos == self.rate => {
+                S::permute(c, &self.constants, &mut self.state);
+                0
+            }
+            SpongeMode::Squeezing(pos) => pos,
+        };
+        self.mode = SpongeMode::Squeezing(pos + 1);
+        self.state[self.capacity() + pos].clone()
+    }
+
+    /// Hashes a fixed-length message into a single field element.
+    ///
+    /// Following section 4.2 of the Poseidon paper, the first capacity
+    /// element is initialised to `inputs.len() * 2^64` so that messages of
+    /// different lengths use separate domains.
+    pub fn hash(
+        c: &mut COM,
+        constants: PoseidonConstants<F>,
+        rate: usize,
+        inputs: &[S::Field],
+    ) -> Result<S::Field, PoseidonError> {
+        let tag = F::from(inputs.len() as u64) * F::from(2u64).pow([64]);
+        let mut sponge = Self::with_domain_tag(c, constants, rate, tag)?;
+        sponge.absorb(c, inputs);
+        Ok(sponge.squeeze(c))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test,
+        poseidon::{NativeSpec, PlonkSpec},
+        test::gadget_tester,
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::TEModelParameters;
+    use ark_std::{test_rng, UniformRand};
+    use plonk_core::{
+        commitment::HomomorphicCommitment, constraint_system::StandardComposer,
+    };
+
+    type NativeSponge<F> = PoseidonSponge<(), NativeSpec, F>;
+    type PlonkSponge<F, P> =
+        PoseidonSponge<StandardComposer<F, P>, PlonkSpec, F>;
+
+    fn test_permutation_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        for width in [3, 5] {
+            let constants = PoseidonConstants::<F>::generate(width).unwrap();
+            let input =
+                (0..width).map(|_| F::rand(&mut rng)).collect::<Vec<_>>();
+            let mut expected = input.clone();
+            NativeSpec::permute(&mut (), &constants, &mut expected);
+
+            let res = gadget_tester::<F, P, PC>(
+                |composer: &mut StandardComposer<F, P>| {
+                    let mut state = input
+                        .iter()
+                        .map(|x| composer.add_input(*x))
+                        .collect::<Vec<_>>();
+                    PlonkSpec::permute(composer, &constants, &mut state);
+                    for (var, value) in state.iter().zip(expected.iter()) {
+                        assert_eq!(composer.value_of_var(*var), *value);
+                        composer.constrain_to_constant(*var, *value, None);
+                    }
+                },
+                2000,
+            );
+            assert!(res.is_ok(), "{:?}", res.err().unwrap());
+        }
+    }
+
+    fn test_sponge_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        let constants = PoseidonConstants::<F>::generate(3).unwrap();
+        let input = (0..7).map(|_| F::rand(&mut rng)).collect::<Vec<_>>();
+
+        let mut native = NativeSponge::new(&mut (), constants.clone(), 2)
+            .expect("valid rate");
+        native.absorb(&mut (), &input[..3]);
+        let mut expected = vec![native.squeeze(&mut ())];
+        native.absorb(&mut (), &input[3..]);
+        for _ in 0..3 {
+            expected.push(native.squeeze(&mut ()));
+        }
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let input = input
+                    .iter()
+                    .map(|x| composer.add_input(*x))
+                    .collect::<Vec<_>>();
+                let mut sponge =
+                    PlonkSponge::new(composer, constants.clone(), 2)
+                        .expect("valid rate");
+                sponge.absorb(composer, &input[..3]);
+                let mut output = vec![sponge.squeeze(composer)];
+                sponge.absorb(composer, &input[3..]);
+                for _ in 0..3 {
+                    output.push(sponge.squeeze(composer));
+                }
+                for (var, value) in output.iter().zip(expected.iter()) {
+                    assert_eq!(composer.value_of_var(*var), *value);
+                    composer.constrain_to_constant(*var, *value, None);
+                }
+            },
+            4000,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_hash_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        let constants = PoseidonConstants::<F>::generate(5).unwrap();
+        let input = (0..4).map(|_| F::rand(&mut rng)).collect::<Vec<_>>();
+        let expected =
+            NativeSponge::hash(&mut (), constants.clone(), 4, &input).unwrap();
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let input = input
+                    .iter()
+                    .map(|x| composer.add_input(*x))
+                    .collect::<Vec<_>>();
+                let output =
+                    PlonkSponge::hash(composer, constants.clone(), 4, &input)
+                        .unwrap();
+                assert_eq!(composer.value_of_var(output), expected);
+                composer.constrain_to_constant(output, expected, None);
+            },
+            2000,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    #[test]
+    fn test_hash_domain_separation() {
+        use ark_bls12_381::Fr;
+        let constants = PoseidonConstants::<Fr>::generate(3).unwrap();
+        let x = Fr::rand(&mut test_rng());
+        let short = NativeSponge::hash(&mut (), constants.clone(), 2, &[x]);
+        let long = NativeSponge::hash(
+            &mut (),
+            constants.clone(),
+            2,
+            &[x, Fr::from(0u64)],
+        );
+        assert_ne!(short.unwrap(), long.unwrap());
+        assert_eq!(
+            NativeSponge::new(&mut (), constants.clone(), 3).err(),
+            Some(PoseidonError::InvalidRate)
+        );
+        assert_eq!(
+            NativeSponge::new(&mut (), constants, 0).err(),
+            Some(PoseidonError::InvalidRate)
+        );
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_permutation_gadget,
+            test_sponge_gadget,
+            test_hash_gadget
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_permutation_gadget,
+            test_sponge_gadget,
+            test_hash_gadget
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
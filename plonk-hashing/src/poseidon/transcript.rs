@@ -0,0 +1,302 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Poseidon Transcript
+
+use crate::poseidon::{NativeSpec, PoseidonConstants, PoseidonSponge};
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+use plonk_core::transcript::TranscriptProtocol;
+
+/// Width of the permutation of the [`PoseidonTranscript`].
+const WIDTH: usize = 5;
+
+/// Number of elements absorbed per permutation by the [`PoseidonTranscript`].
+const RATE: usize = 4;
+
+/// Fiat-Shamir transcript over a Poseidon duplex sponge on the scalar field
+/// `F` of the proof system.
+///
+/// Scalars are absorbed as they are and challenges are squeezed directly out
+/// of the sponge, so that a verifier circuit over `F` can recompute the
+/// transcript with a few Poseidon permutations. Commitments, and any other
+/// serialized message, are absorbed as their length followed by their bytes
+/// packed little-endian into field elements of `(MODULUS_BITS - 1) / 8`
+/// bytes each.
+///
+/// Only the labels of [`TranscriptProtocol::new`], which separates the
+/// transcripts of different protocols, and of
+/// [`TranscriptProtocol::append_message`] are absorbed: the order of the
+/// other messages is fixed by the protocol.
+///
+/// The constants of the permutation are generated once per field and shared
+/// by all the transcripts over it, when the `std` feature is enabled.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct PoseidonTranscript<F>
+where
+    F: PrimeField,
+{
+    sponge: PoseidonSponge<(), NativeSpec, F>,
+}
+
+/// Returns the constants of the permutation of the [`PoseidonTranscript`]
+/// over `F`, generated on the first call for every field.
+#[cfg(any(feature = "std", test))]
+fn transcript_constants<F>() -> PoseidonConstants<F>
+where
+    F: PrimeField,
+{
+    use std::{
+        any::{Any, TypeId},
+        collections::HashMap,
+        sync::{Mutex, OnceLock},
+    };
+
+    type ConstantsCache = HashMap<TypeId, Box<dyn Any + Send + Sync>>;
+    static CONSTANTS: OnceLock<Mutex<ConstantsCache>> = OnceLock::new();
+
+    let mut cache = CONSTANTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    cache
+        .entry(TypeId::of::<F>())
+        .or_insert_with(|| Box::new(generate_transcript_constants::<F>()))
+        .downcast_ref::<PoseidonConstants<F>>()
+        .expect("the constants are cached under the type id of their field")
+        .clone()
+}
+
+/// Returns the constants of the permutation of the [`PoseidonTranscript`]
+/// over `F`.
+#[cfg(not(any(feature = "std", test)))]
+fn transcript_constants<F>() -> PoseidonConstants<F>
+where
+    F: PrimeField,
+{
+    generate_transcript_constants()
+}
+
+/// Generates the constants of the permutation of the [`PoseidonTranscript`]
+/// over `F`.
+fn generate_transcript_constants<F>() -> PoseidonConstants<F>
+where
+    F: PrimeField,
+{
+    PoseidonConstants::generate(WIDTH)
+        .expect("Poseidon parameters exist for a width of 5")
+}
+
+impl<F> PoseidonTranscript<F>
+where
+    F: PrimeField,
+{
+    /// Absorbs the length of `bytes` followed by their packing into field
+    /// elements.
+    fn absorb_bytes(&mut self, bytes: &[u8]) {
+        let chunk_size = (F::size_in_bits() - 1) / 8;
+        let mut elements = Vec::with_capacity(1 + bytes.len() / chunk_size);
+        elements.push(F::from(bytes.len() as u64));
+        elements
+            .extend(bytes.chunks(chunk_size).map(F::from_le_bytes_mod_order));
+        self.sponge.absorb(&mut (), &elements);
+    }
+}
+
+impl<F> TranscriptProtocol<F> for PoseidonTranscript<F>
+where
+    F: PrimeField,
+{
+    fn new(label: &'static [u8]) -> Self {
+        let sponge = PoseidonSponge::new(&mut (), transcript_constants(), RATE)
+            .expect("the rate is smaller than the width");
+        let mut transcript = Self { sponge };
+        transcript.absorb_bytes(label);
+        transcript
+    }
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.absorb_bytes(label);
+        self.absorb_bytes(message);
+    }
+
+    fn append(&mut self, _: &'static [u8], item: &impl CanonicalSerialize) {
+        let mut bytes = Vec::new();
+        item.serialize(&mut bytes).unwrap();
+        self.absorb_bytes(&bytes);
+    }
+
+    fn append_scalar(&mut self, _: &'static [u8], scalar: &F) {
+        self.sponge.absorb(&mut (), &[*scalar]);
+    }
+
+    fn challenge_scalar(&mut self, _: &'static [u8]) -> F {
+        self.sponge.squeeze(&mut ())
+    }
+
+    fn circuit_domain_sep(&mut self, n: u64) {
+        self.sponge.absorb(&mut (), &[F::from(n)]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test,
+        test::{gadget_tester, gadget_tester_with_transcript},
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::TEModelParameters;
+    use ark_std::{test_rng, UniformRand};
+    use plonk_core::{
+        commitment::HomomorphicCommitment,
+        constraint_system::StandardComposer,
+        error::{to_pc_error, Error},
+        proof_system::{Prover, Verifier},
+    };
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_challenges() {
+        use ark_bls12_381::Fr;
+        let mut rng = test_rng();
+        let x = Fr::rand(&mut rng);
+
+        let mut t1 = PoseidonTranscript::<Fr>::new(b"test");
+        let mut t2 = t1.clone();
+        t1.append_scalar(b"x", &x);
+        t2.append_scalar(b"x", &x);
+        let c1 = t1.challenge_scalar(b"c");
+        assert_eq!(c1, t2.challenge_scalar(b"c"));
+        assert_ne!(c1, t1.challenge_scalar(b"c"));
+
+        // A serialized scalar is absorbed differently from the scalar itself.
+        let mut t3 = PoseidonTranscript::<Fr>::new(b"test");
+        t3.append(b"x", &x);
+        assert_ne!(c1, t3.challenge_scalar(b"c"));
+
+        let mut t4 = PoseidonTranscript::<Fr>::new(b"other");
+        t4.append_scalar(b"x", &x);
+        assert_ne!(c1, t4.challenge_scalar(b"c"));
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        use ark_bls12_381::Fr;
+
+        // The label of the transcript separates the challenges, even when
+        // nothing else is absorbed.
+        let mut a = PoseidonTranscript::<Fr>::new(b"a");
+        let mut b = PoseidonTranscript::<Fr>::new(b"b");
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+
+        // So do the labels of the raw messages.
+        let mut a = PoseidonTranscript::<Fr>::new(b"plonk");
+        let mut b = a.clone();
+        a.append_message(b"a", b"message");
+        b.append_message(b"b", b"message");
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+    }
+
+    #[test]
+    fn test_cached_constants() {
+        use ark_bls12_377::Fr as Fr377;
+        use ark_bls12_381::Fr as Fr381;
+
+        // The constants are generated once per field.
+        assert_eq!(
+            transcript_constants::<Fr381>(),
+            generate_transcript_constants::<Fr381>()
+        );
+        assert_eq!(
+            transcript_constants::<Fr377>(),
+            generate_transcript_constants::<Fr377>()
+        );
+        assert_eq!(
+            PoseidonTranscript::<Fr381>::new(b"plonk").challenge_scalar(b"c"),
+            PoseidonTranscript::<Fr381>::new(b"plonk").challenge_scalar(b"c")
+        );
+    }
+
+    fn test_proof<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res =
+            gadget_tester_with_transcript::<F, P, PC, PoseidonTranscript<F>>(
+                |composer: &mut StandardComposer<F, P>| {
+                    let a = composer.add_input(F::from(3u64));
+                    let b = composer.add_input(F::from(5u64));
+                    let c = composer.arithmetic_gate(|gate| {
+                        gate.witness(a, b, None).mul(F::one())
+                    });
+                    composer.constrain_to_constant(c, F::from(15u64), None);
+                    composer.range_gate(a, 8);
+                },
+                200,
+            );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_wrong_transcript<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let gadget = |composer: &mut StandardComposer<F, P>| {
+            let a = composer.add_input(F::from(3u64));
+            composer.constrain_to_constant(a, F::from(3u64), None);
+        };
+        let res = (|| -> Result<(), Error> {
+            let pp = PC::setup(2 * 64, None, &mut OsRng)
+                .map_err(to_pc_error::<F, PC>)?;
+            let mut prover = Prover::<F, P, PC>::new(b"demo");
+            gadget(prover.mut_cs());
+            let (ck, vk) = PC::trim(&pp, prover.circuit_bound(), 0, None)
+                .map_err(to_pc_error::<F, PC>)?;
+            prover.preprocess(&ck)?;
+            let public_inputs = prover.mut_cs().get_pi().clone();
+            let proof = prover.prove(&ck)?;
+
+            let mut verifier =
+                Verifier::<F, P, PC, PoseidonTranscript<F>>::new(b"demo");
+            gadget(verifier.mut_cs());
+            verifier.preprocess(&ck)?;
+            verifier.verify(&proof, &vk, &public_inputs)
+        })();
+        assert!(res.is_err());
+
+        // The same circuit is accepted with the default transcript.
+        assert!(gadget_tester::<F, P, PC>(gadget, 64).is_ok());
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_proof,
+            test_wrong_transcript
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_proof,
+            test_wrong_transcript
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
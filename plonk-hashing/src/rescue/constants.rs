@@ -0,0 +1,329 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Rescue-Prime Parameters
+
+use crate::{
+    arithmetic::{binomial, inverse_exponent},
+    digest::shake256,
+    poseidon::smallest_alpha,
+    rescue::RescueError,
+};
+use ark_ff::{FpParameters, PrimeField};
+use ark_std::{format, vec::Vec};
+use num_bigint::BigUint;
+
+/// Parameters of a Rescue-XLIX permutation of a given width.
+///
+/// Every round applies the S-box `x^alpha` to the whole state, multiplies
+/// it by the MDS matrix and adds `width` round constants, then does the same
+/// with the inverse S-box `x^(1/alpha)` and `width` other round constants.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug, PartialEq, Eq)]
+pub struct RescueConstants<F>
+where
+    F: PrimeField,
+{
+    /// Number of field elements in the state.
+    pub width: usize,
+    /// Number of field elements of the state that are never absorbed into
+    /// nor squeezed out of.
+    pub capacity: usize,
+    /// S-box exponent.
+    pub alpha: u64,
+    /// Inverse of `alpha` modulo `p - 1`, as little-endian limbs.
+    pub alpha_inv: Vec<u64>,
+    /// Number of rounds.
+    pub rounds: usize,
+    /// MDS matrix, stored row by row.
+    pub mds_matrix: Vec<Vec<F>>,
+    /// Round constants, `2 * width` per round.
+    pub round_constants: Vec<F>,
+}
+
+impl<F> RescueConstants<F>
+where
+    F: PrimeField,
+{
+    /// Builds a new set of Rescue-Prime parameters, checking that they are
+    /// consistent with each other.
+    pub fn new(
+        width: usize,
+        capacity: usize,
+        alpha: u64,
+        rounds: usize,
+        mds_matrix: Vec<Vec<F>>,
+        round_constants: Vec<F>,
+    ) -> Result<Self, RescueError> {
+        if width < 2 {
+            return Err(RescueError::InvalidWidth);
+        }
+        if capacity == 0 || capacity >= width {
+            return Err(RescueError::InvalidCapacity);
+        }
+        let alpha_inv = match inverse_exponent::<F>(alpha) {
+            Some(alpha_inv) if alpha >= 3 => alpha_inv,
+            _ => return Err(RescueError::InvalidAlpha),
+        };
+        if rounds == 0 {
+            return Err(RescueError::InvalidRounds);
+        }
+        if mds_matrix.len() != width
+            || mds_matrix.iter().any(|row| row.len() != width)
+        {
+            return Err(RescueError::InvalidMdsMatrix);
+        }
+        if round_constants.len() != 2 * width * rounds {
+            return Err(RescueError::InvalidRoundConstants);
+        }
+        Ok(Self {
+            width,
+            capacity,
+            alpha,
+            alpha_inv,
+            rounds,
+            mds_matrix,
+            round_constants,
+        })
+    }
+
+    /// Generates the parameters of a permutation of the given `width` and
+    /// `capacity` over `F` with 128 bits of security, the same way as the
+    /// reference implementation of the specification.
+    ///
+    /// The S-box exponent is the smallest `alpha` for which `x^alpha` is a
+    /// permutation of `F`, and the number of rounds protects against
+    /// Gröbner basis attacks with a 50% security margin. The MDS matrix is
+    /// derived from a Vandermonde matrix of powers of the multiplicative
+    /// generator of `F`, and the round constants are sampled out of SHAKE256
+    /// seeded with the parameters of the permutation.
+    ///
+    /// The reference implementation uses the smallest primitive element of
+    /// the field as generator, which is also the multiplicative generator of
+    /// the BLS12-381 scalar field in arkworks.
+    pub fn generate(
+        width: usize,
+        capacity: usize,
+    ) -> Result<Self, RescueError> {
+        const SECURITY_LEVEL: usize = 128;
+        if width < 2 {
+            return Err(RescueError::InvalidWidth);
+        }
+        if capacity == 0 || capacity >= width {
+            return Err(RescueError::InvalidCapacity);
+        }
+        let alpha = smallest_alpha::<F>();
+        let rounds = rounds(width, capacity, alpha, SECURITY_LEVEL);
+        let mds_matrix = mds_matrix::<F>(width);
+        let round_constants =
+            round_constants::<F>(width, capacity, SECURITY_LEVEL, rounds);
+        Self::new(width, capacity, alpha, rounds, mds_matrix, round_constants)
+    }
+
+    /// Number of field elements absorbed or squeezed per permutation.
+    pub fn rate(&self) -> usize {
+        self.width - self.capacity
+    }
+
+    /// Round constants added to the state after the S-box and after the
+    /// inverse S-box of `round`.
+    pub fn round_constants(&self, round: usize) -> (&[F], &[F]) {
+        let start = 2 * self.width * round;
+        self.round_constants[start..start + 2 * self.width].split_at(self.width)
+    }
+}
+
+/// Returns the number of rounds of a permutation of the given `width` and
+/// `capacity` with the S-box `x^alpha`, reaching `security_level` bits of
+/// security against Gröbner basis attacks, with a 50% security margin.
+fn rounds(
+    width: usize,
+    capacity: usize,
+    alpha: u64,
+    security_level: usize,
+) -> usize {
+    let rate = (width - capacity) as u64;
+    let m = width as u64;
+    let target = BigUint::from(1u64) << security_level;
+    let secure_rounds = (1..25)
+        .find(|n| {
+            let degree = (alpha - 1) * m * (n - 1) / 2 + 2;
+            let variables = m * (n - 1) + rate;
+            let binom = binomial(variables + degree, variables);
+            &binom * &binom > target
+        })
+        .unwrap_or(24) as usize;
+    let secure_rounds = secure_rounds.max(5);
+    secure_rounds + secure_rounds / 2 + secure_rounds % 2
+}
+
+/// Returns the MDS matrix of a permutation of the given `width`.
+///
+/// The matrix `[I | A]` in reduced row echelon form generating the same
+/// code as the `width x 2 * width` Vandermonde matrix `V[i][j] = g^(i * j)`,
+/// where `g` is the multiplicative generator of `F`, is computed and the
+/// MDS matrix is the transpose of `A`.
+fn mds_matrix<F>(width: usize) -> Vec<Vec<F>>
+where
+    F: PrimeField,
+{
+    let g = F::multiplicative_generator();
+    let mut rows = (0..width)
+        .map(|i| {
+            (0..2 * width)
+                .map(|j| g.pow([(i * j) as u64]))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    for col in 0..width {
+        let pivot = (col..width)
+            .find(|i| !rows[*i][col].is_zero())
+            .expect("Vandermonde matrices of distinct points are invertible");
+        rows.swap(col, pivot);
+        let inv = rows[col][col].inverse().expect("pivot is non-zero");
+        for x in rows[col].iter_mut() {
+            *x *= inv;
+        }
+        let pivot_row = rows[col].clone();
+        for (_, row) in rows.iter_mut().enumerate().filter(|(i, _)| *i != col) {
+            let factor = row[col];
+            for (x, p) in row.iter_mut().zip(&pivot_row).skip(col) {
+                *x -= factor * p;
+            }
+        }
+    }
+    (0..width)
+        .map(|i| (0..width).map(|j| rows[j][width + i]).collect())
+        .collect()
+}
+
+/// Returns the `2 * width * rounds` round constants of a permutation, sampled
+/// as little-endian integers of `ceil(log2(p) / 8) + 1` bytes reduced modulo
+/// `p`, out of the SHAKE256 output for the seed
+/// `Rescue-XLIX(p,width,capacity,security_level)`.
+fn round_constants<F>(
+    width: usize,
+    capacity: usize,
+    security_level: usize,
+    rounds: usize,
+) -> Vec<F>
+where
+    F: PrimeField,
+{
+    let modulus_bits = F::Params::MODULUS_BITS as usize;
+    let bytes_per_int = (modulus_bits - 1) / 8 + 2;
+    let num = 2 * width * rounds;
+    let modulus: BigUint = F::Params::MODULUS.into();
+    let seed = format!(
+        "Rescue-XLIX({},{},{},{})",
+        modulus, width, capacity, security_level
+    );
+    shake256(seed.as_bytes(), bytes_per_int * num)
+        .chunks(bytes_per_int)
+        .map(F::from_le_bytes_mod_order)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rescue::{NativeSpec, RescueSpec};
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_parameter_checks() {
+        let mds = vec![vec![Fr::from(1u64); 3]; 3];
+        let rc = vec![Fr::from(2u64); 2 * 3 * 4];
+        assert!(
+            RescueConstants::new(3, 1, 5, 4, mds.clone(), rc.clone()).is_ok()
+        );
+        assert_eq!(
+            RescueConstants::new(1, 1, 5, 4, mds.clone(), rc.clone()),
+            Err(RescueError::InvalidWidth)
+        );
+        assert_eq!(
+            RescueConstants::new(3, 3, 5, 4, mds.clone(), rc.clone()),
+            Err(RescueError::InvalidCapacity)
+        );
+        assert_eq!(
+            RescueConstants::new(3, 1, 3, 4, mds.clone(), rc.clone()),
+            Err(RescueError::InvalidAlpha)
+        );
+        assert_eq!(
+            RescueConstants::new(3, 1, 5, 0, mds.clone(), vec![]),
+            Err(RescueError::InvalidRounds)
+        );
+        assert_eq!(
+            RescueConstants::new(3, 1, 5, 4, mds[..2].to_vec(), rc.clone()),
+            Err(RescueError::InvalidMdsMatrix)
+        );
+        assert_eq!(
+            RescueConstants::new(3, 1, 5, 5, mds, rc),
+            Err(RescueError::InvalidRoundConstants)
+        );
+    }
+
+    /// Parses a big-endian hexadecimal string.
+    fn from_hex(hex: &str) -> Fr {
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+        Fr::from_be_bytes_mod_order(&bytes)
+    }
+
+    #[test]
+    fn test_reference_parameters() {
+        // Known-answer vectors computed with a transcription of the
+        // reference `rescue_prime.py` algorithms of the specification.
+        let constants = RescueConstants::<Fr>::generate(3, 1).unwrap();
+        assert_eq!((constants.alpha, constants.rounds), (5, 14));
+        assert_eq!(constants.mds_matrix[0][0], Fr::from(343u64));
+        assert_eq!(
+            constants.round_constants[0],
+            from_hex(
+                "4e79ebb1e5a43abef900bd773cdde906e4bf3244749cb64424f7db47ba0dda87"
+            )
+        );
+        let mut state = vec![Fr::from(0u64), Fr::from(1u64), Fr::from(2u64)];
+        NativeSpec::permute(&mut (), &constants, &mut state);
+        assert_eq!(
+            state,
+            [
+                "2e1183b4ae571061ed9514118392ede2904ae1376d61653de09083cf0b31abce",
+                "38f9e521c67c329a53403dd42999b19c3bfe355e594752c87ada74da35c74b85",
+                "69a193e3c2734c26d85d191a1e521c1bc8024c9047bb5c79835ed5cfc2d8440e",
+            ]
+            .iter()
+            .map(|h| from_hex(h))
+            .collect::<Vec<_>>()
+        );
+        let digest = NativeSpec::hash(
+            &mut (),
+            &constants,
+            &[Fr::from(1u64), Fr::from(2u64)],
+        );
+        assert_eq!(
+            digest,
+            [
+                "5d87015dfb62279a3dd4b271658e028e7d2a971fa2588b12b600d8d2f439aebb",
+                "0c4fd77e3245d00d08c0330314630b4ca7dfbd65c6256093b95276fa5334061b",
+            ]
+            .iter()
+            .map(|h| from_hex(h))
+            .collect::<Vec<_>>()
+        );
+
+        let constants = RescueConstants::<Fr>::generate(4, 2).unwrap();
+        assert_eq!(constants.rounds, 11);
+        assert_eq!(
+            constants.round_constants[0],
+            from_hex(
+                "64e20c1c5c0a0cc43b6eb69cbe2c40609d5777db635a44bafe7b1cd80b4b1810"
+            )
+        );
+    }
+}
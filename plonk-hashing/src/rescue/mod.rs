@@ -0,0 +1,135 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Rescue-Prime Hash Function
+//!
+//! Native and in-circuit implementations of the Rescue-XLIX permutation and
+//! of the Rescue-Prime hash function built on top of it (see
+//! <https://eprint.iacr.org/2020/1143>). As for Poseidon, both
+//! implementations share the same [`RescueConstants`] and the same sponge
+//! logic through the [`RescueSpec`] trait.
+//!
+//! Every round applies the power map `x^alpha` followed by its inverse
+//! `x^(1/alpha)`, whose exponent is as large as the field. The gadget never
+//! computes the latter: it witnesses the root and checks that raising it to
+//! the power `alpha` gives back the input.
+
+mod constants;
+mod native;
+mod plonk;
+
+pub use constants::RescueConstants;
+pub use native::NativeSpec;
+pub use plonk::PlonkSpec;
+
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use core::fmt;
+
+/// Arithmetic backend over which the Rescue-XLIX permutation is evaluated.
+///
+/// `COM` is the context the computation happens in, `()` for the native
+/// implementation and a
+/// [`StandardComposer`](plonk_core::constraint_system::StandardComposer) for
+/// the gadget.
+pub trait RescueSpec<COM, F>
+where
+    F: PrimeField,
+{
+    /// Representation of a state element.
+    type Field: Clone;
+
+    /// Returns a state element holding zero.
+    fn zero(c: &mut COM) -> Self::Field;
+
+    /// Returns a state element holding the fixed value `value`.
+    fn constant(c: &mut COM, value: F) -> Self::Field;
+
+    /// Returns `x + y`.
+    fn add(c: &mut COM, x: &Self::Field, y: &Self::Field) -> Self::Field;
+
+    /// Applies the Rescue-XLIX permutation defined by `constants` to
+    /// `state`.
+    fn permute(
+        c: &mut COM,
+        constants: &RescueConstants<F>,
+        state: &mut [Self::Field],
+    );
+
+    /// Hashes `inputs` with Rescue-Prime, returning `rate` field elements.
+    ///
+    /// The inputs are padded with a one followed by as many zeros as needed
+    /// to fill the rate, then absorbed into the first `rate` elements of an
+    /// all-zero state, the state being permuted after each block. The output
+    /// is the first `rate` elements of the final state.
+    fn hash(
+        c: &mut COM,
+        constants: &RescueConstants<F>,
+        inputs: &[Self::Field],
+    ) -> Vec<Self::Field> {
+        let rate = constants.rate();
+        let mut padded = inputs.to_vec();
+        padded.push(Self::constant(c, F::one()));
+        while padded.len() % rate != 0 {
+            padded.push(Self::zero(c));
+        }
+        let mut state = (0..constants.width)
+            .map(|_| Self::zero(c))
+            .collect::<Vec<_>>();
+        for block in padded.chunks(rate) {
+            for (s, input) in state.iter_mut().zip(block) {
+                *s = Self::add(c, s, input);
+            }
+            Self::permute(c, constants, &mut state);
+        }
+        state.truncate(rate);
+        state
+    }
+}
+
+/// Rescue-Prime Errors
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RescueError {
+    /// The width of the permutation must be at least two.
+    InvalidWidth,
+    /// The capacity must be between one and `width - 1`.
+    InvalidCapacity,
+    /// The S-box exponent must be at least three and `x^alpha` must be a
+    /// permutation of the field.
+    InvalidAlpha,
+    /// The number of rounds must be non-zero.
+    InvalidRounds,
+    /// The MDS matrix is not a `width x width` matrix.
+    InvalidMdsMatrix,
+    /// The number of round constants does not match `2 * width * rounds`.
+    InvalidRoundConstants,
+}
+
+impl fmt::Display for RescueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidWidth => write!(f, "Rescue width must be >= 2"),
+            Self::InvalidCapacity => {
+                write!(f, "Capacity must be in [1, width - 1]")
+            }
+            Self::InvalidAlpha => {
+                write!(f, "S-box exponent must be >= 3 and invertible")
+            }
+            Self::InvalidRounds => {
+                write!(f, "Number of rounds must be non-zero")
+            }
+            Self::InvalidMdsMatrix => {
+                write!(f, "MDS matrix must be square, of size width")
+            }
+            Self::InvalidRoundConstants => {
+                write!(f, "Wrong number of round constants")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RescueError {}
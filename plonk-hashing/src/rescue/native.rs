@@ -0,0 +1,61 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Native Rescue-XLIX Permutation
+
+use crate::rescue::{RescueConstants, RescueSpec};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+/// Out-of-circuit Rescue-Prime, evaluated directly over the field `F`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeSpec;
+
+impl<F> RescueSpec<(), F> for NativeSpec
+where
+    F: PrimeField,
+{
+    type Field = F;
+
+    fn zero(_: &mut ()) -> F {
+        F::zero()
+    }
+
+    fn constant(_: &mut (), value: F) -> F {
+        value
+    }
+
+    fn add(_: &mut (), x: &F, y: &F) -> F {
+        *x + y
+    }
+
+    fn permute(_: &mut (), constants: &RescueConstants<F>, state: &mut [F]) {
+        assert_eq!(state.len(), constants.width);
+        let mix = |state: &mut [F], round_constants: &[F]| {
+            let mixed = constants
+                .mds_matrix
+                .iter()
+                .zip(round_constants)
+                .map(|(row, rc)| {
+                    row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum::<F>()
+                        + rc
+                })
+                .collect::<Vec<F>>();
+            state.copy_from_slice(&mixed);
+        };
+        for round in 0..constants.rounds {
+            let (first, second) = constants.round_constants(round);
+            for s in state.iter_mut() {
+                *s = s.pow([constants.alpha]);
+            }
+            mix(state, first);
+            for s in state.iter_mut() {
+                *s = s.pow(&constants.alpha_inv);
+            }
+            mix(state, second);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Rescue-XLIX Permutation Gadget
+
+use crate::{
+    arithmetic::{linear_combination, pow, root},
+    rescue::{RescueConstants, RescueSpec},
+};
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use plonk_core::constraint_system::{StandardComposer, Variable};
+
+/// In-circuit Rescue-Prime, evaluated with the arithmetic gates of a
+/// [`StandardComposer`].
+///
+/// Round constants are folded into the constant selector of the gates of the
+/// linear layer, and the inverse S-box is constrained by witnessing the root
+/// of each element of the state and checking it with a single `poly_gate`
+/// once raised to the power `alpha - 1`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlonkSpec;
+
+impl<F, P> RescueSpec<StandardComposer<F, P>, F> for PlonkSpec
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    type Field = Variable;
+
+    fn zero(c: &mut StandardComposer<F, P>) -> Variable {
+        c.zero_var()
+    }
+
+    fn constant(c: &mut StandardComposer<F, P>, value: F) -> Variable {
+        c.add_witness_to_circuit_description(value)
+    }
+
+    fn add(
+        c: &mut StandardComposer<F, P>,
+        x: &Variable,
+        y: &Variable,
+    ) -> Variable {
+        c.arithmetic_gate(|gate| {
+            gate.witness(*x, *y, None).add(F::one(), F::one())
+        })
+    }
+
+    fn permute(
+        c: &mut StandardComposer<F, P>,
+        constants: &RescueConstants<F>,
+        state: &mut [Variable],
+    ) {
+        assert_eq!(state.len(), constants.width);
+        let mix = |c: &mut StandardComposer<F, P>,
+                   state: &mut [Variable],
+                   round_constants: &[F]| {
+            let mixed = constants
+                .mds_matrix
+                .iter()
+                .zip(round_constants)
+                .map(|(row, rc)| {
+                    let terms = row
+                        .iter()
+                        .copied()
+                        .zip(state.iter().copied())
+                        .collect::<Vec<_>>();
+                    linear_combination(c, &terms, *rc)
+                })
+                .collect::<Vec<_>>();
+            state.copy_from_slice(&mixed);
+        };
+        for round in 0..constants.rounds {
+            let (first, second) = constants.round_constants(round);
+            for s in state.iter_mut() {
+                *s = pow(c, *s, F::zero(), constants.alpha);
+            }
+            mix(c, state, first);
+            for s in state.iter_mut() {
+                *s = root(c, *s, constants.alpha, &constants.alpha_inv);
+            }
+            mix(c, state, second);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, rescue::NativeSpec, test::gadget_tester};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_std::test_rng;
+    use plonk_core::commitment::HomomorphicCommitment;
+
+    fn test_permutation_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        for (width, capacity) in [(3, 1), (4, 2)] {
+            let constants =
+                RescueConstants::<F>::generate(width, capacity).unwrap();
+            let input =
+                (0..width).map(|_| F::rand(&mut rng)).collect::<Vec<_>>();
+            let mut expected = input.clone();
+            NativeSpec::permute(&mut (), &constants, &mut expected);
+
+            let res = gadget_tester::<F, P, PC>(
+                |composer: &mut StandardComposer<F, P>| {
+                    let mut state = input
+                        .iter()
+                        .map(|x| composer.add_input(*x))
+                        .collect::<Vec<_>>();
+                    PlonkSpec::permute(composer, &constants, &mut state);
+                    for (var, value) in state.iter().zip(expected.iter()) {
+                        assert_eq!(composer.value_of_var(*var), *value);
+                        composer.constrain_to_constant(*var, *value, None);
+                    }
+                },
+                1000,
+            );
+            assert!(res.is_ok(), "{:?}", res.err().unwrap());
+        }
+    }
+
+    fn test_hash_gadget<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut rng = test_rng();
+        let constants = RescueConstants::<F>::generate(3, 1).unwrap();
+        let input = (0..3).map(|_| F::rand(&mut rng)).collect::<Vec<_>>();
+        let expected = NativeSpec::hash(&mut (), &constants, &input);
+
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let input = input
+                    .iter()
+                    .map(|x| composer.add_input(*x))
+                    .collect::<Vec<_>>();
+                let output = PlonkSpec::hash(composer, &constants, &input);
+                for (var, value) in output.iter().zip(expected.iter()) {
+                    assert_eq!(composer.value_of_var(*var), *value);
+                    composer.constrain_to_constant(*var, *value, None);
+                }
+            },
+            1000,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_permutation_gadget,
+            test_hash_gadget
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_permutation_gadget,
+            test_hash_gadget
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
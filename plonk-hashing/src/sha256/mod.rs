@@ -0,0 +1,377 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! SHA-256 Gadget
+//!
+//! In-circuit SHA-256 (FIPS 180-4) over a message of a fixed number of
+//! bytes, padded inside the gadget.
+//!
+//! Words are kept as linear combinations of pieces whose dense and spread
+//! forms are read together from the lookup table returned by
+//! [`lookup_table`], added to the circuit under the name [`LOOKUP_TABLE`] by
+//! the first hash. The pieces are cut at every
+//! rotation and shift amount of the word, so that the spread form of the
+//! three rotations of `Σ_0`, `Σ_1`, `σ_0` and `σ_1` is a linear combination
+//! of the spread pieces. The functions are then computed by splitting a sum
+//! of spread words into its even and odd bits:
+//!
+//! - `Σ_0`, `Σ_1`, `σ_0` and `σ_1` are the even bits of the sum of the three
+//!   rotations or shifts of their input,
+//! - `Maj(a, b, c)` is the odd bits of `spread(a) + spread(b) + spread(c)`,
+//! - `Ch(e, f, g)` is the sum of the odd bits of `spread(e) + spread(f)` and of
+//!   `spread(!e) + spread(g)`, the two never overlapping,
+//!
+//! while additions modulo `2^32` witness the result split into pieces and a
+//! carry of at most three bits. A 64-byte block takes about 8,700 gates,
+//! most of them spent in the five splits and two additions of every round,
+//! on top of the 3,838 rows of the lookup table.
+
+mod spread;
+
+use crate::arithmetic::LinearCombination;
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use plonk_core::{
+    constraint_system::{StandardComposer, Variable},
+    lookup::LookupTable,
+};
+use spread::{add_mod, split, tags, to_u64, Tags, Word};
+
+/// Round constants.
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1,
+    0x923f82a4, 0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3,
+    0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786,
+    0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147,
+    0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+    0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+    0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a,
+    0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+    0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Initial hash value.
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c,
+    0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Layout of the words `a`, `b` and `c` of the state, cut at the rotations
+/// of `Σ_0`: 2, 13 and 22.
+const A_LAYOUT: [u32; 4] = [2, 11, 9, 10];
+
+/// Layout of the words `e`, `f` and `g` of the state, cut at the rotations
+/// of `Σ_1`: 6, 11 and 25.
+const E_LAYOUT: [u32; 5] = [6, 5, 7, 7, 7];
+
+/// Layout of the words of the message schedule, cut at the rotations and
+/// shifts of `σ_0`, 3, 7 and 18, and of `σ_1`, 10, 17 and 19.
+const W_LAYOUT: [u32; 8] = [3, 4, 3, 7, 1, 1, 6, 7];
+
+/// Name of the lookup table used by [`sha256`].
+pub const LOOKUP_TABLE: &str = "sha256 spread";
+
+/// Returns the lookup table used by [`sha256`], which it adds to the circuit
+/// under the name [`LOOKUP_TABLE`] when it is not already added.
+pub fn lookup_table<F>() -> LookupTable<F>
+where
+    F: PrimeField,
+{
+    spread::lookup_table()
+}
+
+/// Hashes `message` with SHA-256, returning the eight 32-bit words of the
+/// digest.
+///
+/// The message is padded inside the gadget, its length being part of the
+/// circuit description.
+///
+/// # Note
+///
+/// The bytes of `message` should have previously been constrained to be
+/// bytes, for instance with [`StandardComposer::range_gate`].
+pub fn sha256<F, P>(
+    c: &mut StandardComposer<F, P>,
+    message: &[Variable],
+) -> [Variable; 8]
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let tags = tags(c);
+    let mut bytes = message
+        .iter()
+        .map(|byte| LinearCombination::variable(*byte))
+        .collect::<Vec<_>>();
+    let constant = |byte: u8| LinearCombination::constant(F::from(byte));
+    bytes.push(constant(0x80));
+    while bytes.len() % 64 != 56 {
+        bytes.push(constant(0));
+    }
+    let bit_len = 8 * message.len() as u64;
+    bytes.extend(bit_len.to_be_bytes().iter().map(|byte| constant(*byte)));
+
+    let mut state = IV
+        .iter()
+        .enumerate()
+        .map(|(i, h)| Word::constant(*h, layout(i)))
+        .collect::<Vec<_>>();
+    for block in bytes.chunks(64) {
+        let words = block
+            .chunks(4)
+            .map(|bytes| {
+                let mut word = LinearCombination::constant(F::zero());
+                for (i, byte) in bytes.iter().enumerate() {
+                    word.add_scaled(F::from(1u64 << (24 - 8 * i)), byte);
+                }
+                word
+            })
+            .collect::<Vec<_>>();
+        state = compress(c, &tags, &state, &words);
+    }
+    let mut digest = [c.zero_var(); 8];
+    for (var, word) in digest.iter_mut().zip(state) {
+        *var = word.dense().evaluate(c);
+    }
+    digest
+}
+
+/// Returns the layout of the `i`-th word of the state.
+fn layout(i: usize) -> &'static [u32] {
+    if i < 4 {
+        &A_LAYOUT
+    } else {
+        &E_LAYOUT
+    }
+}
+
+/// Witnesses the word holding the value of `word`, split as `layout`.
+fn witness_word<F, P>(
+    c: &mut StandardComposer<F, P>,
+    tags: &Tags,
+    word: &LinearCombination<F>,
+    layout: &[u32],
+) -> Word<F>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    if word.terms.is_empty() {
+        return Word::constant(to_u64(word.constant) as u32, layout);
+    }
+    let witness = Word::witness(c, tags, to_u64(word.value(c)) as u32, layout);
+    let mut check = word.clone();
+    check.add_scaled(-F::one(), &witness.dense());
+    check.assert_zero(c);
+    witness
+}
+
+/// Returns the sum of at most three spread words `sum`, keeping the bits
+/// selected by `odd`.
+fn xor_or_carry<F, P>(
+    c: &mut StandardComposer<F, P>,
+    tags: &Tags,
+    sum: &LinearCombination<F>,
+    odd: bool,
+) -> LinearCombination<F>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let (even_word, odd_word) = split(c, tags, sum);
+    if odd {
+        odd_word.dense()
+    } else {
+        even_word.dense()
+    }
+}
+
+/// Sums spread words.
+fn sum<F>(words: &[LinearCombination<F>]) -> LinearCombination<F>
+where
+    F: PrimeField,
+{
+    let mut sum = LinearCombination::constant(F::zero());
+    for word in words {
+        sum.add_scaled(F::one(), word);
+    }
+    sum
+}
+
+/// Applies the compression function to `state` with the 16 words of a block.
+fn compress<F, P>(
+    c: &mut StandardComposer<F, P>,
+    tags: &Tags,
+    state: &[Word<F>],
+    block: &[LinearCombination<F>],
+) -> Vec<Word<F>>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    // Message schedule.
+    let mut w = block
+        .iter()
+        .map(|word| witness_word(c, tags, word, &W_LAYOUT))
+        .collect::<Vec<_>>();
+    for t in 16..64 {
+        // σ_0(x) = ROTR^7(x) ^ ROTR^18(x) ^ SHR^3(x)
+        let x = &w[t - 15];
+        let s0 = sum(&[x.rotr(7), x.rotr(18), x.shr(3)]);
+        let s0 = xor_or_carry(c, tags, &s0, false);
+        // σ_1(x) = ROTR^17(x) ^ ROTR^19(x) ^ SHR^10(x)
+        let x = &w[t - 2];
+        let s1 = sum(&[x.rotr(17), x.rotr(19), x.shr(10)]);
+        let s1 = xor_or_carry(c, tags, &s1, false);
+        let total = sum(&[s1, w[t - 7].dense(), s0, w[t - 16].dense()]);
+        w.push(add_mod(c, tags, &total, &W_LAYOUT));
+    }
+
+    let mut v = state.to_vec();
+    let not = LinearCombination::constant(F::from(spread::spread(u32::MAX)));
+    for t in 0..64 {
+        let (a, b, cc, d) = (&v[0], &v[1], &v[2], &v[3]);
+        let (e, f, g, h) = (&v[4], &v[5], &v[6], &v[7]);
+        // Σ_1(e) = ROTR^6(e) ^ ROTR^11(e) ^ ROTR^25(e)
+        let s1 = sum(&[e.rotr(6), e.rotr(11), e.rotr(25)]);
+        let s1 = xor_or_carry(c, tags, &s1, false);
+        // Ch(e, f, g) = (e & f) ^ (!e & g)
+        let ef = xor_or_carry(c, tags, &sum(&[e.spread(), f.spread()]), true);
+        let mut not_e = not.clone();
+        not_e.add_scaled(-F::one(), &e.spread());
+        let ng = xor_or_carry(c, tags, &sum(&[not_e, g.spread()]), true);
+        // Σ_0(a) = ROTR^2(a) ^ ROTR^13(a) ^ ROTR^22(a)
+        let s0 = sum(&[a.rotr(2), a.rotr(13), a.rotr(22)]);
+        let s0 = xor_or_carry(c, tags, &s0, false);
+        // Maj(a, b, c)
+        let maj = sum(&[a.spread(), b.spread(), cc.spread()]);
+        let maj = xor_or_carry(c, tags, &maj, true);
+
+        let round_constant = LinearCombination::constant(F::from(K[t]));
+        let t1 = sum(&[h.dense(), s1, ef, ng, round_constant, w[t].dense()]);
+        let new_e = add_mod(c, tags, &sum(&[d.dense(), t1.clone()]), &E_LAYOUT);
+        let new_a = add_mod(c, tags, &sum(&[t1, s0, maj]), &A_LAYOUT);
+        v.rotate_right(1);
+        v[0] = new_a;
+        v[4] = new_e;
+    }
+    state
+        .iter()
+        .zip(v)
+        .enumerate()
+        .map(|(i, (h, v))| {
+            add_mod(c, tags, &sum(&[h.dense(), v.dense()]), layout(i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, test::gadget_tester};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use plonk_core::commitment::HomomorphicCommitment;
+
+    /// Parses a big-endian hexadecimal digest.
+    fn digest_words(hex: &str) -> Vec<u32> {
+        (0..8)
+            .map(|i| u32::from_str_radix(&hex[8 * i..8 * i + 8], 16).unwrap())
+            .collect()
+    }
+
+    /// Hashes `message` in `composer`, checking the digest against
+    /// `expected`.
+    fn hash_and_check<F, P>(
+        composer: &mut StandardComposer<F, P>,
+        message: &[u8],
+        expected: &str,
+    ) where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let message = message
+            .iter()
+            .map(|byte| {
+                let byte = composer.add_input(F::from(*byte));
+                composer.range_gate(byte, 8);
+                byte
+            })
+            .collect::<Vec<_>>();
+        let digest = sha256(composer, &message);
+        for (var, word) in digest.iter().zip(digest_words(expected)) {
+            assert_eq!(composer.value_of_var(*var), F::from(word));
+            composer.constrain_to_constant(*var, F::from(word), None);
+        }
+    }
+
+    fn test_sha256_abc<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // Test vector of FIPS 180-4.
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let start = composer.circuit_size();
+                hash_and_check(
+                    composer,
+                    b"abc",
+                    "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+                );
+                let gates = composer.circuit_size() - start;
+                assert!(gates < 9000, "{} gates for one block", gates);
+            },
+            16384,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_sha256_two_blocks<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // Test vector of FIPS 180-4, the padding of the 56-byte message
+        // spilling over a second block.
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                hash_and_check(
+                    composer,
+                    b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                    "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+                );
+            },
+            16384,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    // Tests for Bls12-381
+    batch_test!(
+        [
+            test_sha256_abc,
+            test_sha256_two_blocks
+        ] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12-377
+    batch_test!(
+        [
+            test_sha256_abc,
+            test_sha256_two_blocks
+        ] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
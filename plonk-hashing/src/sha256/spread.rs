@@ -0,0 +1,290 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Spread Form of 32-bit Words
+//!
+//! The spread form of a word interleaves its bits with zeros, so that the
+//! `i`-th bit of `x` becomes the `2i`-th bit of `spread(x)`. Adding up to
+//! three spread words never carries from a pair of bits into the next one,
+//! hence the even bits of the sum hold the XOR of the words and the odd bits
+//! hold their majority, or their AND for two words.
+
+use crate::arithmetic::LinearCombination;
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use ark_std::{vec, vec::Vec};
+use plonk_core::{
+    constraint_system::{StandardComposer, Variable},
+    lookup::{LookupTable, LookupTableId},
+};
+
+/// Lengths of the pieces of words stored in the lookup table.
+pub(super) const PIECE_BITS: [u32; 10] = [1, 2, 3, 4, 5, 6, 7, 9, 10, 11];
+
+/// Layout of the words whose spread form is only used through its even or
+/// odd bits.
+pub(super) const DENSE_LAYOUT: [u32; 3] = [11, 11, 10];
+
+/// Returns the spread form of `x`.
+pub(super) fn spread(x: u32) -> u64 {
+    (0..32).fold(0, |acc, i| acc | (u64::from((x >> i) & 1) << (2 * i)))
+}
+
+/// Returns the word made of the even bits of `x`.
+fn even_bits(x: u64) -> u32 {
+    (0..32).fold(0, |acc, i| acc | ((((x >> (2 * i)) & 1) as u32) << i))
+}
+
+/// Returns the lookup table holding the rows `(x, k, spread(x), 0)` for
+/// every piece length `k` of [`PIECE_BITS`] and every `x < 2^k`.
+pub(super) fn lookup_table<F>() -> LookupTable<F>
+where
+    F: PrimeField,
+{
+    let mut table = LookupTable::new();
+    for bits in PIECE_BITS {
+        for x in 0..1u32 << bits {
+            table.insert_row(
+                F::from(x),
+                F::from(bits),
+                F::from(spread(x)),
+                F::zero(),
+            );
+        }
+    }
+    table
+}
+
+/// Converts a field element holding at most 64 bits into an integer.
+pub(super) fn to_u64<F>(x: F) -> u64
+where
+    F: PrimeField,
+{
+    x.into_repr().as_ref()[0]
+}
+
+/// Piece of a word, starting at the bit `offset` and `bits` long.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+struct Piece<F>
+where
+    F: PrimeField,
+{
+    offset: u32,
+    bits: u32,
+    dense: LinearCombination<F>,
+    spread: LinearCombination<F>,
+}
+
+/// 32-bit word split into pieces, each known in dense and in spread form.
+///
+/// The boundaries of the pieces must include every rotation and shift
+/// amount applied to the word.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub(super) struct Word<F>
+where
+    F: PrimeField,
+{
+    pieces: Vec<Piece<F>>,
+}
+
+impl<F> Word<F>
+where
+    F: PrimeField,
+{
+    /// Returns the constant word `value` split as `layout`.
+    pub fn constant(value: u32, layout: &[u32]) -> Self {
+        Self::split_with(value, layout, |_, x| {
+            (
+                LinearCombination::constant(F::from(x)),
+                LinearCombination::constant(F::from(spread(x))),
+            )
+        })
+    }
+
+    /// Witnesses the word `value` split as `layout`, every piece being
+    /// looked up along with its spread form, which constrains the word to 32
+    /// bits.
+    pub fn witness<P>(
+        c: &mut StandardComposer<F, P>,
+        tags: &Tags,
+        value: u32,
+        layout: &[u32],
+    ) -> Self
+    where
+        P: TEModelParameters<BaseField = F>,
+    {
+        Self::split_with(value, layout, |bits, x| {
+            let (dense, spread) = lookup(c, tags, x, bits);
+            (
+                LinearCombination::variable(dense),
+                LinearCombination::variable(spread),
+            )
+        })
+    }
+
+    /// Splits `value` as `layout`, building every piece with `piece`.
+    fn split_with<G>(value: u32, layout: &[u32], mut piece: G) -> Self
+    where
+        G: FnMut(u32, u32) -> (LinearCombination<F>, LinearCombination<F>),
+    {
+        debug_assert_eq!(layout.iter().sum::<u32>(), 32);
+        let mut offset = 0;
+        let pieces = layout
+            .iter()
+            .map(|&bits| {
+                let x = (u64::from(value) >> offset) as u32 & ((1 << bits) - 1);
+                let (dense, spread) = piece(bits, x);
+                let piece = Piece {
+                    offset,
+                    bits,
+                    dense,
+                    spread,
+                };
+                offset += bits;
+                piece
+            })
+            .collect();
+        Self { pieces }
+    }
+
+    /// Returns the word, in dense form.
+    pub fn dense(&self) -> LinearCombination<F> {
+        let mut dense = LinearCombination::constant(F::zero());
+        for piece in &self.pieces {
+            dense.add_scaled(F::from(1u64 << piece.offset), &piece.dense);
+        }
+        dense
+    }
+
+    /// Returns the word rotated right by `r` bits, in spread form.
+    pub fn rotr(&self, r: u32) -> LinearCombination<F> {
+        let mut spread = LinearCombination::constant(F::zero());
+        for piece in &self.pieces {
+            debug_assert!(
+                piece.offset >= r || piece.offset + piece.bits <= r,
+                "rotations must be aligned on the pieces"
+            );
+            let offset = (piece.offset + 32 - r) % 32;
+            spread
+                .add_scaled(F::from(4u64).pow([offset.into()]), &piece.spread);
+        }
+        spread
+    }
+
+    /// Returns the word shifted right by `r` bits, in spread form.
+    pub fn shr(&self, r: u32) -> LinearCombination<F> {
+        let mut spread = LinearCombination::constant(F::zero());
+        for piece in self.pieces.iter().filter(|piece| piece.offset >= r) {
+            let offset = piece.offset - r;
+            spread
+                .add_scaled(F::from(4u64).pow([offset.into()]), &piece.spread);
+        }
+        spread
+    }
+
+    /// Returns the word, in spread form.
+    pub fn spread(&self) -> LinearCombination<F> {
+        self.rotr(0)
+    }
+}
+
+/// Looks up the piece `x` of `bits` bits and its spread form, returning
+/// their variables.
+fn lookup<F, P>(
+    c: &mut StandardComposer<F, P>,
+    tags: &Tags,
+    x: u32,
+    bits: u32,
+) -> (Variable, Variable)
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let dense = c.add_input(F::from(x));
+    let spread = c.add_input(F::from(spread(x)));
+    c.lookup_gate(
+        tags.table,
+        dense,
+        tags.bits[bits as usize],
+        spread,
+        None,
+        None,
+    );
+    (dense, spread)
+}
+
+/// Lookup table of the pieces and the variables with which they are looked
+/// up.
+pub(super) struct Tags {
+    /// Handle to the table returned by [`lookup_table`].
+    table: LookupTableId,
+    /// Variables holding the piece lengths of [`PIECE_BITS`], indexed by
+    /// length.
+    bits: Vec<Variable>,
+}
+
+/// Returns the [`Tags`] with which the pieces are looked up, adding the
+/// lookup table to the circuit if it is not already added.
+pub(super) fn tags<F, P>(c: &mut StandardComposer<F, P>) -> Tags
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let table = c.get_or_add_lookup_table(super::LOOKUP_TABLE, lookup_table);
+    let mut bits = vec![c.zero_var(); 12];
+    for piece_bits in PIECE_BITS {
+        bits[piece_bits as usize] =
+            c.add_witness_to_circuit_description(F::from(piece_bits));
+    }
+    Tags { table, bits }
+}
+
+/// Splits the sum `sum` of at most three spread words into the words made
+/// of its even and of its odd bits.
+pub(super) fn split<F, P>(
+    c: &mut StandardComposer<F, P>,
+    tags: &Tags,
+    sum: &LinearCombination<F>,
+) -> (Word<F>, Word<F>)
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let value = to_u64(sum.value(c));
+    let even = Word::witness(c, tags, even_bits(value), &DENSE_LAYOUT);
+    let odd = Word::witness(c, tags, even_bits(value >> 1), &DENSE_LAYOUT);
+    // sum - spread(even) - 2 * spread(odd) = 0
+    let mut check = sum.clone();
+    check.add_scaled(-F::one(), &even.spread());
+    check.add_scaled(-F::from(2u64), &odd.spread());
+    check.assert_zero(c);
+    (even, odd)
+}
+
+/// Returns the sum `sum` of at most eight words modulo `2^32`, split as
+/// `layout`.
+pub(super) fn add_mod<F, P>(
+    c: &mut StandardComposer<F, P>,
+    tags: &Tags,
+    sum: &LinearCombination<F>,
+    layout: &[u32],
+) -> Word<F>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let value = to_u64(sum.value(c));
+    let word = Word::witness(c, tags, value as u32, layout);
+    let (carry, _) = lookup(c, tags, (value >> 32) as u32, 3);
+    // sum - word - 2^32 * carry = 0
+    let mut check = sum.clone();
+    check.add_scaled(-F::one(), &word.dense());
+    check.add_scaled(-F::from(1u64 << 32), &LinearCombination::variable(carry));
+    check.assert_zero(c);
+    word
+}
@@ -0,0 +1,185 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Test Suite
+
+use ark_ec::TEModelParameters;
+use ark_ff::{
+    biginteger::BigInteger256,
+    fields::{FftParameters, Fp256, Fp256Parameters, FpParameters},
+    PrimeField,
+};
+use plonk_core::{
+    commitment::HomomorphicCommitment,
+    constraint_system::StandardComposer,
+    error::{to_pc_error, Error},
+    proof_system::{Prover, Verifier},
+    transcript::{Transcript, TranscriptProtocol},
+};
+use rand_core::OsRng;
+
+/// Defines a set of tests on a pairing engine / curve combination, using the
+/// KZG10 polynomial commitment scheme.
+#[macro_export]
+macro_rules! batch_test {
+    ( [$($test_set:ident),*] => ($engine:ty, $params:ty) ) => {
+        paste::item! {
+            $(
+                #[test]
+                #[allow(non_snake_case)]
+                fn [< $test_set _on_ $engine >]() {
+                    $test_set::<
+                        <$engine as ark_ec::PairingEngine>::Fr,
+                        $params,
+                        plonk_core::commitment::KZG10<$engine>,
+                    >()
+                }
+            )*
+        }
+    }
+}
+
+/// Takes a gadget function and tests whether it passes an end-to-end test.
+pub(crate) fn gadget_tester<F, P, PC>(
+    gadget: impl Fn(&mut StandardComposer<F, P>),
+    n: usize,
+) -> Result<(), Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    PC: HomomorphicCommitment<F>,
+{
+    gadget_tester_with_transcript::<F, P, PC, Transcript>(gadget, n)
+}
+
+/// Takes a gadget function and tests whether it passes an end-to-end test
+/// using the transcript `T`.
+pub(crate) fn gadget_tester_with_transcript<F, P, PC, T>(
+    gadget: impl Fn(&mut StandardComposer<F, P>),
+    n: usize,
+) -> Result<(), Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol<F>,
+{
+    // Common View
+    let universal_params =
+        PC::setup(2 * n, None, &mut OsRng).map_err(to_pc_error::<F, PC>)?;
+
+    // Provers View
+    let (proof, public_inputs) = {
+        let mut prover = Prover::<F, P, PC, T>::new(b"demo");
+        gadget(prover.mut_cs());
+        let (ck, _) =
+            PC::trim(&universal_params, prover.required_srs_degree(), 0, None)
+                .map_err(to_pc_error::<F, PC>)?;
+        prover.preprocess(&ck)?;
+        let public_inputs = prover.mut_cs().get_pi().clone();
+        (prover.prove(&ck)?, public_inputs)
+    };
+
+    // Verifiers view
+    let mut verifier = Verifier::<F, P, PC, T>::new(b"demo");
+    gadget(verifier.mut_cs());
+    let (ck, vk) =
+        PC::trim(&universal_params, verifier.required_srs_degree(), 0, None)
+            .map_err(to_pc_error::<F, PC>)?;
+    verifier.preprocess(&ck)?;
+    verifier.verify(&proof, &vk, &public_inputs)
+}
+
+/// Scalar field of the BN254 curve, on which the most widely deployed
+/// instances of some hash functions are defined.
+pub(crate) type Bn254Fr = Fp256<Bn254FrParameters>;
+
+/// Parameters of [`Bn254Fr`].
+pub(crate) struct Bn254FrParameters;
+
+impl Fp256Parameters for Bn254FrParameters {}
+
+impl FftParameters for Bn254FrParameters {
+    type BigInt = BigInteger256;
+
+    const TWO_ADICITY: u32 = 28;
+
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger256 = BigInteger256([
+        0x636e735580d13d9c,
+        0xa22bf3742445ffd6,
+        0x56452ac01eb203d8,
+        0x1860ef942963f9e7,
+    ]);
+}
+
+impl FpParameters for Bn254FrParameters {
+    /// MODULUS = 21888242871839275222246405745257275088548364400416034343698204186575808495617
+    #[rustfmt::skip]
+    const MODULUS: BigInteger256 = BigInteger256([
+        0x43e1f593f0000001,
+        0x2833e84879b97091,
+        0xb85045b68181585d,
+        0x30644e72e131a029,
+    ]);
+
+    const MODULUS_BITS: u32 = 254;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 2;
+
+    #[rustfmt::skip]
+    const R: BigInteger256 = BigInteger256([
+        0xac96341c4ffffffb,
+        0x36fc76959f60cd29,
+        0x666ea36f7879462e,
+        0x0e0a77c19a07df2f,
+    ]);
+
+    #[rustfmt::skip]
+    const R2: BigInteger256 = BigInteger256([
+        0x1bb8e645ae216da7,
+        0x53fe3ab1e35c59e3,
+        0x8c49833d53bb8085,
+        0x0216d0b17f4e44a5,
+    ]);
+
+    const INV: u64 = 0xc2e1f593efffffff;
+
+    /// GENERATOR = 5, in Montgomery form
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger256 = BigInteger256([
+        0x1b0d0ef99fffffe6,
+        0xeaba68a3a32a913f,
+        0x47d8eb76d8dd0689,
+        0x15d0085520f5bbc3,
+    ]);
+
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger256 = BigInteger256([
+        0xa1f0fac9f8000000,
+        0x9419f4243cdcb848,
+        0xdc2822db40c0ac2e,
+        0x183227397098d014,
+    ]);
+
+    #[rustfmt::skip]
+    const T: BigInteger256 = BigInteger256([
+        0x9b9709143e1f593f,
+        0x181585d2833e8487,
+        0x131a029b85045b68,
+        0x000000030644e72e,
+    ]);
+
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger256 = BigInteger256([
+        0xcdcb848a1f0fac9f,
+        0x0c0ac2e9419f4243,
+        0x098d014dc2822db4,
+        0x0000000183227397,
+    ]);
+}